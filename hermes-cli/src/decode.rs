@@ -0,0 +1,517 @@
+use std::convert::TryInto;
+
+use hermes::*;
+use hermes_mqtt::topics::*;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+/// A message seen on the bus, decoded according to its topic
+#[derive(Debug)]
+pub struct Event {
+    /// The raw MQTT topic the message was published on
+    pub topic: String,
+    /// The component the topic belongs to (the segment following `hermes/`)
+    pub component: Option<String>,
+    /// The site concerned by the message, taken from the topic or from the payload
+    pub site_id: Option<String>,
+    /// The session concerned by the message, if any
+    pub session_id: Option<String>,
+    pub content: Content,
+}
+
+#[derive(Debug)]
+pub enum Content {
+    /// A message with no payload
+    Empty,
+    /// A json message that was successfully decoded in its ontology type
+    Message { type_name: &'static str, json: Value },
+    /// An audio frame or a replayed audio frame
+    Audio(AudioSummary),
+    /// A binary message that is not audio to analyse (sounds to play, to register...)
+    Binary { type_name: &'static str, len: usize },
+    /// Something that does not respect the protocol
+    Violation(String),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct AudioSummary {
+    /// Size of the whole wav frame
+    pub len: usize,
+    /// Capture timestamp in ms, from the `time` chunk of the frame
+    pub timestamp_ms: Option<u64>,
+    /// Root mean square of the samples, relative to the full scale (between 0 and 1)
+    pub rms: Option<f64>,
+}
+
+impl AudioSummary {
+    pub fn rms_dbfs(&self) -> Option<f64> {
+        self.rms.map(|rms| 20. * rms.max(1e-5).log10())
+    }
+}
+
+pub fn decode(topic: &str, payload: &[u8]) -> Event {
+    let component = topic.split('/').nth(1).map(|it| it.to_string());
+    let (site_id, content) = match HermesTopic::from_path(topic) {
+        Some(hermes_topic) => decode_payload(&hermes_topic, payload),
+        None => (
+            None,
+            Content::Violation(format!("topic '{}' is not part of the hermes protocol", topic)),
+        ),
+    };
+
+    // A violation may still carry enough information to be filtered on
+    let json = match content {
+        Content::Message { ref json, .. } => Some(json.clone()),
+        Content::Violation(_) => serde_json::from_slice(payload).ok(),
+        _ => None,
+    };
+    let site_id = site_id.or_else(|| json.as_ref().and_then(|it| string_field(it, "siteId")));
+    let session_id = json.as_ref().and_then(|it| string_field(it, "sessionId"));
+
+    Event {
+        topic: topic.to_string(),
+        component,
+        site_id,
+        session_id,
+        content,
+    }
+}
+
+fn string_field(json: &Value, name: &str) -> Option<String> {
+    json.get(name).and_then(Value::as_str).map(|it| it.to_string())
+}
+
+macro_rules! json {
+    ($t:ty, $payload:expr) => {
+        decode_json::<$t>(stringify!($t), $payload)
+    };
+}
+
+fn decode_payload(topic: &HermesTopic, payload: &[u8]) -> (Option<String>, Content) {
+    use hermes_mqtt::topics::HermesTopic::*;
+
+    let (site_id, content) = match topic {
+        Feedback(FeedbackCommand::Sound(_)) => (None, json!(SiteMessage, payload)),
+        DialogueManager(site_id, command) => (
+            site_id.clone(),
+            match command {
+                DialogueManagerCommand::ToggleOn | DialogueManagerCommand::ToggleOff => empty(payload),
+                DialogueManagerCommand::StartSession => json!(StartSessionMessage, payload),
+                DialogueManagerCommand::ContinueSession => json!(ContinueSessionMessage, payload),
+                DialogueManagerCommand::EndSession => json!(EndSessionMessage, payload),
+                DialogueManagerCommand::SessionQueued => json!(SessionQueuedMessage, payload),
+                DialogueManagerCommand::SessionStarted => json!(SessionStartedMessage, payload),
+                DialogueManagerCommand::SessionEnded => json!(SessionEndedMessage, payload),
+                DialogueManagerCommand::IntentNotRecognized => json!(IntentNotRecognizedMessage, payload),
+                DialogueManagerCommand::Configure => json!(DialogueConfigureMessage, payload),
+            },
+        ),
+        VoiceActivity(site_id, command) => (
+            Some(site_id.clone()),
+            match command {
+                VoiceActivityCommand::VadUp => json!(VadUpMessage, payload),
+                VoiceActivityCommand::VadDown => json!(VadDownMessage, payload),
+            },
+        ),
+        Hotword(site_id, command) => (
+            site_id.clone(),
+            match command {
+                HotwordCommand::ToggleOn | HotwordCommand::ToggleOff => json!(SiteMessage, payload),
+                HotwordCommand::Detected => json!(HotwordDetectedMessage, payload),
+            },
+        ),
+        Asr(site_id, command) => (
+            site_id.clone(),
+            match command {
+                AsrCommand::ToggleOn | AsrCommand::ToggleOff => empty(payload),
+                AsrCommand::StartListening => json!(AsrStartListeningMessage, payload),
+                AsrCommand::StopListening => json!(SiteMessage, payload),
+                AsrCommand::TextCaptured | AsrCommand::PartialTextCaptured => json!(TextCapturedMessage, payload),
+                AsrCommand::Reload => json!(RequestComponentReloadMessage, payload),
+            },
+        ),
+        Tts(site_id, command) => (
+            site_id.clone(),
+            match command {
+                TtsCommand::Say => json!(SayMessage, payload),
+                TtsCommand::SayFinished => json!(SayFinishedMessage, payload),
+                TtsCommand::RegisterSound(_) => binary("RegisterSoundMessage", payload),
+            },
+        ),
+        Nlu(command) => (
+            None,
+            match command {
+                NluCommand::Query => json!(NluQueryMessage, payload),
+                NluCommand::PartialQuery => json!(NluSlotQueryMessage, payload),
+                NluCommand::SlotParsed => json!(NluSlotMessage, payload),
+                NluCommand::IntentParsed => json!(NluIntentMessage, payload),
+                NluCommand::IntentNotRecognized => json!(NluIntentNotRecognizedMessage, payload),
+                NluCommand::Reload => json!(RequestComponentReloadMessage, payload),
+            },
+        ),
+        Intent(intent_name) => (None, decode_intent(intent_name, payload)),
+        AudioServer(site_id, command) => (
+            site_id.clone(),
+            match command {
+                AudioServerCommand::AudioFrame | AudioServerCommand::ReplayResponse => match summarize_wav(payload) {
+                    Ok(summary) => Content::Audio(summary),
+                    Err(reason) => Content::Violation(format!("audio frame is not a valid wav: {}", reason)),
+                },
+                AudioServerCommand::ReplayRequest => json!(ReplayRequestMessage, payload),
+                AudioServerCommand::PlayBytes(_) => binary("PlayBytesMessage", payload),
+                AudioServerCommand::StreamBytes { .. } => binary("StreamBytesMessage", payload),
+                AudioServerCommand::PlayFinished => json!(PlayFinishedMessage, payload),
+                AudioServerCommand::StreamFinished => json!(StreamFinishedMessage, payload),
+                AudioServerCommand::ToggleOn | AudioServerCommand::ToggleOff => json!(SiteMessage, payload),
+            },
+        ),
+        Injection(command) => (
+            None,
+            match command {
+                InjectionCommand::Perform => json!(InjectionRequestMessage, payload),
+                InjectionCommand::Status => json!(InjectionStatusMessage, payload),
+                InjectionCommand::StatusRequest => empty(payload),
+                InjectionCommand::Complete => json!(InjectionCompleteMessage, payload),
+                InjectionCommand::ResetRequest => json!(InjectionResetRequestMessage, payload),
+                InjectionCommand::ResetComplete => json!(InjectionResetCompleteMessage, payload),
+            },
+        ),
+        Component(site_id, _, command) => (
+            site_id.clone(),
+            match (site_id, command) {
+                (_, ComponentCommand::VersionRequest) => empty(payload),
+                (_, ComponentCommand::Version) => json!(VersionMessage, payload),
+                (None, ComponentCommand::Error) => json!(ErrorMessage, payload),
+                (Some(_), ComponentCommand::Error) => json!(SiteErrorMessage, payload),
+                (None, ComponentCommand::Loaded) => json!(ComponentLoadedMessage, payload),
+                (Some(_), ComponentCommand::Loaded) => json!(ComponentLoadedOnSiteMessage, payload),
+            },
+        ),
+    };
+
+    (site_id, content)
+}
+
+fn decode_intent(intent_name: &str, payload: &[u8]) -> Content {
+    let content = json!(IntentMessage, payload);
+    if let Content::Message { ref json, .. } = content {
+        let published_name = json.pointer("/intent/intentName").and_then(Value::as_str);
+        if published_name != Some(intent_name) {
+            return Content::Violation(format!(
+                "intent {:?} published on the topic of intent '{}'",
+                published_name.unwrap_or_default(),
+                intent_name
+            ));
+        }
+    }
+    content
+}
+
+fn decode_json<T>(type_name: &'static str, payload: &[u8]) -> Content
+where
+    T: DeserializeOwned + Serialize,
+{
+    match serde_json::from_slice::<T>(payload) {
+        Ok(message) => match serde_json::to_value(&message) {
+            Ok(json) => Content::Message { type_name, json },
+            Err(e) => Content::Violation(format!("could not re-encode {}: {}", type_name, e)),
+        },
+        Err(e) => Content::Violation(format!("invalid {}: {}", type_name, e)),
+    }
+}
+
+fn empty(payload: &[u8]) -> Content {
+    if payload.is_empty() {
+        Content::Empty
+    } else {
+        Content::Violation(format!(
+            "unexpected payload of {} bytes on a topic without payload",
+            payload.len()
+        ))
+    }
+}
+
+fn binary(type_name: &'static str, payload: &[u8]) -> Content {
+    Content::Binary {
+        type_name,
+        len: payload.len(),
+    }
+}
+
+/// The encoding of the samples of a wav frame, read from its `fmt ` chunk
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SampleFormat {
+    /// Little endian integers of the given number of bytes, unsigned for a single byte
+    Int(usize),
+    /// Little endian IEEE floats of the given number of bytes
+    Float(usize),
+}
+
+impl SampleFormat {
+    const PCM: u16 = 0x0001;
+    const IEEE_FLOAT: u16 = 0x0003;
+    const EXTENSIBLE: u16 = 0xfffe;
+
+    fn parse(chunk: &[u8]) -> Result<SampleFormat, String> {
+        if chunk.len() < 16 {
+            return Err(format!("fmt chunk of {} bytes is too short", chunk.len()));
+        }
+        let mut format = u16::from_le_bytes([chunk[0], chunk[1]]);
+        let bits = u16::from_le_bytes([chunk[14], chunk[15]]);
+        // the actual format of an extensible one starts its sub format GUID
+        if format == Self::EXTENSIBLE && chunk.len() >= 26 {
+            format = u16::from_le_bytes([chunk[24], chunk[25]]);
+        }
+
+        match (format, bits) {
+            (Self::PCM, 8) | (Self::PCM, 16) | (Self::PCM, 24) | (Self::PCM, 32) => {
+                Ok(SampleFormat::Int(bits as usize / 8))
+            }
+            (Self::IEEE_FLOAT, 32) | (Self::IEEE_FLOAT, 64) => Ok(SampleFormat::Float(bits as usize / 8)),
+            _ => Err(format!(
+                "unsupported format 0x{:04x} with {} bits per sample",
+                format, bits
+            )),
+        }
+    }
+
+    fn size(self) -> usize {
+        match self {
+            SampleFormat::Int(size) | SampleFormat::Float(size) => size,
+        }
+    }
+
+    /// The value of a sample relative to the full scale, between -1 and 1
+    fn value(self, sample: &[u8]) -> f64 {
+        match self {
+            SampleFormat::Int(1) => (f64::from(sample[0]) - 128.) / 128.,
+            SampleFormat::Int(size) => {
+                // the sample is put in the high bytes of an i32 to keep its sign
+                let mut bytes = [0; 4];
+                bytes[4 - size..].copy_from_slice(sample);
+                f64::from(i32::from_le_bytes(bytes)) / 2_147_483_648.
+            }
+            SampleFormat::Float(4) => f64::from(f32::from_le_bytes(sample.try_into().unwrap())),
+            SampleFormat::Float(_) => f64::from_le_bytes(sample.try_into().unwrap()),
+        }
+    }
+}
+
+/// Walk the RIFF chunks of a wav frame to extract the `time` metadata and compute the RMS of the
+/// samples of the `data` chunk, encoded as its `fmt ` chunk says
+pub fn summarize_wav(wav: &[u8]) -> Result<AudioSummary, String> {
+    if wav.len() < 12 || &wav[0..4] != b"RIFF" || &wav[8..12] != b"WAVE" {
+        return Err("no RIFF WAVE header".into());
+    }
+
+    let mut summary = AudioSummary {
+        len: wav.len(),
+        timestamp_ms: None,
+        rms: None,
+    };
+    let mut format = None;
+
+    let mut offset = 12;
+    while offset + 8 <= wav.len() {
+        let id = &wav[offset..offset + 4];
+        let size = u32::from_le_bytes(wav[offset + 4..offset + 8].try_into().unwrap()) as usize;
+        let start = offset + 8;
+        let end = start.saturating_add(size).min(wav.len());
+        let chunk = &wav[start..end];
+
+        match id {
+            b"fmt " => format = Some(SampleFormat::parse(chunk)?),
+            b"time" if chunk.len() == 8 => summary.timestamp_ms = Some(u64::from_le_bytes(chunk.try_into().unwrap())),
+            b"data" => match format {
+                Some(format) => summary.rms = rms(chunk, format),
+                None => return Err("data chunk before the fmt chunk".into()),
+            },
+            _ => {}
+        }
+
+        // chunks are aligned on even offsets
+        offset = start.saturating_add(size).saturating_add(size & 1);
+    }
+
+    Ok(summary)
+}
+
+fn rms(samples: &[u8], format: SampleFormat) -> Option<f64> {
+    let count = samples.len() / format.size();
+    if count == 0 {
+        return None;
+    }
+    let sum_of_squares: f64 = samples
+        .chunks_exact(format.size())
+        .map(|it| format.value(it))
+        .map(|it| it * it)
+        .sum();
+    Some((sum_of_squares / count as f64).sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hermes::hermes_utils::Example;
+
+    /// A wav frame of `data` encoded with the wav `format` on `bits` bits
+    fn wav_with_format(format: u16, bits: u16, timestamp: u64, data: &[u8]) -> Vec<u8> {
+        let block_align = bits / 8;
+        let mut wav = vec![];
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&((4 + 24 + 16 + 8 + data.len()) as u32).to_le_bytes());
+        wav.extend_from_slice(b"WAVE");
+        wav.extend_from_slice(b"fmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        wav.extend_from_slice(&format.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes());
+        wav.extend_from_slice(&16_000u32.to_le_bytes());
+        wav.extend_from_slice(&(16_000 * u32::from(block_align)).to_le_bytes());
+        wav.extend_from_slice(&block_align.to_le_bytes());
+        wav.extend_from_slice(&bits.to_le_bytes());
+        wav.extend_from_slice(b"time");
+        wav.extend_from_slice(&8u32.to_le_bytes());
+        wav.extend_from_slice(&timestamp.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        wav.extend_from_slice(data);
+        wav
+    }
+
+    fn wav(timestamp: u64, samples: &[i16]) -> Vec<u8> {
+        let mut data = vec![];
+        for sample in samples {
+            data.extend_from_slice(&sample.to_le_bytes());
+        }
+        wav_with_format(1, 16, timestamp, &data)
+    }
+
+    #[test]
+    fn decodes_intent() {
+        let intent = IntentMessage::full_example();
+        let topic = format!("hermes/intent/{}", intent.intent.intent_name);
+        let event = decode(&topic, &serde_json::to_vec(&intent).unwrap());
+
+        assert_eq!(event.component, Some("intent".into()));
+        assert_eq!(event.site_id, Some(intent.site_id));
+        assert_eq!(event.session_id, Some(intent.session_id));
+        match event.content {
+            Content::Message { type_name, .. } => assert_eq!(type_name, "IntentMessage"),
+            c => panic!("unexpected content {:?}", c),
+        }
+    }
+
+    #[test]
+    fn flags_intent_published_on_wrong_topic() {
+        let intent = IntentMessage::full_example();
+        let event = decode("hermes/intent/notTheRightOne", &serde_json::to_vec(&intent).unwrap());
+
+        match event.content {
+            Content::Violation(_) => {}
+            c => panic!("unexpected content {:?}", c),
+        }
+        // the session is still extracted so that the violation is not filtered out
+        assert_eq!(event.session_id, Some(intent.session_id));
+    }
+
+    #[test]
+    fn flags_invalid_payloads() {
+        let event = decode("hermes/tts/say", b"{\"text\": 42}");
+        match event.content {
+            Content::Violation(_) => {}
+            c => panic!("unexpected content {:?}", c),
+        }
+    }
+
+    #[test]
+    fn flags_unknown_topics() {
+        let event = decode("hermes/foo/bar", b"");
+        assert_eq!(event.component, Some("foo".into()));
+        match event.content {
+            Content::Violation(_) => {}
+            c => panic!("unexpected content {:?}", c),
+        }
+    }
+
+    #[test]
+    fn flags_unexpected_payload() {
+        let event = decode("hermes/asr/toggleOn", b"{}");
+        match event.content {
+            Content::Violation(_) => {}
+            c => panic!("unexpected content {:?}", c),
+        }
+    }
+
+    #[test]
+    fn summarizes_audio_frames() {
+        let event = decode(
+            "hermes/audioServer/kitchen/audioFrame",
+            &wav(1_558_344_008_374, &[16384, -16384]),
+        );
+
+        assert_eq!(event.site_id, Some("kitchen".into()));
+        match event.content {
+            Content::Audio(summary) => {
+                assert_eq!(summary.timestamp_ms, Some(1_558_344_008_374));
+                assert_eq!(summary.rms, Some(0.5));
+                assert!((summary.rms_dbfs().unwrap() + 6.02).abs() < 0.01);
+            }
+            c => panic!("unexpected content {:?}", c),
+        }
+    }
+
+    #[test]
+    fn summarizes_silence() {
+        let summary = summarize_wav(&wav(0, &[0; 256])).unwrap();
+        assert_eq!(summary.rms, Some(0.));
+        assert!(summary.rms_dbfs().unwrap() < -99.9);
+    }
+
+    #[test]
+    fn summarizes_the_samples_as_the_fmt_chunk_says() {
+        let unsigned = wav_with_format(1, 8, 0, &[192, 64]);
+        assert_eq!(summarize_wav(&unsigned).unwrap().rms, Some(0.5));
+
+        let mut data = vec![];
+        for sample in &[0x40_0000i32, -0x40_0000] {
+            data.extend_from_slice(&sample.to_le_bytes()[..3]);
+        }
+        assert_eq!(summarize_wav(&wav_with_format(1, 24, 0, &data)).unwrap().rms, Some(0.5));
+
+        let mut data = vec![];
+        for sample in &[0.25f32, -0.25] {
+            data.extend_from_slice(&sample.to_le_bytes());
+        }
+        assert_eq!(
+            summarize_wav(&wav_with_format(3, 32, 0, &data)).unwrap().rms,
+            Some(0.25)
+        );
+    }
+
+    #[test]
+    fn flags_unsupported_wav_formats() {
+        // 4 bits IMA ADPCM
+        let event = decode(
+            "hermes/audioServer/kitchen/audioFrame",
+            &wav_with_format(0x11, 4, 0, &[0; 16]),
+        );
+        match event.content {
+            Content::Violation(reason) => assert!(reason.contains("unsupported format 0x0011"), "{}", reason),
+            c => panic!("unexpected content {:?}", c),
+        }
+        assert!(summarize_wav(&wav_with_format(3, 16, 0, &[0; 16])).is_err());
+    }
+
+    #[test]
+    fn rejects_samples_without_format() {
+        let mut wav = wav(0, &[0; 4]);
+        // rename the fmt chunk so that it is skipped
+        wav[12..16].copy_from_slice(b"junk");
+        assert!(summarize_wav(&wav).is_err());
+    }
+
+    #[test]
+    fn rejects_non_wav_audio() {
+        assert!(summarize_wav(b"not a wav at all").is_err());
+    }
+}
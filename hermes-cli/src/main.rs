@@ -0,0 +1,82 @@
+mod decode;
+mod sniff;
+
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use failure::Fallible;
+
+use crate::sniff::{Filter, Printer};
+
+fn main() {
+    let matches = App::new("hermes")
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("Tools to work with the hermes protocol")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            SubCommand::with_name("sniff")
+                .about("Decode and print the hermes messages going through a MQTT bus")
+                .arg(
+                    Arg::with_name("bus")
+                        .long("bus")
+                        .short("b")
+                        .takes_value(true)
+                        .default_value("localhost:1883")
+                        .help("Address of the MQTT broker"),
+                )
+                .arg(
+                    Arg::with_name("site")
+                        .long("site")
+                        .takes_value(true)
+                        .help("Only show the messages concerning this site"),
+                )
+                .arg(
+                    Arg::with_name("session")
+                        .long("session")
+                        .takes_value(true)
+                        .help("Only show the messages concerning this session"),
+                )
+                .arg(
+                    Arg::with_name("component")
+                        .long("component")
+                        .takes_value(true)
+                        .help("Only show the messages of this component (asr, dialogueManager, intent...)"),
+                )
+                .arg(
+                    Arg::with_name("no-payload")
+                        .long("no-payload")
+                        .help("Only print one line per message, without the decoded payload"),
+                )
+                .arg(
+                    Arg::with_name("no-color")
+                        .long("no-color")
+                        .help("Do not colorize the output"),
+                ),
+        )
+        .get_matches();
+
+    let result = match matches.subcommand() {
+        ("sniff", Some(matches)) => run_sniff(matches),
+        _ => unreachable!("a subcommand is required"),
+    };
+
+    if let Err(e) = result {
+        eprintln!("Error: {}", e);
+        for cause in e.iter_causes() {
+            eprintln!("Caused by: {}", cause);
+        }
+        std::process::exit(1);
+    }
+}
+
+fn run_sniff(matches: &ArgMatches) -> Fallible<()> {
+    let filter = Filter {
+        site_id: matches.value_of("site").map(|it| it.to_string()),
+        session_id: matches.value_of("session").map(|it| it.to_string()),
+        component: matches.value_of("component").map(|it| it.to_string()),
+    };
+    let printer = Printer {
+        colour: !matches.is_present("no-color"),
+        payloads: !matches.is_present("no-payload"),
+    };
+
+    sniff::sniff(matches.value_of("bus").unwrap(), filter, printer)
+}
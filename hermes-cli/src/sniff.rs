@@ -0,0 +1,184 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use ansi_term::{Colour, Style};
+use failure::Fallible;
+use hermes_mqtt::MqttHermesProtocolHandler;
+
+use crate::decode::{decode, AudioSummary, Content, Event};
+
+/// Restricts the messages printed by the sniffer, a message must match all the criteria set
+#[derive(Debug, Default)]
+pub struct Filter {
+    pub site_id: Option<String>,
+    pub session_id: Option<String>,
+    pub component: Option<String>,
+}
+
+impl Filter {
+    pub fn accepts(&self, event: &Event) -> bool {
+        fn matches(criterion: &Option<String>, value: &Option<String>) -> bool {
+            criterion.is_none() || criterion == value
+        }
+
+        matches(&self.site_id, &event.site_id)
+            && matches(&self.session_id, &event.session_id)
+            && matches(&self.component, &event.component)
+    }
+}
+
+pub struct Printer {
+    pub colour: bool,
+    pub payloads: bool,
+}
+
+/// Colours used for sessions, picked by hashing the session id so that a given session keeps its
+/// colour for its whole life and interleaved sessions remain readable
+const SESSION_PALETTE: [Colour; 6] = [
+    Colour::Cyan,
+    Colour::Green,
+    Colour::Yellow,
+    Colour::Blue,
+    Colour::Purple,
+    Colour::Fixed(208),
+];
+
+impl Printer {
+    pub fn render(&self, time: &str, event: &Event) -> String {
+        let (summary, details) = match &event.content {
+            Content::Empty => (String::new(), None),
+            Content::Message { type_name, json } => (
+                (*type_name).to_string(),
+                if self.payloads {
+                    serde_json::to_string_pretty(json).ok()
+                } else {
+                    None
+                },
+            ),
+            Content::Audio(summary) => (describe_audio(summary), None),
+            Content::Binary { type_name, len } => (format!("{} ({} bytes)", type_name, len), None),
+            Content::Violation(reason) => (format!("PROTOCOL VIOLATION: {}", reason), None),
+        };
+
+        let header = format!(
+            "{} {:<18} {:<10} {} {}",
+            time,
+            event.component.as_ref().map(String::as_str).unwrap_or("?"),
+            event.site_id.as_ref().map(String::as_str).unwrap_or("-"),
+            event.topic,
+            summary
+        )
+        .trim_end()
+        .to_string();
+
+        let mut rendered = self.style(event).paint(header).to_string();
+        if let Some(details) = details {
+            for line in details.lines() {
+                rendered.push_str("\n    ");
+                rendered.push_str(line);
+            }
+        }
+        rendered
+    }
+
+    fn style(&self, event: &Event) -> Style {
+        if !self.colour {
+            return Style::new();
+        }
+        match (&event.content, &event.session_id) {
+            (Content::Violation(_), _) => Colour::Red.bold(),
+            (Content::Audio(_), _) => Style::new().dimmed(),
+            (_, Some(session_id)) => Style::from(session_colour(session_id)),
+            (_, None) => Style::new(),
+        }
+    }
+}
+
+fn session_colour(session_id: &str) -> Colour {
+    let mut hasher = DefaultHasher::new();
+    session_id.hash(&mut hasher);
+    SESSION_PALETTE[(hasher.finish() % SESSION_PALETTE.len() as u64) as usize]
+}
+
+fn describe_audio(summary: &AudioSummary) -> String {
+    let mut description = format!("{} bytes", summary.len);
+    if let Some(timestamp) = summary.timestamp_ms {
+        description.push_str(&format!(", captured at {}", timestamp));
+    }
+    if let Some(dbfs) = summary.rms_dbfs() {
+        description.push_str(&format!(", rms {:.1} dBFS", dbfs));
+    }
+    description
+}
+
+pub fn sniff(bus: &str, filter: Filter, printer: Printer) -> Fallible<()> {
+    let handler = MqttHermesProtocolHandler::new(bus)?;
+
+    handler.subscribe_raw("hermes/#", move |topic, payload| {
+        let event = decode(topic, payload);
+        if filter.accepts(&event) {
+            let time = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+            println!("{}", printer.render(&time, &event));
+        }
+    })?;
+
+    loop {
+        std::thread::park();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(site_id: Option<&str>, session_id: Option<&str>) -> Event {
+        Event {
+            topic: "hermes/asr/toggleOn".into(),
+            component: Some("asr".into()),
+            site_id: site_id.map(|it| it.into()),
+            session_id: session_id.map(|it| it.into()),
+            content: Content::Empty,
+        }
+    }
+
+    #[test]
+    fn filter_accepts_everything_by_default() {
+        assert!(Filter::default().accepts(&event(None, None)));
+        assert!(Filter::default().accepts(&event(Some("kitchen"), Some("session"))));
+    }
+
+    #[test]
+    fn filter_requires_all_criteria() {
+        let filter = Filter {
+            site_id: Some("kitchen".into()),
+            session_id: None,
+            component: Some("asr".into()),
+        };
+        assert!(filter.accepts(&event(Some("kitchen"), None)));
+        assert!(!filter.accepts(&event(Some("bedroom"), None)));
+        assert!(!filter.accepts(&event(None, None)));
+
+        let filter = Filter {
+            component: Some("tts".into()),
+            ..Filter::default()
+        };
+        assert!(!filter.accepts(&event(Some("kitchen"), None)));
+    }
+
+    #[test]
+    fn session_colour_is_stable() {
+        assert_eq!(session_colour("a-session"), session_colour("a-session"));
+    }
+
+    #[test]
+    fn renders_without_colour() {
+        let printer = Printer {
+            colour: false,
+            payloads: true,
+        };
+        assert_eq!(
+            printer.render("12:00:00.000", &event(Some("kitchen"), None)),
+            "12:00:00.000 asr                kitchen    hermes/asr/toggleOn"
+        );
+    }
+}
@@ -0,0 +1,26 @@
+#!/bin/bash
+
+# Check that the subscriptions made through the C API and their user data are released, either
+# under valgrind (the default) or with the address sanitizer (`./run.sh asan`, needs a nightly
+# toolchain)
+
+set -ex
+
+cd "$(dirname "$0")/.."
+
+TARGET_DIR=../target/leaks
+mkdir -p "$TARGET_DIR"
+
+if [ "$1" == "asan" ]; then
+    RUSTFLAGS="-Z sanitizer=address" cargo +nightly build --target x86_64-unknown-linux-gnu
+    LIB_DIR=../target/x86_64-unknown-linux-gnu/debug
+    cc -fsanitize=address -g leaks/subscriptions.c -L "$LIB_DIR" -lhermes_ffi_test -lpthread -ldl -lm \
+        -o "$TARGET_DIR/subscriptions"
+    ASAN_OPTIONS=detect_leaks=1 LD_LIBRARY_PATH="$LIB_DIR" "$TARGET_DIR/subscriptions"
+else
+    cargo build
+    LIB_DIR=../target/debug
+    cc -g leaks/subscriptions.c -L "$LIB_DIR" -lhermes_ffi_test -lpthread -ldl -lm -o "$TARGET_DIR/subscriptions"
+    LD_LIBRARY_PATH="$LIB_DIR" valgrind --leak-check=full --errors-for-leak-kinds=definite --error-exitcode=1 \
+        "$TARGET_DIR/subscriptions"
+fi
@@ -0,0 +1,109 @@
+#define _POSIX_C_SOURCE 199309L
+
+#include "../../platforms/c/libsnips_hermes_full.h"
+#include <stdatomic.h>
+#include <stdio.h>
+#include <time.h>
+
+SNIPS_RESULT hermes_ffi_test_get_last_error(const char **error);
+
+static atomic_int received = 0;
+static atomic_int destroyed = 0;
+static int *expected_user_data = NULL;
+
+#define check(hermes_expr)                                                     \
+  if (hermes_expr != SNIPS_RESULT_OK) {                                        \
+    const char *error = NULL;                                                  \
+    hermes_ffi_test_get_last_error(&error);                                    \
+    printf("Failed to execute %s at %s:%i\nError was %s\n", #hermes_expr,      \
+           __FILE__, __LINE__, error);                                         \
+    exit(1);                                                                   \
+  }
+
+#define expect(condition)                                                      \
+  if (!(condition)) {                                                          \
+    printf("Expectation %s failed at %s:%i\n", #condition, __FILE__,           \
+           __LINE__);                                                          \
+    exit(1);                                                                   \
+  }
+
+static void sleep_ms(long ms) {
+  struct timespec duration = {.tv_sec = ms / 1000,
+                              .tv_nsec = (ms % 1000) * 1000000};
+  nanosleep(&duration, NULL);
+}
+
+// the messages are delivered on another thread, wait for them for up to 5s
+static int wait_for(atomic_int *counter, int value) {
+  for (int i = 0; i < 500 && atomic_load(counter) < value; i++) {
+    sleep_ms(10);
+  }
+  return atomic_load(counter) == value;
+}
+
+static void on_session_started(const CSessionStartedMessage *message,
+                               void *user_data) {
+  if (user_data != expected_user_data) {
+    printf("unexpected user data %p\n", user_data);
+    exit(1);
+  }
+  atomic_fetch_add(&received, 1);
+  hermes_drop_session_started_message(message);
+}
+
+static void destroy_user_data(void *user_data) {
+  free(user_data);
+  atomic_fetch_add(&destroyed, 1);
+}
+
+int main() {
+  expected_user_data = malloc(sizeof(int));
+
+  const CProtocolHandler *handler;
+  check(hermes_protocol_handler_new_in_process(&handler, NULL,
+                                              expected_user_data));
+  check(hermes_protocol_handler_set_user_data_destructor(handler,
+                                                         destroy_user_data));
+
+  const CDialogueFacade *dialogue;
+  check(hermes_protocol_handler_dialogue_facade(handler, &dialogue));
+  const CDialogueBackendFacade *backend;
+  check(hermes_protocol_handler_dialogue_backend_facade(handler, &backend));
+
+  const CSubscription *cancelled;
+  check(hermes_dialogue_subscribe_session_started_with_subscription(
+      dialogue, on_session_started, &cancelled));
+  const CSubscription *kept;
+  check(hermes_dialogue_subscribe_session_started_with_subscription(
+      dialogue, on_session_started, &kept));
+  // this one has no token and lives as long as the handler
+  check(hermes_dialogue_subscribe_session_started(dialogue,
+                                                  on_session_started));
+
+  const CSessionStartedMessage message = {.session_id = "a session id",
+                                          .custom_data = NULL,
+                                          .site_id = "default",
+                                          .reactivated_from_session_id = NULL};
+
+  check(hermes_dialogue_backend_publish_session_started(backend, &message));
+  expect(wait_for(&received, 3));
+
+  check(hermes_unsubscribe(cancelled));
+  check(hermes_drop_subscription(kept));
+
+  check(hermes_dialogue_backend_publish_session_started(backend, &message));
+  expect(wait_for(&received, 5));
+  sleep_ms(200);
+  expect(atomic_load(&received) == 5);
+
+  check(hermes_drop_dialogue_facade(dialogue));
+  check(hermes_drop_dialogue_backend_facade(backend));
+  expect(atomic_load(&destroyed) == 0);
+
+  check(hermes_destroy_in_process_protocol_handler(
+      (CProtocolHandler *)handler));
+  expect(wait_for(&destroyed, 1));
+
+  printf("ok\n");
+  return 0;
+}
@@ -100,3 +100,19 @@ round_trip_json!(
     hermes_ffi_test_round_trip_text_captured_json,
     hermes::TextCapturedMessage
 );
+
+round_trip_json!(hermes_ffi_test_round_trip_vad_up_json, hermes::VadUpMessage);
+
+round_trip_json!(hermes_ffi_test_round_trip_vad_down_json, hermes::VadDownMessage);
+
+round_trip_json!(hermes_ffi_test_round_trip_stream_bytes_json, hermes::StreamBytesMessage);
+
+round_trip_json!(
+    hermes_ffi_test_round_trip_stream_finished_json,
+    hermes::StreamFinishedMessage
+);
+
+round_trip_json!(
+    hermes_ffi_test_round_trip_replay_request_json,
+    hermes::ReplayRequestMessage
+);
@@ -8,6 +8,8 @@ use libc;
 
 generate_error_handling!(hermes_ffi_test_get_last_error);
 
+generate_struct_version_c_symbol!();
+
 #[cfg(feature = "json")]
 mod json_round_trips;
 
@@ -200,6 +202,14 @@ pub extern "C" fn hermes_ffi_test_round_trip_asr_token_double_array(
     wrap!(round_trip(input, output))
 }
 
+#[no_mangle]
+pub extern "C" fn hermes_ffi_test_round_trip_speaker_id_array(
+    input: *const hermes_ffi::CSpeakerIdArray,
+    output: *mut *const hermes_ffi::CSpeakerIdArray,
+) -> ffi_utils::SNIPS_RESULT {
+    wrap!(round_trip(input, output))
+}
+
 #[no_mangle]
 pub extern "C" fn hermes_ffi_test_round_trip_text_captured(
     input: *const hermes_ffi::CTextCapturedMessage,
@@ -224,6 +234,46 @@ pub extern "C" fn hermes_ffi_test_round_trip_nlu_intent_alternative_array(
     wrap!(round_trip(input, output))
 }
 
+#[no_mangle]
+pub extern "C" fn hermes_ffi_test_round_trip_vad_up(
+    input: *const hermes_ffi::CVadUpMessage,
+    output: *mut *const hermes_ffi::CVadUpMessage,
+) -> ffi_utils::SNIPS_RESULT {
+    wrap!(round_trip(input, output))
+}
+
+#[no_mangle]
+pub extern "C" fn hermes_ffi_test_round_trip_vad_down(
+    input: *const hermes_ffi::CVadDownMessage,
+    output: *mut *const hermes_ffi::CVadDownMessage,
+) -> ffi_utils::SNIPS_RESULT {
+    wrap!(round_trip(input, output))
+}
+
+#[no_mangle]
+pub extern "C" fn hermes_ffi_test_round_trip_stream_bytes(
+    input: *const hermes_ffi::CStreamBytesMessage,
+    output: *mut *const hermes_ffi::CStreamBytesMessage,
+) -> ffi_utils::SNIPS_RESULT {
+    wrap!(round_trip(input, output))
+}
+
+#[no_mangle]
+pub extern "C" fn hermes_ffi_test_round_trip_stream_finished(
+    input: *const hermes_ffi::CStreamFinishedMessage,
+    output: *mut *const hermes_ffi::CStreamFinishedMessage,
+) -> ffi_utils::SNIPS_RESULT {
+    wrap!(round_trip(input, output))
+}
+
+#[no_mangle]
+pub extern "C" fn hermes_ffi_test_round_trip_replay_request(
+    input: *const hermes_ffi::CReplayRequestMessage,
+    output: *mut *const hermes_ffi::CReplayRequestMessage,
+) -> ffi_utils::SNIPS_RESULT {
+    wrap!(round_trip(input, output))
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn hermes_ffi_test_destroy_string(string: *mut libc::c_char) -> SNIPS_RESULT {
     wrap!(std::ffi::CString::from_raw_pointer(string))
@@ -255,6 +305,13 @@ pub unsafe extern "C" fn hermes_ffi_test_destroy_asr_token_double_array(
     wrap!(hermes_ffi::CAsrTokenDoubleArray::drop_raw_pointer(input))
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn hermes_ffi_test_destroy_speaker_id_array(
+    input: *mut hermes_ffi::CSpeakerIdArray,
+) -> SNIPS_RESULT {
+    wrap!(hermes_ffi::CSpeakerIdArray::drop_raw_pointer(input))
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn hermes_ffi_test_destroy_nlu_intent_alternative(
     input: *mut hermes_ffi::CNluIntentAlternative,
@@ -5,6 +5,8 @@ mod protocol_handler;
 #[cfg(feature = "structures")]
 pub use crate::ontology::*;
 pub use crate::protocol_handler::*;
+#[doc(hidden)]
+pub use paste;
 
 pub fn init_debug_logs() -> failure::Fallible<()> {
     env_logger::try_init()?;
@@ -1,13 +1,13 @@
-use std::slice;
-
 use failure::Fallible;
-use failure::ResultExt;
 use ffi_utils::*;
+use hermes_utils_derive::CRepr;
 
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, CRepr)]
+#[target_type(hermes::PlayBytesMessage)]
 pub struct CPlayBytesMessage {
     pub id: *const libc::c_char,
+    #[c_bytes(wav_bytes_len)]
     pub wav_bytes: *const u8,
     // Note: we can't use `libc::size_t` because it's not supported by JNA
     pub wav_bytes_len: libc::c_int,
@@ -22,44 +22,11 @@ impl CPlayBytesMessage {
     }
 }
 
-impl CReprOf<hermes::PlayBytesMessage> for CPlayBytesMessage {
-    fn c_repr_of(input: hermes::PlayBytesMessage) -> Fallible<Self> {
-        Ok(Self {
-            id: convert_to_c_string!(input.id),
-            wav_bytes_len: input.wav_bytes.len() as libc::c_int,
-            wav_bytes: Box::into_raw(input.wav_bytes.into_boxed_slice()) as *const u8,
-            site_id: convert_to_c_string!(input.site_id),
-        })
-    }
-}
-
-impl AsRust<hermes::PlayBytesMessage> for CPlayBytesMessage {
-    fn as_rust(&self) -> Fallible<hermes::PlayBytesMessage> {
-        Ok(hermes::PlayBytesMessage {
-            id: create_rust_string_from!(self.id),
-            wav_bytes: unsafe { slice::from_raw_parts(self.wav_bytes as *const u8, self.wav_bytes_len as usize) }
-                .to_vec(),
-            site_id: create_rust_string_from!(self.site_id),
-        })
-    }
-}
-
-impl Drop for CPlayBytesMessage {
-    fn drop(&mut self) {
-        take_back_c_string!(self.id);
-        let _ = unsafe {
-            Box::from_raw(slice::from_raw_parts_mut(
-                self.wav_bytes as *mut u8,
-                self.wav_bytes_len as usize,
-            ))
-        };
-        take_back_c_string!(self.site_id);
-    }
-}
-
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, CRepr)]
+#[target_type(hermes::AudioFrameMessage)]
 pub struct CAudioFrameMessage {
+    #[c_bytes(wav_frame_len)]
     pub wav_frame: *const u8,
     // Note: we can't use `libc::size_t` because it's not supported by JNA
     pub wav_frame_len: libc::c_int,
@@ -74,74 +41,121 @@ impl CAudioFrameMessage {
     }
 }
 
-impl CReprOf<hermes::AudioFrameMessage> for CAudioFrameMessage {
-    fn c_repr_of(input: hermes::AudioFrameMessage) -> Fallible<Self> {
-        Ok(Self {
-            wav_frame_len: input.wav_frame.len() as libc::c_int,
-            wav_frame: Box::into_raw(input.wav_frame.into_boxed_slice()) as *const u8,
-            site_id: convert_to_c_string!(input.site_id),
-        })
-    }
+#[repr(C)]
+#[derive(Debug, CRepr)]
+#[target_type(hermes::PlayFinishedMessage)]
+pub struct CPlayFinishedMessage {
+    pub id: *const libc::c_char,
+    pub site_id: *const libc::c_char,
 }
 
-impl AsRust<hermes::AudioFrameMessage> for CAudioFrameMessage {
-    fn as_rust(&self) -> Fallible<hermes::AudioFrameMessage> {
-        Ok(hermes::AudioFrameMessage {
-            wav_frame: unsafe { slice::from_raw_parts(self.wav_frame as *const u8, self.wav_frame_len as usize) }
-                .to_vec(),
-            site_id: create_rust_string_from!(self.site_id),
-        })
+unsafe impl Sync for CPlayFinishedMessage {}
+
+impl CPlayFinishedMessage {
+    pub fn from(input: hermes::PlayFinishedMessage) -> Fallible<Self> {
+        Self::c_repr_of(input)
     }
 }
 
-impl Drop for CAudioFrameMessage {
-    fn drop(&mut self) {
-        let _ = unsafe {
-            Box::from_raw(slice::from_raw_parts_mut(
-                self.wav_frame as *mut u8,
-                self.wav_frame_len as usize,
-            ))
-        };
-        take_back_c_string!(self.site_id);
+/// The chunk is exposed as a pointer and a length, as the wav of `CPlayBytesMessage`, so that the
+/// bindings can wrap it without copying it
+#[repr(C)]
+#[derive(Debug, CRepr)]
+#[target_type(hermes::StreamBytesMessage)]
+pub struct CStreamBytesMessage {
+    pub stream_id: *const libc::c_char,
+    #[c_bytes(bytes_len)]
+    pub bytes: *const u8,
+    // Note: we can't use `libc::size_t` because it's not supported by JNA
+    pub bytes_len: libc::c_int,
+    pub site_id: *const libc::c_char,
+    pub chunk_number: u32,
+    /// A boolean to indicate if this is the last chunk of the stream, true = 1, false = 0
+    #[c_bool]
+    pub is_last_chunk: libc::c_uchar,
+}
+
+unsafe impl Sync for CStreamBytesMessage {}
+
+impl CStreamBytesMessage {
+    pub fn from(input: hermes::StreamBytesMessage) -> Fallible<Self> {
+        Self::c_repr_of(input)
     }
 }
 
 #[repr(C)]
-#[derive(Debug)]
-pub struct CPlayFinishedMessage {
+#[derive(Debug, CRepr)]
+#[target_type(hermes::StreamFinishedMessage)]
+pub struct CStreamFinishedMessage {
     pub id: *const libc::c_char,
     pub site_id: *const libc::c_char,
 }
 
-unsafe impl Sync for CPlayFinishedMessage {}
+unsafe impl Sync for CStreamFinishedMessage {}
 
-impl CPlayFinishedMessage {
-    pub fn from(input: hermes::PlayFinishedMessage) -> Fallible<Self> {
+impl CStreamFinishedMessage {
+    pub fn from(input: hermes::StreamFinishedMessage) -> Fallible<Self> {
         Self::c_repr_of(input)
     }
 }
 
-impl CReprOf<hermes::PlayFinishedMessage> for CPlayFinishedMessage {
-    fn c_repr_of(input: hermes::PlayFinishedMessage) -> Fallible<Self> {
-        Ok(Self {
-            id: convert_to_c_string!(input.id),
-            site_id: convert_to_c_string!(input.site_id),
-        })
-    }
+#[repr(C)]
+#[derive(Debug, CRepr)]
+#[target_type(hermes::ReplayRequestMessage)]
+pub struct CReplayRequestMessage {
+    pub request_id: *const libc::c_char,
+    pub start_at_ms: i64,
+    pub site_id: *const libc::c_char,
 }
 
-impl AsRust<hermes::PlayFinishedMessage> for CPlayFinishedMessage {
-    fn as_rust(&self) -> Fallible<hermes::PlayFinishedMessage> {
-        Ok(hermes::PlayFinishedMessage {
-            id: create_rust_string_from!(self.id),
-            site_id: create_rust_string_from!(self.site_id),
-        })
+unsafe impl Sync for CReplayRequestMessage {}
+
+impl CReplayRequestMessage {
+    pub fn from(input: hermes::ReplayRequestMessage) -> Fallible<Self> {
+        Self::c_repr_of(input)
     }
 }
 
-impl Drop for CPlayFinishedMessage {
-    fn drop(&mut self) {
-        take_back_c_string!(self.id);
-        take_back_c_string!(self.site_id);
+#[cfg(test)]
+mod tests {
+    use super::super::tests::round_trip_test;
+    use super::*;
+    use hermes::hermes_utils::Example;
+
+    #[test]
+    fn round_trip_play_bytes() {
+        round_trip_test::<_, CPlayBytesMessage>(hermes::PlayBytesMessage::minimal_example());
+        round_trip_test::<_, CPlayBytesMessage>(hermes::PlayBytesMessage::full_example());
+    }
+
+    #[test]
+    fn round_trip_stream_bytes() {
+        round_trip_test::<_, CStreamBytesMessage>(hermes::StreamBytesMessage::minimal_example());
+        round_trip_test::<_, CStreamBytesMessage>(hermes::StreamBytesMessage::full_example());
+        for is_last_chunk in &[true, false] {
+            round_trip_test::<_, CStreamBytesMessage>(hermes::StreamBytesMessage {
+                is_last_chunk: *is_last_chunk,
+                ..hermes::StreamBytesMessage::full_example()
+            });
+        }
+    }
+
+    #[test]
+    fn stream_bytes_last_chunk_is_one() {
+        let mut message = hermes::StreamBytesMessage::full_example();
+        message.is_last_chunk = true;
+        assert_eq!(CStreamBytesMessage::from(message).unwrap().is_last_chunk, 1);
+    }
+
+    #[test]
+    fn round_trip_stream_finished() {
+        round_trip_test::<_, CStreamFinishedMessage>(hermes::StreamFinishedMessage::minimal_example());
+        round_trip_test::<_, CStreamFinishedMessage>(hermes::StreamFinishedMessage::full_example());
+    }
+
+    #[test]
+    fn round_trip_replay_request() {
+        round_trip_test::<_, CReplayRequestMessage>(hermes::ReplayRequestMessage::minimal_example());
+        round_trip_test::<_, CReplayRequestMessage>(hermes::ReplayRequestMessage::full_example());
     }
 }
@@ -1,21 +1,23 @@
 use std::ptr::null;
-use std::slice;
 
 use failure::format_err;
 use failure::Fallible;
 use failure::ResultExt;
 use ffi_utils::*;
+use hermes_utils_derive::CRepr;
 
-use crate::ontology::asr::CAsrTokenDoubleArray;
+use crate::ontology::asr::{CAsrTokenDoubleArray, CSpeakerIdArray};
 use crate::ontology::nlu::{CNluIntentClassifierResult, CNluSlotArray};
 use crate::CNluIntentAlternativeArray;
 
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, CRepr)]
+#[target_type(hermes::IntentMessage)]
 pub struct CIntentMessage {
     /// The session identifier in which this intent was detected
     pub session_id: *const libc::c_char,
     /// Nullable, the custom data that was given at the session creation
+    #[nullable]
     pub custom_data: *const libc::c_char,
     /// The site where the intent was detected.
     pub site_id: *const libc::c_char,
@@ -24,16 +26,24 @@ pub struct CIntentMessage {
     /// The result of the intent classification
     pub intent: *const CNluIntentClassifierResult,
     /// Nullable, the detected slots, if any
+    #[null_if_empty]
     pub slots: *const CNluSlotArray,
     /// Nullable, alternatives intent resolutions
+    #[nullable]
     pub alternatives: *const CNluIntentAlternativeArray,
-    ///// Nullable
-    //pub speaker_hypotheses: *const CSpeakerIdArray,
     /// Nullable, the tokens detected by the ASR, the first array level represents the asr
     /// invocation, the second one the tokens
+    #[nullable]
     pub asr_tokens: *const CAsrTokenDoubleArray,
     /// Confidence of the asr capture, this value is optional. Any value not in [0,1] should be ignored.
+    #[c_repr_with(asr_confidence_c_repr)]
+    #[as_rust_with(asr_confidence_as_rust)]
     pub asr_confidence: libc::c_float,
+    /// Nullable, only read from the structures given by the C side from the version 2 of the
+    /// structures, see `hermes_set_struct_version`
+    #[nullable]
+    #[struct_version(2)]
+    pub speaker_hypotheses: *const CSpeakerIdArray,
 }
 
 unsafe impl Sync for CIntentMessage {}
@@ -44,183 +54,46 @@ impl CIntentMessage {
     }
 }
 
-impl CReprOf<hermes::IntentMessage> for CIntentMessage {
-    fn c_repr_of(input: hermes::IntentMessage) -> Fallible<Self> {
-        Ok(Self {
-            session_id: convert_to_c_string!(input.session_id),
-            custom_data: convert_to_nullable_c_string!(input.custom_data),
-            site_id: convert_to_c_string!(input.site_id),
-            input: convert_to_c_string!(input.input),
-            intent: CNluIntentClassifierResult::c_repr_of(input.intent)?.into_raw_pointer(),
-            slots: if !input.slots.is_empty() {
-                CNluSlotArray::c_repr_of(input.slots)?.into_raw_pointer()
-            } else {
-                null()
-            },
-            alternatives: if let Some(alternatives) = input.alternatives {
-                CNluIntentAlternativeArray::c_repr_of(alternatives)?.into_raw_pointer()
-            } else {
-                null()
-            },
-            /*speaker_hypotheses: if let Some(speaker_hypotheses) = input.speaker_hypotheses {
-                CSpeakerIdArray::c_repr_of(speaker_hypotheses)?.into_raw_pointer()
-            } else {
-                null()
-            },*/
-            asr_tokens: if let Some(asr_tokens) = input.asr_tokens {
-                CAsrTokenDoubleArray::c_repr_of(asr_tokens)?.into_raw_pointer()
-            } else {
-                null()
-            },
-            asr_confidence: if let Some(asr_confidence) = input.asr_confidence {
-                asr_confidence
-            } else {
-                -1.0
-            },
-        })
-    }
+fn asr_confidence_c_repr(asr_confidence: Option<f32>) -> Fallible<libc::c_float> {
+    Ok(asr_confidence.unwrap_or(-1.0))
 }
 
-impl AsRust<hermes::IntentMessage> for CIntentMessage {
-    fn as_rust(&self) -> Fallible<hermes::IntentMessage> {
-        Ok(hermes::IntentMessage {
-            session_id: create_rust_string_from!(self.session_id),
-            custom_data: create_optional_rust_string_from!(self.custom_data),
-            site_id: create_rust_string_from!(self.site_id),
-            input: create_rust_string_from!(self.input),
-            speaker_hypotheses: None,
-            /* match unsafe { self.speaker_hypotheses.as_ref() } {
-                Some(speaker_hypotheses) => {
-                    Some(unsafe { CSpeakerIdArray::raw_borrow(speaker_hypotheses)? }.as_rust()?)
-                }
-                None => None,
-            }*/
-            asr_tokens: if self.asr_tokens.is_null() {
-                None
-            } else {
-                Some(unsafe { CAsrTokenDoubleArray::raw_borrow(self.asr_tokens) }?.as_rust()?)
-            },
-            asr_confidence: if self.asr_confidence >= 0.0 && self.asr_confidence <= 1.0 {
-                Some(self.asr_confidence)
-            } else {
-                None
-            },
-            intent: unsafe { CNluIntentClassifierResult::raw_borrow(self.intent) }?.as_rust()?,
-            slots: if !self.slots.is_null() {
-                unsafe { CNluSlotArray::raw_borrow(self.slots) }?.as_rust()?
-            } else {
-                vec![]
-            },
-            alternatives: if !self.alternatives.is_null() {
-                Some(unsafe { CNluIntentAlternativeArray::raw_borrow(self.alternatives) }?.as_rust()?)
-            } else {
-                None
-            },
-        })
-    }
-}
-
-impl Drop for CIntentMessage {
-    fn drop(&mut self) {
-        take_back_c_string!(self.session_id);
-        take_back_nullable_c_string!(self.custom_data);
-        take_back_c_string!(self.site_id);
-        take_back_c_string!(self.input);
-        let _ = unsafe { CNluIntentClassifierResult::drop_raw_pointer(self.intent) };
-        if !self.slots.is_null() {
-            let _ = unsafe { CNluSlotArray::drop_raw_pointer(self.slots) };
-        }
-        if !self.asr_tokens.is_null() {
-            let _ = unsafe { CAsrTokenDoubleArray::drop_raw_pointer(self.asr_tokens) };
-        }
-        if !self.alternatives.is_null() {
-            let _ = unsafe { CNluIntentAlternativeArray::drop_raw_pointer(self.alternatives) };
-        }
-        /*if !self.speaker_hypotheses.is_null() {
-            let _ = unsafe { CSpeakerIdArray::drop_raw_pointer(self.speaker_hypotheses) };
-        }*/
-    }
+fn asr_confidence_as_rust(asr_confidence: &libc::c_float) -> Fallible<Option<f32>> {
+    Ok(if *asr_confidence >= 0.0 && *asr_confidence <= 1.0 {
+        Some(*asr_confidence)
+    } else {
+        None
+    })
 }
 
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, CRepr)]
+#[target_type(hermes::IntentNotRecognizedMessage)]
 pub struct CIntentNotRecognizedMessage {
     /// The site where no intent was recognized
     pub site_id: *const libc::c_char,
     /// The session in which no intent was recognized
     pub session_id: *const libc::c_char,
     /// Nullable, the text that didn't match any intent
+    #[nullable]
     pub input: *const libc::c_char,
     /// Nullable, the custom data that was given at the session creation
+    #[nullable]
     pub custom_data: *const libc::c_char,
     /// Nullable, alternatives intent resolutions
+    #[nullable]
     pub alternatives: *const CNluIntentAlternativeArray,
-    ///// Nullable
-    //pub speaker_hypotheses: *const CSpeakerIdArray,
     /// Expresses the confidence that no intent was found
     pub confidence_score: libc::c_float,
+    /// Nullable, only read from the structures given by the C side from the version 2 of the
+    /// structures, see `hermes_set_struct_version`
+    #[nullable]
+    #[struct_version(2)]
+    pub speaker_hypotheses: *const CSpeakerIdArray,
 }
 
 unsafe impl Sync for CIntentNotRecognizedMessage {}
 
-impl CReprOf<hermes::IntentNotRecognizedMessage> for CIntentNotRecognizedMessage {
-    fn c_repr_of(input: hermes::IntentNotRecognizedMessage) -> Fallible<Self> {
-        Ok(Self {
-            site_id: convert_to_c_string!(input.site_id),
-            session_id: convert_to_c_string!(input.session_id),
-            input: convert_to_nullable_c_string!(input.input),
-            alternatives: if let Some(alternatives) = input.alternatives {
-                CNluIntentAlternativeArray::c_repr_of(alternatives)?.into_raw_pointer()
-            } else {
-                null()
-            },
-            /*speaker_hypotheses: if let Some(speaker_hypotheses) = input.speaker_hypotheses {
-                CSpeakerIdArray::c_repr_of(speaker_hypotheses)?.into_raw_pointer()
-            } else {
-                null()
-            },*/
-            custom_data: convert_to_nullable_c_string!(input.custom_data),
-            confidence_score: input.confidence_score,
-        })
-    }
-}
-
-impl AsRust<hermes::IntentNotRecognizedMessage> for CIntentNotRecognizedMessage {
-    fn as_rust(&self) -> Fallible<hermes::IntentNotRecognizedMessage> {
-        Ok(hermes::IntentNotRecognizedMessage {
-            site_id: create_rust_string_from!(self.site_id),
-            session_id: create_rust_string_from!(self.session_id),
-            input: create_optional_rust_string_from!(self.input),
-            speaker_hypotheses: None,
-            /* match unsafe { self.speaker_hypotheses.as_ref() } {
-                Some(speaker_hypotheses) => {
-                    Some(unsafe { CSpeakerIdArray::raw_borrow(speaker_hypotheses)? }.as_rust()?)
-                }
-                None => None,
-            }*/
-            custom_data: create_optional_rust_string_from!(self.custom_data),
-            alternatives: if !self.alternatives.is_null() {
-                Some(unsafe { CNluIntentAlternativeArray::raw_borrow(self.alternatives) }?.as_rust()?)
-            } else {
-                None
-            },
-            confidence_score: self.confidence_score,
-        })
-    }
-}
-
-impl Drop for CIntentNotRecognizedMessage {
-    fn drop(&mut self) {
-        take_back_c_string!(self.site_id);
-        take_back_c_string!(self.session_id);
-        take_back_nullable_c_string!(self.input);
-        take_back_nullable_c_string!(self.custom_data);
-        /*if !self.speaker_hypotheses.is_null() {
-            let _ = unsafe { CSpeakerIdArray::drop_raw_pointer(self.speaker_hypotheses) };
-        }*/
-    }
-}
-
 #[repr(C)]
 #[derive(Debug, PartialEq)]
 pub enum SNIPS_SESSION_INIT_TYPE {
@@ -303,8 +176,8 @@ pub struct CSessionInit {
     value: *const libc::c_void,
 }
 
-impl CSessionInit {
-    fn from(init: hermes::SessionInit) -> Fallible<Self> {
+impl CReprOf<hermes::SessionInit> for CSessionInit {
+    fn c_repr_of(init: hermes::SessionInit) -> Fallible<Self> {
         let init_type = SNIPS_SESSION_INIT_TYPE::from(&init);
         let value: *const libc::c_void = match init {
             hermes::SessionInit::Action {
@@ -322,8 +195,10 @@ impl CSessionInit {
         };
         Ok(Self { init_type, value })
     }
+}
 
-    fn to_session_init(&self) -> Fallible<hermes::SessionInit> {
+impl AsRust<hermes::SessionInit> for CSessionInit {
+    fn as_rust(&self) -> Fallible<hermes::SessionInit> {
         match self.init_type {
             SNIPS_SESSION_INIT_TYPE::SNIPS_SESSION_INIT_TYPE_ACTION => {
                 unsafe { (self.value as *const CActionSessionInit).as_ref() }
@@ -353,16 +228,19 @@ impl Drop for CSessionInit {
 }
 
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, CRepr)]
+#[target_type(hermes::StartSessionMessage)]
 pub struct CStartSessionMessage {
     /// The way this session should be created
     pub init: CSessionInit,
     /// An optional string that will be given back in `CIntentMessage`,
     /// `CIntentNotRecognizedMessage`, `CSessionQueuedMessage`, `CSessionStartedMessage` and
     /// `CSessionEndedMessage` that are related to this session
+    #[nullable]
     pub custom_data: *const libc::c_char,
     /// The site where the session should be started, a null value will be interpreted as the
     /// default one
+    #[nullable]
     pub site_id: *const libc::c_char,
 }
 
@@ -378,45 +256,21 @@ impl CStartSessionMessage {
     }
 }
 
-impl CReprOf<hermes::StartSessionMessage> for CStartSessionMessage {
-    fn c_repr_of(input: hermes::StartSessionMessage) -> Fallible<Self> {
-        Ok(Self {
-            init: CSessionInit::from(input.init)?,
-            custom_data: convert_to_nullable_c_string!(input.custom_data),
-            site_id: convert_to_nullable_c_string!(input.site_id),
-        })
-    }
-}
-
-impl AsRust<hermes::StartSessionMessage> for CStartSessionMessage {
-    fn as_rust(&self) -> Fallible<hermes::StartSessionMessage> {
-        Ok(hermes::StartSessionMessage {
-            init: self.init.to_session_init()?,
-            custom_data: create_optional_rust_string_from!(self.custom_data),
-            site_id: create_optional_rust_string_from!(self.site_id),
-        })
-    }
-}
-
-impl Drop for CStartSessionMessage {
-    fn drop(&mut self) {
-        take_back_nullable_c_string!(self.custom_data);
-        take_back_nullable_c_string!(self.site_id);
-    }
-}
-
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, CRepr)]
+#[target_type(hermes::SessionStartedMessage)]
 pub struct CSessionStartedMessage {
     /// The id of the session that was started
     pub session_id: *const libc::c_char,
     /// Nullable, the custom data that was given at the creation of the session
+    #[nullable]
     pub custom_data: *const libc::c_char,
     /// The site on which this session was started
     pub site_id: *const libc::c_char,
     /// Nullable, this field indicates this session is a reactivation of a previously ended session.
     /// This is for example provided when the user continues talking to the platform without saying
     /// the hotword again after a session was ended.
+    #[nullable]
     pub reactivated_from_session_id: *const libc::c_char,
 }
 
@@ -428,43 +282,14 @@ impl CSessionStartedMessage {
     }
 }
 
-impl CReprOf<hermes::SessionStartedMessage> for CSessionStartedMessage {
-    fn c_repr_of(input: hermes::SessionStartedMessage) -> Fallible<Self> {
-        Ok(Self {
-            session_id: convert_to_c_string!(input.session_id),
-            custom_data: convert_to_nullable_c_string!(input.custom_data),
-            site_id: convert_to_c_string!(input.site_id),
-            reactivated_from_session_id: convert_to_nullable_c_string!(input.reactivated_from_session_id),
-        })
-    }
-}
-
-impl AsRust<hermes::SessionStartedMessage> for CSessionStartedMessage {
-    fn as_rust(&self) -> Fallible<hermes::SessionStartedMessage> {
-        Ok(hermes::SessionStartedMessage {
-            session_id: create_rust_string_from!(self.session_id),
-            custom_data: create_optional_rust_string_from!(self.custom_data),
-            site_id: create_rust_string_from!(self.site_id),
-            reactivated_from_session_id: create_optional_rust_string_from!(self.reactivated_from_session_id),
-        })
-    }
-}
-
-impl Drop for CSessionStartedMessage {
-    fn drop(&mut self) {
-        take_back_c_string!(self.session_id);
-        take_back_nullable_c_string!(self.custom_data);
-        take_back_c_string!(self.site_id);
-        take_back_nullable_c_string!(self.reactivated_from_session_id);
-    }
-}
-
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, CRepr)]
+#[target_type(hermes::SessionQueuedMessage)]
 pub struct CSessionQueuedMessage {
     /// The id of the session that was queued
     pub session_id: *const libc::c_char,
     /// Nullable, the custom data that was given at the creation of the session
+    #[nullable]
     pub custom_data: *const libc::c_char,
     /// The site on which this session was queued
     pub site_id: *const libc::c_char,
@@ -478,57 +303,34 @@ impl CSessionQueuedMessage {
     }
 }
 
-impl CReprOf<hermes::SessionQueuedMessage> for CSessionQueuedMessage {
-    fn c_repr_of(input: hermes::SessionQueuedMessage) -> Fallible<Self> {
-        Ok(Self {
-            session_id: convert_to_c_string!(input.session_id),
-            custom_data: convert_to_nullable_c_string!(input.custom_data),
-            site_id: convert_to_c_string!(input.site_id),
-        })
-    }
-}
-
-impl AsRust<hermes::SessionQueuedMessage> for CSessionQueuedMessage {
-    fn as_rust(&self) -> Fallible<hermes::SessionQueuedMessage> {
-        Ok(hermes::SessionQueuedMessage {
-            session_id: create_rust_string_from!(self.session_id),
-            custom_data: create_optional_rust_string_from!(self.custom_data),
-            site_id: create_rust_string_from!(self.site_id),
-        })
-    }
-}
-
-impl Drop for CSessionQueuedMessage {
-    fn drop(&mut self) {
-        take_back_c_string!(self.session_id);
-        take_back_nullable_c_string!(self.custom_data);
-        take_back_c_string!(self.site_id);
-    }
-}
-
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, CRepr)]
+#[target_type(hermes::ContinueSessionMessage)]
 pub struct CContinueSessionMessage {
     /// The id of the session this action applies to
     pub session_id: *const libc::c_char,
     /// The text to say to the user
     pub text: *const libc::c_char,
     /// Nullable, an optional list of intent name to restrict the parsing of the user response to
+    #[nullable]
     pub intent_filter: *const CStringArray,
     /// Nullable, an optional piece of data that will be given back in `CIntentMessage`,
     /// `CIntentNotRecognizedMessage` and `CSessionEndedMessage` that are related
     /// to this session. If set it will replace any existing custom data previously set on this
     /// session
+    #[nullable]
     pub custom_data: *const libc::c_char,
     /// Nullable,  An optional string, requires `intent_filter` to contain a single value. If set,
     /// the dialogue engine will not run the the intent classification on the user response and go
     /// straight to slot filling, assuming the intent is the one passed in the `intent_filter`, and
     /// searching the value of the given slot
+    #[nullable]
     pub slot: *const libc::c_char,
     /// A boolean to indicate whether the dialogue manager should handle not recognized
     /// intents by itself or sent them as a `CIntentNotRecognizedMessage` for the client to handle.
     /// This setting applies only to the next conversation turn. The default value is false (and
     /// the dialogue manager will handle non recognized intents by itself) true = 1, false = 0
+    #[c_bool]
     pub send_intent_not_recognized: libc::c_uchar,
 }
 
@@ -544,51 +346,14 @@ impl CContinueSessionMessage {
     }
 }
 
-impl CReprOf<hermes::ContinueSessionMessage> for CContinueSessionMessage {
-    fn c_repr_of(input: hermes::ContinueSessionMessage) -> Fallible<Self> {
-        Ok(Self {
-            session_id: convert_to_c_string!(input.session_id),
-            text: convert_to_c_string!(input.text),
-            intent_filter: convert_to_nullable_c_string_array!(input.intent_filter),
-            custom_data: convert_to_nullable_c_string!(input.custom_data),
-            slot: convert_to_nullable_c_string!(input.slot),
-            send_intent_not_recognized: if input.send_intent_not_recognized { 1 } else { 0 },
-        })
-    }
-}
-
-impl AsRust<hermes::ContinueSessionMessage> for CContinueSessionMessage {
-    fn as_rust(&self) -> Fallible<hermes::ContinueSessionMessage> {
-        Ok(hermes::ContinueSessionMessage {
-            session_id: create_rust_string_from!(self.session_id),
-            text: create_rust_string_from!(self.text),
-            intent_filter: match unsafe { self.intent_filter.as_ref() } {
-                Some(it) => Some(it.as_rust()?),
-                None => None,
-            },
-            custom_data: create_optional_rust_string_from!(self.custom_data),
-            slot: create_optional_rust_string_from!(self.slot),
-            send_intent_not_recognized: self.send_intent_not_recognized == 1,
-        })
-    }
-}
-
-impl Drop for CContinueSessionMessage {
-    fn drop(&mut self) {
-        take_back_c_string!(self.session_id);
-        take_back_c_string!(self.text);
-        take_back_nullable_c_string_array!(self.intent_filter);
-        take_back_nullable_c_string!(self.custom_data);
-        take_back_nullable_c_string!(self.slot);
-    }
-}
-
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, CRepr)]
+#[target_type(hermes::EndSessionMessage)]
 pub struct CEndSessionMessage {
     /// The id of the session to end
     pub session_id: *const libc::c_char,
     /// Nullable, an optional text to be told to the user before ending the session
+    #[nullable]
     pub text: *const libc::c_char,
 }
 
@@ -604,31 +369,6 @@ impl CEndSessionMessage {
     }
 }
 
-impl CReprOf<hermes::EndSessionMessage> for CEndSessionMessage {
-    fn c_repr_of(input: hermes::EndSessionMessage) -> Fallible<Self> {
-        Ok(Self {
-            session_id: convert_to_c_string!(input.session_id),
-            text: convert_to_nullable_c_string!(input.text),
-        })
-    }
-}
-
-impl AsRust<hermes::EndSessionMessage> for CEndSessionMessage {
-    fn as_rust(&self) -> Fallible<hermes::EndSessionMessage> {
-        Ok(hermes::EndSessionMessage {
-            session_id: create_rust_string_from!(self.session_id),
-            text: create_optional_rust_string_from!(self.text),
-        })
-    }
-}
-
-impl Drop for CEndSessionMessage {
-    fn drop(&mut self) {
-        take_back_c_string!(self.session_id);
-        take_back_nullable_c_string!(self.text);
-    }
-}
-
 #[repr(C)]
 #[derive(Debug)]
 pub enum SNIPS_HERMES_COMPONENT {
@@ -730,8 +470,8 @@ pub struct CSessionTermination {
     component: SNIPS_HERMES_COMPONENT,
 }
 
-impl CSessionTermination {
-    fn from(termination: hermes::SessionTerminationType) -> Fallible<Self> {
+impl CReprOf<hermes::SessionTerminationType> for CSessionTermination {
+    fn c_repr_of(termination: hermes::SessionTerminationType) -> Fallible<Self> {
         let termination_type = SNIPS_SESSION_TERMINATION_TYPE::from(&termination);
         let (data, component): (*const libc::c_char, _) = match termination {
             hermes::SessionTerminationType::Error { error } => (
@@ -785,11 +525,13 @@ impl Drop for CSessionTermination {
 }
 
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, CRepr)]
+#[target_type(hermes::SessionEndedMessage)]
 pub struct CSessionEndedMessage {
     /// The id of the session that was terminated
     pub session_id: *const libc::c_char,
     /// Nullable, the custom data associated to this session
+    #[nullable]
     pub custom_data: *const libc::c_char,
     /// How the session was ended
     pub termination: CSessionTermination,
@@ -805,36 +547,6 @@ impl CSessionEndedMessage {
     }
 }
 
-impl CReprOf<hermes::SessionEndedMessage> for CSessionEndedMessage {
-    fn c_repr_of(input: hermes::SessionEndedMessage) -> Fallible<Self> {
-        Ok(Self {
-            session_id: convert_to_c_string!(input.session_id),
-            custom_data: convert_to_nullable_c_string!(input.custom_data),
-            termination: CSessionTermination::from(input.termination)?,
-            site_id: convert_to_c_string!(input.site_id),
-        })
-    }
-}
-
-impl AsRust<hermes::SessionEndedMessage> for CSessionEndedMessage {
-    fn as_rust(&self) -> Fallible<hermes::SessionEndedMessage> {
-        Ok(hermes::SessionEndedMessage {
-            session_id: create_rust_string_from!(self.session_id),
-            custom_data: create_optional_rust_string_from!(self.custom_data),
-            termination: self.termination.as_rust()?,
-            site_id: create_rust_string_from!(self.site_id),
-        })
-    }
-}
-
-impl Drop for CSessionEndedMessage {
-    fn drop(&mut self) {
-        take_back_c_string!(self.session_id);
-        take_back_nullable_c_string!(self.custom_data);
-        take_back_c_string!(self.site_id);
-    }
-}
-
 #[repr(C)]
 #[derive(Debug)]
 pub struct CDialogueConfigureIntent {
@@ -878,7 +590,9 @@ impl Drop for CDialogueConfigureIntent {
 }
 
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, CRepr)]
+#[target_type(Vec<hermes::DialogueConfigureIntent>)]
+#[c_array]
 pub struct CDialogueConfigureIntentArray {
     /// Pointer to the first intent configuration
     pub entries: *const *const CDialogueConfigureIntent,
@@ -886,114 +600,36 @@ pub struct CDialogueConfigureIntentArray {
     pub count: libc::c_int,
 }
 
-impl CReprOf<Vec<hermes::DialogueConfigureIntent>> for CDialogueConfigureIntentArray {
-    fn c_repr_of(input: Vec<hermes::DialogueConfigureIntent>) -> Fallible<Self> {
-        let array = Self {
-            count: input.len() as _,
-            entries: Box::into_raw(
-                input
-                    .into_iter()
-                    .map(|e| CDialogueConfigureIntent::c_repr_of(e).map(RawPointerConverter::into_raw_pointer))
-                    .collect::<Fallible<Vec<_>>>()
-                    .context("Could not convert map to C Repr")?
-                    .into_boxed_slice(),
-            ) as *const *const _,
-        };
-        Ok(array)
-    }
-}
-
-impl AsRust<Vec<hermes::DialogueConfigureIntent>> for CDialogueConfigureIntentArray {
-    fn as_rust(&self) -> Fallible<Vec<hermes::DialogueConfigureIntent>> {
-        let mut result = Vec::with_capacity(self.count as usize);
-
-        if self.count > 0 {
-            for e in unsafe { slice::from_raw_parts(self.entries, self.count as usize) } {
-                result.push(unsafe { CDialogueConfigureIntent::raw_borrow(*e) }?.as_rust()?);
-            }
-        }
-        Ok(result)
-    }
-}
-
-impl Drop for CDialogueConfigureIntentArray {
-    fn drop(&mut self) {
-        unsafe {
-            let slots = Box::from_raw(std::slice::from_raw_parts_mut(
-                self.entries as *mut *mut CDialogueConfigureIntent,
-                self.count as usize,
-            ));
-
-            for e in slots.iter() {
-                let _ = CDialogueConfigureIntent::drop_raw_pointer(*e);
-            }
-        }
-    }
-}
-
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, CRepr)]
+#[target_type(hermes::DialogueConfigureMessage)]
 pub struct CDialogueConfigureMessage {
     /// Nullable, the site on which this configuration applies, if `null` the configuration will
     /// be applied to all sites
+    #[nullable]
     pub site_id: *const libc::c_char,
     /// Nullable, Intent configurations to apply
+    #[nullable]
     pub intents: *const CDialogueConfigureIntentArray,
 }
 
 unsafe impl Sync for CDialogueConfigureMessage {}
 
-impl CReprOf<hermes::DialogueConfigureMessage> for CDialogueConfigureMessage {
-    fn c_repr_of(input: hermes::DialogueConfigureMessage) -> Fallible<Self> {
-        Ok(Self {
-            site_id: convert_to_nullable_c_string!(input.site_id),
-            intents: if let Some(intents) = input.intents {
-                CDialogueConfigureIntentArray::c_repr_of(intents)?.into_raw_pointer()
-            } else {
-                null()
-            },
-        })
-    }
-}
-
-impl AsRust<hermes::DialogueConfigureMessage> for CDialogueConfigureMessage {
-    fn as_rust(&self) -> Fallible<hermes::DialogueConfigureMessage> {
-        Ok(hermes::DialogueConfigureMessage {
-            site_id: create_optional_rust_string_from!(self.site_id),
-            intents: if self.intents.is_null() {
-                None
-            } else {
-                Some(unsafe { &*self.intents }.as_rust()?)
-            },
-        })
-    }
-}
-
-impl Drop for CDialogueConfigureMessage {
-    fn drop(&mut self) {
-        take_back_nullable_c_string!(self.site_id);
-        if !self.intents.is_null() {
-            let _ = unsafe { CDialogueConfigureIntentArray::drop_raw_pointer(self.intents) };
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use std::ops::Range;
 
     use hermes::hermes_utils::Example;
 
-    use super::super::tests::round_trip_test;
+    use super::super::tests::{round_trip_test, with_struct_version};
+    use super::super::SNIPS_HERMES_STRUCT_VERSION;
     use super::*;
 
     #[test]
     fn round_trip_intent_not_recognized() {
-        round_trip_test::<_, CIntentNotRecognizedMessage>(hermes::IntentNotRecognizedMessage::minimal_example());
-
-        round_trip_test::<_, CIntentNotRecognizedMessage>(hermes::IntentNotRecognizedMessage {
-            speaker_hypotheses: None, // TODO these are not supported by the ffi just yet
-            ..hermes::IntentNotRecognizedMessage::full_example()
+        with_struct_version(SNIPS_HERMES_STRUCT_VERSION, || {
+            round_trip_test::<_, CIntentNotRecognizedMessage>(hermes::IntentNotRecognizedMessage::minimal_example());
+            round_trip_test::<_, CIntentNotRecognizedMessage>(hermes::IntentNotRecognizedMessage::full_example());
         });
     }
 
@@ -1237,12 +873,12 @@ mod tests {
             },
         ];
 
-        round_trip_test::<_, CIntentMessage>(hermes::IntentMessage {
+        let message = hermes::IntentMessage {
             session_id: "a session id".to_string(),
             custom_data: Some("a custom datum".to_string()),
             site_id: "a site id".to_string(),
             input: "What's the weather in Guadeloupe ?".to_string(),
-            speaker_hypotheses: None,
+            speaker_hypotheses: Some(vec![hermes::SpeakerId::full_example()]),
             asr_tokens: Some(asr_token_double_array),
             asr_confidence: Some(0.7),
             intent: hermes::nlu::NluIntentClassifierResult {
@@ -1251,6 +887,9 @@ mod tests {
             },
             slots: vec![slot],
             alternatives: Some(alternatives),
-        })
+        };
+        with_struct_version(SNIPS_HERMES_STRUCT_VERSION, || {
+            round_trip_test::<_, CIntentMessage>(message)
+        });
     }
 }
@@ -1,122 +1,40 @@
 use std::collections::HashMap;
 use std::ptr::null;
-use std::slice;
 
 use failure::Fallible;
 use failure::ResultExt;
 use ffi_utils::*;
+use hermes_utils_derive::CRepr;
 
 use super::CMapStringToStringArray;
 
 #[repr(C)]
+#[derive(CRepr)]
+#[target_type(hermes::EntityValue)]
 pub struct CEntityValue {
     pub value: *const libc::c_char,
     pub weight: u32,
 }
 
-impl CReprOf<hermes::EntityValue> for CEntityValue {
-    fn c_repr_of(input: hermes::EntityValue) -> Fallible<Self> {
-        Ok(Self {
-            value: convert_to_c_string!(input.value),
-            weight: input.weight,
-        })
-    }
-}
-
-impl AsRust<hermes::EntityValue> for CEntityValue {
-    fn as_rust(&self) -> Fallible<hermes::EntityValue> {
-        Ok(hermes::EntityValue {
-            value: create_rust_string_from!(self.value),
-            weight: self.weight,
-        })
-    }
-}
-
-impl Drop for CEntityValue {
-    fn drop(&mut self) {
-        take_back_c_string!(self.value);
-    }
-}
-
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, CRepr)]
+#[target_type(Vec<hermes::EntityValue>)]
+#[c_array]
 pub struct CEntityValueArray {
     pub values: *const *const CEntityValue,
     pub count: libc::c_int,
 }
 
-impl Drop for CEntityValueArray {
-    fn drop(&mut self) {
-        unsafe {
-            for e in Box::from_raw(std::slice::from_raw_parts_mut(
-                self.values as *mut *mut CEntityValue,
-                self.count as usize,
-            ))
-            .iter()
-            {
-                let _ = CEntityValue::drop_raw_pointer(*e);
-            }
-        };
-    }
-}
-
-impl CReprOf<Vec<hermes::EntityValue>> for CEntityValueArray {
-    fn c_repr_of(input: Vec<hermes::EntityValue>) -> Fallible<Self> {
-        let array = Self {
-            count: input.len() as _,
-            values: Box::into_raw(
-                input
-                    .into_iter()
-                    .map(|e| CEntityValue::c_repr_of(e).map(RawPointerConverter::into_raw_pointer))
-                    .collect::<Fallible<Vec<_>>>()
-                    .context("Could not convert map to C Repr")?
-                    .into_boxed_slice(),
-            ) as *const *const _,
-        };
-        Ok(array)
-    }
-}
-
-impl AsRust<Vec<hermes::EntityValue>> for CEntityValueArray {
-    fn as_rust(&self) -> Fallible<Vec<hermes::EntityValue>> {
-        let mut result = Vec::with_capacity(self.count as usize);
-
-        if self.count > 0 {
-            for e in unsafe { slice::from_raw_parts(self.values, self.count as usize) } {
-                let entity = unsafe { CEntityValue::raw_borrow(*e) }?.as_rust()?;
-                result.push(entity);
-            }
-        }
-
-        Ok(result)
-    }
-}
-
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, CRepr)]
+#[target_type(hermes::InjectionKind)]
 pub enum SNIPS_INJECTION_KIND {
+    #[target_variant(Add)]
     SNIPS_INJECTION_KIND_ADD = 1,
+    #[target_variant(AddFromVanilla)]
     SNIPS_INJECTION_KIND_ADD_FROM_VANILLA = 2,
 }
 
-impl CReprOf<hermes::InjectionKind> for SNIPS_INJECTION_KIND {
-    fn c_repr_of(input: hermes::InjectionKind) -> Fallible<Self> {
-        Ok(match input {
-            hermes::InjectionKind::Add => SNIPS_INJECTION_KIND::SNIPS_INJECTION_KIND_ADD,
-            hermes::InjectionKind::AddFromVanilla => SNIPS_INJECTION_KIND::SNIPS_INJECTION_KIND_ADD_FROM_VANILLA,
-        })
-    }
-}
-
-impl AsRust<hermes::InjectionKind> for SNIPS_INJECTION_KIND {
-    fn as_rust(&self) -> Fallible<hermes::InjectionKind> {
-        Ok(match self {
-            SNIPS_INJECTION_KIND::SNIPS_INJECTION_KIND_ADD => hermes::InjectionKind::Add,
-            SNIPS_INJECTION_KIND::SNIPS_INJECTION_KIND_ADD_FROM_VANILLA => hermes::InjectionKind::AddFromVanilla,
-        })
-    }
-}
-
 #[repr(C)]
 #[derive(Debug)]
 pub struct CInjectionRequestOperation {
@@ -165,7 +83,9 @@ impl AsRust<(hermes::InjectionKind, HashMap<String, Vec<hermes::EntityValue>>)>
 }
 
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, CRepr)]
+#[target_type(Vec<CInjectionRequest>)]
+#[c_array]
 pub struct CInjectionRequestOperations {
     pub operations: *const *const CInjectionRequestOperation,
     pub count: libc::c_int,
@@ -173,95 +93,20 @@ pub struct CInjectionRequestOperations {
 
 type CInjectionRequest = (hermes::InjectionKind, HashMap<String, Vec<hermes::EntityValue>>);
 
-impl Drop for CInjectionRequestOperations {
-    fn drop(&mut self) {
-        unsafe {
-            let operations = Box::from_raw(std::slice::from_raw_parts_mut(
-                self.operations as *mut *mut CInjectionRequestOperation,
-                self.count as usize,
-            ));
-
-            for e in operations.iter() {
-                let _ = CInjectionRequestOperation::drop_raw_pointer(*e);
-            }
-        }
-    }
-}
-
-impl CReprOf<Vec<CInjectionRequest>> for CInjectionRequestOperations {
-    fn c_repr_of(input: Vec<CInjectionRequest>) -> Fallible<Self> {
-        Ok(Self {
-            count: input.len() as libc::c_int,
-            operations: Box::into_raw(
-                input
-                    .into_iter()
-                    .map(|e| CInjectionRequestOperation::c_repr_of(e).map(RawPointerConverter::into_raw_pointer))
-                    .collect::<Fallible<Vec<*const CInjectionRequestOperation>>>()
-                    .context("Could not convert map to C Repr")?
-                    .into_boxed_slice(),
-            ) as *const *const CInjectionRequestOperation,
-        })
-    }
-}
-
-impl AsRust<Vec<CInjectionRequest>> for CInjectionRequestOperations {
-    fn as_rust(&self) -> Fallible<Vec<CInjectionRequest>> {
-        let mut result = Vec::with_capacity(self.count as usize);
-
-        if self.count > 0 {
-            for e in unsafe { slice::from_raw_parts(self.operations, self.count as usize) } {
-                result.push(unsafe { CInjectionRequestOperation::raw_borrow(*e) }?.as_rust()?);
-            }
-        }
-
-        Ok(result)
-    }
-}
-
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, CRepr)]
+#[target_type(hermes::InjectionRequestMessage)]
 pub struct CInjectionRequestMessage {
     operations: *const CInjectionRequestOperations,
     lexicon: *const CMapStringToStringArray,
     /// Nullable
+    #[nullable]
     cross_language: *const libc::c_char,
     /// Nullable
+    #[nullable]
     id: *const libc::c_char,
 }
 
-impl Drop for CInjectionRequestMessage {
-    fn drop(&mut self) {
-        let _ = unsafe { CInjectionRequestOperations::drop_raw_pointer(self.operations) };
-        let _ = unsafe { CMapStringToStringArray::drop_raw_pointer(self.lexicon) };
-        take_back_nullable_c_string!(self.cross_language);
-        take_back_nullable_c_string!(self.id);
-    }
-}
-
-impl CReprOf<hermes::InjectionRequestMessage> for CInjectionRequestMessage {
-    fn c_repr_of(input: hermes::InjectionRequestMessage) -> Fallible<Self> {
-        Ok(Self {
-            operations: CInjectionRequestOperations::c_repr_of(input.operations)?.into_raw_pointer(),
-            lexicon: CMapStringToStringArray::c_repr_of(input.lexicon)?.into_raw_pointer(),
-            cross_language: convert_to_nullable_c_string!(input.cross_language),
-            id: convert_to_nullable_c_string!(input.id),
-        })
-    }
-}
-
-impl AsRust<hermes::InjectionRequestMessage> for CInjectionRequestMessage {
-    fn as_rust(&self) -> Fallible<hermes::InjectionRequestMessage> {
-        let operations = unsafe { CInjectionRequestOperations::raw_borrow(self.operations) }?.as_rust()?;
-        let lexicon = unsafe { CMapStringToStringArray::raw_borrow(self.lexicon) }?.as_rust()?;
-        Ok(hermes::InjectionRequestMessage {
-            operations,
-            lexicon,
-            cross_language: create_optional_rust_string_from!(self.cross_language),
-            id: create_optional_rust_string_from!(self.id),
-        })
-    }
-}
-
 #[repr(C)]
 #[derive(Debug)]
 pub struct CInjectionStatusMessage {
@@ -300,98 +145,47 @@ impl AsRust<hermes::InjectionStatusMessage> for CInjectionStatusMessage {
 }
 
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, CRepr)]
+#[target_type(hermes::InjectionCompleteMessage)]
 pub struct CInjectionCompleteMessage {
+    #[nullable]
     pub request_id: *const libc::c_char,
 }
 
 unsafe impl Sync for CInjectionCompleteMessage {}
 
-impl Drop for CInjectionCompleteMessage {
-    fn drop(&mut self) {
-        take_back_nullable_c_string!(self.request_id);
-    }
-}
-
-impl CReprOf<hermes::InjectionCompleteMessage> for CInjectionCompleteMessage {
-    fn c_repr_of(message: hermes::InjectionCompleteMessage) -> Fallible<Self> {
-        Ok(Self {
-            request_id: convert_to_nullable_c_string!(message.request_id),
-        })
-    }
-}
-
-impl AsRust<hermes::InjectionCompleteMessage> for CInjectionCompleteMessage {
-    fn as_rust(&self) -> Fallible<hermes::InjectionCompleteMessage> {
-        let request_id = create_optional_rust_string_from!(self.request_id);
-        Ok(hermes::InjectionCompleteMessage { request_id })
-    }
-}
-
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, CRepr)]
+#[target_type(hermes::InjectionResetRequestMessage)]
 pub struct CInjectionResetRequestMessage {
+    #[nullable]
     pub request_id: *const libc::c_char,
 }
 
 unsafe impl Sync for CInjectionResetRequestMessage {}
 
-impl Drop for CInjectionResetRequestMessage {
-    fn drop(&mut self) {
-        take_back_nullable_c_string!(self.request_id);
-    }
-}
-
-impl CReprOf<hermes::InjectionResetRequestMessage> for CInjectionResetRequestMessage {
-    fn c_repr_of(message: hermes::InjectionResetRequestMessage) -> Fallible<Self> {
-        Ok(Self {
-            request_id: convert_to_nullable_c_string!(message.request_id),
-        })
-    }
-}
-
-impl AsRust<hermes::InjectionResetRequestMessage> for CInjectionResetRequestMessage {
-    fn as_rust(&self) -> Fallible<hermes::InjectionResetRequestMessage> {
-        let request_id = create_optional_rust_string_from!(self.request_id);
-        Ok(hermes::InjectionResetRequestMessage { request_id })
-    }
-}
-
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, CRepr)]
+#[target_type(hermes::InjectionResetCompleteMessage)]
 pub struct CInjectionResetCompleteMessage {
+    #[nullable]
     pub request_id: *const libc::c_char,
 }
 
 unsafe impl Sync for CInjectionResetCompleteMessage {}
 
-impl Drop for CInjectionResetCompleteMessage {
-    fn drop(&mut self) {
-        take_back_nullable_c_string!(self.request_id);
-    }
-}
-
-impl CReprOf<hermes::InjectionResetCompleteMessage> for CInjectionResetCompleteMessage {
-    fn c_repr_of(message: hermes::InjectionResetCompleteMessage) -> Fallible<Self> {
-        Ok(Self {
-            request_id: convert_to_nullable_c_string!(message.request_id),
-        })
-    }
-}
-
-impl AsRust<hermes::InjectionResetCompleteMessage> for CInjectionResetCompleteMessage {
-    fn as_rust(&self) -> Fallible<hermes::InjectionResetCompleteMessage> {
-        let request_id = create_optional_rust_string_from!(self.request_id);
-        Ok(hermes::InjectionResetCompleteMessage { request_id })
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::super::tests::round_trip_test;
     use super::*;
     use hermes::hermes_utils::Example;
 
+    #[test]
+    fn round_trip_injection_kind() {
+        round_trip_test::<_, SNIPS_INJECTION_KIND>(hermes::InjectionKind::Add);
+        round_trip_test::<_, SNIPS_INJECTION_KIND>(hermes::InjectionKind::AddFromVanilla);
+    }
+
     #[test]
     fn round_trip_injection_request_operation() {
         round_trip_test::<_, CInjectionRequestOperation>((hermes::InjectionKind::Add, HashMap::new()));
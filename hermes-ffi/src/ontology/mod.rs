@@ -1,12 +1,14 @@
 #![allow(non_camel_case_types)]
 
 use std::collections::HashMap;
-use std::ptr::null;
 use std::slice;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
+use failure::format_err;
 use failure::Fallible;
 use failure::ResultExt;
 use ffi_utils::*;
+use hermes_utils_derive::CRepr;
 
 pub mod asr;
 pub mod audio_server;
@@ -15,6 +17,7 @@ pub mod hotword;
 pub mod injection;
 pub mod nlu;
 pub mod tts;
+pub mod vad;
 
 pub use self::asr::*;
 pub use self::audio_server::*;
@@ -23,12 +26,42 @@ pub use self::hotword::*;
 pub use self::injection::*;
 pub use self::nlu::*;
 pub use self::tts::*;
+pub use self::vad::*;
+
+/// The latest version of the layout of the structures. A version only appends fields at the end
+/// of the structures of the previous one:
+/// - 1: the initial layout
+/// - 2: `speaker_hypotheses` in `CTextCapturedMessage`, `CIntentMessage` and
+///   `CIntentNotRecognizedMessage`
+pub const SNIPS_HERMES_STRUCT_VERSION: libc::c_uint = 2;
+
+/// The version of the structures the C side was built with. The structures given to the library
+/// are read up to the fields of this version, the ones it allocates always have the latest layout
+static STRUCT_VERSION: AtomicUsize = AtomicUsize::new(1);
+
+pub fn set_struct_version(version: libc::c_uint) -> Fallible<()> {
+    if version == 0 || version > SNIPS_HERMES_STRUCT_VERSION {
+        return Err(format_err!(
+            "unsupported struct version {}, supported versions are 1 to {}",
+            version,
+            SNIPS_HERMES_STRUCT_VERSION
+        ));
+    }
+    STRUCT_VERSION.store(version as usize, Ordering::SeqCst);
+    Ok(())
+}
+
+pub fn struct_version() -> libc::c_uint {
+    STRUCT_VERSION.load(Ordering::SeqCst) as libc::c_uint
+}
 
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, CRepr)]
+#[target_type(hermes::SiteMessage)]
 pub struct CSiteMessage {
     pub site_id: *const libc::c_char,
     /// Nullable
+    #[nullable]
     pub session_id: *const libc::c_char,
 }
 
@@ -40,30 +73,14 @@ impl CSiteMessage {
     }
 }
 
-impl CReprOf<hermes::SiteMessage> for CSiteMessage {
-    fn c_repr_of(input: hermes::SiteMessage) -> Fallible<Self> {
-        Ok(Self {
-            site_id: convert_to_c_string!(input.site_id),
-            session_id: convert_to_nullable_c_string!(input.session_id),
-        })
-    }
-}
-
-impl AsRust<hermes::SiteMessage> for CSiteMessage {
-    fn as_rust(&self) -> Fallible<hermes::SiteMessage> {
-        Ok(hermes::SiteMessage {
-            site_id: create_rust_string_from!(self.site_id),
-            session_id: create_optional_rust_string_from!(self.session_id),
-        })
-    }
+#[repr(C)]
+#[derive(Debug, CRepr)]
+#[target_type(hermes::RequestComponentReloadMessage)]
+pub struct CRequestComponentReloadMessage {
+    pub id: *const libc::c_char,
 }
 
-impl Drop for CSiteMessage {
-    fn drop(&mut self) {
-        take_back_c_string!(self.site_id);
-        take_back_nullable_c_string!(self.session_id);
-    }
-}
+unsafe impl Sync for CRequestComponentReloadMessage {}
 
 #[repr(C)]
 #[derive(Debug)]
@@ -84,30 +101,21 @@ impl CVersionMessage {
 }
 
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, CRepr)]
+#[target_type(hermes::ErrorMessage)]
 pub struct CErrorMessage {
     /// Nullable
+    #[nullable]
     pub session_id: *const libc::c_char,
     pub error: *const libc::c_char,
     /// Nullable
+    #[nullable]
     pub context: *const libc::c_char,
 }
 
 impl CErrorMessage {
     pub fn from(input: hermes::ErrorMessage) -> Fallible<Self> {
-        Ok(Self {
-            session_id: convert_to_nullable_c_string!(input.session_id),
-            error: convert_to_c_string!(input.error),
-            context: convert_to_nullable_c_string!(input.context),
-        })
-    }
-}
-
-impl Drop for CErrorMessage {
-    fn drop(&mut self) {
-        take_back_nullable_c_string!(self.session_id);
-        take_back_c_string!(self.error);
-        take_back_nullable_c_string!(self.context);
+        Self::c_repr_of(input)
     }
 }
 
@@ -195,9 +203,24 @@ impl AsRust<HashMap<String, Vec<String>>> for CMapStringToStringArray {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Mutex;
+
     use super::*;
+    use lazy_static::lazy_static;
     use spectral::prelude::*;
 
+    lazy_static! {
+        static ref STRUCT_VERSION_LOCK: Mutex<()> = Mutex::new(());
+    }
+
+    /// Run `test` with the structures of `version`, the tests changing the version run one at a
+    /// time as it is global
+    pub fn with_struct_version<F: FnOnce()>(version: libc::c_uint, test: F) {
+        let _lock = STRUCT_VERSION_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        set_struct_version(version).unwrap();
+        test();
+    }
+
     pub fn round_trip_test<T, U>(input: T)
     where
         T: Clone + PartialEq + std::fmt::Debug,
@@ -210,6 +233,31 @@ mod tests {
         assert_that!(result).is_equal_to(input);
     }
 
+    #[test]
+    fn unknown_struct_versions_are_rejected() {
+        with_struct_version(1, || {
+            assert_that!(set_struct_version(0)).is_err();
+            assert_that!(set_struct_version(SNIPS_HERMES_STRUCT_VERSION + 1)).is_err();
+            assert_that!(struct_version()).is_equal_to(1);
+        });
+    }
+
+    #[test]
+    fn round_trip_error() {
+        use hermes::hermes_utils::Example;
+
+        round_trip_test::<_, CErrorMessage>(hermes::ErrorMessage::minimal_example());
+        round_trip_test::<_, CErrorMessage>(hermes::ErrorMessage::full_example());
+    }
+
+    #[test]
+    fn round_trip_request_component_reload() {
+        use hermes::hermes_utils::Example;
+
+        round_trip_test::<_, CRequestComponentReloadMessage>(hermes::RequestComponentReloadMessage::minimal_example());
+        round_trip_test::<_, CRequestComponentReloadMessage>(hermes::RequestComponentReloadMessage::full_example());
+    }
+
     #[test]
     fn round_trip_map_string_to_string_array_entry() {
         round_trip_test::<_, CMapStringToStringArrayEntry>((
@@ -0,0 +1,56 @@
+use failure::Fallible;
+use ffi_utils::*;
+use hermes_utils_derive::CRepr;
+
+#[repr(C)]
+#[derive(Debug, CRepr)]
+#[target_type(hermes::VadUpMessage)]
+pub struct CVadUpMessage {
+    pub site_id: *const libc::c_char,
+    #[none_sentinel(-1)]
+    pub signal_ms: i64, // -1 mean None
+}
+
+unsafe impl Sync for CVadUpMessage {}
+
+impl CVadUpMessage {
+    pub fn from(input: hermes::VadUpMessage) -> Fallible<Self> {
+        Self::c_repr_of(input)
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, CRepr)]
+#[target_type(hermes::VadDownMessage)]
+pub struct CVadDownMessage {
+    pub site_id: *const libc::c_char,
+    #[none_sentinel(-1)]
+    pub signal_ms: i64, // -1 mean None
+}
+
+unsafe impl Sync for CVadDownMessage {}
+
+impl CVadDownMessage {
+    pub fn from(input: hermes::VadDownMessage) -> Fallible<Self> {
+        Self::c_repr_of(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::round_trip_test;
+    use super::*;
+    use hermes::hermes_utils::Example;
+
+    #[test]
+    fn round_trip_vad_up() {
+        round_trip_test::<_, CVadUpMessage>(hermes::VadUpMessage::minimal_example());
+        round_trip_test::<_, CVadUpMessage>(hermes::VadUpMessage::full_example());
+    }
+
+    #[test]
+    fn round_trip_vad_down() {
+        round_trip_test::<_, CVadDownMessage>(hermes::VadDownMessage::minimal_example());
+        round_trip_test::<_, CVadDownMessage>(hermes::VadDownMessage::full_example());
+    }
+}
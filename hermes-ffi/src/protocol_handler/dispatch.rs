@@ -0,0 +1,299 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, Weak};
+use std::time::{Duration, Instant};
+
+use failure::{format_err, Fallible};
+use lazy_static::lazy_static;
+
+type PendingCallback = Box<dyn FnOnce() + Send>;
+
+lazy_static! {
+    static ref QUEUED_DISPATCHERS: Mutex<Vec<Weak<Dispatcher>>> = Mutex::new(vec![]);
+}
+
+/// Decides on which thread the C callbacks of a handler are called. By default they are called
+/// right away on the thread of the communication layer, once queued they wait for the host to
+/// call `poll` or `dispatch_pending`.
+#[derive(Default)]
+pub struct Dispatcher {
+    queued: AtomicBool,
+    pending: Mutex<VecDeque<PendingCallback>>,
+    available: Condvar,
+    #[cfg(unix)]
+    notifier: Mutex<Option<pipe::Notifier>>,
+}
+
+impl Dispatcher {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// Queue the callbacks from now on instead of calling them right away
+    pub fn enable_queue(self: &Arc<Self>) -> Fallible<()> {
+        if !self.queued.swap(true, Ordering::SeqCst) {
+            let mut dispatchers = QUEUED_DISPATCHERS
+                .lock()
+                .map_err(|_| format_err!("poisoned dispatchers"))?;
+            dispatchers.retain(|it| it.upgrade().is_some());
+            dispatchers.push(Arc::downgrade(self));
+        }
+        Ok(())
+    }
+
+    pub fn is_queued(&self) -> bool {
+        self.queued.load(Ordering::SeqCst)
+    }
+
+    pub fn dispatch(&self, callback: PendingCallback) -> Fallible<()> {
+        if !self.is_queued() {
+            callback();
+            return Ok(());
+        }
+        let mut pending = self.lock_pending()?;
+        pending.push_back(callback);
+        #[cfg(unix)]
+        {
+            if let Some(ref notifier) = *self.notifier.lock().map_err(|_| format_err!("poisoned notifier"))? {
+                notifier.notify()
+            }
+        }
+        self.available.notify_all();
+        Ok(())
+    }
+
+    /// Call the pending callbacks, waiting up to `timeout` for one if there are none. A `None`
+    /// timeout waits as long as needed. Returns the number of callbacks called.
+    pub fn poll(&self, timeout: Option<Duration>) -> Fallible<usize> {
+        let deadline = timeout.map(|it| Instant::now() + it);
+        let mut pending = self.lock_pending()?;
+        while pending.is_empty() {
+            pending = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        break;
+                    }
+                    self.available
+                        .wait_timeout(pending, deadline - now)
+                        .map_err(|_| format_err!("poisoned pending callbacks"))?
+                        .0
+                }
+                None => self
+                    .available
+                    .wait(pending)
+                    .map_err(|_| format_err!("poisoned pending callbacks"))?,
+            }
+        }
+        self.run(pending)
+    }
+
+    /// Call the pending callbacks without waiting. Returns the number of callbacks called.
+    pub fn dispatch_pending(&self) -> Fallible<usize> {
+        let pending = self.lock_pending()?;
+        self.run(pending)
+    }
+
+    /// A file descriptor that is readable while callbacks are pending, for hosts that want to
+    /// integrate the queue in their own event loop
+    #[cfg(unix)]
+    pub fn fd(&self) -> Fallible<libc::c_int> {
+        if !self.is_queued() {
+            failure::bail!("the callbacks of this handler aren't queued")
+        }
+        let pending = self.lock_pending()?;
+        let mut notifier = self.notifier.lock().map_err(|_| format_err!("poisoned notifier"))?;
+        if notifier.is_none() {
+            let created = pipe::Notifier::new()?;
+            if !pending.is_empty() {
+                created.notify()
+            }
+            *notifier = Some(created);
+        }
+        Ok(notifier.as_ref().unwrap().fd())
+    }
+
+    #[cfg(not(unix))]
+    pub fn fd(&self) -> Fallible<libc::c_int> {
+        failure::bail!("the dispatch file descriptor is only available on unix")
+    }
+
+    fn lock_pending(&self) -> Fallible<MutexGuard<VecDeque<PendingCallback>>> {
+        self.pending
+            .lock()
+            .map_err(|_| format_err!("poisoned pending callbacks"))
+    }
+
+    fn run(&self, mut pending: MutexGuard<VecDeque<PendingCallback>>) -> Fallible<usize> {
+        #[cfg(unix)]
+        {
+            if let Some(ref notifier) = *self.notifier.lock().map_err(|_| format_err!("poisoned notifier"))? {
+                notifier.clear()
+            }
+        }
+        let callbacks: Vec<_> = pending.drain(..).collect();
+        // the callbacks may use the handler, they must be called without holding the lock
+        drop(pending);
+        let count = callbacks.len();
+        for callback in callbacks {
+            callback()
+        }
+        Ok(count)
+    }
+}
+
+/// Call the pending callbacks of all the handlers queuing them, without waiting. Returns the
+/// number of callbacks called.
+pub fn dispatch_all_pending() -> Fallible<usize> {
+    let dispatchers: Vec<_> = QUEUED_DISPATCHERS
+        .lock()
+        .map_err(|_| format_err!("poisoned dispatchers"))?
+        .iter()
+        .filter_map(Weak::upgrade)
+        .collect();
+    let mut count = 0;
+    for dispatcher in dispatchers {
+        count += dispatcher.dispatch_pending()?;
+    }
+    Ok(count)
+}
+
+#[cfg(unix)]
+mod pipe {
+    use std::os::unix::io::RawFd;
+
+    use failure::{bail, Fallible};
+
+    /// A pipe holding a byte for each pending callback
+    pub struct Notifier {
+        read: RawFd,
+        write: RawFd,
+    }
+
+    impl Notifier {
+        pub fn new() -> Fallible<Self> {
+            let mut fds = [0 as libc::c_int; 2];
+            if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+                bail!("could not create pipe: {}", std::io::Error::last_os_error())
+            }
+            for fd in &fds {
+                unsafe {
+                    libc::fcntl(*fd, libc::F_SETFL, libc::fcntl(*fd, libc::F_GETFL) | libc::O_NONBLOCK);
+                    libc::fcntl(*fd, libc::F_SETFD, libc::FD_CLOEXEC);
+                }
+            }
+            Ok(Self {
+                read: fds[0],
+                write: fds[1],
+            })
+        }
+
+        pub fn fd(&self) -> RawFd {
+            self.read
+        }
+
+        pub fn notify(&self) {
+            // a full pipe is readable anyway
+            let byte = 1u8;
+            let _ = unsafe { libc::write(self.write, &byte as *const u8 as *const libc::c_void, 1) };
+        }
+
+        pub fn clear(&self) {
+            let mut buffer = [0u8; 64];
+            while unsafe { libc::read(self.read, buffer.as_mut_ptr() as *mut libc::c_void, buffer.len()) } > 0 {}
+        }
+    }
+
+    impl Drop for Notifier {
+        fn drop(&mut self) {
+            unsafe {
+                libc::close(self.read);
+                libc::close(self.write);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use super::*;
+
+    fn counting_callback(counter: &Arc<AtomicUsize>) -> PendingCallback {
+        let counter = Arc::clone(counter);
+        Box::new(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        })
+    }
+
+    #[test]
+    fn callbacks_are_called_right_away_by_default() {
+        let dispatcher = Dispatcher::new();
+        let counter = Arc::new(AtomicUsize::new(0));
+
+        dispatcher.dispatch(counting_callback(&counter)).unwrap();
+
+        assert_eq!(counter.load(Ordering::SeqCst), 1);
+        assert_eq!(dispatcher.dispatch_pending().unwrap(), 0);
+    }
+
+    #[test]
+    fn queued_callbacks_wait_for_the_host() {
+        let dispatcher = Dispatcher::new();
+        dispatcher.enable_queue().unwrap();
+        let counter = Arc::new(AtomicUsize::new(0));
+
+        dispatcher.dispatch(counting_callback(&counter)).unwrap();
+        dispatcher.dispatch(counting_callback(&counter)).unwrap();
+        assert_eq!(counter.load(Ordering::SeqCst), 0);
+
+        assert_eq!(dispatcher.poll(Some(Duration::from_millis(0))).unwrap(), 2);
+        assert_eq!(counter.load(Ordering::SeqCst), 2);
+        assert_eq!(dispatcher.poll(Some(Duration::from_millis(10))).unwrap(), 0);
+    }
+
+    #[test]
+    fn poll_waits_for_a_callback() {
+        let dispatcher = Dispatcher::new();
+        dispatcher.enable_queue().unwrap();
+        let counter = Arc::new(AtomicUsize::new(0));
+
+        let producer = {
+            let dispatcher = Arc::clone(&dispatcher);
+            let callback = counting_callback(&counter);
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(50));
+                dispatcher.dispatch(callback).unwrap();
+            })
+        };
+
+        assert_eq!(dispatcher.poll(None).unwrap(), 1);
+        assert_eq!(counter.load(Ordering::SeqCst), 1);
+        producer.join().unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn fd_is_readable_while_callbacks_are_pending() {
+        let dispatcher = Dispatcher::new();
+        dispatcher.enable_queue().unwrap();
+        let counter = Arc::new(AtomicUsize::new(0));
+        let fd = dispatcher.fd().unwrap();
+
+        let readable = || {
+            let mut poll_fd = libc::pollfd {
+                fd,
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            unsafe { libc::poll(&mut poll_fd, 1, 0) == 1 }
+        };
+
+        assert!(!readable());
+        dispatcher.dispatch(counting_callback(&counter)).unwrap();
+        assert!(readable());
+        assert_eq!(dispatcher.dispatch_pending().unwrap(), 1);
+        assert!(!readable());
+    }
+}
@@ -1,5 +1,11 @@
-use ffi_utils::RawPointerConverter;
+use std::sync::{Arc, Mutex, Weak};
+
+use failure::{format_err, Fallible};
+use ffi_utils::*;
 use hermes::HermesProtocolHandler;
+use hermes_inprocess::InProcessHermesProtocolHandler;
+
+use super::dispatch::Dispatcher;
 
 #[repr(C)]
 #[derive(Debug)]
@@ -10,26 +16,187 @@ pub struct CProtocolHandler {
     pub user_data: *mut libc::c_void,
 }
 
-pub struct UserData(pub *mut libc::c_void);
+/// A function freeing the `user_data` given at the creation of a handler
+pub type UserDataDestructor = unsafe extern "C" fn(*mut libc::c_void);
+
+struct SharedUserData {
+    pointer: *mut libc::c_void,
+    destructor: Mutex<Option<UserDataDestructor>>,
+    dispatcher: Arc<Dispatcher>,
+}
+
+unsafe impl Send for SharedUserData {}
+unsafe impl Sync for SharedUserData {}
 
-unsafe impl Send for UserData {}
-unsafe impl Sync for UserData {}
+impl Drop for SharedUserData {
+    fn drop(&mut self) {
+        if let Some(destructor) = self.destructor.lock().ok().and_then(|mut it| it.take()) {
+            unsafe { destructor(self.pointer) }
+        }
+    }
+}
+
+/// The `user_data` given by the C side at the creation of a handler, shared by the handler, its
+/// facades and its subscriptions. Its destructor, if any, is called once they are all gone. It
+/// also carries the dispatcher calling the C callbacks of the handler.
+pub struct UserData(Arc<SharedUserData>);
 
 impl UserData {
+    pub fn new(pointer: *mut libc::c_void) -> Self {
+        Self(Arc::new(SharedUserData {
+            pointer,
+            destructor: Mutex::new(None),
+            dispatcher: Dispatcher::new(),
+        }))
+    }
+
     pub fn duplicate(&self) -> Self {
-        Self(self.0)
+        Self(Arc::clone(&self.0))
+    }
+
+    pub fn pointer(&self) -> *mut libc::c_void {
+        self.0.pointer
+    }
+
+    pub fn dispatcher(&self) -> &Arc<Dispatcher> {
+        &self.0.dispatcher
+    }
+
+    pub fn set_destructor(&self, destructor: Option<UserDataDestructor>) -> Fallible<()> {
+        *self
+            .0
+            .destructor
+            .lock()
+            .map_err(|_| format_err!("poisoned user data destructor"))? = destructor;
+        Ok(())
+    }
+}
+
+/// The state shared by the callback of a subscription made through the C API and its
+/// `CSubscription` token
+pub struct Subscription {
+    user_data: Mutex<Option<UserData>>,
+    /// Cancelled along with the subscription, unregisters the callback from the handler
+    handle: hermes::Subscription,
+}
+
+impl Subscription {
+    pub fn new(user_data: UserData) -> Arc<Self> {
+        Arc::new(Self {
+            user_data: Mutex::new(Some(user_data)),
+            handle: hermes::Subscription::new(),
+        })
+    }
+
+    /// The user data to give to the C callback, `None` once unsubscribed. The user data is kept
+    /// alive until the returned value is dropped, even if unsubscribing concurrently.
+    pub fn user_data(&self) -> Option<UserData> {
+        self.user_data.lock().ok()?.as_ref().map(UserData::duplicate)
+    }
+
+    /// Call `callback` with the user data, right away or once the host dispatches the pending
+    /// callbacks if the handler queues them. Nothing is called if the subscription was cancelled
+    /// in the meantime.
+    pub fn dispatch<F>(subscription: &Arc<Self>, callback: F) -> Fallible<()>
+    where
+        F: FnOnce(*mut libc::c_void) + Send + 'static,
+    {
+        let dispatcher = match subscription.user_data() {
+            Some(user_data) => Arc::clone(user_data.dispatcher()),
+            None => return Ok(()),
+        };
+        // a weak reference, the queue must not keep the user data alive
+        let subscription = Arc::downgrade(subscription);
+        dispatcher.dispatch(Box::new(move || {
+            if let Some(user_data) = subscription.upgrade().and_then(|it| it.user_data()) {
+                callback(user_data.pointer())
+            }
+        }))
+    }
+
+    /// Unregister the callback made with `subscription` from the handler when this subscription
+    /// is cancelled
+    pub fn cancel_with(&self, subscription: hermes::Subscription) {
+        self.handle.on_cancel(move || subscription.cancel())
+    }
+
+    /// Stop calling the C callback, unregister it from the handler and release the user data
+    pub fn cancel(&self) {
+        let user_data = self.user_data.lock().ok().and_then(|mut it| it.take());
+        self.handle.cancel();
+        drop(user_data)
+    }
+}
+
+/// A token identifying a subscription, returned by the `hermes_*_subscribe_*_with_subscription`
+/// functions through their last parameter when it isn't null. It must be given to
+/// `hermes_unsubscribe` or `hermes_drop_subscription`.
+#[repr(C)]
+#[derive(Debug)]
+pub struct CSubscription {
+    // hides a Box<Weak<Subscription>>, the subscription is owned by its callback so that it goes
+    // away with the handler or once unsubscribed
+    subscription: *const libc::c_void,
+}
+
+impl CSubscription {
+    /// Give a token for `subscription` back to the C side, if it asked for one
+    pub fn write_to(subscription: &Arc<Subscription>, output: *mut *const CSubscription) {
+        if !output.is_null() {
+            let token = Self {
+                subscription: Box::into_raw(Box::new(Arc::downgrade(subscription))) as *const libc::c_void,
+            };
+            unsafe { *output = token.into_raw_pointer() }
+        }
+    }
+
+    pub fn unsubscribe(&self) {
+        if let Some(subscription) = unsafe { &*(self.subscription as *const Weak<Subscription>) }.upgrade() {
+            subscription.cancel()
+        }
+    }
+}
+
+impl Drop for CSubscription {
+    fn drop(&mut self) {
+        let _ = unsafe { Box::from_raw(self.subscription as *mut Weak<Subscription>) };
     }
 }
 
+pub type CCallback0 = unsafe extern "C" fn(*mut libc::c_void);
+
+pub fn ptr_to_callback0(ptr: Option<CCallback0>, subscription: Arc<Subscription>) -> Fallible<hermes::Callback0> {
+    let ptr = ptr.ok_or_else(|| format_err!("null pointer"))?;
+    let shared = Arc::clone(&subscription);
+    let callback = hermes::Callback0::new(move || {
+        let dispatched = Subscription::dispatch(&shared, move |user_data| unsafe { ptr(user_data) });
+        if let Err(e) = dispatched {
+            log::error!("could not dispatch message: {}", e)
+        }
+    });
+    subscription.cancel_with(callback.subscription());
+    Ok(callback)
+}
+
 impl CProtocolHandler {
     pub fn new(handler: Box<dyn HermesProtocolHandler>, user_data: *mut libc::c_void) -> Self {
-        let user_data = UserData(user_data).into_raw_pointer() as _;
+        let user_data = UserData::new(user_data).into_raw_pointer() as _;
         Self {
             handler: Box::into_raw(Box::new(handler)) as *const libc::c_void,
             user_data,
         }
     }
 
+    /// Create a handler delivering the messages inside the process, on the bus named `bus_name`
+    /// if it isn't null or on a bus of its own otherwise
+    pub fn new_in_process(bus_name: *const libc::c_char, user_data: *mut libc::c_void) -> Fallible<Self> {
+        let handler = match create_optional_rust_string_from!(bus_name) {
+            Some(name) => InProcessHermesProtocolHandler::new_on_named_bus(&name),
+            None => InProcessHermesProtocolHandler::new(),
+        };
+        Ok(Self::new(Box::new(handler), user_data))
+    }
+
     pub fn extract(&self) -> &dyn HermesProtocolHandler {
         unsafe { &(**(self.handler as *const Box<dyn HermesProtocolHandler>)) }
     }
@@ -40,6 +207,7 @@ impl CProtocolHandler {
 
     pub fn destroy(self) {
         let _ = unsafe { Box::from_raw(self.handler as *mut Box<dyn HermesProtocolHandler>) };
+        let _ = unsafe { UserData::drop_raw_pointer(self.user_data as *const UserData) };
     }
 }
 
@@ -80,7 +248,10 @@ macro_rules! generate_facade_wrapper {
 
         impl Drop for $wrapper_name {
             fn drop(&mut self) {
+                use ffi_utils::RawPointerConverter;
+
                 unsafe { Box::from_raw(self.facade as *mut Box<$facade>) };
+                let _ = unsafe { $crate::UserData::drop_raw_pointer(self.user_data as *const $crate::UserData) };
             }
         }
 
@@ -110,6 +281,42 @@ macro_rules! generate_facade_wrapper {
     };
 }
 
+/// Only the facades most hosts need are exported by default, the others require the
+/// `full_bindings` feature. Speaking through the TTS facade also requires it, hosts are expected
+/// to go through the dialogue.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __facade_binding {
+    (sound_feedback $method:ident $($item:tt)*) => { $($item)* };
+    (dialogue $method:ident $($item:tt)*) => { $($item)* };
+    (injection $method:ident $($item:tt)*) => { $($item)* };
+    (tts publish_say $($item:item)*) => { $(#[cfg(feature = "full_bindings")] $item)* };
+    (tts subscribe_say_finished $($item:item)*) => { $(#[cfg(feature = "full_bindings")] $item)* };
+    (tts $method:ident $($item:tt)*) => { $($item)* };
+    ($getter:ident $method:ident $($item:item)*) => {
+        $(#[cfg(feature = "full_bindings")] $item)*
+    };
+}
+
+/// The messages of the ASR, TTS and dialogue manager were subscribed to for all the sites before
+/// their topics were scoped by site. Their symbols keep this signature, the ones taking a site are
+/// named after them with `_for_site`. Expands to `$global` for the subscribe methods of these
+/// messages and to `$keyed` for the others.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __if_global_subscribe {
+    (asr subscribe_text_captured { $($global:tt)* } else { $($keyed:tt)* }) => { $($global)* };
+    (asr subscribe_partial_text_captured { $($global:tt)* } else { $($keyed:tt)* }) => { $($global)* };
+    (asr_backend subscribe_start_listening { $($global:tt)* } else { $($keyed:tt)* }) => { $($global)* };
+    (asr_backend subscribe_stop_listening { $($global:tt)* } else { $($keyed:tt)* }) => { $($global)* };
+    (tts_backend subscribe_say { $($global:tt)* } else { $($keyed:tt)* }) => { $($global)* };
+    (dialogue subscribe_session_queued { $($global:tt)* } else { $($keyed:tt)* }) => { $($global)* };
+    (dialogue subscribe_session_started { $($global:tt)* } else { $($keyed:tt)* }) => { $($global)* };
+    (dialogue subscribe_session_ended { $($global:tt)* } else { $($keyed:tt)* }) => { $($global)* };
+    (dialogue subscribe_intent_not_recognized { $($global:tt)* } else { $($keyed:tt)* }) => { $($global)* };
+    ($getter:ident $subscribe:ident { $($global:tt)* } else { $($keyed:tt)* }) => { $($keyed)* };
+}
+
 #[macro_export]
 macro_rules! generate_facade_c_symbols {
     () => {
@@ -137,6 +344,9 @@ macro_rules! generate_facade_c_symbols {
                 $crate::generate_facade_wrapper!(CAudioServerFacade for hermes::AudioServerFacade, hermes_drop_audio_server_facade, hermes_protocol_handler_audio_server_facade = handler.audio_server);
                 $crate::generate_facade_wrapper!(CAudioServerBackendFacade for hermes::AudioServerBackendFacade, hermes_drop_audio_server_backend_facade, hermes_protocol_handler_audio_server_backend_facade = handler.audio_server_backend);
                 $crate::generate_facade_wrapper!(CDialogueBackendFacade for hermes::DialogueBackendFacade, hermes_drop_dialogue_backend_facade, hermes_protocol_handler_dialogue_backend_facade = handler.dialogue_backend);
+                $crate::generate_facade_wrapper!(CVoiceActivityFacade for hermes::VoiceActivityFacade, hermes_drop_voice_activity_facade, hermes_protocol_handler_voice_activity_facade = handler.voice_activity);
+                $crate::generate_facade_wrapper!(CVoiceActivityBackendFacade for hermes::VoiceActivityBackendFacade, hermes_drop_voice_activity_backend_facade, hermes_protocol_handler_voice_activity_backend_facade = handler.voice_activity_backend);
+                $crate::generate_facade_wrapper!(CInjectionBackendFacade for hermes::InjectionBackendFacade, hermes_drop_injection_backend_facade, hermes_protocol_handler_injection_backend_facade = handler.injection_backend);
             }
         }
     };
@@ -1,22 +1,34 @@
+use std::sync::Arc;
+
 use failure::Fallible;
 use hermes::ontology::HermesMessage;
 
-use crate::UserData;
+use crate::protocol_handler::Subscription;
 
 pub type CJsonCallback = unsafe extern "C" fn(*const libc::c_char, *mut libc::c_void);
 
-pub fn json_ptr_to_callback<'de, T>(ptr: Option<CJsonCallback>, user_data: UserData) -> Fallible<hermes::Callback<T>>
+pub fn json_ptr_to_callback<'de, T>(
+    ptr: Option<CJsonCallback>,
+    subscription: Arc<Subscription>,
+) -> Fallible<hermes::Callback<T>>
 where
     T: HermesMessage<'de>,
 {
-    match ptr {
-        Some(ptr) => Ok(hermes::Callback::new(move |payload: &T| {
-            let json = serde_json::to_string(&payload).expect("json serialization failed");
-            let c_string = std::ffi::CString::new(json).expect("CString::new failed");
-            unsafe { ptr(c_string.as_ptr(), user_data.0) }
-        })),
-        None => failure::bail!("null pointer"),
-    }
+    let ptr = ptr.ok_or_else(|| failure::format_err!("null pointer"))?;
+    let shared = Arc::clone(&subscription);
+    // the json given to the callback is the message as sent over MQTT, with its envelope
+    let callback = hermes::Callback::with_envelope(move |payload: &T, envelope| {
+        let json = hermes::envelope::to_json_value(payload, envelope)
+            .and_then(|value| Ok(serde_json::to_string(&value)?))
+            .expect("json serialization failed");
+        let c_string = std::ffi::CString::new(json).expect("CString::new failed");
+        let dispatched = Subscription::dispatch(&shared, move |user_data| unsafe { ptr(c_string.as_ptr(), user_data) });
+        if let Err(e) = dispatched {
+            log::error!("could not dispatch message: {}", e)
+        }
+    });
+    subscription.cancel_with(callback.subscription());
+    Ok(callback)
 }
 
 pub fn json_from_slice<'a, T>(v: &'a [u8]) -> Fallible<T>
@@ -35,6 +47,26 @@ macro_rules! generate_facade_publish_json {
         }
     };
 
+    ($c_symbol:ident = $facade:ty:$method:ident($($filter_name:ident)*) without message) => {
+        #[no_mangle]
+        pub extern "C" fn $c_symbol(
+            facade: *const $facade,
+            $($filter_name: *const libc::c_char,)*
+        ) -> ffi_utils::SNIPS_RESULT {
+            fn fun(
+                facade: *const $facade,
+                $($filter_name: *const libc::c_char,)*
+            ) -> failure::Fallible<()> {
+                use std::ffi::CStr;
+
+                unsafe { (*facade).extract() }.$method(
+                    $(unsafe { CStr::from_ptr($filter_name) }.to_string_lossy().into_owned(),)*
+                )
+            }
+            ffi_utils::wrap!(fun(facade, $($filter_name,)*))
+        }
+    };
+
     ($c_symbol:ident = $facade:ty:$method:ident($($filter_name:ident)*)) => {
         #[no_mangle]
         pub extern "C" fn $c_symbol(
@@ -62,126 +94,500 @@ macro_rules! generate_facade_publish_json {
     };
 }
 
+/// Generates `$c_symbol`, and `$c_symbol` followed by `_with_subscription` which also gives back
+/// a `CSubscription` token to unsubscribe with
 #[macro_export]
 macro_rules! generate_facade_subscribe_json {
+    ($c_symbol:ident = $facade:ty:$method:ident($($filter_name:ident)*) without message) => {
+        $crate::paste::item! {
+            #[no_mangle]
+            pub extern "C" fn $c_symbol(
+                facade: *const $facade,
+                $($filter_name: *const libc::c_char,)*
+                handler: Option<unsafe extern "C" fn(*mut libc::c_void)>,
+            ) -> ffi_utils::SNIPS_RESULT {
+                [<$c_symbol _with_subscription>](facade, $($filter_name,)* handler, std::ptr::null_mut())
+            }
+
+            #[no_mangle]
+            pub extern "C" fn [<$c_symbol _with_subscription>](
+                facade: *const $facade,
+                $($filter_name: *const libc::c_char,)*
+                handler: Option<unsafe extern "C" fn(*mut libc::c_void)>,
+                subscription: *mut *const $crate::CSubscription,
+            ) -> ffi_utils::SNIPS_RESULT {
+
+                fn fun(facade: *const $facade,
+                    $($filter_name: *const libc::c_char,)*
+                    handler: Option<$crate::CCallback0>,
+                    subscription: *mut *const $crate::CSubscription,
+                )-> failure::Fallible<()> {
+                    use std::ffi::CStr;
+
+                    let shared = $crate::Subscription::new(unsafe { (*facade).user_data().duplicate() });
+                    let callback = $crate::ptr_to_callback0(handler, std::sync::Arc::clone(&shared))?;
+
+                    unsafe { (*facade).extract() }.$method(
+                        $(unsafe { CStr::from_ptr($filter_name) }.to_string_lossy().into_owned(),)*
+                        callback,
+                    )?;
+                    $crate::CSubscription::write_to(&shared, subscription);
+                    Ok(())
+                }
+
+                ffi_utils::wrap!(fun(facade, $($filter_name,)* handler, subscription))
+            }
+        }
+    };
+
     ($c_symbol:ident = $facade:ty:$method:ident($($filter_name:ident)*)) => {
-        #[no_mangle]
-        pub extern "C" fn $c_symbol(
-            facade: *const $facade,
-            $($filter_name: *const libc::c_char,)*
-            handler: Option<unsafe extern "C" fn(*const libc::c_char, *mut libc::c_void)>,
-        ) -> ffi_utils::SNIPS_RESULT {
+        $crate::paste::item! {
+            #[no_mangle]
+            pub extern "C" fn $c_symbol(
+                facade: *const $facade,
+                $($filter_name: *const libc::c_char,)*
+                handler: Option<unsafe extern "C" fn(*const libc::c_char, *mut libc::c_void)>,
+            ) -> ffi_utils::SNIPS_RESULT {
+                [<$c_symbol _with_subscription>](facade, $($filter_name,)* handler, std::ptr::null_mut())
+            }
 
-            fn fun(facade: *const $facade,
+            #[no_mangle]
+            pub extern "C" fn [<$c_symbol _with_subscription>](
+                facade: *const $facade,
                 $($filter_name: *const libc::c_char,)*
-                handler: Option<$crate::CJsonCallback>,
-            )-> failure::Fallible<()> {
-                use std::ffi::CStr;
+                handler: Option<unsafe extern "C" fn(*const libc::c_char, *mut libc::c_void)>,
+                subscription: *mut *const $crate::CSubscription,
+            ) -> ffi_utils::SNIPS_RESULT {
 
-                let user_data = unsafe { (*facade).user_data().duplicate() };
-                let callback = $crate::json_ptr_to_callback(handler, user_data)?;
+                fn fun(facade: *const $facade,
+                    $($filter_name: *const libc::c_char,)*
+                    handler: Option<$crate::CJsonCallback>,
+                    subscription: *mut *const $crate::CSubscription,
+                )-> failure::Fallible<()> {
+                    use std::ffi::CStr;
 
-                unsafe { (*facade).extract() }.$method(
-                    $(unsafe { CStr::from_ptr($filter_name) }.to_string_lossy().into_owned(),)*
-                    callback,
-                )
+                    let shared = $crate::Subscription::new(unsafe { (*facade).user_data().duplicate() });
+                    let callback = $crate::json_ptr_to_callback(handler, std::sync::Arc::clone(&shared))?;
+
+                    unsafe { (*facade).extract() }.$method(
+                        $(unsafe { CStr::from_ptr($filter_name) }.to_string_lossy().into_owned(),)*
+                        callback,
+                    )?;
+                    $crate::CSubscription::write_to(&shared, subscription);
+                    Ok(())
+                }
+
+                ffi_utils::wrap!(fun(facade, $($filter_name,)* handler, subscription))
             }
+        }
+    };
+}
 
-            ffi_utils::wrap!(fun(facade, $($filter_name,)* handler))
+/// The symbols of the methods shared by the components, for the facade returned by `$getter`
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __json_shared_symbols {
+    ($getter:ident $facade:ident ComponentFacade) => {
+        $crate::paste::item! {
+            $crate::__facade_binding!($getter shared
+                $crate::generate_facade_publish_json!([<hermes_ $getter _publish_version_request_json>] = [<C $facade>]: publish_version_request);
+                $crate::generate_facade_subscribe_json!([<hermes_ $getter _subscribe_version_json>] = [<C $facade>]: subscribe_version());
+                $crate::generate_facade_subscribe_json!([<hermes_ $getter _subscribe_error_json>] = [<C $facade>]: subscribe_error());
+                $crate::generate_facade_subscribe_json!([<hermes_ $getter _subscribe_component_loaded_json>] = [<C $facade>]: subscribe_component_loaded());
+            );
+        }
+    };
+    ($getter:ident $facade:ident IdentifiableComponentFacade) => {
+        $crate::paste::item! {
+            $crate::__facade_binding!($getter shared
+                $crate::generate_facade_publish_json!([<hermes_ $getter _publish_version_request_json>] = [<C $facade>]: publish_version_request(id) without message);
+                $crate::generate_facade_subscribe_json!([<hermes_ $getter _subscribe_version_json>] = [<C $facade>]: subscribe_version(id));
+                $crate::generate_facade_subscribe_json!([<hermes_ $getter _subscribe_error_json>] = [<C $facade>]: subscribe_error(id));
+                $crate::generate_facade_subscribe_json!([<hermes_ $getter _subscribe_all_error_json>] = [<C $facade>]: subscribe_all_error());
+                $crate::generate_facade_subscribe_json!([<hermes_ $getter _subscribe_component_loaded_json>] = [<C $facade>]: subscribe_component_loaded(id));
+                $crate::generate_facade_subscribe_json!([<hermes_ $getter _subscribe_all_component_loaded_json>] = [<C $facade>]: subscribe_all_component_loaded());
+            );
+        }
+    };
+    ($getter:ident $facade:ident ComponentBackendFacade) => {
+        $crate::paste::item! {
+            $crate::__facade_binding!($getter shared
+                $crate::generate_facade_subscribe_json!([<hermes_ $getter _subscribe_version_request_json>] = [<C $facade>]: subscribe_version_request() without message);
+                $crate::generate_facade_publish_json!([<hermes_ $getter _publish_version_json>] = [<C $facade>]: publish_version());
+                $crate::generate_facade_publish_json!([<hermes_ $getter _publish_error_json>] = [<C $facade>]: publish_error());
+                $crate::generate_facade_publish_json!([<hermes_ $getter _publish_component_loaded_json>] = [<C $facade>]: publish_component_loaded());
+            );
+        }
+    };
+    ($getter:ident $facade:ident IdentifiableComponentBackendFacade) => {
+        $crate::paste::item! {
+            $crate::__facade_binding!($getter shared
+                $crate::generate_facade_subscribe_json!([<hermes_ $getter _subscribe_version_request_json>] = [<C $facade>]: subscribe_version_request(id) without message);
+                $crate::generate_facade_publish_json!([<hermes_ $getter _publish_version_json>] = [<C $facade>]: publish_version(id));
+                $crate::generate_facade_publish_json!([<hermes_ $getter _publish_error_json>] = [<C $facade>]: publish_error(id));
+                $crate::generate_facade_publish_json!([<hermes_ $getter _publish_component_loaded_json>] = [<C $facade>]: publish_component_loaded(id));
+            );
+        }
+    };
+    ($getter:ident $facade:ident ToggleableFacade) => {
+        $crate::paste::item! {
+            $crate::__facade_binding!($getter shared
+                $crate::generate_facade_publish_json!([<hermes_ $getter _publish_toggle_on_json>] = [<C $facade>]: publish_toggle_on);
+                $crate::generate_facade_publish_json!([<hermes_ $getter _publish_toggle_off_json>] = [<C $facade>]: publish_toggle_off);
+            );
+        }
+    };
+    ($getter:ident $facade:ident ToggleableBackendFacade) => {
+        $crate::paste::item! {
+            $crate::__facade_binding!($getter shared
+                $crate::generate_facade_subscribe_json!([<hermes_ $getter _subscribe_toggle_on_json>] = [<C $facade>]: subscribe_toggle_on() without message);
+                $crate::generate_facade_subscribe_json!([<hermes_ $getter _subscribe_toggle_off_json>] = [<C $facade>]: subscribe_toggle_off() without message);
+            );
+        }
+    };
+    ($getter:ident $facade:ident IdentifiableToggleableFacade) => {
+        $crate::paste::item! {
+            $crate::__facade_binding!($getter shared
+                $crate::generate_facade_publish_json!([<hermes_ $getter _publish_toggle_on_json>] = [<C $facade>]: publish_toggle_on());
+                $crate::generate_facade_publish_json!([<hermes_ $getter _publish_toggle_off_json>] = [<C $facade>]: publish_toggle_off());
+            );
+        }
+    };
+    ($getter:ident $facade:ident IdentifiableToggleableBackendFacade) => {
+        $crate::paste::item! {
+            $crate::__facade_binding!($getter shared
+                $crate::generate_facade_subscribe_json!([<hermes_ $getter _subscribe_toggle_on_json>] = [<C $facade>]: subscribe_toggle_on(site_id));
+                $crate::generate_facade_subscribe_json!([<hermes_ $getter _subscribe_all_toggle_on_json>] = [<C $facade>]: subscribe_all_toggle_on());
+                $crate::generate_facade_subscribe_json!([<hermes_ $getter _subscribe_toggle_off_json>] = [<C $facade>]: subscribe_toggle_off(site_id));
+                $crate::generate_facade_subscribe_json!([<hermes_ $getter _subscribe_all_toggle_off_json>] = [<C $facade>]: subscribe_all_toggle_off());
+            );
         }
     };
 }
 
+/// The symbols of the methods of the facade returned by `$getter` for a message, published when
+/// the message comes from `$facade_side` and subscribed to otherwise
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __json_message_symbols {
+    (frontend frontend $getter:ident $facade:ident $($message:tt)*) => {
+        $crate::__json_publish_symbol!($getter $facade $($message)*);
+    };
+    (backend backend $getter:ident $facade:ident $($message:tt)*) => {
+        $crate::__json_publish_symbol!($getter $facade $($message)*);
+    };
+    (frontend backend $getter:ident $facade:ident $($message:tt)*) => {
+        $crate::__json_subscribe_symbols!($getter $facade $($message)*);
+    };
+    (backend frontend $getter:ident $facade:ident $($message:tt)*) => {
+        $crate::__json_subscribe_symbols!($getter $facade $($message)*);
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __json_publish_symbol {
+    ($getter:ident $facade:ident [] $publish:ident $($rest:tt)*) => {
+        $crate::paste::item! {
+            $crate::__facade_binding!($getter $publish
+                $crate::generate_facade_publish_json!([<hermes_ $getter _ $publish _json>] = [<C $facade>]: $publish);
+            );
+        }
+    };
+    ($getter:ident $facade:ident $payload:tt $publish:ident $subscribe:ident [$key:ident] $($rest:tt)*) => {
+        $crate::paste::item! {
+            $crate::__facade_binding!($getter $publish
+                $crate::generate_facade_publish_json!([<hermes_ $getter _ $publish _json>] = [<C $facade>]: $publish($key));
+            );
+        }
+    };
+    ($getter:ident $facade:ident $payload:tt $publish:ident $($rest:tt)*) => {
+        $crate::paste::item! {
+            $crate::__facade_binding!($getter $publish
+                $crate::generate_facade_publish_json!([<hermes_ $getter _ $publish _json>] = [<C $facade>]: $publish());
+            );
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __json_subscribe_symbols {
+    ($getter:ident $facade:ident [] $publish:ident $subscribe:ident $($rest:tt)*) => {
+        $crate::paste::item! {
+            $crate::__facade_binding!($getter $subscribe
+                $crate::generate_facade_subscribe_json!([<hermes_ $getter _ $subscribe _json>] = [<C $facade>]: $subscribe() without message);
+            );
+        }
+    };
+    ($getter:ident $facade:ident $payload:tt $publish:ident $subscribe:ident [] $($rest:tt)*) => {
+        $crate::paste::item! {
+            $crate::__facade_binding!($getter $subscribe
+                $crate::generate_facade_subscribe_json!([<hermes_ $getter _ $subscribe _json>] = [<C $facade>]: $subscribe());
+            );
+        }
+    };
+    ($getter:ident $facade:ident $payload:tt $publish:ident $subscribe:ident [$key:ident $($key_path:tt)*] [] $($rest:tt)*) => {
+        $crate::paste::item! {
+            $crate::__facade_binding!($getter $subscribe
+                $crate::generate_facade_subscribe_json!([<hermes_ $getter _ $subscribe _json>] = [<C $facade>]: $subscribe($key));
+            );
+        }
+    };
+    (
+        $getter:ident
+        $facade:ident
+        $payload:tt
+        $publish:ident
+        $subscribe:ident
+        [$key:ident $($key_path:tt)*]
+        [$subscribe_all:ident $($wildcard:tt)*]
+        $($rest:tt)*
+    ) => {
+        $crate::paste::item! {
+            $crate::__if_global_subscribe!($getter $subscribe {
+                $crate::__facade_binding!($getter $subscribe
+                    $crate::generate_facade_subscribe_json!([<hermes_ $getter _ $subscribe _json>] = [<C $facade>]: $subscribe_all());
+                    $crate::generate_facade_subscribe_json!([<hermes_ $getter _ $subscribe _for_site_json>] = [<C $facade>]: $subscribe($key));
+                );
+            } else {
+                $crate::__facade_binding!($getter $subscribe
+                    $crate::generate_facade_subscribe_json!([<hermes_ $getter _ $subscribe _json>] = [<C $facade>]: $subscribe($key));
+                    $crate::generate_facade_subscribe_json!([<hermes_ $getter _ $subscribe_all _json>] = [<C $facade>]: $subscribe_all());
+                );
+            });
+        }
+    };
+}
+
+/// The symbols exported under the name of the wrong facade method before every method had its
+/// own, kept for the binaries and bindings built against them
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __json_deprecated_symbols {
+    () => {
+        $crate::__facade_binding!(asr_backend deprecated
+            /// Deprecated, use `hermes_asr_backend_subscribe_start_listening_json`
+            #[no_mangle]
+            pub extern "C" fn hermes_asr_backend_publish_start_listening_json(
+                facade: *const CAsrBackendFacade,
+                handler: Option<unsafe extern "C" fn(*const libc::c_char, *mut libc::c_void)>,
+            ) -> ffi_utils::SNIPS_RESULT {
+                hermes_asr_backend_subscribe_start_listening_json(facade, handler)
+            }
+
+            /// Deprecated, use `hermes_asr_backend_subscribe_stop_listening_json`
+            #[no_mangle]
+            pub extern "C" fn hermes_asr_backend_publish_stop_listening_json(
+                facade: *const CAsrBackendFacade,
+                handler: Option<unsafe extern "C" fn(*const libc::c_char, *mut libc::c_void)>,
+            ) -> ffi_utils::SNIPS_RESULT {
+                hermes_asr_backend_subscribe_stop_listening_json(facade, handler)
+            }
+
+            /// Deprecated, use `hermes_asr_backend_publish_text_captured_json`
+            #[no_mangle]
+            pub extern "C" fn hermes_asr_backend_subscribe_text_captured_json(
+                facade: *const CAsrBackendFacade,
+                message: *const libc::c_char,
+            ) -> ffi_utils::SNIPS_RESULT {
+                hermes_asr_backend_publish_text_captured_json(facade, message)
+            }
+
+            /// Deprecated, use `hermes_asr_backend_publish_partial_text_captured_json`
+            #[no_mangle]
+            pub extern "C" fn hermes_asr_backend_subscribe_partial_text_captured_json(
+                facade: *const CAsrBackendFacade,
+                message: *const libc::c_char,
+            ) -> ffi_utils::SNIPS_RESULT {
+                hermes_asr_backend_publish_partial_text_captured_json(facade, message)
+            }
+        );
+    };
+}
+
+/// Generates the JSON symbols of the facades from `hermes::hermes_protocol!`
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __json_facades_symbols {
+    ($(
+        $component:ident {
+            facade $facade_doc:tt $facade:ident [$facade_super:ident $(+ $facade_supers:ident)*] $facade_getter:ident;
+            backend $backend_doc:tt $backend:ident [$backend_super:ident $(+ $backend_supers:ident)*] $backend_getter:ident;
+            mqtt $mqtt_component:tt $mqtt_toggle:tt $mqtt_topic:tt;
+            messages [$({ $attributes:tt $side:ident $message:ident $route:tt $($rest:tt)* })*];
+        }
+    )*) => {
+        $(
+            $crate::__json_shared_symbols!($facade_getter $facade $facade_super);
+            $($crate::__json_shared_symbols!($facade_getter $facade $facade_supers);)*
+            $crate::__json_shared_symbols!($backend_getter $backend $backend_super);
+            $($crate::__json_shared_symbols!($backend_getter $backend $backend_supers);)*
+            $($crate::__json_message_symbols!(frontend $side $facade_getter $facade $($rest)*);)*
+            $($crate::__json_message_symbols!(backend $side $backend_getter $backend $($rest)*);)*
+        )*
+    };
+}
+
 #[macro_export]
 macro_rules! generate_json_c_symbols {
     () => {
-        #[rustfmt::skip]
         pub mod json {
+            #[cfg(feature = "full_bindings")]
+            use super::facades::full_bindings::*;
             use super::facades::*;
             use super::LAST_ERROR;
+            use $crate::__json_facades_symbols;
+
+            hermes::hermes_protocol!(__json_facades_symbols);
+            $crate::__json_deprecated_symbols!();
+        }
+    };
+}
 
-            $crate::generate_facade_publish_json!(hermes_sound_feedback_publish_toggle_on_json = CSoundFeedbackFacade: publish_toggle_on());
-            $crate::generate_facade_publish_json!(hermes_sound_feedback_publish_toggle_off_json = CSoundFeedbackFacade: publish_toggle_off());
-
-            $crate::generate_facade_subscribe_json!(hermes_dialogue_subscribe_session_queued_json = CDialogueFacade: subscribe_session_queued());
-            $crate::generate_facade_subscribe_json!(hermes_dialogue_subscribe_session_started_json = CDialogueFacade: subscribe_session_started());
-            $crate::generate_facade_subscribe_json!(hermes_dialogue_subscribe_intent_json = CDialogueFacade: subscribe_intent(intent_name));
-            $crate::generate_facade_subscribe_json!(hermes_dialogue_subscribe_intents_json = CDialogueFacade: subscribe_intents());
-            $crate::generate_facade_subscribe_json!(hermes_dialogue_subscribe_intent_not_recognized_json = CDialogueFacade: subscribe_intent_not_recognized());
-            $crate::generate_facade_subscribe_json!(hermes_dialogue_subscribe_session_ended_json = CDialogueFacade: subscribe_session_ended());
-            $crate::generate_facade_publish_json!(hermes_dialogue_publish_start_session_json = CDialogueFacade: publish_start_session());
-            $crate::generate_facade_publish_json!(hermes_dialogue_publish_continue_session_json = CDialogueFacade: publish_continue_session());
-            $crate::generate_facade_publish_json!(hermes_dialogue_publish_end_session_json = CDialogueFacade: publish_end_session());
-            $crate::generate_facade_publish_json!(hermes_dialogue_publish_configure_json = CDialogueFacade: publish_configure());
-
-            $crate::generate_facade_publish_json!(hermes_tts_publish_register_sound_json = CTtsFacade: publish_register_sound());
-
-            $crate::generate_facade_publish_json!(hermes_injection_publish_injection_request_json = CInjectionFacade: publish_injection_request());
-            $crate::generate_facade_publish_json!(hermes_injection_publish_injection_status_request_json = CInjectionFacade: publish_injection_status_request);
-            $crate::generate_facade_publish_json!(hermes_injection_publish_injection_reset_request_json = CInjectionFacade: publish_injection_reset_request());
-            $crate::generate_facade_subscribe_json!(hermes_injection_subscribe_injection_status_json = CInjectionFacade: subscribe_injection_status());
-            $crate::generate_facade_subscribe_json!(hermes_injection_subscribe_injection_complete_json = CInjectionFacade: subscribe_injection_complete());
-            $crate::generate_facade_subscribe_json!(hermes_injection_subscribe_injection_reset_complete_json = CInjectionFacade: subscribe_injection_reset_complete());
+#[cfg(test)]
+mod tests {
+    const JSON_HEADER: &str = include_str!("../../../platforms/c/libsnips_hermes_json.h");
 
-            #[cfg(feature = "full_bindings")]
-            pub mod full_bindings {
-                use super::super::facades::full_bindings::*;
-                use super::super::LAST_ERROR;
-
-                $crate::generate_facade_subscribe_json!(hermes_hotword_subscribe_detected_json = CHotwordFacade: subscribe_detected(hotword_id));
-                $crate::generate_facade_subscribe_json!(hermes_hotword_subscribe_all_detected_json = CHotwordFacade: subscribe_all_detected());
-
-                $crate::generate_facade_publish_json!(hermes_hotword_backend_publish_detected_json = CHotwordBackendFacade: publish_detected(hotword_id));
-
-                $crate::generate_facade_publish_json!(hermes_asr_publish_start_listening_json = CAsrFacade: publish_start_listening());
-                $crate::generate_facade_publish_json!(hermes_asr_publish_stop_listening_json = CAsrFacade: publish_stop_listening());
-                $crate::generate_facade_subscribe_json!(hermes_asr_subscribe_text_captured_json = CAsrFacade: subscribe_text_captured());
-                $crate::generate_facade_subscribe_json!(hermes_asr_subscribe_partial_text_captured_json = CAsrFacade: subscribe_partial_text_captured());
-
-                $crate::generate_facade_subscribe_json!(hermes_asr_backend_publish_start_listening_json = CAsrBackendFacade: subscribe_start_listening());
-                $crate::generate_facade_subscribe_json!(hermes_asr_backend_publish_stop_listening_json = CAsrBackendFacade: subscribe_stop_listening());
-                $crate::generate_facade_publish_json!(hermes_asr_backend_subscribe_text_captured_json = CAsrBackendFacade: publish_text_captured());
-                $crate::generate_facade_publish_json!(hermes_asr_backend_subscribe_partial_text_captured_json = CAsrBackendFacade: publish_partial_text_captured());
-
-                $crate::generate_facade_publish_json!(hermes_tts_publish_say_json = CTtsFacade: publish_say());
-                $crate::generate_facade_subscribe_json!(hermes_tts_subscribe_say_finished_json = CTtsFacade: subscribe_say_finished());
-
-                $crate::generate_facade_subscribe_json!(hermes_tts_backend_subscribe_say_json = CTtsBackendFacade: subscribe_say());
-                $crate::generate_facade_publish_json!(hermes_tts_backend_publish_say_finished_json = CTtsBackendFacade: publish_say_finished());
-                $crate::generate_facade_subscribe_json!(hermes_tts_backend_subscribe_register_sound_json = CTtsBackendFacade: subscribe_register_sound());
-
-                $crate::generate_facade_publish_json!(hermes_nlu_publish_query_json = CNluFacade: publish_query());
-                $crate::generate_facade_publish_json!(hermes_nlu_publish_partial_query_json = CNluFacade: publish_partial_query());
-                $crate::generate_facade_subscribe_json!(hermes_nlu_subscribe_slot_parsed_json = CNluFacade: subscribe_slot_parsed());
-                $crate::generate_facade_subscribe_json!(hermes_nlu_subscribe_intent_parsed_json = CNluFacade: subscribe_intent_parsed());
-                $crate::generate_facade_subscribe_json!(hermes_nlu_subscribe_intent_not_recognized_json = CNluFacade: subscribe_intent_not_recognized());
-
-                $crate::generate_facade_subscribe_json!(hermes_nlu_backend_subscribe_query_json = CNluBackendFacade: subscribe_query());
-                $crate::generate_facade_subscribe_json!(hermes_nlu_backend_subscribe_partial_query_json = CNluBackendFacade: subscribe_partial_query());
-                $crate::generate_facade_publish_json!(hermes_nlu_backend_publish_slot_parsed_json = CNluBackendFacade: publish_slot_parsed());
-                $crate::generate_facade_publish_json!(hermes_nlu_backend_publish_intent_parsed_json = CNluBackendFacade: publish_intent_parsed());
-                $crate::generate_facade_publish_json!(hermes_nlu_backend_publish_intent_not_recognized_json = CNluBackendFacade: publish_intent_not_recognized());
-
-                $crate::generate_facade_publish_json!(hermes_audio_server_publish_play_bytes_json = CAudioServerFacade: publish_play_bytes());
-                $crate::generate_facade_subscribe_json!(hermes_audio_server_subscribe_play_finished_json = CAudioServerFacade: subscribe_play_finished(site_id));
-                $crate::generate_facade_subscribe_json!(hermes_audio_server_subscribe_all_play_finished_json = CAudioServerFacade: subscribe_all_play_finished());
-                $crate::generate_facade_subscribe_json!(hermes_audio_server_subscribe_audio_frame_json = CAudioServerFacade: subscribe_audio_frame(site_id));
-
-                $crate::generate_facade_subscribe_json!(hermes_audio_server_backend_subscribe_play_bytes_json = CAudioServerBackendFacade: subscribe_play_bytes(site_id));
-                $crate::generate_facade_subscribe_json!(hermes_audio_server_backend_subscribe_all_play_bytes_json = CAudioServerBackendFacade: subscribe_all_play_bytes());
-                $crate::generate_facade_publish_json!(hermes_audio_server_backend_publish_play_finished_json = CAudioServerBackendFacade: publish_play_finished());
-                $crate::generate_facade_publish_json!(hermes_audio_server_backend_publish_audio_frame_json = CAudioServerBackendFacade: publish_audio_frame());
-
-                $crate::generate_facade_publish_json!(hermes_dialogue_backend_publish_session_queued_json = CDialogueBackendFacade: publish_session_queued());
-                $crate::generate_facade_publish_json!(hermes_dialogue_backend_publish_session_started_json = CDialogueBackendFacade: publish_session_started());
-                $crate::generate_facade_publish_json!(hermes_dialogue_backend_publish_intent_json = CDialogueBackendFacade: publish_intent());
-                $crate::generate_facade_publish_json!(hermes_dialogue_backend_publish_intent_not_recognized_json = CDialogueBackendFacade: publish_intent_not_recognized());
-                $crate::generate_facade_publish_json!(hermes_dialogue_backend_publish_session_ended_json = CDialogueBackendFacade: publish_session_ended());
-                $crate::generate_facade_subscribe_json!(hermes_dialogue_backend_subscribe_start_session_json = CDialogueBackendFacade: subscribe_start_session());
-                $crate::generate_facade_subscribe_json!(hermes_dialogue_backend_subscribe_continue_session_json = CDialogueBackendFacade: subscribe_continue_session());
-                $crate::generate_facade_subscribe_json!(hermes_dialogue_backend_subscribe_end_session_json = CDialogueBackendFacade: subscribe_end_session());
-                $crate::generate_facade_subscribe_json!(hermes_dialogue_backend_subscribe_configure_json = CDialogueBackendFacade: subscribe_configure());
+    /// The symbols kept under their old name, see `__json_deprecated_symbols!`
+    const DEPRECATED_JSON_SYMBOLS: &[&str] = &[
+        "hermes_asr_backend_publish_start_listening_json",
+        "hermes_asr_backend_publish_stop_listening_json",
+        "hermes_asr_backend_subscribe_text_captured_json",
+        "hermes_asr_backend_subscribe_partial_text_captured_json",
+    ];
+
+    macro_rules! json_symbol {
+        ($getter:ident $method:ident) => {
+            concat!("hermes_", stringify!($getter), "_", stringify!($method), "_json")
+        };
+    }
+
+    /// The symbols of a message for the facade returned by `$getter`, published when the message
+    /// comes from `$facade_side` and subscribed to otherwise
+    macro_rules! message_symbols {
+        (frontend frontend $getter:ident $payload:tt $publish:ident $($rest:tt)*) => {
+            vec![json_symbol!($getter $publish)]
+        };
+        (backend backend $getter:ident $payload:tt $publish:ident $($rest:tt)*) => {
+            vec![json_symbol!($getter $publish)]
+        };
+        ($facade_side:ident $side:ident $getter:ident $payload:tt $publish:ident $subscribe:ident $key:tt [] $($rest:tt)*) => {
+            vec![json_symbol!($getter $subscribe)]
+        };
+        (
+            $facade_side:ident
+            $side:ident
+            $getter:ident
+            $payload:tt
+            $publish:ident
+            $subscribe:ident
+            $key:tt
+            [$subscribe_all:ident $($wildcard:tt)*]
+            $($rest:tt)*
+        ) => {
+            crate::__if_global_subscribe!($getter $subscribe {
+                vec![
+                    json_symbol!($getter $subscribe),
+                    concat!("hermes_", stringify!($getter), "_", stringify!($subscribe), "_for_site_json"),
+                ]
+            } else {
+                vec![json_symbol!($getter $subscribe), json_symbol!($getter $subscribe_all)]
+            })
+        };
+    }
+
+    /// The shared facades of each facade getter and the symbols of the messages of
+    /// `hermes_protocol!`
+    macro_rules! protocol_json_symbols {
+        ($(
+            $component:ident {
+                facade $facade_doc:tt $facade:ident [$facade_super:ident $(+ $facade_supers:ident)*] $facade_getter:ident;
+                backend $backend_doc:tt $backend:ident [$backend_super:ident $(+ $backend_supers:ident)*] $backend_getter:ident;
+                mqtt $mqtt_component:tt $mqtt_toggle:tt $mqtt_topic:tt;
+                messages [$({ $attributes:tt $side:ident $message:ident $route:tt $($rest:tt)* })*];
+            }
+        )*) => {{
+            let mut facades: Vec<(&str, Vec<&str>)> = vec![];
+            let mut symbols: Vec<&str> = vec![];
+            $(
+                facades.push((
+                    stringify!($facade_getter),
+                    vec![stringify!($facade_super) $(, stringify!($facade_supers))*],
+                ));
+                facades.push((
+                    stringify!($backend_getter),
+                    vec![stringify!($backend_super) $(, stringify!($backend_supers))*],
+                ));
+                $(symbols.extend(message_symbols!(frontend $side $facade_getter $($rest)*));)*
+                $(symbols.extend(message_symbols!(backend $side $backend_getter $($rest)*));)*
+            )*
+            (facades, symbols)
+        }};
+    }
+
+    /// The methods of the facades shared by the components, from `hermes_shared_facades!`
+    macro_rules! shared_methods {
+        ($(
+            $(#[$doc:meta])*
+            $facade:ident [$(fn $method:ident(&self $(, $argument:ident: $argument_type:ty)*);)*]
+        )*) => {
+            vec![$((stringify!($facade), vec![$(stringify!($method)),*])),*]
+        };
+    }
+
+    /// The JSON symbols of every method of every facade: the ones of the messages described by
+    /// `hermes_protocol!` and the ones of the shared facades they implement, described by
+    /// `hermes_shared_facades!`, along with the `_with_subscription` variant of the subscribe ones
+    fn expected_json_symbols() -> Vec<String> {
+        let traits: Vec<(&str, Vec<&str>)> = hermes::hermes_shared_facades!(shared_methods);
+        let (facades, message_symbols) = hermes::hermes_protocol!(protocol_json_symbols);
+
+        let mut symbols: Vec<String> = message_symbols.into_iter().map(String::from).collect();
+        for (getter, supers) in facades {
+            for name in supers {
+                let (_, methods) = traits
+                    .iter()
+                    .find(|(it, _)| *it == name)
+                    .unwrap_or_else(|| panic!("{} is not a shared facade", name));
+                symbols.extend(
+                    methods
+                        .iter()
+                        .map(|method| format!("hermes_{}_{}_json", getter, method)),
+                );
             }
         }
-    };
+        let with_subscription: Vec<String> = symbols
+            .iter()
+            .filter(|it| it.contains("_subscribe_"))
+            .map(|it| format!("{}_with_subscription", it))
+            .collect();
+        symbols.extend(with_subscription);
+        symbols
+    }
+
+    #[test]
+    fn the_json_header_declares_every_json_symbol() {
+        let missing: Vec<_> = expected_json_symbols()
+            .into_iter()
+            .filter(|symbol| !JSON_HEADER.contains(&format!(" {}(", symbol)))
+            .collect();
+
+        assert!(
+            missing.is_empty(),
+            "libsnips_hermes_json.h is outdated, run update_c_headers.sh, missing: {:?}",
+            missing
+        );
+    }
+
+    #[test]
+    fn the_json_header_keeps_the_deprecated_symbols() {
+        for symbol in DEPRECATED_JSON_SYMBOLS {
+            assert!(
+                JSON_HEADER.contains(&format!(" {}(", symbol)),
+                "{} is not declared anymore",
+                symbol
+            );
+        }
+    }
 }
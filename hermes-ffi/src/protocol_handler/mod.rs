@@ -1,10 +1,14 @@
+pub mod dispatch;
 pub mod facades;
 #[cfg(feature = "json")]
 pub mod json;
 #[cfg(feature = "structures")]
 pub mod structures;
 
-pub use facades::{CProtocolHandler, UserData};
+pub use dispatch::{dispatch_all_pending, Dispatcher};
+pub use facades::{
+    ptr_to_callback0, CCallback0, CProtocolHandler, CSubscription, Subscription, UserData, UserDataDestructor,
+};
 #[cfg(feature = "json")]
 pub use json::{json_from_slice, json_ptr_to_callback, CJsonCallback};
 #[cfg(feature = "structures")]
@@ -18,6 +22,128 @@ macro_rules! generate_hermes_c_symbols {
             ffi_utils::wrap!($crate::init_debug_logs())
         }
 
+        /// Create a handler delivering the messages inside the process, without a broker. The
+        /// handlers created with the same non null `bus_name` share their bus as long as one of
+        /// them is alive, a null `bus_name` gives a handler with a bus of its own.
+        #[no_mangle]
+        pub extern "C" fn hermes_protocol_handler_new_in_process(
+            handler: *mut *const $crate::CProtocolHandler,
+            bus_name: *const libc::c_char,
+            user_data: *mut libc::c_void,
+        ) -> ffi_utils::SNIPS_RESULT {
+            fn new_in_process_handler(
+                handler: *mut *const $crate::CProtocolHandler,
+                bus_name: *const libc::c_char,
+                user_data: *mut libc::c_void,
+            ) -> failure::Fallible<()> {
+                use ffi_utils::RawPointerConverter;
+
+                let cph = $crate::CProtocolHandler::new_in_process(bus_name, user_data)?;
+                let ptr = $crate::CProtocolHandler::into_raw_pointer(cph);
+                unsafe {
+                    *handler = ptr;
+                }
+                Ok(())
+            }
+
+            ffi_utils::wrap!(new_in_process_handler(handler, bus_name, user_data))
+        }
+
+        #[no_mangle]
+        pub extern "C" fn hermes_destroy_in_process_protocol_handler(
+            handler: *mut $crate::CProtocolHandler,
+        ) -> ffi_utils::SNIPS_RESULT {
+            fn destroy_in_process_handler(handler: *mut $crate::CProtocolHandler) -> failure::Fallible<()> {
+                use ffi_utils::RawPointerConverter;
+
+                unsafe { $crate::CProtocolHandler::from_raw_pointer(handler) }?.destroy();
+                Ok(())
+            }
+
+            ffi_utils::wrap!(destroy_in_process_handler(handler))
+        }
+
+        /// Set the function called with the `user_data` given at the creation of `handler` once the
+        /// handler, its facades and its subscriptions are all gone
+        #[no_mangle]
+        pub extern "C" fn hermes_protocol_handler_set_user_data_destructor(
+            handler: *const $crate::CProtocolHandler,
+            destructor: Option<unsafe extern "C" fn(*mut libc::c_void)>,
+        ) -> ffi_utils::SNIPS_RESULT {
+            ffi_utils::wrap!(unsafe { (*handler).user_data() }.set_destructor(destructor))
+        }
+
+        /// Queue the callbacks of the subscriptions made through `handler` and its facades instead
+        /// of calling them on the thread of the communication layer. They are then called by
+        /// `hermes_poll` or `hermes_dispatch_pending`, on the thread of the caller.
+        #[no_mangle]
+        pub extern "C" fn hermes_protocol_handler_enable_queued_dispatch(
+            handler: *const $crate::CProtocolHandler,
+        ) -> ffi_utils::SNIPS_RESULT {
+            ffi_utils::wrap!(unsafe { (*handler).user_data() }.dispatcher().enable_queue())
+        }
+
+        /// Give a file descriptor that is readable while callbacks of `handler` are queued, so that
+        /// hosts can wait for them in their own event loop. The queue must be enabled first and the
+        /// descriptor stays owned by the handler. Only available on unix.
+        #[no_mangle]
+        pub extern "C" fn hermes_protocol_handler_dispatch_fd(
+            handler: *const $crate::CProtocolHandler,
+            fd: *mut libc::c_int,
+        ) -> ffi_utils::SNIPS_RESULT {
+            fn fun(handler: *const $crate::CProtocolHandler, fd: *mut libc::c_int) -> failure::Fallible<()> {
+                let dispatch_fd = unsafe { (*handler).user_data() }.dispatcher().fd()?;
+                unsafe { *fd = dispatch_fd };
+                Ok(())
+            }
+
+            ffi_utils::wrap!(fun(handler, fd))
+        }
+
+        /// Call the queued callbacks of `handler`, waiting up to `timeout_ms` milliseconds for one
+        /// if there are none. A negative timeout waits as long as needed.
+        #[no_mangle]
+        pub extern "C" fn hermes_poll(
+            handler: *const $crate::CProtocolHandler,
+            timeout_ms: libc::c_int,
+        ) -> ffi_utils::SNIPS_RESULT {
+            fn fun(handler: *const $crate::CProtocolHandler, timeout_ms: libc::c_int) -> failure::Fallible<()> {
+                let timeout = if timeout_ms < 0 {
+                    None
+                } else {
+                    Some(std::time::Duration::from_millis(timeout_ms as u64))
+                };
+                unsafe { (*handler).user_data() }.dispatcher().poll(timeout)?;
+                Ok(())
+            }
+
+            ffi_utils::wrap!(fun(handler, timeout_ms))
+        }
+
+        /// Call the queued callbacks of all the handlers, without waiting
+        #[no_mangle]
+        pub extern "C" fn hermes_dispatch_pending() -> ffi_utils::SNIPS_RESULT {
+            ffi_utils::wrap!($crate::dispatch_all_pending().map(|_| ()))
+        }
+
+        /// Unregister the handler of `subscription` from the protocol handler and release the token.
+        /// The `user_data` of the handler isn't given to it anymore once this returns.
+        #[no_mangle]
+        pub extern "C" fn hermes_unsubscribe(subscription: *const $crate::CSubscription) -> ffi_utils::SNIPS_RESULT {
+            fn fun(subscription: *const $crate::CSubscription) -> failure::Fallible<()> {
+                use ffi_utils::RawPointerConverter;
+
+                unsafe { $crate::CSubscription::from_raw_pointer(subscription) }?.unsubscribe();
+                Ok(())
+            }
+
+            ffi_utils::wrap!(fun(subscription))
+        }
+
+        // release a subscription without unsubscribing, its handler is then called as long as the
+        // protocol handler lives
+        $crate::generate_destroy!(hermes_drop_subscription for $crate::CSubscription);
+
         generate_facade_c_symbols!();
 
         #[cfg(feature = "structures")]
@@ -1,24 +1,32 @@
+use std::sync::Arc;
+
 use failure::Fallible;
 use ffi_utils::CReprOf;
 
-use crate::protocol_handler::UserData;
+use crate::protocol_handler::Subscription;
 
 pub fn structure_ptr_to_callback<T, U>(
     ptr: Option<unsafe extern "C" fn(*const U, *mut libc::c_void)>,
-    user_data: UserData,
+    subscription: Arc<Subscription>,
 ) -> Fallible<hermes::Callback<T>>
 where
-    T: Clone + Sync,
+    T: Clone + Send + Sync + 'static,
     U: CReprOf<T> + Sync + 'static,
 {
-    if let Some(ptr) = ptr {
-        Ok(hermes::Callback::new(move |payload: &T| {
-            let param = Box::into_raw(Box::new(U::c_repr_of(payload.clone()).unwrap()));
-            unsafe { ptr(param, user_data.0) }
-        }))
-    } else {
-        Err(failure::format_err!("null pointer"))
-    }
+    let ptr = ptr.ok_or_else(|| failure::format_err!("null pointer"))?;
+    let shared = Arc::clone(&subscription);
+    let callback = hermes::Callback::new(move |payload: &T| {
+        let payload = payload.clone();
+        let dispatched = Subscription::dispatch(&shared, move |user_data| {
+            let param = Box::into_raw(Box::new(U::c_repr_of(payload).unwrap()));
+            unsafe { ptr(param, user_data) }
+        });
+        if let Err(e) = dispatched {
+            log::error!("could not dispatch message: {}", e)
+        }
+    });
+    subscription.cancel_with(callback.subscription());
+    Ok(callback)
 }
 
 #[macro_export]
@@ -63,21 +71,236 @@ macro_rules! generate_facade_publish {
     };
 }
 
+/// Generates `$c_symbol`, and `$c_symbol` followed by `_with_subscription` which also gives back
+/// a `CSubscription` token to unsubscribe with
 #[macro_export]
 macro_rules! generate_facade_subscribe {
+    ($c_symbol:ident = $facade:ty:$method:ident($( $filter_name:ident : $filter:ty as $filter_raw:ty,)*) without message) => {
+        $crate::paste::item! {
+            #[no_mangle]
+            pub extern "C" fn $c_symbol(facade: *const $facade, $($filter_name : *const $filter_raw,)* handler: Option<unsafe extern "C" fn(*mut libc::c_void)>) -> ffi_utils::SNIPS_RESULT {
+                [<$c_symbol _with_subscription>](facade, $($filter_name,)* handler, std::ptr::null_mut())
+            }
+
+            #[no_mangle]
+            pub extern "C" fn [<$c_symbol _with_subscription>](facade: *const $facade, $($filter_name : *const $filter_raw,)* handler: Option<unsafe extern "C" fn(*mut libc::c_void)>, subscription: *mut *const $crate::CSubscription) -> ffi_utils::SNIPS_RESULT {
+                fn fun(facade: *const $facade, $($filter_name : *const $filter_raw,)* handler: Option<$crate::CCallback0>, subscription: *mut *const $crate::CSubscription) -> failure::Fallible<()> {
+                    use ffi_utils::{AsRust, RawBorrow};
+
+                    let shared = $crate::Subscription::new(unsafe { (*facade).user_data().duplicate() });
+                    let callback = $crate::ptr_to_callback0(handler, std::sync::Arc::clone(&shared))?;
+                    unsafe { (*facade).extract().$method($(<$filter as RawBorrow<$filter_raw>>::raw_borrow($filter_name)?.as_rust()?,)* callback) }?;
+                    $crate::CSubscription::write_to(&shared, subscription);
+                    Ok(())
+                }
+
+                ffi_utils::wrap!(fun(facade, $($filter_name,)* handler, subscription))
+            }
+        }
+    };
     ($c_symbol:ident = $facade:ty:$method:ident($( $filter_name:ident : $filter:ty as $filter_raw:ty,)* | $arg:ty|)) => {
-        #[no_mangle]
-        pub extern "C" fn $c_symbol(facade: *const $facade, $($filter_name : *const $filter_raw,)* handler: Option<unsafe extern "C" fn(*const $arg, *mut libc::c_void)>) -> ffi_utils::SNIPS_RESULT {
-            fn fun(facade: *const $facade, $($filter_name : *const $filter_raw,)* handler: Option<unsafe extern "C" fn(*const $arg, *mut libc::c_void)>) -> failure::Fallible<()> {
-                use ffi_utils::{AsRust, RawBorrow};
+        $crate::paste::item! {
+            #[no_mangle]
+            pub extern "C" fn $c_symbol(facade: *const $facade, $($filter_name : *const $filter_raw,)* handler: Option<unsafe extern "C" fn(*const $arg, *mut libc::c_void)>) -> ffi_utils::SNIPS_RESULT {
+                [<$c_symbol _with_subscription>](facade, $($filter_name,)* handler, std::ptr::null_mut())
+            }
+
+            #[no_mangle]
+            pub extern "C" fn [<$c_symbol _with_subscription>](facade: *const $facade, $($filter_name : *const $filter_raw,)* handler: Option<unsafe extern "C" fn(*const $arg, *mut libc::c_void)>, subscription: *mut *const $crate::CSubscription) -> ffi_utils::SNIPS_RESULT {
+                fn fun(facade: *const $facade, $($filter_name : *const $filter_raw,)* handler: Option<unsafe extern "C" fn(*const $arg, *mut libc::c_void)>, subscription: *mut *const $crate::CSubscription) -> failure::Fallible<()> {
+                    use ffi_utils::{AsRust, RawBorrow};
 
-                let user_data = unsafe { (*facade).user_data().duplicate() };
-                let callback = $crate::structure_ptr_to_callback(handler, user_data)?;
-                unsafe { (*facade).extract().$method($(<$filter as RawBorrow<$filter_raw>>::raw_borrow($filter_name)?.as_rust()?,)* callback) }
+                    let shared = $crate::Subscription::new(unsafe { (*facade).user_data().duplicate() });
+                    let callback = $crate::structure_ptr_to_callback(handler, std::sync::Arc::clone(&shared))?;
+                    unsafe { (*facade).extract().$method($(<$filter as RawBorrow<$filter_raw>>::raw_borrow($filter_name)?.as_rust()?,)* callback) }?;
+                    $crate::CSubscription::write_to(&shared, subscription);
+                    Ok(())
+                }
+
+                ffi_utils::wrap!(fun(facade, $($filter_name,)* handler, subscription))
             }
+        }
+    };
+}
+
+/// Generates `hermes_set_struct_version`, for the libraries exchanging structures with the C side
+#[macro_export]
+macro_rules! generate_struct_version_c_symbol {
+    () => {
+        #[no_mangle]
+        pub extern "C" fn hermes_set_struct_version(version: libc::c_uint) -> ffi_utils::SNIPS_RESULT {
+            ffi_utils::wrap!($crate::ontology::set_struct_version(version))
+        }
+    };
+}
+
+/// The symbols of the methods shared by the components, for the facade returned by `$getter`. The
+/// version, error and component loaded messages have no C structures yet, the methods of the
+/// component facades are only exported as JSON.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __structures_shared_symbols {
+    ($getter:ident $facade:ident ToggleableFacade) => {
+        $crate::paste::item! {
+            $crate::__facade_binding!($getter shared
+                $crate::generate_facade_publish!([<hermes_ $getter _publish_toggle_on>] = [<C $facade>]: publish_toggle_on());
+                $crate::generate_facade_publish!([<hermes_ $getter _publish_toggle_off>] = [<C $facade>]: publish_toggle_off());
+            );
+        }
+    };
+    ($getter:ident $facade:ident ToggleableBackendFacade) => {
+        $crate::paste::item! {
+            $crate::__facade_binding!($getter shared
+                $crate::generate_facade_subscribe!([<hermes_ $getter _subscribe_toggle_on>] = [<C $facade>]: subscribe_toggle_on() without message);
+                $crate::generate_facade_subscribe!([<hermes_ $getter _subscribe_toggle_off>] = [<C $facade>]: subscribe_toggle_off() without message);
+            );
+        }
+    };
+    ($getter:ident $facade:ident IdentifiableToggleableFacade) => {
+        $crate::paste::item! {
+            $crate::__facade_binding!($getter shared
+                $crate::generate_facade_publish!([<hermes_ $getter _publish_toggle_on>] = [<C $facade>]: publish_toggle_on(CSiteMessage));
+                $crate::generate_facade_publish!([<hermes_ $getter _publish_toggle_off>] = [<C $facade>]: publish_toggle_off(CSiteMessage));
+            );
+        }
+    };
+    ($getter:ident $facade:ident IdentifiableToggleableBackendFacade) => {
+        $crate::paste::item! {
+            $crate::__facade_binding!($getter shared
+                $crate::generate_facade_subscribe!([<hermes_ $getter _subscribe_toggle_on>] = [<C $facade>]: subscribe_toggle_on(site_id: std::ffi::CStr as libc::c_char, |CSiteMessage|));
+                $crate::generate_facade_subscribe!([<hermes_ $getter _subscribe_all_toggle_on>] = [<C $facade>]: subscribe_all_toggle_on(|CSiteMessage|));
+                $crate::generate_facade_subscribe!([<hermes_ $getter _subscribe_toggle_off>] = [<C $facade>]: subscribe_toggle_off(site_id: std::ffi::CStr as libc::c_char, |CSiteMessage|));
+                $crate::generate_facade_subscribe!([<hermes_ $getter _subscribe_all_toggle_off>] = [<C $facade>]: subscribe_all_toggle_off(|CSiteMessage|));
+            );
+        }
+    };
+    ($getter:ident $facade:ident $shared:ident) => {};
+}
+
+/// The symbols of the methods of the facade returned by `$getter` for a message, published when
+/// the message comes from `$facade_side` and subscribed to otherwise
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __structures_message_symbols {
+    (frontend frontend $getter:ident $facade:ident $($message:tt)*) => {
+        $crate::__structures_publish_symbol!($getter $facade $($message)*);
+    };
+    (backend backend $getter:ident $facade:ident $($message:tt)*) => {
+        $crate::__structures_publish_symbol!($getter $facade $($message)*);
+    };
+    (frontend backend $getter:ident $facade:ident $($message:tt)*) => {
+        $crate::__structures_subscribe_symbols!($getter $facade $($message)*);
+    };
+    (backend frontend $getter:ident $facade:ident $($message:tt)*) => {
+        $crate::__structures_subscribe_symbols!($getter $facade $($message)*);
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __structures_publish_symbol {
+    ($getter:ident $facade:ident [] $publish:ident $($rest:tt)*) => {
+        $crate::paste::item! {
+            $crate::__facade_binding!($getter $publish
+                $crate::generate_facade_publish!([<hermes_ $getter _ $publish>] = [<C $facade>]: $publish());
+            );
+        }
+    };
+    ($getter:ident $facade:ident [$payload:ident: $payload_type:ident] $publish:ident $subscribe:ident [$key:ident] $($rest:tt)*) => {
+        $crate::paste::item! {
+            $crate::__facade_binding!($getter $publish
+                $crate::generate_facade_publish!([<hermes_ $getter _ $publish>] = [<C $facade>]: $publish( + $key: std::ffi::CStr as libc::c_char, [<C $payload_type>]));
+            );
+        }
+    };
+    ($getter:ident $facade:ident [$payload:ident: $payload_type:ident] $publish:ident $($rest:tt)*) => {
+        $crate::paste::item! {
+            $crate::__facade_binding!($getter $publish
+                $crate::generate_facade_publish!([<hermes_ $getter _ $publish>] = [<C $facade>]: $publish([<C $payload_type>]));
+            );
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __structures_subscribe_symbols {
+    ($getter:ident $facade:ident [] $publish:ident $subscribe:ident $($rest:tt)*) => {
+        $crate::paste::item! {
+            $crate::__facade_binding!($getter $subscribe
+                $crate::generate_facade_subscribe!([<hermes_ $getter _ $subscribe>] = [<C $facade>]: $subscribe() without message);
+            );
+        }
+    };
+    ($getter:ident $facade:ident [$payload:ident: $payload_type:ident] $publish:ident $subscribe:ident [] $($rest:tt)*) => {
+        $crate::paste::item! {
+            $crate::__facade_binding!($getter $subscribe
+                $crate::generate_facade_subscribe!([<hermes_ $getter _ $subscribe>] = [<C $facade>]: $subscribe(|[<C $payload_type>]|));
+            );
+        }
+    };
+    (
+        $getter:ident
+        $facade:ident
+        [$payload:ident: $payload_type:ident]
+        $publish:ident
+        $subscribe:ident
+        [$key:ident $($key_path:tt)*]
+        []
+        $($rest:tt)*
+    ) => {
+        $crate::paste::item! {
+            $crate::__facade_binding!($getter $subscribe
+                $crate::generate_facade_subscribe!([<hermes_ $getter _ $subscribe>] = [<C $facade>]: $subscribe($key: std::ffi::CStr as libc::c_char, |[<C $payload_type>]|));
+            );
+        }
+    };
+    (
+        $getter:ident
+        $facade:ident
+        [$payload:ident: $payload_type:ident]
+        $publish:ident
+        $subscribe:ident
+        [$key:ident $($key_path:tt)*]
+        [$subscribe_all:ident $($wildcard:tt)*]
+        $($rest:tt)*
+    ) => {
+        $crate::paste::item! {
+            $crate::__if_global_subscribe!($getter $subscribe {
+                $crate::__facade_binding!($getter $subscribe
+                    $crate::generate_facade_subscribe!([<hermes_ $getter _ $subscribe>] = [<C $facade>]: $subscribe_all(|[<C $payload_type>]|));
+                    $crate::generate_facade_subscribe!([<hermes_ $getter _ $subscribe _for_site>] = [<C $facade>]: $subscribe($key: std::ffi::CStr as libc::c_char, |[<C $payload_type>]|));
+                );
+            } else {
+                $crate::__facade_binding!($getter $subscribe
+                    $crate::generate_facade_subscribe!([<hermes_ $getter _ $subscribe>] = [<C $facade>]: $subscribe($key: std::ffi::CStr as libc::c_char, |[<C $payload_type>]|));
+                    $crate::generate_facade_subscribe!([<hermes_ $getter _ $subscribe_all>] = [<C $facade>]: $subscribe_all(|[<C $payload_type>]|));
+                );
+            });
+        }
+    };
+}
 
-            ffi_utils::wrap!(fun(facade, $($filter_name,)* handler))
+/// Generates the structures symbols of the facades from `hermes::hermes_protocol!`
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __structures_facades_symbols {
+    ($(
+        $component:ident {
+            facade $facade_doc:tt $facade:ident [$facade_super:ident $(+ $facade_supers:ident)*] $facade_getter:ident;
+            backend $backend_doc:tt $backend:ident [$backend_super:ident $(+ $backend_supers:ident)*] $backend_getter:ident;
+            mqtt $mqtt_component:tt $mqtt_toggle:tt $mqtt_topic:tt;
+            messages [$({ $attributes:tt $side:ident $message:ident $route:tt $($rest:tt)* })*];
         }
+    )*) => {
+        $(
+            $crate::__structures_shared_symbols!($facade_getter $facade $facade_super);
+            $($crate::__structures_shared_symbols!($facade_getter $facade $facade_supers);)*
+            $crate::__structures_shared_symbols!($backend_getter $backend $backend_super);
+            $($crate::__structures_shared_symbols!($backend_getter $backend $backend_supers);)*
+            $($crate::__structures_message_symbols!(frontend $side $facade_getter $facade $($rest)*);)*
+            $($crate::__structures_message_symbols!(backend $side $backend_getter $backend $($rest)*);)*
+        )*
     };
 }
 
@@ -85,32 +308,16 @@ macro_rules! generate_facade_subscribe {
 macro_rules! generate_structures_c_symbols {
     () => {
         pub mod structures {
-            use super::LAST_ERROR;
+            #[cfg(feature = "full_bindings")]
+            use super::facades::full_bindings::*;
             use super::facades::*;
+            use super::LAST_ERROR;
             use hermes_ffi::ontology::*;
+            use $crate::__structures_facades_symbols;
+
+            $crate::generate_struct_version_c_symbol!();
 
-            $crate::generate_facade_publish!(hermes_sound_feedback_publish_toggle_on = CSoundFeedbackFacade: publish_toggle_on(CSiteMessage));
-            $crate::generate_facade_publish!(hermes_sound_feedback_publish_toggle_off = CSoundFeedbackFacade: publish_toggle_off(CSiteMessage));
-
-            $crate::generate_facade_subscribe!(hermes_dialogue_subscribe_session_queued = CDialogueFacade: subscribe_session_queued(|CSessionQueuedMessage|));
-            $crate::generate_facade_subscribe!(hermes_dialogue_subscribe_session_started = CDialogueFacade: subscribe_session_started(|CSessionStartedMessage|));
-            $crate::generate_facade_subscribe!(hermes_dialogue_subscribe_intent = CDialogueFacade: subscribe_intent(intent_name: std::ffi::CStr as libc::c_char, |CIntentMessage| ));
-            $crate::generate_facade_subscribe!(hermes_dialogue_subscribe_intents = CDialogueFacade: subscribe_intents(|CIntentMessage|));
-            $crate::generate_facade_subscribe!(hermes_dialogue_subscribe_intent_not_recognized = CDialogueFacade: subscribe_intent_not_recognized(|CIntentNotRecognizedMessage| ));
-            $crate::generate_facade_subscribe!(hermes_dialogue_subscribe_session_ended = CDialogueFacade: subscribe_session_ended(|CSessionEndedMessage|));
-            $crate::generate_facade_publish!(hermes_dialogue_publish_start_session = CDialogueFacade: publish_start_session(CStartSessionMessage));
-            $crate::generate_facade_publish!(hermes_dialogue_publish_continue_session = CDialogueFacade: publish_continue_session(CContinueSessionMessage));
-            $crate::generate_facade_publish!(hermes_dialogue_publish_end_session = CDialogueFacade: publish_end_session(CEndSessionMessage));
-            $crate::generate_facade_publish!(hermes_dialogue_publish_configure = CDialogueFacade: publish_configure(CDialogueConfigureMessage));
-
-            $crate::generate_facade_publish!(hermes_injection_publish_injection_request = CInjectionFacade: publish_injection_request(CInjectionRequestMessage));
-            $crate::generate_facade_publish!(hermes_injection_publish_injection_status_request = CInjectionFacade: publish_injection_status_request());
-            $crate::generate_facade_publish!(hermes_injection_publish_injection_reset_request = CInjectionFacade: publish_injection_reset_request(CInjectionResetRequestMessage));
-            $crate::generate_facade_subscribe!(hermes_injection_subscribe_injection_status = CInjectionFacade: subscribe_injection_status(|CInjectionStatusMessage|));
-            $crate::generate_facade_subscribe!(hermes_injection_subscribe_injection_complete = CInjectionFacade: subscribe_injection_complete(|CInjectionCompleteMessage|));
-            $crate::generate_facade_subscribe!(hermes_injection_subscribe_injection_reset_complete = CInjectionFacade: subscribe_injection_reset_complete(|CInjectionResetCompleteMessage|));
-
-            $crate::generate_facade_publish!(hermes_tts_publish_register_sound = CTtsFacade: publish_register_sound(CRegisterSoundMessage));
+            hermes::hermes_protocol!(__structures_facades_symbols);
 
             $crate::generate_destroy!(hermes_drop_intent_message for CIntentMessage);
             $crate::generate_destroy!(hermes_drop_intent_not_recognized_message for CIntentNotRecognizedMessage);
@@ -125,64 +332,8 @@ macro_rules! generate_structures_c_symbols {
 
             #[cfg(feature = "full_bindings")]
             pub mod full_bindings {
-                use super::super::LAST_ERROR;
-                use super::super::facades::full_bindings::*;
                 use hermes_ffi::ontology::*;
 
-                $crate::generate_facade_subscribe!(hermes_hotword_subscribe_detected = CHotwordFacade: subscribe_detected(hotword_id: std::ffi::CStr as libc::c_char, |CHotwordDetectedMessage|));
-                $crate::generate_facade_subscribe!(hermes_hotword_subscribe_all_detected = CHotwordFacade: subscribe_all_detected(|CHotwordDetectedMessage|));
-
-                $crate::generate_facade_publish!(hermes_hotword_backend_publish_detected = CHotwordBackendFacade: publish_detected( + hotword_id: std::ffi::CStr as libc::c_char, CHotwordDetectedMessage));
-
-                $crate::generate_facade_publish!(hermes_asr_publish_start_listening = CAsrFacade: publish_start_listening(CAsrStartListeningMessage));
-                $crate::generate_facade_publish!(hermes_asr_publish_stop_listening = CAsrFacade: publish_stop_listening(CSiteMessage));
-                $crate::generate_facade_subscribe!(hermes_asr_subscribe_text_captured = CAsrFacade: subscribe_text_captured(|CTextCapturedMessage|));
-                $crate::generate_facade_subscribe!(hermes_asr_subscribe_partial_text_captured = CAsrFacade: subscribe_partial_text_captured(|CTextCapturedMessage|));
-
-                $crate::generate_facade_subscribe!(hermes_asr_backend_subscribe_start_listening = CAsrBackendFacade: subscribe_start_listening(|CAsrStartListeningMessage|));
-                $crate::generate_facade_subscribe!(hermes_asr_backend_subscribe_stop_listening = CAsrBackendFacade: subscribe_stop_listening(|CSiteMessage|));
-                $crate::generate_facade_publish!(hermes_asr_backend_publish_text_captured = CAsrBackendFacade: publish_text_captured(CTextCapturedMessage));
-                $crate::generate_facade_publish!(hermes_asr_backend_publish_partial_text_captured = CAsrBackendFacade: publish_partial_text_captured(CTextCapturedMessage));
-
-                $crate::generate_facade_publish!(hermes_tts_publish_say = CTtsFacade: publish_say(CSayMessage));
-                $crate::generate_facade_subscribe!(hermes_tts_subscribe_say_finished = CTtsFacade: subscribe_say_finished(|CSayFinishedMessage|));
-
-                $crate::generate_facade_subscribe!(hermes_tts_backend_subscribe_say = CTtsBackendFacade: subscribe_say(|CSayMessage|));
-                $crate::generate_facade_publish!(hermes_tts_backend_publish_say_finished = CTtsBackendFacade: publish_say_finished(CSayFinishedMessage));
-                $crate::generate_facade_subscribe!(hermes_tts_backend_subscribe_register_sound = CTtsBackendFacade: subscribe_register_sound(|CRegisterSoundMessage|));
-
-                $crate::generate_facade_publish!(hermes_nlu_publish_query = CNluFacade: publish_query(CNluQueryMessage));
-                $crate::generate_facade_publish!(hermes_nlu_publish_partial_query = CNluFacade: publish_partial_query(CNluSlotQueryMessage));
-                $crate::generate_facade_subscribe!(hermes_nlu_subscribe_slot_parsed = CNluFacade: subscribe_slot_parsed(|CNluSlotMessage|));
-                $crate::generate_facade_subscribe!(hermes_nlu_subscribe_intent_parsed = CNluFacade: subscribe_intent_parsed(|CNluIntentMessage|));
-                $crate::generate_facade_subscribe!(hermes_nlu_subscribe_intent_not_recognized = CNluFacade: subscribe_intent_not_recognized(|CNluIntentNotRecognizedMessage|));
-
-                $crate::generate_facade_subscribe!(hermes_nlu_backend_subscribe_query = CNluBackendFacade: subscribe_query(|CNluQueryMessage|));
-                $crate::generate_facade_subscribe!(hermes_nlu_backend_subscribe_partial_query = CNluBackendFacade: subscribe_partial_query(|CNluSlotQueryMessage|));
-                $crate::generate_facade_publish!(hermes_nlu_backend_publish_slot_parsed = CNluBackendFacade: publish_slot_parsed(CNluSlotMessage));
-                $crate::generate_facade_publish!(hermes_nlu_backend_publish_intent_parsed = CNluBackendFacade: publish_intent_parsed(CNluIntentMessage));
-                $crate::generate_facade_publish!(hermes_nlu_backend_publish_intent_not_recognized = CNluBackendFacade: publish_intent_not_recognized(CNluIntentNotRecognizedMessage));
-
-                $crate::generate_facade_publish!(hermes_audio_server_publish_play_bytes = CAudioServerFacade: publish_play_bytes(CPlayBytesMessage));
-                $crate::generate_facade_subscribe!(hermes_audio_server_subscribe_play_finished = CAudioServerFacade: subscribe_play_finished(site_id: std::ffi::CStr as libc::c_char, |CPlayFinishedMessage|));
-                $crate::generate_facade_subscribe!(hermes_audio_server_subscribe_all_play_finished = CAudioServerFacade: subscribe_all_play_finished(|CPlayFinishedMessage|));
-                $crate::generate_facade_subscribe!(hermes_audio_server_subscribe_audio_frame = CAudioServerFacade: subscribe_audio_frame(site_id: std::ffi::CStr as libc::c_char, |CAudioFrameMessage|));
-
-                $crate::generate_facade_subscribe!(hermes_audio_server_backend_subscribe_play_bytes = CAudioServerBackendFacade: subscribe_play_bytes(site_id: std::ffi::CStr as libc::c_char, |CPlayBytesMessage|));
-                $crate::generate_facade_subscribe!(hermes_audio_server_backend_subscribe_all_play_bytes = CAudioServerBackendFacade: subscribe_all_play_bytes(|CPlayBytesMessage|));
-                $crate::generate_facade_publish!(hermes_audio_server_backend_publish_play_finished = CAudioServerBackendFacade: publish_play_finished(CPlayFinishedMessage));
-                $crate::generate_facade_publish!(hermes_audio_server_backend_publish_audio_frame = CAudioServerBackendFacade: publish_audio_frame(CAudioFrameMessage));
-
-                $crate::generate_facade_publish!(hermes_dialogue_backend_publish_session_queued = CDialogueBackendFacade: publish_session_queued(CSessionQueuedMessage));
-                $crate::generate_facade_publish!(hermes_dialogue_backend_publish_session_started = CDialogueBackendFacade: publish_session_started(CSessionStartedMessage));
-                $crate::generate_facade_publish!(hermes_dialogue_backend_publish_intent = CDialogueBackendFacade: publish_intent(CIntentMessage));
-                $crate::generate_facade_publish!(hermes_dialogue_backend_publish_intent_not_recognized = CDialogueBackendFacade: publish_intent_not_recognized(CIntentNotRecognizedMessage));
-                $crate::generate_facade_publish!(hermes_dialogue_backend_publish_session_ended = CDialogueBackendFacade: publish_session_ended(CSessionEndedMessage));
-                $crate::generate_facade_subscribe!(hermes_dialogue_backend_subscribe_start_session = CDialogueBackendFacade: subscribe_start_session(|CStartSessionMessage|));
-                $crate::generate_facade_subscribe!(hermes_dialogue_backend_subscribe_continue_session = CDialogueBackendFacade: subscribe_continue_session(|CContinueSessionMessage|));
-                $crate::generate_facade_subscribe!(hermes_dialogue_backend_subscribe_end_session = CDialogueBackendFacade: subscribe_end_session(|CEndSessionMessage|));
-                $crate::generate_facade_subscribe!(hermes_dialogue_backend_subscribe_configure = CDialogueBackendFacade: subscribe_configure(|CDialogueConfigureMessage|));
-
                 $crate::generate_destroy!(hermes_drop_site_message for CSiteMessage);
                 $crate::generate_destroy!(hermes_drop_hotword_detected_message for CHotwordDetectedMessage);
                 $crate::generate_destroy!(hermes_drop_text_captured_message for CTextCapturedMessage);
@@ -191,6 +342,9 @@ macro_rules! generate_structures_c_symbols {
                 $crate::generate_destroy!(hermes_drop_play_bytes_message for CPlayBytesMessage);
                 $crate::generate_destroy!(hermes_drop_audio_frame_message for CAudioFrameMessage);
                 $crate::generate_destroy!(hermes_drop_play_finished_message for CPlayFinishedMessage);
+                $crate::generate_destroy!(hermes_drop_stream_bytes_message for CStreamBytesMessage);
+                $crate::generate_destroy!(hermes_drop_stream_finished_message for CStreamFinishedMessage);
+                $crate::generate_destroy!(hermes_drop_replay_request_message for CReplayRequestMessage);
                 $crate::generate_destroy!(hermes_drop_say_message for CSayMessage);
                 $crate::generate_destroy!(hermes_drop_say_finished_message for CSayFinishedMessage);
                 $crate::generate_destroy!(hermes_drop_nlu_slot_message for CNluSlotMessage);
@@ -203,7 +357,134 @@ macro_rules! generate_structures_c_symbols {
                 $crate::generate_destroy!(hermes_drop_injection_request_message for CInjectionRequestMessage);
                 $crate::generate_destroy!(hermes_drop_injection_reset_request_message for CInjectionResetRequestMessage);
                 $crate::generate_destroy!(hermes_drop_register_sound_message for CRegisterSoundMessage);
+                $crate::generate_destroy!(hermes_drop_request_component_reload_message for CRequestComponentReloadMessage);
+                $crate::generate_destroy!(hermes_drop_vad_up_message for CVadUpMessage);
+                $crate::generate_destroy!(hermes_drop_vad_down_message for CVadDownMessage);
             }
         }
     };
 }
+
+#[cfg(test)]
+mod tests {
+    const FULL_HEADER: &str = include_str!("../../../platforms/c/libsnips_hermes_full.h");
+
+    macro_rules! symbol {
+        ($getter:ident $method:ident) => {
+            concat!("hermes_", stringify!($getter), "_", stringify!($method))
+        };
+    }
+
+    /// The symbols of a message for the facade returned by `$getter`, published when the message
+    /// comes from `$facade_side` and subscribed to otherwise
+    macro_rules! message_symbols {
+        (frontend frontend $getter:ident $payload:tt $publish:ident $($rest:tt)*) => {
+            vec![symbol!($getter $publish)]
+        };
+        (backend backend $getter:ident $payload:tt $publish:ident $($rest:tt)*) => {
+            vec![symbol!($getter $publish)]
+        };
+        ($facade_side:ident $side:ident $getter:ident $payload:tt $publish:ident $subscribe:ident $key:tt [] $($rest:tt)*) => {
+            vec![symbol!($getter $subscribe)]
+        };
+        (
+            $facade_side:ident
+            $side:ident
+            $getter:ident
+            $payload:tt
+            $publish:ident
+            $subscribe:ident
+            $key:tt
+            [$subscribe_all:ident $($wildcard:tt)*]
+            $($rest:tt)*
+        ) => {
+            crate::__if_global_subscribe!($getter $subscribe {
+                vec![
+                    symbol!($getter $subscribe),
+                    concat!("hermes_", stringify!($getter), "_", stringify!($subscribe), "_for_site"),
+                ]
+            } else {
+                vec![symbol!($getter $subscribe), symbol!($getter $subscribe_all)]
+            })
+        };
+    }
+
+    /// The shared facades of each facade getter and the symbols of the messages of
+    /// `hermes_protocol!`
+    macro_rules! protocol_symbols {
+        ($(
+            $component:ident {
+                facade $facade_doc:tt $facade:ident [$facade_super:ident $(+ $facade_supers:ident)*] $facade_getter:ident;
+                backend $backend_doc:tt $backend:ident [$backend_super:ident $(+ $backend_supers:ident)*] $backend_getter:ident;
+                mqtt $mqtt_component:tt $mqtt_toggle:tt $mqtt_topic:tt;
+                messages [$({ $attributes:tt $side:ident $message:ident $route:tt $($rest:tt)* })*];
+            }
+        )*) => {{
+            let mut facades: Vec<(&str, Vec<&str>)> = vec![];
+            let mut symbols: Vec<&str> = vec![];
+            $(
+                facades.push((
+                    stringify!($facade_getter),
+                    vec![stringify!($facade_super) $(, stringify!($facade_supers))*],
+                ));
+                facades.push((
+                    stringify!($backend_getter),
+                    vec![stringify!($backend_super) $(, stringify!($backend_supers))*],
+                ));
+                $(symbols.extend(message_symbols!(frontend $side $facade_getter $($rest)*));)*
+                $(symbols.extend(message_symbols!(backend $side $backend_getter $($rest)*));)*
+            )*
+            (facades, symbols)
+        }};
+    }
+
+    /// The methods of the facades shared by the components, from `hermes_shared_facades!`
+    macro_rules! shared_methods {
+        ($(
+            $(#[$doc:meta])*
+            $facade:ident [$(fn $method:ident(&self $(, $argument:ident: $argument_type:ty)*);)*]
+        )*) => {
+            vec![$((stringify!($facade), vec![$(stringify!($method)),*])),*]
+        };
+    }
+
+    /// The structures symbols of every method of every facade: the ones of the messages described
+    /// by `hermes_protocol!` and the ones of the toggleable facades they implement, along with the
+    /// `_with_subscription` variant of the subscribe ones. The component facades have no structures
+    /// symbols.
+    fn expected_symbols() -> Vec<String> {
+        let traits: Vec<(&str, Vec<&str>)> = hermes::hermes_shared_facades!(shared_methods);
+        let (facades, message_symbols) = hermes::hermes_protocol!(protocol_symbols);
+
+        let mut symbols: Vec<String> = message_symbols.into_iter().map(String::from).collect();
+        for (getter, supers) in facades {
+            for (_, methods) in traits
+                .iter()
+                .filter(|(name, _)| supers.contains(name) && name.contains("Toggleable"))
+            {
+                symbols.extend(methods.iter().map(|method| format!("hermes_{}_{}", getter, method)));
+            }
+        }
+        let with_subscription: Vec<String> = symbols
+            .iter()
+            .filter(|it| it.contains("_subscribe_"))
+            .map(|it| format!("{}_with_subscription", it))
+            .collect();
+        symbols.extend(with_subscription);
+        symbols
+    }
+
+    #[test]
+    fn the_full_header_declares_every_structures_symbol() {
+        let missing: Vec<_> = expected_symbols()
+            .into_iter()
+            .filter(|symbol| !FULL_HEADER.contains(&format!(" {}(", symbol)))
+            .collect();
+
+        assert!(
+            missing.is_empty(),
+            "libsnips_hermes_full.h is outdated, run update_c_headers.sh, missing: {:?}",
+            missing
+        );
+    }
+}
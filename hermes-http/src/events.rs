@@ -0,0 +1,208 @@
+use std::io::{self, Write};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+
+use failure::Fallible;
+use hermes::*;
+use log::*;
+
+/// How often a comment is sent on an idle stream, so that proxies keep it open and a disconnected
+/// client is noticed
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+const HEADER: &[u8] = b"HTTP/1.1 200 OK\r\n\
+Content-Type: text/event-stream\r\n\
+Cache-Control: no-cache\r\n\
+Connection: close\r\n\
+\r\n";
+
+pub struct Event {
+    name: &'static str,
+    intent_name: Option<String>,
+    site_id: String,
+    data: String,
+}
+
+/// The events a stream is interested in, an empty list keeps all the intents or sites
+#[derive(Debug, Default)]
+pub struct Filter {
+    pub intents: Vec<String>,
+    pub sites: Vec<String>,
+}
+
+impl Filter {
+    /// The intent names only filter the intents, the sessions events are kept whatever their
+    /// intents
+    fn matches(&self, event: &Event) -> bool {
+        (self.sites.is_empty() || self.sites.contains(&event.site_id))
+            && (self.intents.is_empty() || event.intent_name.as_ref().map_or(true, |it| self.intents.contains(it)))
+    }
+}
+
+/// Dispatches the dialogue events to the streams opened by the clients
+#[derive(Default)]
+pub struct Events {
+    streams: Mutex<Vec<(Filter, Sender<Arc<Event>>)>>,
+}
+
+impl Events {
+    /// Start receiving the events of `dialogue`. The subscriptions only hold a weak reference to
+    /// `events`, they do nothing once the gateway is dropped.
+    pub fn subscribe(events: &Arc<Events>, dialogue: &dyn DialogueFacade) -> Fallible<()> {
+        let intent_events = Arc::downgrade(events);
+        dialogue.subscribe_intents(Callback::with_envelope(move |message: &IntentMessage, envelope| {
+            let intent_name = Some(message.intent.intent_name.clone());
+            dispatch(
+                &intent_events,
+                "intent",
+                intent_name,
+                &message.site_id,
+                message,
+                envelope,
+            )
+        }))?;
+
+        let started_events = Arc::downgrade(events);
+        dialogue.subscribe_all_session_started(Callback::with_envelope(
+            move |message: &SessionStartedMessage, envelope| {
+                dispatch(
+                    &started_events,
+                    "sessionStarted",
+                    None,
+                    &message.site_id,
+                    message,
+                    envelope,
+                )
+            },
+        ))?;
+
+        let ended_events = Arc::downgrade(events);
+        dialogue.subscribe_all_session_ended(Callback::with_envelope(
+            move |message: &SessionEndedMessage, envelope| {
+                dispatch(&ended_events, "sessionEnded", None, &message.site_id, message, envelope)
+            },
+        ))
+    }
+
+    pub fn open(&self, filter: Filter) -> Receiver<Arc<Event>> {
+        let (sender, receiver) = mpsc::channel();
+        self.streams.lock().unwrap().push((filter, sender));
+        receiver
+    }
+
+    /// End all the streams
+    pub fn close(&self) {
+        self.streams.lock().unwrap().clear();
+    }
+
+    fn dispatch<M: serde::Serialize>(
+        &self,
+        name: &'static str,
+        intent_name: Option<String>,
+        site_id: &str,
+        message: &M,
+        envelope: Option<&Envelope>,
+    ) {
+        // the data of an event is its message as sent over MQTT, with its envelope
+        let data = match hermes::envelope::to_json_value(message, envelope)
+            .and_then(|value| Ok(serde_json::to_string(&value)?))
+        {
+            Ok(data) => data,
+            Err(e) => {
+                error!("Could not serialize {} event: {}", name, e);
+                return;
+            }
+        };
+        let event = Arc::new(Event {
+            name,
+            intent_name,
+            site_id: site_id.to_string(),
+            data,
+        });
+        // the streams whose client went away are dropped here
+        self.streams
+            .lock()
+            .unwrap()
+            .retain(|(filter, sender)| !filter.matches(&event) || sender.send(Arc::clone(&event)).is_ok());
+    }
+}
+
+fn dispatch<M: serde::Serialize>(
+    events: &Weak<Events>,
+    name: &'static str,
+    intent_name: Option<String>,
+    site_id: &str,
+    message: &M,
+    envelope: Option<&Envelope>,
+) {
+    if let Some(events) = events.upgrade() {
+        events.dispatch(name, intent_name, site_id, message, envelope)
+    }
+}
+
+/// Write the events received on `receiver` as Server-Sent Events until the client goes away or
+/// the gateway is dropped
+pub fn stream(writer: &mut dyn Write, receiver: &Receiver<Arc<Event>>) -> io::Result<()> {
+    writer.write_all(HEADER)?;
+    writer.flush()?;
+    loop {
+        match receiver.recv_timeout(KEEP_ALIVE_INTERVAL) {
+            Ok(event) => write!(writer, "event: {}\ndata: {}\n\n", event.name, event.data)?,
+            Err(RecvTimeoutError::Timeout) => writer.write_all(b": keep-alive\n\n")?,
+            Err(RecvTimeoutError::Disconnected) => return Ok(()),
+        }
+        writer.flush()?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hermes::hermes_utils::Example;
+
+    fn event(intent_name: Option<&str>, site_id: &str) -> Event {
+        Event {
+            name: "intent",
+            intent_name: intent_name.map(|it| it.to_string()),
+            site_id: site_id.into(),
+            data: "{}".into(),
+        }
+    }
+
+    #[test]
+    fn subscriptions_do_not_keep_the_events_alive() {
+        let handler = hermes_inprocess::InProcessHermesProtocolHandler::new();
+        let events = Arc::new(Events::default());
+        Events::subscribe(&events, &*handler.dialogue()).unwrap();
+        let weak_events = Arc::downgrade(&events);
+        drop(events);
+        assert!(weak_events.upgrade().is_none());
+        handler
+            .dialogue_backend()
+            .publish_intent(IntentMessage::minimal_example())
+            .unwrap();
+    }
+
+    #[test]
+    fn intent_filter_keeps_session_events() {
+        let filter = Filter {
+            intents: vec!["lightsOn".into()],
+            sites: vec![],
+        };
+        assert!(filter.matches(&event(Some("lightsOn"), "kitchen")));
+        assert!(!filter.matches(&event(Some("lightsOff"), "kitchen")));
+        assert!(filter.matches(&event(None, "kitchen")));
+    }
+
+    #[test]
+    fn site_filter_applies_to_all_events() {
+        let filter = Filter {
+            intents: vec![],
+            sites: vec!["kitchen".into(), "bedroom".into()],
+        };
+        assert!(filter.matches(&event(Some("lightsOn"), "bedroom")));
+        assert!(!filter.matches(&event(Some("lightsOn"), "garage")));
+        assert!(!filter.matches(&event(None, "garage")));
+    }
+}
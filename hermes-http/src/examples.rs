@@ -0,0 +1,50 @@
+use failure::Fallible;
+use hermes::hermes_utils::Example;
+use hermes::*;
+
+macro_rules! examples {
+    ($($message:ident),*) => {
+        /// The messages accepted and sent by the gateway, of which an example can be fetched
+        pub const MESSAGES: &[&str] = &[$(stringify!($message)),*];
+
+        /// An example of the message named `name`, `None` if the gateway doesn't know it
+        pub fn example(name: &str, minimal: bool) -> Option<Fallible<String>> {
+            match name {
+                $(stringify!($message) => Some(to_json(&if minimal {
+                    $message::minimal_example()
+                } else {
+                    $message::full_example()
+                })),)*
+                _ => None,
+            }
+        }
+    };
+}
+
+examples!(
+    StartSessionMessage,
+    ContinueSessionMessage,
+    EndSessionMessage,
+    IntentMessage,
+    SessionStartedMessage,
+    SessionEndedMessage
+);
+
+fn to_json<M: serde::Serialize>(message: &M) -> Fallible<String> {
+    Ok(serde_json::to_string_pretty(message)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_messages_have_examples() {
+        for name in MESSAGES {
+            for minimal in &[true, false] {
+                assert!(example(name, *minimal).unwrap().is_ok(), "no example for {}", name);
+            }
+        }
+        assert!(example("PlayBytesMessage", false).is_none());
+    }
+}
@@ -0,0 +1,427 @@
+//! An HTTP gateway to the dialogue of hermes, for the skills that would rather use webhooks and
+//! Server-Sent Events than a MQTT client.
+//!
+//! - `POST /sessions` starts a session, the body is a `StartSessionMessage`
+//! - `POST /sessions/{id}/continue` continues a session, the body is a `ContinueSessionMessage`
+//!   whose `sessionId` can be left out
+//! - `POST /sessions/{id}/end` ends a session, the body is an optional `EndSessionMessage` whose
+//!   `sessionId` can be left out
+//! - `GET /events` streams the intents and the started and ended sessions as Server-Sent Events
+//!   named `intent`, `sessionStarted` and `sessionEnded`. The `intent` and `site` query parameters,
+//!   that can be repeated, only keep the intents with these names and the events on these sites.
+//! - `GET /examples` lists the messages above and `GET /examples/{message}` gives an example of
+//!   one of them, leaving out the optional fields with `?minimal`
+//!
+//! The requests publishing a message are answered with `202 Accepted` once it is published. The id
+//! of a started session is not known at this point, it comes with its `sessionStarted` event.
+
+mod events;
+mod examples;
+
+use std::borrow::Cow;
+use std::io::Read;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::sync::Arc;
+use std::thread;
+
+use failure::{format_err, Fail, Fallible};
+use hermes::*;
+use log::*;
+use percent_encoding::percent_decode_str;
+use serde_json::Value;
+use tiny_http::{Header, Method, Request, Response, ResponseBox, Server};
+
+use crate::events::{Events, Filter};
+
+/// An HTTP server exposing the dialogue facade of a `HermesProtocolHandler`, it is stopped and
+/// all its event streams are ended when dropped
+pub struct HttpGateway {
+    address: SocketAddr,
+    server: Arc<Server>,
+    events: Arc<Events>,
+}
+
+impl HttpGateway {
+    /// Serve the dialogue of `handler` to the HTTP clients connecting on `address`
+    pub fn bind<A: ToSocketAddrs>(address: A, handler: &dyn HermesProtocolHandler) -> Fallible<HttpGateway> {
+        let server = Arc::new(Server::http(address).map_err(|e| format_err!("could not start HTTP server: {}", e))?);
+        let address = server.server_addr();
+        let dialogue: Arc<dyn DialogueFacade> = handler.dialogue().into();
+
+        let events = Arc::new(Events::default());
+        Events::subscribe(&events, &*dialogue)?;
+
+        let thread_server = Arc::clone(&server);
+        let thread_events = Arc::clone(&events);
+        thread::Builder::new()
+            .name(format!("hermes-http-{}", address.port()))
+            .spawn(move || serve(&thread_server, &dialogue, &thread_events))?;
+
+        info!("Serving the dialogue of {} over HTTP on {}", handler, address);
+        Ok(HttpGateway {
+            address,
+            server,
+            events,
+        })
+    }
+
+    /// The address the gateway listens on
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+}
+
+impl Drop for HttpGateway {
+    fn drop(&mut self) {
+        self.server.unblock();
+        self.events.close();
+    }
+}
+
+#[derive(Debug, Fail)]
+enum HttpError {
+    #[fail(display = "not found")]
+    NotFound,
+    #[fail(display = "bad request: {}", _0)]
+    BadRequest(String),
+    #[fail(display = "could not publish: {}", _0)]
+    Publish(failure::Error),
+}
+
+impl HttpError {
+    fn status_code(&self) -> u16 {
+        match self {
+            HttpError::NotFound => 404,
+            HttpError::BadRequest(_) => 400,
+            HttpError::Publish(_) => 500,
+        }
+    }
+}
+
+fn serve(server: &Server, dialogue: &Arc<dyn DialogueFacade>, events: &Arc<Events>) {
+    for request in server.incoming_requests() {
+        let dialogue = Arc::clone(dialogue);
+        let events = Arc::clone(events);
+        // each request has its own thread as the event streams stay open
+        let spawned = thread::Builder::new()
+            .name("hermes-http-request".into())
+            .spawn(move || handle(request, &*dialogue, &events));
+        if let Err(e) = spawned {
+            warn!("Could not spawn an HTTP request thread: {}", e);
+        }
+    }
+}
+
+fn handle(mut request: Request, dialogue: &dyn DialogueFacade, events: &Events) {
+    let method = request.method().clone();
+    let url = request.url().to_string();
+    let (path, query) = match url.find('?') {
+        Some(index) => (&url[..index], &url[index + 1..]),
+        None => (url.as_str(), ""),
+    };
+    debug!("{} {}", method, url);
+    let segments = match segments(path) {
+        Ok(segments) => segments,
+        Err(e) => return respond(request, &method, &url, Err(e)),
+    };
+    let segments: Vec<&str> = segments.iter().map(|it| it.as_ref()).collect();
+
+    let response = match (&method, segments.as_slice()) {
+        (Method::Get, ["events"]) => {
+            let receiver = events.open(filter(query));
+            if let Err(e) = crate::events::stream(&mut request.into_writer(), &receiver) {
+                debug!("Event stream closed: {}", e);
+            }
+            return;
+        }
+        (Method::Post, ["sessions"]) => {
+            read_message(&mut request, None).and_then(|message| published(dialogue.publish_start_session(message)))
+        }
+        (Method::Post, ["sessions", session_id, "continue"]) => read_message(&mut request, Some(*session_id))
+            .and_then(|message| published(dialogue.publish_continue_session(message))),
+        (Method::Post, ["sessions", session_id, "end"]) => read_message(&mut request, Some(*session_id))
+            .and_then(|message| published(dialogue.publish_end_session(message))),
+        (Method::Get, ["examples"]) => Ok(json(Value::from(examples::MESSAGES.to_vec()).to_string())),
+        (Method::Get, ["examples", name]) => match examples::example(name, minimal(query)) {
+            Some(Ok(example)) => Ok(json(example)),
+            Some(Err(e)) => Err(HttpError::BadRequest(e.to_string())),
+            None => Err(HttpError::NotFound),
+        },
+        _ => Err(HttpError::NotFound),
+    };
+    respond(request, &method, &url, response)
+}
+
+fn respond(request: Request, method: &Method, url: &str, response: Result<ResponseBox, HttpError>) {
+    let response = response.unwrap_or_else(|e| {
+        warn!("{} {}: {}", method, url, e);
+        Response::from_string(e.to_string())
+            .with_status_code(e.status_code())
+            .boxed()
+    });
+    if let Err(e) = request.respond(response) {
+        debug!("Could not answer {} {}: {}", method, url, e);
+    }
+}
+
+/// The percent-decoded segments of `path`, so that `/sessions/a%2Fb/end` ends the session `a/b`
+fn segments(path: &str) -> Result<Vec<Cow<'_, str>>, HttpError> {
+    path.trim_matches('/')
+        .split('/')
+        .map(|segment| {
+            percent_decode_str(segment)
+                .decode_utf8()
+                .map_err(|e| HttpError::BadRequest(format!("invalid path segment {}: {}", segment, e)))
+        })
+        .collect()
+}
+
+/// Read the json message in the body of `request`, its session id is set to `session_id`. An
+/// empty body is read as an empty object, leaving the defaults of the message.
+fn read_message<M: serde::de::DeserializeOwned>(
+    request: &mut Request,
+    session_id: Option<&str>,
+) -> Result<M, HttpError> {
+    let mut body = String::new();
+    request
+        .as_reader()
+        .read_to_string(&mut body)
+        .map_err(|e| HttpError::BadRequest(e.to_string()))?;
+    let mut value = if body.trim().is_empty() {
+        Value::Object(Default::default())
+    } else {
+        serde_json::from_str(&body).map_err(|e| HttpError::BadRequest(e.to_string()))?
+    };
+    if let Some(session_id) = session_id {
+        match value {
+            Value::Object(ref mut fields) => {
+                fields.insert("sessionId".into(), session_id.into());
+            }
+            _ => return Err(HttpError::BadRequest("the body must be a json object".into())),
+        }
+    }
+    serde_json::from_value(value).map_err(|e| HttpError::BadRequest(e.to_string()))
+}
+
+fn published(result: Fallible<()>) -> Result<ResponseBox, HttpError> {
+    result.map_err(HttpError::Publish)?;
+    Ok(Response::empty(202).boxed())
+}
+
+fn json(body: String) -> ResponseBox {
+    Response::from_string(body)
+        .with_header(Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap())
+        .boxed()
+}
+
+fn query_pairs(query: &str) -> impl Iterator<Item = (String, String)> + '_ {
+    url::form_urlencoded::parse(query.as_bytes()).map(|(key, value)| (key.into_owned(), value.into_owned()))
+}
+
+fn filter(query: &str) -> Filter {
+    let mut filter = Filter::default();
+    for (key, value) in query_pairs(query) {
+        match key.as_str() {
+            "intent" => filter.intents.push(value),
+            "site" => filter.sites.push(value),
+            _ => {}
+        }
+    }
+    filter
+}
+
+fn minimal(query: &str) -> bool {
+    query_pairs(query).any(|(key, value)| key == "minimal" && value != "false")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hermes::hermes_utils::Example;
+    use hermes_inprocess::InProcessHermesProtocolHandler;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpStream;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    fn send(gateway: &HttpGateway, method: &str, path: &str, body: &str) -> TcpStream {
+        let mut stream = TcpStream::connect(gateway.address()).unwrap();
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            method,
+            path,
+            body.len(),
+            body
+        )
+        .unwrap();
+        stream
+    }
+
+    fn request(gateway: &HttpGateway, method: &str, path: &str, body: &str) -> (u16, String) {
+        let mut response = String::new();
+        send(gateway, method, path, body).read_to_string(&mut response).unwrap();
+        let status = response[9..12].parse().unwrap();
+        let body = response.splitn(2, "\r\n\r\n").nth(1).unwrap_or("").to_string();
+        (status, body)
+    }
+
+    /// Read the data of the next event of a stream opened with `send`
+    fn next_event(stream: &mut BufReader<TcpStream>) -> (String, String) {
+        let mut name = String::new();
+        let mut line = String::new();
+        loop {
+            line.clear();
+            stream.read_line(&mut line).unwrap();
+            if line.starts_with("event: ") {
+                name = line["event: ".len()..].trim().to_string();
+            } else if line.starts_with("data: ") {
+                return (name, line["data: ".len()..].trim().to_string());
+            }
+        }
+    }
+
+    #[test]
+    fn sessions_are_published() {
+        let handler = InProcessHermesProtocolHandler::new();
+        let gateway = HttpGateway::bind("127.0.0.1:0", &handler).unwrap();
+        let (start_tx, start_rx) = mpsc::channel();
+        let start_tx = std::sync::Mutex::new(start_tx);
+        handler
+            .dialogue_backend()
+            .subscribe_start_session(Callback::new(move |m: &StartSessionMessage| {
+                start_tx.lock().unwrap().send(m.clone()).unwrap()
+            }))
+            .unwrap();
+        let (continue_tx, continue_rx) = mpsc::channel();
+        let continue_tx = std::sync::Mutex::new(continue_tx);
+        handler
+            .dialogue_backend()
+            .subscribe_continue_session(Callback::new(move |m: &ContinueSessionMessage| {
+                continue_tx.lock().unwrap().send(m.clone()).unwrap()
+            }))
+            .unwrap();
+        let (end_tx, end_rx) = mpsc::channel();
+        let end_tx = std::sync::Mutex::new(end_tx);
+        handler
+            .dialogue_backend()
+            .subscribe_end_session(Callback::new(move |m: &EndSessionMessage| {
+                end_tx.lock().unwrap().send(m.clone()).unwrap()
+            }))
+            .unwrap();
+
+        let start = StartSessionMessage::full_example();
+        let (status, _) = request(&gateway, "POST", "/sessions", &serde_json::to_string(&start).unwrap());
+        assert_eq!(status, 202);
+        assert_eq!(start_rx.recv_timeout(Duration::from_secs(1)).unwrap(), start);
+
+        let (status, _) = request(&gateway, "POST", "/sessions/abc/continue", r#"{"text": "which room?"}"#);
+        assert_eq!(status, 202);
+        let continued = continue_rx.recv_timeout(Duration::from_secs(1)).unwrap();
+        assert_eq!(continued.session_id, "abc");
+        assert_eq!(continued.text, "which room?");
+
+        let (status, _) = request(&gateway, "POST", "/sessions/abc/end", "");
+        assert_eq!(status, 202);
+        let ended = end_rx.recv_timeout(Duration::from_secs(1)).unwrap();
+        assert_eq!(ended.session_id, "abc");
+        assert_eq!(ended.text, None);
+    }
+
+    #[test]
+    fn session_ids_are_percent_decoded() {
+        let handler = InProcessHermesProtocolHandler::new();
+        let gateway = HttpGateway::bind("127.0.0.1:0", &handler).unwrap();
+        let (end_tx, end_rx) = mpsc::channel();
+        let end_tx = std::sync::Mutex::new(end_tx);
+        handler
+            .dialogue_backend()
+            .subscribe_end_session(Callback::new(move |m: &EndSessionMessage| {
+                end_tx.lock().unwrap().send(m.clone()).unwrap()
+            }))
+            .unwrap();
+
+        let (status, _) = request(&gateway, "POST", "/sessions/a%2Fb%20c/end", "");
+        assert_eq!(status, 202);
+        let ended = end_rx.recv_timeout(Duration::from_secs(1)).unwrap();
+        assert_eq!(ended.session_id, "a/b c");
+
+        assert_eq!(request(&gateway, "POST", "/sessions/%FF/end", "").0, 400);
+    }
+
+    #[test]
+    fn invalid_requests_are_rejected() {
+        let handler = InProcessHermesProtocolHandler::new();
+        let gateway = HttpGateway::bind("127.0.0.1:0", &handler).unwrap();
+
+        assert_eq!(request(&gateway, "POST", "/sessions", "not json").0, 400);
+        assert_eq!(request(&gateway, "POST", "/sessions/abc/continue", "[]").0, 400);
+        assert_eq!(request(&gateway, "POST", "/sessions/abc/pause", "").0, 404);
+        assert_eq!(request(&gateway, "GET", "/sessions", "").0, 404);
+        assert_eq!(request(&gateway, "GET", "/examples/PlayBytesMessage", "").0, 404);
+    }
+
+    #[test]
+    fn events_are_streamed_and_filtered() {
+        let handler = InProcessHermesProtocolHandler::new();
+        let gateway = HttpGateway::bind("127.0.0.1:0", &handler).unwrap();
+
+        let mut stream = BufReader::new(send(&gateway, "GET", "/events?intent=lightsOn&site=kitchen", ""));
+        let mut status = String::new();
+        stream.read_line(&mut status).unwrap();
+        assert_eq!(status.trim(), "HTTP/1.1 200 OK");
+
+        let intent = |intent_name: &str, site_id: &str| {
+            let mut intent = IntentMessage::minimal_example();
+            intent.intent.intent_name = intent_name.into();
+            intent.site_id = site_id.into();
+            intent
+        };
+        let backend = handler.dialogue_backend();
+        backend.publish_intent(intent("lightsOff", "kitchen")).unwrap();
+        backend.publish_intent(intent("lightsOn", "bedroom")).unwrap();
+        let mut ended = SessionEndedMessage::minimal_example();
+        ended.site_id = "kitchen".into();
+        backend.publish_session_ended(ended.clone()).unwrap();
+        backend.publish_intent(intent("lightsOn", "kitchen")).unwrap();
+
+        // the intents and the sessions events are subscribed to separately, their order may change
+        let mut events = vec![next_event(&mut stream), next_event(&mut stream)];
+        events.sort();
+        assert_eq!(events[0].0, "intent");
+        assert_eq!(
+            serde_json::from_str::<IntentMessage>(&events[0].1).unwrap(),
+            intent("lightsOn", "kitchen")
+        );
+        assert_eq!(events[1].0, "sessionEnded");
+        assert_eq!(
+            serde_json::from_str::<SessionEndedMessage>(&events[1].1).unwrap(),
+            ended
+        );
+    }
+
+    #[test]
+    fn examples_are_served() {
+        let handler = InProcessHermesProtocolHandler::new();
+        let gateway = HttpGateway::bind("127.0.0.1:0", &handler).unwrap();
+
+        let (status, body) = request(&gateway, "GET", "/examples", "");
+        assert_eq!(status, 200);
+        assert!(serde_json::from_str::<Vec<String>>(&body)
+            .unwrap()
+            .contains(&"IntentMessage".to_string()));
+
+        let (status, body) = request(&gateway, "GET", "/examples/SessionStartedMessage", "");
+        assert_eq!(status, 200);
+        assert_eq!(
+            serde_json::from_str::<SessionStartedMessage>(&body).unwrap(),
+            SessionStartedMessage::full_example()
+        );
+
+        let (_, body) = request(&gateway, "GET", "/examples/SessionStartedMessage?minimal", "");
+        assert_eq!(
+            serde_json::from_str::<SessionStartedMessage>(&body).unwrap(),
+            SessionStartedMessage::minimal_example()
+        );
+    }
+}
@@ -0,0 +1,84 @@
+//! Helpers for the serialization-faithful mode: messages are sent through the MQTT codec and topic
+//! path before being delivered on the bus, so that what would be lost or mangled over MQTT also is
+//! in process.
+
+use failure::{format_err, Fallible};
+use hermes_mqtt::codec;
+use hermes_mqtt::topics::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Write the topic as a path and parse it back, the parsed topic is returned so that the fields
+/// carried by the topic are taken from it. Topics that can't be published over MQTT are rejected.
+pub fn topic(topic: HermesTopic) -> Fallible<HermesTopic> {
+    let path = topic.publish_path()?;
+    match HermesTopic::from_path(&path) {
+        Some(parsed) => {
+            if parsed == topic {
+                Ok(parsed)
+            } else {
+                Err(format_err!(
+                    "topic {:?} is read back as {:?} from {}",
+                    topic,
+                    parsed,
+                    path
+                ))
+            }
+        }
+        None => Err(format_err!("topic {:?} can not be read back from {}", topic, path)),
+    }
+}
+
+/// Send a json message through MQTT
+pub fn json<P: Serialize + DeserializeOwned>(topic: HermesTopic, payload: &P) -> Fallible<(HermesTopic, P)> {
+    let topic = self::topic(topic)?;
+    let payload = codec::decode_json(&codec::encode_json(payload)?)?;
+    Ok((topic, payload))
+}
+
+/// Send a binary message through MQTT using the given codec functions
+pub fn binary<P, E, D>(payload: P, encode: E, decode: D) -> Fallible<P>
+where
+    E: FnOnce(P) -> (HermesTopic, Vec<u8>),
+    D: FnOnce(&HermesTopic, &[u8]) -> Result<P, codec::DecodeError>,
+{
+    let (topic, bytes) = encode(payload);
+    let topic = self::topic(topic)?;
+    Ok(decode(&topic, &bytes)?)
+}
+
+/// Get the site id carried by a topic
+pub fn site_id(topic: &HermesTopic) -> Fallible<String> {
+    match topic {
+        HermesTopic::Component(Some(site_id), _, _)
+        | HermesTopic::Hotword(Some(site_id), _)
+        | HermesTopic::AudioServer(Some(site_id), _)
+        | HermesTopic::VoiceActivity(site_id, _) => Ok(site_id.clone()),
+        _ => Err(format_err!("topic {:?} does not carry a site id", topic)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn topic_survives_round_trip() {
+        let topic = HermesTopic::Intent("lightsOn".into());
+        assert_eq!(self::topic(topic.clone()).unwrap(), topic);
+    }
+
+    #[test]
+    fn topic_loss_is_detected() {
+        assert!(self::topic(HermesTopic::Intent("lights/on".into())).is_err());
+        assert!(self::topic(HermesTopic::Hotword(
+            Some("kitchen/left".into()),
+            HotwordCommand::Detected
+        ))
+        .is_err());
+    }
+
+    #[test]
+    fn nan_is_detected() {
+        assert!(json(HermesTopic::Asr(None, AsrCommand::TextCaptured), &std::f32::NAN).is_err());
+    }
+}
@@ -1,14 +1,30 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::sync::{Arc, Mutex, Weak};
 
 use failure::Fallible;
+use lazy_static::lazy_static;
 use log::*;
 
 use hermes::*;
+#[cfg(feature = "serialization-faithful")]
+use hermes_mqtt::{codec, topics::*};
+
+#[cfg(feature = "serialization-faithful")]
+mod faithful;
+
+lazy_static! {
+    static ref NAMED_BUSES: Mutex<HashMap<String, Weak<Mutex<ripb::Bus>>>> = Mutex::new(HashMap::new());
+}
 
 pub struct InProcessHermesProtocolHandler {
     subscribers: Arc<Mutex<Vec<Arc<ripb::Subscriber>>>>,
     bus: Arc<Mutex<ripb::Bus>>,
+    /// The source of the envelopes of the published messages, they have none without it
+    envelope_source: Option<MessageSource>,
+    #[cfg(feature = "serialization-faithful")]
+    serialization_faithful: bool,
 }
 
 impl InProcessHermesProtocolHandler {
@@ -16,6 +32,46 @@ impl InProcessHermesProtocolHandler {
         Self {
             subscribers: Arc::new(Mutex::new(Vec::new())),
             bus: Arc::new(Mutex::new(ripb::Bus::new())),
+            envelope_source: None,
+            #[cfg(feature = "serialization-faithful")]
+            serialization_faithful: false,
+        }
+    }
+
+    /// Create a handler on which every message is encoded and decoded through the MQTT codec and
+    /// topic path before being delivered. What would be lost or mangled over MQTT then also is in
+    /// process, and publishing a message that can't go through MQTT fails. This is slower than the
+    /// default mode and mostly intended for tests.
+    #[cfg(feature = "serialization-faithful")]
+    pub fn new_serialization_faithful() -> Self {
+        Self {
+            serialization_faithful: true,
+            ..Self::new()
+        }
+    }
+
+    /// Create a handler on the bus named `name`. The handlers created with the same name share
+    /// their bus, and so see each other's messages, as long as one of them is alive. This lets
+    /// several parts of a process, possibly written in different languages, talk to each other
+    /// without a broker.
+    pub fn new_on_named_bus(name: &str) -> Self {
+        let mut buses = NAMED_BUSES.lock().unwrap();
+        buses.retain(|_, bus| bus.upgrade().is_some());
+        let bus = buses.get(name).and_then(Weak::upgrade).unwrap_or_else(|| {
+            let bus = Arc::new(Mutex::new(ripb::Bus::new()));
+            buses.insert(name.to_string(), Arc::downgrade(&bus));
+            bus
+        });
+        Self { bus, ..Self::new() }
+    }
+
+    /// Give an `Envelope` from `source` to the messages published by the facades, the callbacks
+    /// created with `Callback::with_envelope` are given the envelope of their message. In
+    /// serialization-faithful mode the binary messages lose it, as they do over MQTT.
+    pub fn with_envelope(self, source: MessageSource) -> Self {
+        Self {
+            envelope_source: Some(source),
+            ..self
         }
     }
 
@@ -25,6 +81,10 @@ impl InProcessHermesProtocolHandler {
             bus: Arc::downgrade(&self.bus),
             subscriber: Mutex::new(None),
             subscribers: Arc::clone(&self.subscribers),
+            handlers: Mutex::new(HashMap::new()),
+            envelope_source: self.envelope_source.clone(),
+            #[cfg(feature = "serialization-faithful")]
+            serialization_faithful: self.serialization_faithful,
         })
     }
 }
@@ -35,106 +95,181 @@ impl Default for InProcessHermesProtocolHandler {
     }
 }
 
-impl HermesProtocolHandler for InProcessHermesProtocolHandler {
-    fn voice_activity(&self) -> Box<dyn VoiceActivityFacade> {
-        self.get_handler(VoiceActivity)
-    }
-
-    fn hotword(&self) -> Box<dyn HotwordFacade> {
-        self.get_handler(Hotword)
-    }
-
-    fn sound_feedback(&self) -> Box<dyn SoundFeedbackFacade> {
-        self.get_handler(Sound)
-    }
-
-    fn asr(&self) -> Box<dyn AsrFacade> {
-        self.get_handler(Asr)
-    }
-
-    fn tts(&self) -> Box<dyn TtsFacade> {
-        self.get_handler(Tts)
+impl std::fmt::Display for InProcessHermesProtocolHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Snips InProcess Bus")
     }
+}
 
-    fn nlu(&self) -> Box<dyn NluFacade> {
-        self.get_handler(Nlu)
-    }
+struct InProcessComponent<T: Send + Sync + Debug> {
+    component: T,
+    bus: Weak<Mutex<ripb::Bus>>,
+    subscriber: Mutex<Option<Arc<ripb::Subscriber>>>,
+    subscribers: Arc<Mutex<Vec<Arc<ripb::Subscriber>>>>,
+    /// The `Handlers<M>` of each message type `M` this component subscribed to
+    handlers: Mutex<HashMap<TypeId, Box<dyn Any + Send>>>,
+    envelope_source: Option<MessageSource>,
+    #[cfg(feature = "serialization-faithful")]
+    serialization_faithful: bool,
+}
 
-    fn audio_server(&self) -> Box<dyn AudioServerFacade> {
-        self.get_handler(AudioServer)
-    }
+/// What is published on the bus, a message and its envelope
+#[derive(Debug)]
+struct Enveloped<M> {
+    message: M,
+    envelope: Option<Envelope>,
+}
 
-    fn dialogue(&self) -> Box<dyn DialogueFacade> {
-        self.get_handler(Dialogue)
-    }
+/// A callback subscribed to the messages `M`
+type Handler<M> = Arc<dyn Fn(&M, Option<&Envelope>) + Send + Sync>;
 
-    fn injection(&self) -> Box<dyn InjectionFacade> {
-        self.get_handler(Injection)
-    }
+/// The callbacks subscribed to a message type on a component. The component registers a single
+/// handler on the bus for each type, which calls these ones. The bus can't remove a handler, so
+/// the callbacks are removed from here when their subscription is cancelled.
+struct Handlers<M> {
+    next_id: usize,
+    handlers: Vec<(usize, Handler<M>)>,
+}
 
-    fn voice_activity_backend(&self) -> Box<dyn VoiceActivityBackendFacade> {
-        self.get_handler(VoiceActivity)
+impl<M> Handlers<M> {
+    fn add(&mut self, handler: Handler<M>) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.handlers.push((id, handler));
+        id
     }
 
-    fn hotword_backend(&self) -> Box<dyn HotwordBackendFacade> {
-        self.get_handler(Hotword)
+    fn remove(&mut self, id: usize) {
+        self.handlers.retain(|(it, _)| *it != id)
     }
 
-    fn sound_feedback_backend(&self) -> Box<dyn SoundFeedbackBackendFacade> {
-        self.get_handler(Sound)
+    /// The callbacks to call with a message, taken out of the lock so that they can subscribe and
+    /// unsubscribe
+    fn snapshot(&self) -> Vec<Handler<M>> {
+        self.handlers.iter().map(|(_, handler)| Arc::clone(handler)).collect()
     }
+}
 
-    fn asr_backend(&self) -> Box<dyn AsrBackendFacade> {
-        self.get_handler(Asr)
-    }
+/// A message published on the bus. In serialization-faithful mode it is sent through the MQTT
+/// codec and topic path before being published, and comes back as a subscriber over MQTT would
+/// see it. Every message must implement it, so that none silently skips the round trip.
+trait ThroughMqtt: Sized {
+    /// Whether the message keeps its envelope over MQTT, only the json messages do
+    #[cfg(feature = "serialization-faithful")]
+    const ENVELOPED: bool = true;
 
-    fn tts_backend(&self) -> Box<dyn TtsBackendFacade> {
-        self.get_handler(Tts)
-    }
+    #[cfg(feature = "serialization-faithful")]
+    fn through_mqtt(self) -> Fallible<Self>;
+}
 
-    fn nlu_backend(&self) -> Box<dyn NluBackendFacade> {
-        self.get_handler(Nlu)
-    }
+/// The marker types identifying the components on the bus
+trait ComponentKind: Send + Sync + Debug + Copy + 'static {
+    #[cfg(feature = "serialization-faithful")]
+    fn mqtt_component(self) -> Fallible<Component>;
 
-    fn audio_server_backend(&self) -> Box<dyn AudioServerBackendFacade> {
-        self.get_handler(AudioServer)
-    }
+    #[cfg(feature = "serialization-faithful")]
+    fn mqtt_toggle_topic(self, on: bool) -> Fallible<HermesTopic>;
+}
 
-    fn dialogue_backend(&self) -> Box<dyn DialogueBackendFacade> {
-        self.get_handler(Dialogue)
-    }
+macro_rules! component_kind {
+    ($t:ty, $component:expr, $toggle_on:expr, $toggle_off:expr) => {
+        impl ComponentKind for $t {
+            #[cfg(feature = "serialization-faithful")]
+            fn mqtt_component(self) -> Fallible<Component> {
+                let component: Option<Component> = $component;
+                component.ok_or_else(|| failure::format_err!("{:?} is not a component over MQTT", self))
+            }
 
-    fn injection_backend(&self) -> Box<dyn InjectionBackendFacade> {
-        self.get_handler(Injection)
-    }
+            #[cfg(feature = "serialization-faithful")]
+            fn mqtt_toggle_topic(self, on: bool) -> Fallible<HermesTopic> {
+                let topic: Option<HermesTopic> = if on { $toggle_on } else { $toggle_off };
+                topic.ok_or_else(|| failure::format_err!("{:?} can't be toggled over MQTT", self))
+            }
+        }
+    };
 }
 
-impl std::fmt::Display for InProcessHermesProtocolHandler {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "Snips InProcess Bus")
-    }
-}
+macro_rules! through_mqtt {
+    // a message without payload
+    ($t:ident => $topic:expr) => {
+        impl ThroughMqtt for $t {
+            #[cfg(feature = "serialization-faithful")]
+            const ENVELOPED: bool = false;
 
-struct InProcessComponent<T: Send + Sync + Debug> {
-    component: T,
-    bus: Weak<Mutex<ripb::Bus>>,
-    subscriber: Mutex<Option<Arc<ripb::Subscriber>>>,
-    subscribers: Arc<Mutex<Vec<Arc<ripb::Subscriber>>>>,
+            #[cfg(feature = "serialization-faithful")]
+            fn through_mqtt(self) -> Fallible<Self> {
+                faithful::topic($topic)?;
+                Ok(self)
+            }
+        }
+    };
+    // a json message on a topic that may be computed from it
+    ($t:ident { $field:ident } => |$it:ident| $topic:expr) => {
+        impl ThroughMqtt for $t {
+            #[cfg(feature = "serialization-faithful")]
+            fn through_mqtt(self) -> Fallible<Self> {
+                let $it = &self.$field;
+                let (_, $field) = faithful::json($topic, $it)?;
+                Ok($t { $field })
+            }
+        }
+    };
+    // a json message on a topic built from a key published alongside it, the key is read back
+    // from the topic
+    ($t:ident { $key:ident, $field:ident } => $topic:expr) => {
+        impl ThroughMqtt for $t {
+            #[cfg(feature = "serialization-faithful")]
+            fn through_mqtt(self) -> Fallible<Self> {
+                let $key = self.$key;
+                let (topic, $field) = faithful::json($topic, &self.$field)?;
+                Ok($t {
+                    $key: faithful::site_id(&topic)?,
+                    $field,
+                })
+            }
+        }
+    };
+    // a binary message, using the encoder and decoder of the MQTT codec
+    ($t:ident { $field:ident } => binary $encode:expr, $decode:path) => {
+        impl ThroughMqtt for $t {
+            #[cfg(feature = "serialization-faithful")]
+            const ENVELOPED: bool = false;
+
+            #[cfg(feature = "serialization-faithful")]
+            fn through_mqtt(self) -> Fallible<Self> {
+                let $field = faithful::binary(self.$field, $encode, $decode)?;
+                Ok($t { $field })
+            }
+        }
+    };
 }
 
 impl<T: Send + Sync + Debug> InProcessComponent<T> {
-    fn publish<M: ripb::Message + Debug + 'static>(&self, message: M) -> Fallible<()> {
+    fn publish<M: Debug + ThroughMqtt + 'static>(&self, message: M) -> Fallible<()>
+    where
+        Enveloped<M>: ripb::Message,
+    {
         debug!("Publishing {:?}/{:#?}", self.component, message);
         self.publish_quiet(message)
     }
 
-    fn publish_quiet<M: ripb::Message + Debug + 'static>(&self, message: M) -> Fallible<()> {
+    fn publish_quiet<M: Debug + ThroughMqtt + 'static>(&self, message: M) -> Fallible<()>
+    where
+        Enveloped<M>: ripb::Message,
+    {
+        let envelope = self.envelope_source.clone().map(Envelope::new);
+        #[cfg(feature = "serialization-faithful")]
+        let (message, envelope) = if self.serialization_faithful {
+            (message.through_mqtt()?, envelope.filter(|_| M::ENVELOPED))
+        } else {
+            (message, envelope)
+        };
         let bus = self
             .bus
             .upgrade()
             .ok_or_else(|| failure::format_err!("bus was killed"))?;
         let bus = bus.lock().map_err(PoisonLock::from)?;
-        bus.publish(message);
+        bus.publish(Enveloped { message, envelope });
         Ok(())
     }
 
@@ -158,71 +293,109 @@ impl<T: Send + Sync + Debug> InProcessComponent<T> {
         Ok(())
     }
 
-    fn subscribe0<M: ripb::Message + 'static>(&self, callback: Callback0) -> Fallible<()> {
+    /// The callbacks subscribed to `M`, registering the handler calling them on the bus the
+    /// first time
+    fn handlers<M: 'static>(&self) -> Fallible<Arc<Mutex<Handlers<M>>>>
+    where
+        Enveloped<M>: ripb::Message,
+    {
+        let mut all = self.handlers.lock().map_err(PoisonLock::from)?;
+        if let Some(handlers) = all
+            .get(&TypeId::of::<M>())
+            .and_then(|it| it.downcast_ref::<Arc<Mutex<Handlers<M>>>>())
+        {
+            return Ok(Arc::clone(handlers));
+        }
+
+        let handlers = Arc::new(Mutex::new(Handlers {
+            next_id: 0,
+            handlers: vec![],
+        }));
         self.ensure_has_subscriber()?;
+        let dispatched = Arc::clone(&handlers);
         self.subscriber
             .lock()
             .map_err(PoisonLock::from)?
             .as_ref()
             .unwrap()
-            .on_message(move |_: &M| callback.call())?;
+            .on_message(move |m: &Enveloped<M>| {
+                let snapshot = match dispatched.lock() {
+                    Ok(handlers) => handlers.snapshot(),
+                    Err(_) => return,
+                };
+                for handler in snapshot {
+                    handler(&m.message, m.envelope.as_ref())
+                }
+            })?;
+        all.insert(TypeId::of::<M>(), Box::new(Arc::clone(&handlers)));
+        Ok(handlers)
+    }
+
+    /// Call `handler` with the messages `M` and their envelope until `subscription` is cancelled
+    fn on_message<M, H>(&self, subscription: Subscription, handler: H) -> Fallible<()>
+    where
+        M: 'static,
+        Enveloped<M>: ripb::Message,
+        H: Fn(&M, Option<&Envelope>) + Send + Sync + 'static,
+    {
+        let handlers = self.handlers::<M>()?;
+        let id = handlers.lock().map_err(PoisonLock::from)?.add(Arc::new(handler));
+        let handlers = Arc::downgrade(&handlers);
+        subscription.on_cancel(move || {
+            if let Some(handlers) = handlers.upgrade() {
+                if let Ok(mut handlers) = handlers.lock() {
+                    handlers.remove(id)
+                }
+            }
+        });
         Ok(())
     }
 
+    fn subscribe0<M: 'static>(&self, callback: Callback0) -> Fallible<()>
+    where
+        Enveloped<M>: ripb::Message,
+    {
+        self.on_message(callback.subscription(), move |_: &M, _| callback.call())
+    }
+
     fn subscribe<M, P, C>(&self, callback: Callback<P>, converter: C) -> Fallible<()>
     where
-        M: ripb::Message + Debug + 'static,
+        M: Debug + 'static,
+        Enveloped<M>: ripb::Message,
         P: 'static,
-        C: Fn(&M) -> &P + Send + 'static,
+        C: Fn(&M) -> &P + Send + Sync + 'static,
     {
-        self.ensure_has_subscriber()?;
-        self.subscriber
-            .lock()
-            .map_err(PoisonLock::from)?
-            .as_ref()
-            .unwrap()
-            .on_message(move |m: &M| callback.call(converter(m)))?;
-        Ok(())
+        self.on_message(callback.subscription(), move |m: &M, envelope| {
+            callback.call_with_envelope(converter(m), envelope)
+        })
     }
 
     fn subscribe0_filter<M, F>(&self, callback: Callback0, filter: F) -> Fallible<()>
     where
-        M: ripb::Message + 'static,
-        F: Fn(&M) -> bool + Send + 'static,
+        M: 'static,
+        Enveloped<M>: ripb::Message,
+        F: Fn(&M) -> bool + Send + Sync + 'static,
     {
-        self.ensure_has_subscriber()?;
-        self.subscriber
-            .lock()
-            .map_err(PoisonLock::from)?
-            .as_ref()
-            .unwrap()
-            .on_message(move |m: &M| {
-                if filter(m) {
-                    callback.call()
-                }
-            })?;
-        Ok(())
+        self.on_message(callback.subscription(), move |m: &M, _| {
+            if filter(m) {
+                callback.call()
+            }
+        })
     }
 
     fn subscribe_filter<M, P, C, F>(&self, callback: Callback<P>, converter: C, filter: F) -> Fallible<()>
     where
-        M: ripb::Message + Debug + 'static,
+        M: Debug + 'static,
+        Enveloped<M>: ripb::Message,
         P: 'static,
-        C: Fn(&M) -> &P + Send + 'static,
-        F: Fn(&M) -> bool + Send + 'static,
+        C: Fn(&M) -> &P + Send + Sync + 'static,
+        F: Fn(&M) -> bool + Send + Sync + 'static,
     {
-        self.ensure_has_subscriber()?;
-        self.subscriber
-            .lock()
-            .map_err(PoisonLock::from)?
-            .as_ref()
-            .unwrap()
-            .on_message(move |m: &M| {
-                if filter(m) {
-                    callback.call(converter(m))
-                }
-            })?;
-        Ok(())
+        self.on_message(callback.subscription(), move |m: &M, envelope| {
+            if filter(m) {
+                callback.call_with_envelope(converter(m), envelope)
+            }
+        })
     }
 }
 
@@ -286,7 +459,56 @@ struct ComponentLoaded<T: Debug> {
     component: T,
 }
 
-impl<T: Send + Sync + Debug + Copy + 'static> ComponentFacade for InProcessComponent<T> {
+impl<T: ComponentKind> ThroughMqtt for ComponentVersionRequest<T> {
+    #[cfg(feature = "serialization-faithful")]
+    const ENVELOPED: bool = false;
+
+    #[cfg(feature = "serialization-faithful")]
+    fn through_mqtt(self) -> Fallible<Self> {
+        let component = self.component.mqtt_component()?;
+        faithful::topic(HermesTopic::Component(
+            None,
+            component,
+            ComponentCommand::VersionRequest,
+        ))?;
+        Ok(self)
+    }
+}
+
+impl<T: ComponentKind> ThroughMqtt for ComponentVersion<T> {
+    #[cfg(feature = "serialization-faithful")]
+    fn through_mqtt(self) -> Fallible<Self> {
+        let component = self.component.mqtt_component()?;
+        let topic = HermesTopic::Component(None, component, ComponentCommand::Version);
+        let (_, version) = faithful::json(topic, &self.version)?;
+        Ok(ComponentVersion { version, ..self })
+    }
+}
+
+impl<T: ComponentKind> ThroughMqtt for ComponentError<T> {
+    #[cfg(feature = "serialization-faithful")]
+    fn through_mqtt(self) -> Fallible<Self> {
+        let component = self.component.mqtt_component()?;
+        let topic = HermesTopic::Component(None, component, ComponentCommand::Error);
+        let (_, error) = faithful::json(topic, &self.error)?;
+        Ok(ComponentError { error, ..self })
+    }
+}
+
+impl<T: ComponentKind> ThroughMqtt for ComponentLoaded<T> {
+    #[cfg(feature = "serialization-faithful")]
+    fn through_mqtt(self) -> Fallible<Self> {
+        let component = self.component.mqtt_component()?;
+        let topic = HermesTopic::Component(None, component, ComponentCommand::Loaded);
+        let (_, component_loaded) = faithful::json(topic, &self.component_loaded)?;
+        Ok(ComponentLoaded {
+            component_loaded,
+            ..self
+        })
+    }
+}
+
+impl<T: ComponentKind> ComponentFacade for InProcessComponent<T> {
     fn publish_version_request(&self) -> Fallible<()> {
         self.publish(ComponentVersionRequest {
             component: self.component,
@@ -306,7 +528,7 @@ impl<T: Send + Sync + Debug + Copy + 'static> ComponentFacade for InProcessCompo
     }
 }
 
-impl<T: Send + Sync + Debug + Copy + 'static> ComponentBackendFacade for InProcessComponent<T> {
+impl<T: ComponentKind> ComponentBackendFacade for InProcessComponent<T> {
     fn subscribe_version_request(&self, handler: Callback0) -> Fallible<()> {
         subscribe!(self, ComponentVersionRequest<T>, handler)
     }
@@ -362,7 +584,65 @@ struct IdentifiableComponentLoaded<T: Debug> {
     component: T,
 }
 
-impl<T: Send + Sync + Debug + Copy + 'static> IdentifiableComponentFacade for InProcessComponent<T> {
+impl<T: ComponentKind> ThroughMqtt for IdentifiableComponentVersionRequest<T> {
+    #[cfg(feature = "serialization-faithful")]
+    const ENVELOPED: bool = false;
+
+    #[cfg(feature = "serialization-faithful")]
+    fn through_mqtt(self) -> Fallible<Self> {
+        let component = self.component.mqtt_component()?;
+        let topic = HermesTopic::Component(Some(self.site_id), component, ComponentCommand::VersionRequest);
+        let topic = faithful::topic(topic)?;
+        Ok(IdentifiableComponentVersionRequest {
+            site_id: faithful::site_id(&topic)?,
+            component: self.component,
+        })
+    }
+}
+
+impl<T: ComponentKind> ThroughMqtt for IdentifiableComponentVersion<T> {
+    #[cfg(feature = "serialization-faithful")]
+    fn through_mqtt(self) -> Fallible<Self> {
+        let component = self.component.mqtt_component()?;
+        let topic = HermesTopic::Component(Some(self.site_id), component, ComponentCommand::Version);
+        let (topic, version) = faithful::json(topic, &self.version)?;
+        Ok(IdentifiableComponentVersion {
+            site_id: faithful::site_id(&topic)?,
+            version,
+            component: self.component,
+        })
+    }
+}
+
+impl<T: ComponentKind> ThroughMqtt for IdentifiableComponentError<T> {
+    #[cfg(feature = "serialization-faithful")]
+    fn through_mqtt(self) -> Fallible<Self> {
+        let component = self.component.mqtt_component()?;
+        let topic = HermesTopic::Component(Some(self.site_id), component, ComponentCommand::Error);
+        let (topic, error) = faithful::json(topic, &self.error)?;
+        Ok(IdentifiableComponentError {
+            site_id: faithful::site_id(&topic)?,
+            error,
+            component: self.component,
+        })
+    }
+}
+
+impl<T: ComponentKind> ThroughMqtt for IdentifiableComponentLoaded<T> {
+    #[cfg(feature = "serialization-faithful")]
+    fn through_mqtt(self) -> Fallible<Self> {
+        let component = self.component.mqtt_component()?;
+        let topic = HermesTopic::Component(Some(self.site_id), component, ComponentCommand::Loaded);
+        let (topic, component_loaded) = faithful::json(topic, &self.component_loaded)?;
+        Ok(IdentifiableComponentLoaded {
+            site_id: faithful::site_id(&topic)?,
+            component_loaded,
+            component: self.component,
+        })
+    }
+}
+
+impl<T: ComponentKind> IdentifiableComponentFacade for InProcessComponent<T> {
     fn publish_version_request(&self, site_id: String) -> Fallible<()> {
         let version_request = IdentifiableComponentVersionRequest {
             site_id,
@@ -396,7 +676,7 @@ impl<T: Send + Sync + Debug + Copy + 'static> IdentifiableComponentFacade for In
     }
 }
 
-impl<T: Send + Sync + Debug + Copy + 'static> IdentifiableComponentBackendFacade for InProcessComponent<T> {
+impl<T: ComponentKind> IdentifiableComponentBackendFacade for InProcessComponent<T> {
     fn subscribe_version_request(&self, site_id: String, handler: Callback0) -> Fallible<()> {
         subscribe_filter!(self, IdentifiableComponentVersionRequest<T>, handler, site_id)
     }
@@ -445,7 +725,31 @@ struct IdentifiableToggleableToggleOff<T> {
     component: T,
 }
 
-impl<T: Send + Sync + Debug + Copy + 'static> IdentifiableToggleableFacade for InProcessComponent<T> {
+impl<T: ComponentKind> ThroughMqtt for IdentifiableToggleableToggleOn<T> {
+    #[cfg(feature = "serialization-faithful")]
+    fn through_mqtt(self) -> Fallible<Self> {
+        let topic = self
+            .component
+            .mqtt_toggle_topic(true)?
+            .with_site(self.site.site_id.clone());
+        let (_, site) = faithful::json(topic, &self.site)?;
+        Ok(IdentifiableToggleableToggleOn { site, ..self })
+    }
+}
+
+impl<T: ComponentKind> ThroughMqtt for IdentifiableToggleableToggleOff<T> {
+    #[cfg(feature = "serialization-faithful")]
+    fn through_mqtt(self) -> Fallible<Self> {
+        let topic = self
+            .component
+            .mqtt_toggle_topic(false)?
+            .with_site(self.site.site_id.clone());
+        let (_, site) = faithful::json(topic, &self.site)?;
+        Ok(IdentifiableToggleableToggleOff { site, ..self })
+    }
+}
+
+impl<T: ComponentKind> IdentifiableToggleableFacade for InProcessComponent<T> {
     fn publish_toggle_on(&self, site: SiteMessage) -> Fallible<()> {
         let toggle_on: IdentifiableToggleableToggleOn<T> = IdentifiableToggleableToggleOn {
             site,
@@ -463,98 +767,21 @@ impl<T: Send + Sync + Debug + Copy + 'static> IdentifiableToggleableFacade for I
     }
 }
 
-impl<T: Send + Sync + Debug + 'static> IdentifiableToggleableBackendFacade for InProcessComponent<T> {
-    fn subscribe_toggle_on(&self, handler: Callback<SiteMessage>) -> Fallible<()> {
-        subscribe!(self, IdentifiableToggleableToggleOn<T> { site }, handler)
-    }
-
-    fn subscribe_toggle_off(&self, handler: Callback<SiteMessage>) -> Fallible<()> {
-        subscribe!(self, IdentifiableToggleableToggleOff<T> { site }, handler)
-    }
-}
-
-#[derive(Debug, Clone, Copy)]
-struct Nlu;
-
-#[derive(Debug)]
-struct NluQuery {
-    query: NluQueryMessage,
-}
-
-#[derive(Debug)]
-struct NluPartialQuery {
-    query: NluSlotQueryMessage,
-}
-
-#[derive(Debug)]
-struct NluSlotParsed {
-    slot: NluSlotMessage,
-}
-
-#[derive(Debug)]
-struct NluIntentParsed {
-    intent: NluIntentMessage,
-}
-
-#[derive(Debug)]
-struct NluIntentNotRecognized {
-    status: NluIntentNotRecognizedMessage,
-}
-
-#[derive(Debug)]
-struct NluReload {
-    component_reload: RequestComponentReloadMessage,
-}
-
-impl NluFacade for InProcessComponent<Nlu> {
-    fn publish_query(&self, query: NluQueryMessage) -> Fallible<()> {
-        self.publish(NluQuery { query })
-    }
-
-    fn publish_partial_query(&self, query: NluSlotQueryMessage) -> Fallible<()> {
-        self.publish(NluPartialQuery { query })
-    }
-
-    fn publish_component_reload(&self, component_reload: RequestComponentReloadMessage) -> Fallible<()> {
-        self.publish(NluReload { component_reload })
-    }
-
-    fn subscribe_slot_parsed(&self, handler: Callback<NluSlotMessage>) -> Fallible<()> {
-        subscribe!(self, NluSlotParsed { slot }, handler)
-    }
-
-    fn subscribe_intent_parsed(&self, handler: Callback<NluIntentMessage>) -> Fallible<()> {
-        subscribe!(self, NluIntentParsed { intent }, handler)
-    }
-
-    fn subscribe_intent_not_recognized(&self, handler: Callback<NluIntentNotRecognizedMessage>) -> Fallible<()> {
-        subscribe!(self, NluIntentNotRecognized { status }, handler)
-    }
-}
-
-impl NluBackendFacade for InProcessComponent<Nlu> {
-    fn subscribe_query(&self, handler: Callback<NluQueryMessage>) -> Fallible<()> {
-        subscribe!(self, NluQuery { query }, handler)
-    }
-
-    fn subscribe_partial_query(&self, handler: Callback<NluSlotQueryMessage>) -> Fallible<()> {
-        subscribe!(self, NluPartialQuery { query }, handler)
-    }
-
-    fn subscribe_component_reload(&self, handler: Callback<RequestComponentReloadMessage>) -> Fallible<()> {
-        subscribe!(self, NluReload { component_reload }, handler)
+impl<T: ComponentKind> IdentifiableToggleableBackendFacade for InProcessComponent<T> {
+    fn subscribe_toggle_on(&self, site_id: String, handler: Callback<SiteMessage>) -> Fallible<()> {
+        subscribe_filter!(self, IdentifiableToggleableToggleOn<T> { site }, handler, site_id)
     }
 
-    fn publish_slot_parsed(&self, slot: NluSlotMessage) -> Fallible<()> {
-        self.publish(NluSlotParsed { slot })
+    fn subscribe_all_toggle_on(&self, handler: Callback<SiteMessage>) -> Fallible<()> {
+        subscribe!(self, IdentifiableToggleableToggleOn<T> { site }, handler)
     }
 
-    fn publish_intent_parsed(&self, intent: NluIntentMessage) -> Fallible<()> {
-        self.publish(NluIntentParsed { intent })
+    fn subscribe_toggle_off(&self, site_id: String, handler: Callback<SiteMessage>) -> Fallible<()> {
+        subscribe_filter!(self, IdentifiableToggleableToggleOff<T> { site }, handler, site_id)
     }
 
-    fn publish_intent_not_recognized(&self, status: NluIntentNotRecognizedMessage) -> Fallible<()> {
-        self.publish(NluIntentNotRecognized { status })
+    fn subscribe_all_toggle_off(&self, handler: Callback<SiteMessage>) -> Fallible<()> {
+        subscribe!(self, IdentifiableToggleableToggleOff<T> { site }, handler)
     }
 }
 
@@ -568,7 +795,23 @@ struct ToggleableToggleOff<T> {
     component: T,
 }
 
-impl<T: Send + Sync + Debug + Copy + 'static> ToggleableFacade for InProcessComponent<T> {
+impl<T: ComponentKind> ThroughMqtt for ToggleableToggleOn<T> {
+    #[cfg(feature = "serialization-faithful")]
+    fn through_mqtt(self) -> Fallible<Self> {
+        faithful::topic(self.component.mqtt_toggle_topic(true)?)?;
+        Ok(self)
+    }
+}
+
+impl<T: ComponentKind> ThroughMqtt for ToggleableToggleOff<T> {
+    #[cfg(feature = "serialization-faithful")]
+    fn through_mqtt(self) -> Fallible<Self> {
+        faithful::topic(self.component.mqtt_toggle_topic(false)?)?;
+        Ok(self)
+    }
+}
+
+impl<T: ComponentKind> ToggleableFacade for InProcessComponent<T> {
     fn publish_toggle_on(&self) -> Fallible<()> {
         let toggle_on: ToggleableToggleOn<T> = ToggleableToggleOn {
             component: self.component,
@@ -584,7 +827,7 @@ impl<T: Send + Sync + Debug + Copy + 'static> ToggleableFacade for InProcessComp
     }
 }
 
-impl<T: Send + Sync + Debug + 'static> ToggleableBackendFacade for InProcessComponent<T> {
+impl<T: ComponentKind> ToggleableBackendFacade for InProcessComponent<T> {
     fn subscribe_toggle_on(&self, handler: Callback0) -> Fallible<()> {
         subscribe!(self, ToggleableToggleOn<T>, handler)
     }
@@ -594,541 +837,390 @@ impl<T: Send + Sync + Debug + 'static> ToggleableBackendFacade for InProcessComp
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-struct VoiceActivity;
-
-#[derive(Debug)]
-struct VoiceActivityVadUp {
-    vad_up: VadUpMessage,
-}
-
-#[derive(Debug)]
-struct VoiceActivityVadDown {
-    vad_down: VadDownMessage,
-}
-
-impl VoiceActivityFacade for InProcessComponent<VoiceActivity> {
-    fn subscribe_vad_up(&self, site_id: String, handler: Callback<VadUpMessage>) -> Fallible<()> {
-        subscribe_filter!(self, VoiceActivityVadUp { vad_up }, handler, site_id, |it| &it
-            .vad_up
-            .site_id)
-    }
-
-    fn subscribe_vad_down(&self, site_id: String, handler: Callback<VadDownMessage>) -> Fallible<()> {
-        subscribe_filter!(self, VoiceActivityVadDown { vad_down }, handler, site_id, |it| &it
-            .vad_down
-            .site_id)
-    }
-
-    fn subscribe_all_vad_up(&self, handler: Callback<VadUpMessage>) -> Fallible<()> {
-        subscribe!(self, VoiceActivityVadUp { vad_up }, handler)
-    }
-
-    fn subscribe_all_vad_down(&self, handler: Callback<VadDownMessage>) -> Fallible<()> {
-        subscribe!(self, VoiceActivityVadDown { vad_down }, handler)
-    }
-}
-
-impl VoiceActivityBackendFacade for InProcessComponent<VoiceActivity> {
-    fn publish_vad_up(&self, vad_up: VadUpMessage) -> Fallible<()> {
-        self.publish(VoiceActivityVadUp { vad_up })
-    }
-
-    fn publish_vad_down(&self, vad_down: VadDownMessage) -> Fallible<()> {
-        self.publish(VoiceActivityVadDown { vad_down })
-    }
-}
-
-#[derive(Debug, Clone, Copy)]
-struct Hotword;
-
-#[derive(Debug)]
-struct HotwordDetected {
-    id: String,
-    message: HotwordDetectedMessage,
-}
-
-impl HotwordFacade for InProcessComponent<Hotword> {
-    fn subscribe_detected(&self, id: String, handler: Callback<HotwordDetectedMessage>) -> Fallible<()> {
-        subscribe_filter!(self, HotwordDetected { message }, handler, id, |it| &it.id)
-    }
-
-    fn subscribe_all_detected(&self, handler: Callback<HotwordDetectedMessage>) -> Fallible<()> {
-        subscribe!(self, HotwordDetected { message }, handler)
-    }
-}
-
-impl HotwordBackendFacade for InProcessComponent<Hotword> {
-    fn publish_detected(&self, id: String, message: HotwordDetectedMessage) -> Fallible<()> {
-        self.publish(HotwordDetected { id, message })
-    }
-}
-
-#[derive(Debug, Clone, Copy)]
-struct Sound;
-
-impl SoundFeedbackFacade for InProcessComponent<Sound> {}
-
-impl SoundFeedbackBackendFacade for InProcessComponent<Sound> {}
-
-#[derive(Debug, Clone, Copy)]
-struct Asr;
-
-#[derive(Debug)]
-struct AsrStartListening {
-    start: AsrStartListeningMessage,
-}
-
-#[derive(Debug)]
-struct AsrStopListening {
-    site: SiteMessage,
-}
-
-#[derive(Debug)]
-struct AsrReload {
-    component_reload: RequestComponentReloadMessage,
-}
-
-#[derive(Debug)]
-struct AsrTextCaptured {
-    text_captured: TextCapturedMessage,
-}
-
-#[derive(Debug)]
-struct AsrPartialTextCaptured {
-    text_captured: TextCapturedMessage,
-}
-
-impl AsrFacade for InProcessComponent<Asr> {
-    fn publish_start_listening(&self, start: AsrStartListeningMessage) -> Fallible<()> {
-        self.publish(AsrStartListening { start })
-    }
-
-    fn publish_stop_listening(&self, site: SiteMessage) -> Fallible<()> {
-        self.publish(AsrStopListening { site })
-    }
-
-    fn publish_component_reload(&self, component_reload: RequestComponentReloadMessage) -> Fallible<()> {
-        self.publish(AsrReload { component_reload })
-    }
-
-    fn subscribe_text_captured(&self, handler: Callback<TextCapturedMessage>) -> Fallible<()> {
-        subscribe!(self, AsrTextCaptured { text_captured }, handler)
-    }
-
-    fn subscribe_partial_text_captured(&self, handler: Callback<TextCapturedMessage>) -> Fallible<()> {
-        subscribe!(self, AsrPartialTextCaptured { text_captured }, handler)
-    }
-}
-
-impl AsrBackendFacade for InProcessComponent<Asr> {
-    fn subscribe_start_listening(&self, handler: Callback<AsrStartListeningMessage>) -> Fallible<()> {
-        subscribe!(self, AsrStartListening { start }, handler)
-    }
-
-    fn subscribe_stop_listening(&self, handler: Callback<SiteMessage>) -> Fallible<()> {
-        subscribe!(self, AsrStopListening { site }, handler)
-    }
-
-    fn subscribe_component_reload(&self, handler: Callback<RequestComponentReloadMessage>) -> Fallible<()> {
-        subscribe!(self, AsrReload { component_reload }, handler)
-    }
-
-    fn publish_text_captured(&self, text_captured: TextCapturedMessage) -> Fallible<()> {
-        self.publish(AsrTextCaptured { text_captured })
-    }
-
-    fn publish_partial_text_captured(&self, text_captured: TextCapturedMessage) -> Fallible<()> {
-        self.publish(AsrPartialTextCaptured { text_captured })
-    }
-}
-
-#[derive(Debug, Clone, Copy)]
-struct Tts;
-
-#[derive(Debug)]
-struct TtsSay {
-    to_say: SayMessage,
-}
-
-#[derive(Debug)]
-struct TtsSayFinished {
-    status: SayFinishedMessage,
-}
-
-#[derive(Debug)]
-struct TtsRegisterSound {
-    sound: RegisterSoundMessage,
-}
-
-impl TtsFacade for InProcessComponent<Tts> {
-    fn publish_say(&self, to_say: SayMessage) -> Fallible<()> {
-        self.publish(TtsSay { to_say })
-    }
-
-    fn subscribe_say_finished(&self, handler: Callback<SayFinishedMessage>) -> Fallible<()> {
-        subscribe!(self, TtsSayFinished { status }, handler)
-    }
-
-    fn publish_register_sound(&self, sound: RegisterSoundMessage) -> Fallible<()> {
-        self.publish(TtsRegisterSound { sound })
-    }
-}
-
-impl TtsBackendFacade for InProcessComponent<Tts> {
-    fn publish_say_finished(&self, status: SayFinishedMessage) -> Fallible<()> {
-        self.publish(TtsSayFinished { status })
-    }
-
-    fn subscribe_say(&self, handler: Callback<SayMessage>) -> Fallible<()> {
-        subscribe!(self, TtsSay { to_say }, handler)
-    }
-
-    fn subscribe_register_sound(&self, handler: Callback<RegisterSoundMessage>) -> Fallible<()> {
-        subscribe!(self, TtsRegisterSound { sound }, handler)
-    }
-}
+/// The MQTT component or toggle topic of a component, if it has one
+#[cfg(feature = "serialization-faithful")]
+macro_rules! mqtt_option {
+    ([]) => {
+        None
+    };
+    ([$component:expr]) => {
+        Some($component)
+    };
+    ($on_or_off:ident []) => {
+        None
+    };
+    (on [$toggle_on:expr, $toggle_off:expr]) => {
+        Some($toggle_on)
+    };
+    (off [$toggle_on:expr, $toggle_off:expr]) => {
+        Some($toggle_off)
+    };
+}
+
+/// The message struct of a message and how it goes through MQTT
+macro_rules! inprocess_message {
+    ($message:ident [] $publish:ident $subscribe:ident [] [] [$topic:expr] []) => {
+        #[derive(Debug)]
+        pub struct $message {}
+
+        through_mqtt!($message => $topic);
+    };
+    ($message:ident [$payload:ident: $payload_type:ty] $publish:ident $subscribe:ident [$key:ident] $all:tt [$topic:expr] []) => {
+        #[derive(Debug)]
+        pub struct $message {
+            pub $key: String,
+            pub $payload: $payload_type,
+        }
 
-#[derive(Debug, Clone, Copy)]
-struct AudioServer;
+        through_mqtt!($message { $key, $payload } => $topic);
+    };
+    ($message:ident [$payload:ident: $payload_type:ty] $publish:ident $subscribe:ident [] $all:tt [$topic:expr] []) => {
+        #[derive(Debug)]
+        pub struct $message {
+            pub $payload: $payload_type,
+        }
 
-#[derive(Debug)]
-struct AudioServerPlayBytes {
-    bytes: PlayBytesMessage,
-}
+        through_mqtt!($message { $payload } => |_it| $topic);
+    };
+    (
+        $message:ident
+        [$payload:ident: $payload_type:ty]
+        $publish:ident
+        $subscribe:ident
+        [$key:ident in $($key_path:ident).+]
+        $all:tt
+        [$topic:expr]
+        []
+    ) => {
+        #[derive(Debug)]
+        pub struct $message {
+            pub $payload: $payload_type,
+        }
 
-#[derive(Debug)]
-struct AudioServerPlayFinished {
-    status: PlayFinishedMessage,
-}
+        through_mqtt!($message { $payload } => |it| {
+            let $key = it.$($key_path).+.clone();
+            $topic
+        });
+    };
+    (
+        $message:ident
+        [$payload:ident: $payload_type:ty]
+        $publish:ident
+        $subscribe:ident
+        $key:tt
+        $all:tt
+        $topic:tt
+        [$encode:expr, $decode:path]
+    ) => {
+        #[derive(Debug)]
+        pub struct $message {
+            pub $payload: $payload_type,
+        }
 
-#[derive(Debug)]
-struct AudioServerAudioFrame {
-    frame: AudioFrameMessage,
+        through_mqtt!($message { $payload } => binary $encode, $decode);
+    };
 }
 
-#[derive(Debug)]
-struct AudioServerReplayRequest {
-    request: ReplayRequestMessage,
-}
+/// The module holding the messages of a component, if it has any
+macro_rules! inprocess_messages {
+    ($module:ident) => {};
+    ($module:ident $({ $message:ident $($rest:tt)* })+) => {
+        mod $module {
+            use super::*;
 
-#[derive(Debug)]
-struct AudioServerReplayResponse {
-    frame: AudioFrameMessage,
+            $(inprocess_message!($message $($rest)*);)+
+        }
+    };
 }
 
-#[derive(Debug)]
-struct AudioServerStreamBytes {
-    bytes: StreamBytesMessage,
+macro_rules! inprocess_publish {
+    ($sel:ident, [quiet $($attribute:ident)*], $message:expr) => {
+        $sel.publish_quiet($message)
+    };
+    ($sel:ident, [], $message:expr) => {
+        $sel.publish($message)
+    };
+    ($sel:ident, [$attribute:ident $($attributes:ident)*], $message:expr) => {
+        inprocess_publish!($sel, [$($attributes)*], $message)
+    };
 }
 
-#[derive(Debug)]
-struct AudioServerStreamFinished {
-    status: StreamFinishedMessage,
+macro_rules! inprocess_publish_method {
+    ($module:ident $attributes:tt $message:ident [] $publish:ident $($rest:tt)*) => {
+        fn $publish(&self) -> Fallible<()> {
+            inprocess_publish!(self, $attributes, $module::$message {})
+        }
+    };
+    (
+        $module:ident
+        $attributes:tt
+        $message:ident
+        [$payload:ident: $payload_type:ty]
+        $publish:ident
+        $subscribe:ident
+        [$key:ident]
+        $($rest:tt)*
+    ) => {
+        fn $publish(&self, $key: String, $payload: $payload_type) -> Fallible<()> {
+            inprocess_publish!(self, $attributes, $module::$message { $key, $payload })
+        }
+    };
+    ($module:ident $attributes:tt $message:ident [$payload:ident: $payload_type:ty] $publish:ident $($rest:tt)*) => {
+        fn $publish(&self, $payload: $payload_type) -> Fallible<()> {
+            inprocess_publish!(self, $attributes, $module::$message { $payload })
+        }
+    };
 }
 
-impl AudioServerFacade for InProcessComponent<AudioServer> {
-    fn publish_play_bytes(&self, bytes: PlayBytesMessage) -> Fallible<()> {
-        self.publish(AudioServerPlayBytes { bytes })
-    }
-
-    fn subscribe_play_finished(&self, site_id: String, handler: Callback<PlayFinishedMessage>) -> Fallible<()> {
-        subscribe_filter!(self, AudioServerPlayFinished { status }, handler, site_id)
-    }
-
-    fn subscribe_all_play_finished(&self, handler: Callback<PlayFinishedMessage>) -> Fallible<()> {
-        subscribe!(self, AudioServerPlayFinished { status }, handler)
-    }
-
-    fn subscribe_audio_frame(&self, site_id: String, handler: Callback<AudioFrameMessage>) -> Fallible<()> {
-        subscribe_filter!(self, AudioServerAudioFrame { frame }, handler, site_id)
-    }
-
-    fn publish_replay_request(&self, request: ReplayRequestMessage) -> Fallible<()> {
-        self.publish(AudioServerReplayRequest { request })
-    }
-
-    fn subscribe_replay_response(&self, site_id: String, handler: Callback<AudioFrameMessage>) -> Fallible<()> {
-        subscribe_filter!(self, AudioServerReplayResponse { frame }, handler, site_id)
-    }
-
-    fn publish_stream_bytes(&self, stream_bytes_message: StreamBytesMessage) -> Fallible<()> {
-        self.publish(AudioServerStreamBytes {
-            bytes: stream_bytes_message,
-        })
-    }
-
-    fn subscribe_stream_finished(&self, site_id: String, handler: Callback<StreamFinishedMessage>) -> Fallible<()> {
-        subscribe_filter!(self, AudioServerStreamFinished { status }, handler, site_id)
-    }
-
-    fn subscribe_all_stream_finished(&self, handler: Callback<StreamFinishedMessage>) -> Fallible<()> {
-        subscribe!(self, AudioServerStreamFinished { status }, handler)
+/// Check a key subscribed to, the MQTT wildcards given alone are rejected as they are by the MQTT
+/// handler, where they would subscribe to every key
+fn check_key(key: &str) -> Fallible<()> {
+    if key == "+" || key == "#" {
+        return Err(failure::format_err!(
+            "the MQTT wildcard '{}' can't be used as a key",
+            key
+        ));
     }
+    Ok(())
 }
 
-impl AudioServerBackendFacade for InProcessComponent<AudioServer> {
-    fn subscribe_play_bytes(&self, site_id: String, handler: Callback<PlayBytesMessage>) -> Fallible<()> {
-        subscribe_filter!(self, AudioServerPlayBytes { bytes }, handler, site_id)
-    }
-
-    fn subscribe_all_play_bytes(&self, handler: Callback<PlayBytesMessage>) -> Fallible<()> {
-        subscribe!(self, AudioServerPlayBytes { bytes }, handler)
-    }
-
-    fn publish_play_finished(&self, status: PlayFinishedMessage) -> Fallible<()> {
-        self.publish(AudioServerPlayFinished { status })
-    }
-
-    fn publish_audio_frame(&self, frame: AudioFrameMessage) -> Fallible<()> {
-        self.publish_quiet(AudioServerAudioFrame { frame })
-    }
-
-    fn subscribe_replay_request(&self, site_id: String, handler: Callback<ReplayRequestMessage>) -> Fallible<()> {
-        subscribe_filter!(self, AudioServerReplayRequest { request }, handler, site_id)
-    }
-
-    fn publish_replay_response(&self, frame: AudioFrameMessage) -> Fallible<()> {
-        self.publish_quiet(AudioServerReplayResponse { frame })
-    }
-
-    fn subscribe_stream_bytes(&self, site_id: String, handler: Callback<StreamBytesMessage>) -> Fallible<()> {
-        subscribe_filter!(self, AudioServerStreamBytes { bytes }, handler, site_id)
-    }
-
-    fn subscribe_all_stream_bytes(&self, handler: Callback<StreamBytesMessage>) -> Fallible<()> {
-        subscribe!(self, AudioServerStreamBytes { bytes }, handler)
-    }
-
-    fn publish_stream_finished(&self, status: StreamFinishedMessage) -> Fallible<()> {
-        self.publish(AudioServerStreamFinished { status })
-    }
-}
-
-#[derive(Debug, Clone, Copy)]
-struct Dialogue;
-
-#[derive(Debug)]
-struct DialogueSessionQueued {
-    status: SessionQueuedMessage,
-}
-
-#[derive(Debug)]
-struct DialogueSessionStarted {
-    status: SessionStartedMessage,
-}
-
-#[derive(Debug)]
-struct DialogueIntent {
-    intent: IntentMessage,
-}
-
-#[derive(Debug)]
-struct DialogueIntentNotRecognized {
-    intent_not_recognized: IntentNotRecognizedMessage,
-}
+macro_rules! inprocess_subscribe_methods {
+    ($module:ident $attributes:tt $message:ident [] $publish:ident $subscribe:ident $($rest:tt)*) => {
+        fn $subscribe(&self, handler: Callback0) -> Fallible<()> {
+            subscribe!(self, $module::$message, handler)
+        }
+    };
+    (
+        $module:ident
+        $attributes:tt
+        $message:ident
+        [$payload:ident: $payload_type:ty]
+        $publish:ident
+        $subscribe:ident
+        []
+        $($rest:tt)*
+    ) => {
+        fn $subscribe(&self, handler: Callback<$payload_type>) -> Fallible<()> {
+            subscribe!(self, $module::$message { $payload }, handler)
+        }
+    };
+    (
+        $module:ident
+        $attributes:tt
+        $message:ident
+        [$payload:ident: $payload_type:ty]
+        $publish:ident
+        $subscribe:ident
+        [$key:ident]
+        $all:tt
+        $($rest:tt)*
+    ) => {
+        fn $subscribe(&self, $key: String, handler: Callback<$payload_type>) -> Fallible<()> {
+            check_key(&$key)?;
+            subscribe_filter!(self, $module::$message { $payload }, handler, $key, |it| &it.$key)
+        }
 
-#[derive(Debug)]
-struct DialogueSessionEnded {
-    status: SessionEndedMessage,
-}
+        inprocess_subscribe_all_method!($module $message [$payload: $payload_type] $all);
+    };
+    (
+        $module:ident
+        $attributes:tt
+        $message:ident
+        [$payload:ident: $payload_type:ty]
+        $publish:ident
+        $subscribe:ident
+        [$key:ident in $($key_path:ident).+]
+        $all:tt
+        $($rest:tt)*
+    ) => {
+        fn $subscribe(&self, $key: String, handler: Callback<$payload_type>) -> Fallible<()> {
+            check_key(&$key)?;
+            subscribe_filter!(self, $module::$message { $payload }, handler, $key, |it| &it
+                .$payload
+                .$($key_path).+)
+        }
 
-#[derive(Debug)]
-struct DialogueStartSession {
-    start_session: StartSessionMessage,
+        inprocess_subscribe_all_method!($module $message [$payload: $payload_type] $all);
+    };
 }
 
-#[derive(Debug)]
-struct DialogueContinueSession {
-    continue_session: ContinueSessionMessage,
-}
-
-#[derive(Debug)]
-struct DialogueEndSession {
-    end_session: EndSessionMessage,
+macro_rules! inprocess_subscribe_all_method {
+    ($module:ident $message:ident [$payload:ident: $payload_type:ty] []) => {};
+    ($module:ident $message:ident [$payload:ident: $payload_type:ty] [$subscribe_all:ident $($wildcard:tt)*]) => {
+        fn $subscribe_all(&self, handler: Callback<$payload_type>) -> Fallible<()> {
+            subscribe!(self, $module::$message { $payload }, handler)
+        }
+    };
 }
 
-#[derive(Debug)]
-struct DialogueConfigure {
-    config: DialogueConfigureMessage,
+/// The methods of a facade for a message, `$facade_side` is the side of the facade and `$side` the
+/// one publishing the message
+macro_rules! inprocess_methods {
+    (frontend frontend $($message:tt)*) => { inprocess_publish_method!($($message)*); };
+    (backend backend $($message:tt)*) => { inprocess_publish_method!($($message)*); };
+    (frontend backend $($message:tt)*) => { inprocess_subscribe_methods!($($message)*); };
+    (backend frontend $($message:tt)*) => { inprocess_subscribe_methods!($($message)*); };
 }
 
-impl DialogueFacade for InProcessComponent<Dialogue> {
-    fn subscribe_session_queued(&self, handler: Callback<SessionQueuedMessage>) -> Fallible<()> {
-        subscribe!(self, DialogueSessionQueued { status }, handler)
-    }
-
-    fn subscribe_session_started(&self, handler: Callback<SessionStartedMessage>) -> Fallible<()> {
-        subscribe!(self, DialogueSessionStarted { status }, handler)
-    }
-
-    fn subscribe_intent(&self, intent_name: String, handler: Callback<IntentMessage>) -> Fallible<()> {
-        subscribe_filter!(self, DialogueIntent { intent }, handler, intent_name, |it| &it
-            .intent
-            .intent
-            .intent_name)
-    }
+macro_rules! inprocess_facades {
+    ($(
+        $component:ident {
+            facade $facade_doc:tt $facade:ident $facade_supers:tt $facade_getter:ident;
+            backend $backend_doc:tt $backend:ident $backend_supers:tt $backend_getter:ident;
+            mqtt $mqtt_component:tt $mqtt_toggle:tt $mqtt_topic:tt;
+            messages [$({ $attributes:tt $side:ident $message:ident $route:tt $($rest:tt)* })*];
+        }
+    )*) => {
+        $(
+            #[derive(Debug, Clone, Copy)]
+            struct $component;
+
+            component_kind!(
+                $component,
+                mqtt_option!($mqtt_component),
+                mqtt_option!(on $mqtt_toggle),
+                mqtt_option!(off $mqtt_toggle)
+            );
 
-    fn subscribe_intents(&self, handler: Callback<IntentMessage>) -> Fallible<()> {
-        subscribe!(self, DialogueIntent { intent }, handler)
-    }
+            inprocess_messages!($facade_getter $({ $message $($rest)* })*);
 
-    fn subscribe_intent_not_recognized(&self, handler: Callback<IntentNotRecognizedMessage>) -> Fallible<()> {
-        subscribe!(self, DialogueIntentNotRecognized { intent_not_recognized }, handler)
-    }
+            impl $facade for InProcessComponent<$component> {
+                $(inprocess_methods!(frontend $side $facade_getter $attributes $message $($rest)*);)*
+            }
 
-    fn subscribe_session_ended(&self, handler: Callback<SessionEndedMessage>) -> Fallible<()> {
-        subscribe!(self, DialogueSessionEnded { status }, handler)
-    }
+            impl $backend for InProcessComponent<$component> {
+                $(inprocess_methods!(backend $side $facade_getter $attributes $message $($rest)*);)*
+            }
+        )*
 
-    fn publish_start_session(&self, start_session: StartSessionMessage) -> Fallible<()> {
-        self.publish(DialogueStartSession { start_session })
-    }
-
-    fn publish_continue_session(&self, continue_session: ContinueSessionMessage) -> Fallible<()> {
-        self.publish(DialogueContinueSession { continue_session })
-    }
-
-    fn publish_end_session(&self, end_session: EndSessionMessage) -> Fallible<()> {
-        self.publish(DialogueEndSession { end_session })
-    }
+        impl HermesProtocolHandler for InProcessHermesProtocolHandler {
+            $(
+                fn $facade_getter(&self) -> Box<dyn $facade> {
+                    self.get_handler($component)
+                }
+            )*
 
-    fn publish_configure(&self, config: DialogueConfigureMessage) -> Fallible<()> {
-        self.publish(DialogueConfigure { config })
-    }
+            $(
+                fn $backend_getter(&self) -> Box<dyn $backend> {
+                    self.get_handler($component)
+                }
+            )*
+        }
+    };
 }
 
-impl DialogueBackendFacade for InProcessComponent<Dialogue> {
-    fn publish_session_queued(&self, status: SessionQueuedMessage) -> Fallible<()> {
-        self.publish(DialogueSessionQueued { status })
-    }
-
-    fn publish_session_started(&self, status: SessionStartedMessage) -> Fallible<()> {
-        self.publish(DialogueSessionStarted { status })
-    }
+hermes_protocol!(inprocess_facades);
 
-    fn publish_intent(&self, intent: IntentMessage) -> Fallible<()> {
-        self.publish(DialogueIntent { intent })
-    }
-
-    fn publish_intent_not_recognized(&self, intent_not_recognized: IntentNotRecognizedMessage) -> Fallible<()> {
-        self.publish(DialogueIntentNotRecognized { intent_not_recognized })
-    }
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
 
-    fn publish_session_ended(&self, status: SessionEndedMessage) -> Fallible<()> {
-        self.publish(DialogueSessionEnded { status })
-    }
+    use super::*;
 
-    fn subscribe_start_session(&self, handler: Callback<StartSessionMessage>) -> Fallible<()> {
-        subscribe!(self, DialogueStartSession { start_session }, handler)
+    fn create_handlers() -> (Rc<InProcessHermesProtocolHandler>, Rc<InProcessHermesProtocolHandler>) {
+        let handler = Rc::new(InProcessHermesProtocolHandler::new());
+        (Rc::clone(&handler), handler)
     }
 
-    fn subscribe_continue_session(&self, handler: Callback<ContinueSessionMessage>) -> Fallible<()> {
-        subscribe!(self, DialogueContinueSession { continue_session }, handler)
-    }
+    hermes_test_suite::test_suite!();
 
-    fn subscribe_end_session(&self, handler: Callback<EndSessionMessage>) -> Fallible<()> {
-        subscribe!(self, DialogueEndSession { end_session }, handler)
-    }
+    #[test]
+    fn envelopes_are_given_to_the_callbacks() {
+        use hermes::hermes_utils::Example;
 
-    fn subscribe_configure(&self, handler: Callback<DialogueConfigureMessage>) -> Fallible<()> {
-        subscribe!(self, DialogueConfigure { config }, handler)
+        let source = MessageSource {
+            component: HermesComponent::AudioServer,
+            site_id: Some("kitchen".into()),
+        };
+        let with_envelope = InProcessHermesProtocolHandler::new_on_named_bus("envelopes").with_envelope(source.clone());
+        let without_envelope = InProcessHermesProtocolHandler::new_on_named_bus("envelopes");
+        let (tx, rx) = std::sync::mpsc::channel();
+        let tx = Mutex::new(tx);
+        without_envelope
+            .audio_server()
+            .subscribe_all_audio_frame(Callback::with_envelope(move |_: &AudioFrameMessage, envelope| {
+                tx.lock().unwrap().send(envelope.cloned()).unwrap()
+            }))
+            .unwrap();
+
+        with_envelope
+            .audio_server_backend()
+            .publish_audio_frame(AudioFrameMessage::full_example())
+            .unwrap();
+        without_envelope
+            .audio_server_backend()
+            .publish_audio_frame(AudioFrameMessage::full_example())
+            .unwrap();
+
+        // the bus delivers the messages asynchronously, WAIT_DURATION is 0 for this handler
+        let timeout = std::time::Duration::from_secs(1);
+        let envelope = rx
+            .recv_timeout(timeout)
+            .expect("didn't receive the first frame after one second");
+        assert_eq!(envelope.as_ref().map(|it| &it.source), Some(&source));
+        let envelope = rx
+            .recv_timeout(timeout)
+            .expect("didn't receive the second frame after one second");
+        assert_eq!(envelope, None);
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-struct Injection;
-
-#[derive(Debug)]
-struct InjectionPerform {
-    request: InjectionRequestMessage,
-}
-
-#[derive(Debug)]
-struct InjectionStatus {
-    status: InjectionStatusMessage,
-}
-
-#[derive(Debug)]
-struct InjectionStatusRequest {}
-
-#[derive(Debug)]
-struct InjectionComplete {
-    message: InjectionCompleteMessage,
-}
-
-#[derive(Debug)]
-struct InjectionResetPerform {
-    request: InjectionResetRequestMessage,
-}
-
-#[derive(Debug)]
-struct InjectionResetComplete {
-    message: InjectionResetCompleteMessage,
-}
-
-impl InjectionFacade for InProcessComponent<Injection> {
-    fn publish_injection_request(&self, request: InjectionRequestMessage) -> Fallible<()> {
-        self.publish(InjectionPerform { request })
-    }
-
-    fn publish_injection_status_request(&self) -> Fallible<()> {
-        self.publish(InjectionStatusRequest {})
-    }
-
-    fn publish_injection_reset_request(&self, request: InjectionResetRequestMessage) -> Fallible<()> {
-        self.publish(InjectionResetPerform { request })
-    }
-
-    fn subscribe_injection_status(&self, handler: Callback<InjectionStatusMessage>) -> Fallible<()> {
-        subscribe!(self, InjectionStatus { status }, handler)
-    }
-
-    fn subscribe_injection_complete(&self, handler: Callback<InjectionCompleteMessage>) -> Fallible<()> {
-        subscribe!(self, InjectionComplete { message }, handler)
-    }
-
-    fn subscribe_injection_reset_complete(&self, handler: Callback<InjectionResetCompleteMessage>) -> Fallible<()> {
-        subscribe!(self, InjectionResetComplete { message }, handler)
-    }
-}
+#[cfg(test)]
+mod named_bus_tests {
+    use std::rc::Rc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
-impl InjectionBackendFacade for InProcessComponent<Injection> {
-    fn subscribe_injection_request(&self, handler: Callback<InjectionRequestMessage>) -> Fallible<()> {
-        subscribe!(self, InjectionPerform { request }, handler)
-    }
+    use super::*;
 
-    fn subscribe_injection_status_request(&self, handler: Callback0) -> Fallible<()> {
-        subscribe!(self, InjectionStatusRequest, handler)
-    }
+    static NEXT_BUS: AtomicUsize = AtomicUsize::new(0);
 
-    fn subscribe_injection_reset_request(&self, handler: Callback<InjectionResetRequestMessage>) -> Fallible<()> {
-        subscribe!(self, InjectionResetPerform { request }, handler)
+    // each test gets its own bus as they run concurrently
+    fn create_handlers() -> (Rc<InProcessHermesProtocolHandler>, Rc<InProcessHermesProtocolHandler>) {
+        let name = format!("test bus {}", NEXT_BUS.fetch_add(1, Ordering::SeqCst));
+        (
+            Rc::new(InProcessHermesProtocolHandler::new_on_named_bus(&name)),
+            Rc::new(InProcessHermesProtocolHandler::new_on_named_bus(&name)),
+        )
     }
 
-    fn publish_injection_status(&self, status: InjectionStatusMessage) -> Fallible<()> {
-        self.publish(InjectionStatus { status })
-    }
+    hermes_test_suite::test_suite!();
 
-    fn publish_injection_complete(&self, message: InjectionCompleteMessage) -> Fallible<()> {
-        self.publish(InjectionComplete { message })
-    }
+    #[test]
+    fn named_bus_is_released_with_its_handlers() {
+        let handler = InProcessHermesProtocolHandler::new_on_named_bus("released bus");
+        let bus = Arc::downgrade(&handler.bus);
+        drop(handler);
+        assert!(bus.upgrade().is_none());
 
-    fn publish_injection_reset_complete(&self, message: InjectionResetCompleteMessage) -> Fallible<()> {
-        self.publish(InjectionResetComplete { message })
+        let other = InProcessHermesProtocolHandler::new_on_named_bus("released bus");
+        assert!(!Arc::ptr_eq(&other.bus, &InProcessHermesProtocolHandler::new().bus));
+        assert!(Arc::ptr_eq(
+            &other.bus,
+            &InProcessHermesProtocolHandler::new_on_named_bus("released bus").bus
+        ));
     }
 }
 
-#[cfg(test)]
-mod tests {
+#[cfg(all(test, feature = "serialization-faithful"))]
+mod serialization_faithful_tests {
     use std::rc::Rc;
 
     use super::*;
 
     fn create_handlers() -> (Rc<InProcessHermesProtocolHandler>, Rc<InProcessHermesProtocolHandler>) {
-        let handler = Rc::new(InProcessHermesProtocolHandler::new());
+        let handler = Rc::new(InProcessHermesProtocolHandler::new_serialization_faithful());
         (Rc::clone(&handler), handler)
     }
 
     hermes_test_suite::test_suite!();
+
+    #[test]
+    fn intent_name_that_can_not_be_a_topic_is_rejected() {
+        use hermes::hermes_utils::Example;
+
+        let handler = InProcessHermesProtocolHandler::new_serialization_faithful();
+        let mut intent = IntentMessage::minimal_example();
+        intent.intent.intent_name = "lights/on".into();
+        assert!(handler.dialogue_backend().publish_intent(intent).is_err());
+    }
 }
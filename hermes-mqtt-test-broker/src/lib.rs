@@ -0,0 +1,459 @@
+//! A minimal MQTT 3.1.1 broker running in process, so that the tests of the MQTT implementation of
+//! hermes do not depend on an external `mosquitto` binary.
+//!
+//! Only what hermes needs is supported: clean sessions, no will, no authentication and no
+//! retained messages. Messages published with QoS 1 or 2 are acknowledged, and every message is
+//! forwarded to the subscribers with QoS 0.
+
+use std::collections::HashMap;
+use std::io::{self, BufReader, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use log::*;
+
+const CONNECT: u8 = 1;
+const CONNACK: u8 = 2;
+const PUBLISH: u8 = 3;
+const PUBACK: u8 = 4;
+const PUBREC: u8 = 5;
+const PUBREL: u8 = 6;
+const PUBCOMP: u8 = 7;
+const SUBSCRIBE: u8 = 8;
+const SUBACK: u8 = 9;
+const UNSUBSCRIBE: u8 = 10;
+const UNSUBACK: u8 = 11;
+const PINGREQ: u8 = 12;
+const PINGRESP: u8 = 13;
+const DISCONNECT: u8 = 14;
+
+/// A running broker, it is stopped and all its clients are disconnected when dropped
+pub struct Broker {
+    address: SocketAddr,
+    state: Arc<State>,
+}
+
+struct State {
+    running: AtomicBool,
+    next_client_id: AtomicUsize,
+    clients: Mutex<HashMap<usize, Client>>,
+}
+
+struct Client {
+    // every write to a client goes through this lock so that packets are never interleaved
+    writer: Arc<Mutex<TcpStream>>,
+    subscriptions: Vec<String>,
+}
+
+impl Broker {
+    /// Start a broker listening on a port of the loopback interface chosen by the OS
+    pub fn start() -> io::Result<Broker> {
+        Self::bind("127.0.0.1:0")
+    }
+
+    /// Start a broker listening on the given address
+    pub fn bind<A: ToSocketAddrs>(address: A) -> io::Result<Broker> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        let state = Arc::new(State {
+            running: AtomicBool::new(true),
+            next_client_id: AtomicUsize::new(0),
+            clients: Mutex::new(HashMap::new()),
+        });
+
+        let accept_state = Arc::clone(&state);
+        thread::Builder::new()
+            .name(format!("mqtt-broker-{}", address.port()))
+            .spawn(move || accept(&listener, &accept_state))?;
+
+        debug!("MQTT test broker listening on {}", address);
+        Ok(Broker { address, state })
+    }
+
+    /// The address the broker listens on, in a form that can be given to an MQTT client
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+}
+
+impl Drop for Broker {
+    fn drop(&mut self) {
+        self.state.running.store(false, Ordering::SeqCst);
+        // wake up the accept loop so that it notices the broker is stopped
+        let _ = TcpStream::connect(self.address);
+        for client in self.state.clients.lock().unwrap().values() {
+            let _ = client.writer.lock().unwrap().shutdown(Shutdown::Both);
+        }
+    }
+}
+
+fn accept(listener: &TcpListener, state: &Arc<State>) {
+    for stream in listener.incoming() {
+        if !state.running.load(Ordering::SeqCst) {
+            break;
+        }
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                warn!("MQTT test broker could not accept a connection: {}", e);
+                continue;
+            }
+        };
+        let id = state.next_client_id.fetch_add(1, Ordering::SeqCst);
+        let state = Arc::clone(state);
+        let spawned = thread::Builder::new()
+            .name(format!("mqtt-broker-client-{}", id))
+            .spawn(move || {
+                if let Err(e) = serve(&state, id, stream) {
+                    debug!("MQTT test broker client {} disconnected: {}", id, e);
+                }
+                state.clients.lock().unwrap().remove(&id);
+            });
+        if let Err(e) = spawned {
+            warn!("MQTT test broker could not spawn a client thread: {}", e);
+        }
+    }
+}
+
+fn serve(state: &State, id: usize, stream: TcpStream) -> io::Result<()> {
+    stream.set_nodelay(true)?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let connect = Packet::read(&mut reader)?;
+    if connect.kind() != CONNECT {
+        return Err(protocol_error("first packet is not CONNECT"));
+    }
+    let writer = Arc::new(Mutex::new(stream));
+    // session present = 0, connection accepted
+    Packet::new(CONNACK << 4, vec![0x00, 0x00]).write_to(&writer)?;
+    state.clients.lock().unwrap().insert(
+        id,
+        Client {
+            writer: Arc::clone(&writer),
+            subscriptions: vec![],
+        },
+    );
+
+    loop {
+        let packet = Packet::read(&mut reader)?;
+        match packet.kind() {
+            PUBLISH => {
+                let qos = (packet.header >> 1) & 0x03;
+                let mut body = &packet.body[..];
+                let topic = read_string(&mut body)?;
+                if qos > 0 {
+                    let packet_id = read_u16(&mut body)?;
+                    let ack = if qos == 1 { PUBACK << 4 } else { PUBREC << 4 };
+                    Packet::new(ack, packet_id.to_be_bytes().to_vec()).write_to(&writer)?;
+                }
+                trace!("MQTT test broker: client {} published on {}", id, topic);
+                forward(state, &topic, body);
+            }
+            PUBREL => {
+                let packet_id = read_u16(&mut &packet.body[..])?;
+                Packet::new(PUBCOMP << 4, packet_id.to_be_bytes().to_vec()).write_to(&writer)?;
+            }
+            SUBSCRIBE => {
+                let mut body = &packet.body[..];
+                let packet_id = read_u16(&mut body)?;
+                let mut filters = vec![];
+                while !body.is_empty() {
+                    filters.push(read_string(&mut body)?);
+                    // requested QoS, we only ever grant 0
+                    read_u8(&mut body)?;
+                }
+                let mut suback = packet_id.to_be_bytes().to_vec();
+                suback.extend(filters.iter().map(|_| 0x00));
+                debug!("MQTT test broker: client {} subscribed to {:?}", id, filters);
+                if let Some(client) = state.clients.lock().unwrap().get_mut(&id) {
+                    client.subscriptions.extend(filters);
+                }
+                Packet::new(SUBACK << 4, suback).write_to(&writer)?;
+            }
+            UNSUBSCRIBE => {
+                let mut body = &packet.body[..];
+                let packet_id = read_u16(&mut body)?;
+                let mut filters = vec![];
+                while !body.is_empty() {
+                    filters.push(read_string(&mut body)?);
+                }
+                if let Some(client) = state.clients.lock().unwrap().get_mut(&id) {
+                    client.subscriptions.retain(|it| !filters.contains(it));
+                }
+                Packet::new(UNSUBACK << 4, packet_id.to_be_bytes().to_vec()).write_to(&writer)?;
+            }
+            PINGREQ => Packet::new(PINGRESP << 4, vec![]).write_to(&writer)?,
+            DISCONNECT => return Ok(()),
+            // acknowledgements of the messages we forwarded, which are always sent with QoS 0
+            PUBACK | PUBREC | PUBCOMP => {}
+            kind => return Err(protocol_error(&format!("unexpected packet type {}", kind))),
+        }
+    }
+}
+
+fn forward(state: &State, topic: &str, payload: &[u8]) {
+    let mut body = Vec::with_capacity(2 + topic.len() + payload.len());
+    write_string(&mut body, topic);
+    body.extend_from_slice(payload);
+    let packet = Packet::new(PUBLISH << 4, body);
+
+    let subscribers: Vec<(usize, Arc<Mutex<TcpStream>>)> = state
+        .clients
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(_, client)| client.subscriptions.iter().any(|filter| topic_matches(filter, topic)))
+        .map(|(id, client)| (*id, Arc::clone(&client.writer)))
+        .collect();
+
+    for (id, writer) in subscribers {
+        if let Err(e) = packet.write_to(&writer) {
+            debug!("MQTT test broker could not forward {} to client {}: {}", topic, id, e);
+        }
+    }
+}
+
+/// Check whether `topic` matches the subscription `filter`, following the MQTT 3.1.1 rules for
+/// the `+` and `#` wildcards
+pub fn topic_matches(filter: &str, topic: &str) -> bool {
+    // wildcards at the first level never match topics reserved to the server
+    if topic.starts_with('$') && (filter.starts_with('+') || filter.starts_with('#')) {
+        return false;
+    }
+
+    let mut filter_levels = filter.split('/');
+    let mut topic_levels = topic.split('/');
+    loop {
+        match (filter_levels.next(), topic_levels.next()) {
+            (Some("#"), _) => return true,
+            (Some("+"), Some(_)) => {}
+            (Some(f), Some(t)) if f == t => {}
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
+
+struct Packet {
+    header: u8,
+    body: Vec<u8>,
+}
+
+impl Packet {
+    fn new(header: u8, body: Vec<u8>) -> Packet {
+        Packet { header, body }
+    }
+
+    fn kind(&self) -> u8 {
+        self.header >> 4
+    }
+
+    fn read<R: Read>(reader: &mut R) -> io::Result<Packet> {
+        let header = read_u8(reader)?;
+
+        let mut remaining_length = 0usize;
+        for i in 0..4 {
+            let byte = read_u8(reader)?;
+            remaining_length |= ((byte & 0x7F) as usize) << (7 * i);
+            if byte & 0x80 == 0 {
+                let mut body = vec![0; remaining_length];
+                reader.read_exact(&mut body)?;
+                return Ok(Packet { header, body });
+            }
+        }
+        Err(protocol_error("malformed remaining length"))
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let mut bytes = Vec::with_capacity(self.body.len() + 5);
+        bytes.push(self.header);
+        let mut remaining_length = self.body.len();
+        loop {
+            let mut byte = (remaining_length % 128) as u8;
+            remaining_length /= 128;
+            if remaining_length > 0 {
+                byte |= 0x80;
+            }
+            bytes.push(byte);
+            if remaining_length == 0 {
+                break;
+            }
+        }
+        bytes.extend_from_slice(&self.body);
+        writer.write_all(&bytes)?;
+        writer.flush()
+    }
+
+    fn write_to(&self, writer: &Mutex<TcpStream>) -> io::Result<()> {
+        self.write(&mut *writer.lock().unwrap())
+    }
+}
+
+fn read_u8<R: Read>(reader: &mut R) -> io::Result<u8> {
+    let mut byte = [0u8];
+    reader.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+fn read_u16<R: Read>(reader: &mut R) -> io::Result<u16> {
+    let mut bytes = [0u8; 2];
+    reader.read_exact(&mut bytes)?;
+    Ok(u16::from_be_bytes(bytes))
+}
+
+fn read_string<R: Read>(reader: &mut R) -> io::Result<String> {
+    let mut bytes = vec![0; read_u16(reader)? as usize];
+    reader.read_exact(&mut bytes)?;
+    String::from_utf8(bytes).map_err(|_| protocol_error("string is not valid UTF-8"))
+}
+
+fn write_string(buffer: &mut Vec<u8>, string: &str) {
+    buffer.extend_from_slice(&(string.len() as u16).to_be_bytes());
+    buffer.extend_from_slice(string.as_bytes());
+}
+
+fn protocol_error(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    struct TestClient {
+        stream: TcpStream,
+    }
+
+    impl TestClient {
+        fn connect(broker: &Broker) -> TestClient {
+            let mut stream = TcpStream::connect(broker.address()).unwrap();
+            stream.set_read_timeout(Some(Duration::from_secs(1))).unwrap();
+            let mut connect = vec![];
+            write_string(&mut connect, "MQTT");
+            // protocol level 4, clean session, keep alive 60s
+            connect.extend_from_slice(&[0x04, 0x02, 0x00, 0x3C]);
+            write_string(&mut connect, "test-client");
+            Packet::new(CONNECT << 4, connect).write(&mut stream).unwrap();
+            let connack = Packet::read(&mut stream).unwrap();
+            assert_eq!(connack.kind(), CONNACK);
+            assert_eq!(connack.body, vec![0x00, 0x00]);
+            TestClient { stream }
+        }
+
+        fn subscribe(&mut self, filter: &str) {
+            let mut body = vec![0x00, 0x01];
+            write_string(&mut body, filter);
+            body.push(0x00);
+            Packet::new(SUBSCRIBE << 4 | 0x02, body)
+                .write(&mut self.stream)
+                .unwrap();
+            let suback = Packet::read(&mut self.stream).unwrap();
+            assert_eq!(suback.kind(), SUBACK);
+            assert_eq!(suback.body, vec![0x00, 0x01, 0x00]);
+        }
+
+        fn publish(&mut self, topic: &str, payload: &[u8]) {
+            let mut body = vec![];
+            write_string(&mut body, topic);
+            body.extend_from_slice(payload);
+            Packet::new(PUBLISH << 4, body).write(&mut self.stream).unwrap();
+        }
+
+        fn receive(&mut self) -> io::Result<(String, Vec<u8>)> {
+            let packet = Packet::read(&mut self.stream)?;
+            assert_eq!(packet.kind(), PUBLISH);
+            let mut body = &packet.body[..];
+            let topic = read_string(&mut body)?;
+            Ok((topic, body.to_vec()))
+        }
+    }
+
+    #[test]
+    fn topic_matching() {
+        assert!(topic_matches("hermes/asr/textCaptured", "hermes/asr/textCaptured"));
+        assert!(!topic_matches(
+            "hermes/asr/textCaptured",
+            "hermes/asr/partialTextCaptured"
+        ));
+        assert!(topic_matches("hermes/intent/#", "hermes/intent/foo"));
+        assert!(topic_matches("hermes/intent/#", "hermes/intent/foo/bar"));
+        assert!(topic_matches("hermes/intent/#", "hermes/intent"));
+        assert!(topic_matches("#", "hermes/intent/foo"));
+        assert!(topic_matches(
+            "hermes/hotword/+/detected",
+            "hermes/hotword/default/detected"
+        ));
+        assert!(!topic_matches("hermes/hotword/+/detected", "hermes/hotword/detected"));
+        assert!(!topic_matches("hermes/hotword/+", "hermes/hotword/default/detected"));
+        assert!(!topic_matches("hermes/+", "hermes"));
+        assert!(!topic_matches("#", "$SYS/uptime"));
+        assert!(topic_matches("$SYS/#", "$SYS/uptime"));
+    }
+
+    #[test]
+    fn remaining_length_round_trip() {
+        for &size in &[0, 1, 127, 128, 16_383, 16_384, 2_097_152] {
+            let mut bytes = vec![];
+            Packet::new(PUBLISH << 4, vec![0x42; size]).write(&mut bytes).unwrap();
+            let packet = Packet::read(&mut &bytes[..]).unwrap();
+            assert_eq!(packet.body.len(), size);
+        }
+    }
+
+    #[test]
+    fn forwards_to_matching_subscribers_only() {
+        let broker = Broker::start().unwrap();
+        let mut subscriber = TestClient::connect(&broker);
+        let mut other_subscriber = TestClient::connect(&broker);
+        let mut publisher = TestClient::connect(&broker);
+
+        subscriber.subscribe("hermes/intent/#");
+        other_subscriber.subscribe("hermes/tts/say");
+
+        publisher.publish("hermes/intent/foo", b"hello");
+        assert_eq!(
+            subscriber.receive().unwrap(),
+            ("hermes/intent/foo".to_string(), b"hello".to_vec())
+        );
+        assert!(other_subscriber.receive().is_err());
+    }
+
+    #[test]
+    fn acknowledges_qos1_publishes() {
+        let broker = Broker::start().unwrap();
+        let mut publisher = TestClient::connect(&broker);
+
+        let mut body = vec![];
+        write_string(&mut body, "hermes/tts/say");
+        body.extend_from_slice(&[0x00, 0x2A]);
+        Packet::new(PUBLISH << 4 | 0x02, body)
+            .write(&mut publisher.stream)
+            .unwrap();
+
+        let puback = Packet::read(&mut publisher.stream).unwrap();
+        assert_eq!(puback.kind(), PUBACK);
+        assert_eq!(puback.body, vec![0x00, 0x2A]);
+    }
+
+    #[test]
+    fn answers_pings() {
+        let broker = Broker::start().unwrap();
+        let mut client = TestClient::connect(&broker);
+
+        Packet::new(PINGREQ << 4, vec![]).write(&mut client.stream).unwrap();
+        assert_eq!(Packet::read(&mut client.stream).unwrap().kind(), PINGRESP);
+    }
+
+    #[test]
+    fn disconnects_clients_when_dropped() {
+        let broker = Broker::start().unwrap();
+        let mut client = TestClient::connect(&broker);
+        drop(broker);
+
+        let mut buffer = [0u8; 1];
+        assert_eq!(client.stream.read(&mut buffer).unwrap_or(0), 0);
+    }
+}
@@ -0,0 +1,19 @@
+//! Runs the test broker until it is killed, so that the tests of the bindings do not depend on an
+//! external `mosquitto` binary either. It listens on the address given as first argument, or on
+//! `127.0.0.1:1883`.
+
+use std::env;
+use std::io;
+use std::thread;
+
+use hermes_mqtt_test_broker::Broker;
+
+fn main() -> io::Result<()> {
+    let address = env::args().nth(1).unwrap_or_else(|| "127.0.0.1:1883".into());
+    let broker = Broker::bind(address)?;
+    println!("MQTT test broker listening on {}", broker.address());
+
+    loop {
+        thread::park();
+    }
+}
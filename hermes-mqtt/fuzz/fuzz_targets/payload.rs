@@ -0,0 +1,104 @@
+#![no_main]
+use hermes::*;
+use hermes_mqtt::codec::*;
+use hermes_mqtt::topics::*;
+use libfuzzer_sys::fuzz_target;
+
+macro_rules! json {
+    ($t:ty, $payload:expr) => {{
+        let _ = decode_json_in_envelope::<$t>($payload);
+    }};
+}
+
+// the topic name is given before the first nul byte, the payload after it, decoding the payload as
+// the message its topic carries must never panic
+fuzz_target!(|data: &[u8]| {
+    use hermes_mqtt::topics::HermesTopic::*;
+
+    let (topic_name, payload) = match data.iter().position(|it| *it == 0) {
+        Some(index) => (&data[..index], &data[index + 1..]),
+        None => (data, &[][..]),
+    };
+
+    let topic = match std::str::from_utf8(topic_name).ok().and_then(HermesTopic::from_path) {
+        Some(topic) => topic,
+        None => return,
+    };
+
+    match &topic {
+        Feedback(FeedbackCommand::Sound(_)) => json!(SiteMessage, payload),
+        DialogueManager(_, command) => match command {
+            DialogueManagerCommand::ToggleOn | DialogueManagerCommand::ToggleOff => (),
+            DialogueManagerCommand::StartSession => json!(StartSessionMessage, payload),
+            DialogueManagerCommand::ContinueSession => json!(ContinueSessionMessage, payload),
+            DialogueManagerCommand::EndSession => json!(EndSessionMessage, payload),
+            DialogueManagerCommand::SessionQueued => json!(SessionQueuedMessage, payload),
+            DialogueManagerCommand::SessionStarted => json!(SessionStartedMessage, payload),
+            DialogueManagerCommand::SessionEnded => json!(SessionEndedMessage, payload),
+            DialogueManagerCommand::IntentNotRecognized => json!(IntentNotRecognizedMessage, payload),
+            DialogueManagerCommand::Configure => json!(DialogueConfigureMessage, payload),
+        },
+        VoiceActivity(_, command) => match command {
+            VoiceActivityCommand::VadUp => json!(VadUpMessage, payload),
+            VoiceActivityCommand::VadDown => json!(VadDownMessage, payload),
+        },
+        Hotword(_, command) => match command {
+            HotwordCommand::ToggleOn | HotwordCommand::ToggleOff => json!(SiteMessage, payload),
+            HotwordCommand::Detected => json!(HotwordDetectedMessage, payload),
+        },
+        Asr(_, command) => match command {
+            AsrCommand::ToggleOn | AsrCommand::ToggleOff => (),
+            AsrCommand::StartListening => json!(AsrStartListeningMessage, payload),
+            AsrCommand::StopListening => json!(SiteMessage, payload),
+            AsrCommand::TextCaptured | AsrCommand::PartialTextCaptured => json!(TextCapturedMessage, payload),
+            AsrCommand::Reload => json!(RequestComponentReloadMessage, payload),
+        },
+        Tts(_, command) => match command {
+            TtsCommand::Say => json!(SayMessage, payload),
+            TtsCommand::SayFinished => json!(SayFinishedMessage, payload),
+            TtsCommand::RegisterSound(_) => {
+                let _ = decode_register_sound(&topic, payload);
+            }
+        },
+        Nlu(command) => match command {
+            NluCommand::Query => json!(NluQueryMessage, payload),
+            NluCommand::PartialQuery => json!(NluSlotQueryMessage, payload),
+            NluCommand::SlotParsed => json!(NluSlotMessage, payload),
+            NluCommand::IntentParsed => json!(NluIntentMessage, payload),
+            NluCommand::IntentNotRecognized => json!(NluIntentNotRecognizedMessage, payload),
+            NluCommand::Reload => json!(RequestComponentReloadMessage, payload),
+        },
+        Intent(_) => json!(IntentMessage, payload),
+        AudioServer(_, command) => match command {
+            AudioServerCommand::AudioFrame | AudioServerCommand::ReplayResponse => {
+                let _ = decode_audio_frame(&topic, payload);
+            }
+            AudioServerCommand::ReplayRequest => json!(ReplayRequestMessage, payload),
+            AudioServerCommand::PlayBytes(_) => {
+                let _ = decode_play_bytes(&topic, payload);
+            }
+            AudioServerCommand::StreamBytes { .. } => {
+                let _ = decode_stream_bytes(&topic, payload);
+            }
+            AudioServerCommand::PlayFinished => json!(PlayFinishedMessage, payload),
+            AudioServerCommand::StreamFinished => json!(StreamFinishedMessage, payload),
+            AudioServerCommand::ToggleOn | AudioServerCommand::ToggleOff => json!(SiteMessage, payload),
+        },
+        Injection(command) => match command {
+            InjectionCommand::Perform => json!(InjectionRequestMessage, payload),
+            InjectionCommand::Status => json!(InjectionStatusMessage, payload),
+            InjectionCommand::StatusRequest => (),
+            InjectionCommand::Complete => json!(InjectionCompleteMessage, payload),
+            InjectionCommand::ResetRequest => json!(InjectionResetRequestMessage, payload),
+            InjectionCommand::ResetComplete => json!(InjectionResetCompleteMessage, payload),
+        },
+        Component(site_id, _, command) => match (site_id, command) {
+            (_, ComponentCommand::VersionRequest) => (),
+            (_, ComponentCommand::Version) => json!(VersionMessage, payload),
+            (None, ComponentCommand::Error) => json!(ErrorMessage, payload),
+            (Some(_), ComponentCommand::Error) => json!(SiteErrorMessage, payload),
+            (None, ComponentCommand::Loaded) => json!(ComponentLoadedMessage, payload),
+            (Some(_), ComponentCommand::Loaded) => json!(ComponentLoadedOnSiteMessage, payload),
+        },
+    }
+});
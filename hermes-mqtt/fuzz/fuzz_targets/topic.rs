@@ -0,0 +1,12 @@
+#![no_main]
+use hermes_mqtt::topics::*;
+use libfuzzer_sys::fuzz_target;
+
+// any topic name read must be written back the same way
+fuzz_target!(|data: &[u8]| {
+    if let Ok(path) = std::str::from_utf8(data) {
+        if let Some(topic) = HermesTopic::from_path(path) {
+            assert_eq!(topic.as_path(), path);
+        }
+    }
+});
@@ -0,0 +1,258 @@
+//! How hermes messages are encoded in MQTT publications.
+//!
+//! Most messages are serialized as json on a fixed topic. The binary ones (audio, sounds) are sent
+//! raw to avoid base64, their other fields are then carried by the topic.
+//!
+//! The `Envelope` of a json message is its `envelope` field. MQTT 3.1.1 has no message properties
+//! to carry it, so the binary messages and the ones without payload have none.
+
+use failure::{Fail, Fallible};
+use hermes::envelope::{take_envelope, to_json_value};
+use hermes::*;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+use crate::topics::*;
+
+/// Whether the messages published on `topic` carry raw bytes rather than json
+pub fn is_binary(topic: &HermesTopic) -> bool {
+    match *topic {
+        HermesTopic::AudioServer(_, AudioServerCommand::AudioFrame)
+        | HermesTopic::AudioServer(_, AudioServerCommand::ReplayResponse)
+        | HermesTopic::AudioServer(_, AudioServerCommand::PlayBytes(_))
+        | HermesTopic::AudioServer(_, AudioServerCommand::StreamBytes { .. })
+        | HermesTopic::Tts(_, TtsCommand::RegisterSound(_)) => true,
+        _ => false,
+    }
+}
+
+/// Why a message received from MQTT could not be decoded
+#[derive(Debug, Fail)]
+pub enum DecodeError {
+    #[fail(display = "could not parse topic '{}'", _0)]
+    UnknownTopic(String),
+    #[fail(display = "topic {:?} does not carry a {}", topic, message)]
+    UnexpectedTopic { topic: HermesTopic, message: &'static str },
+    #[fail(display = "invalid chunk number '{}' in topic", _0)]
+    InvalidChunkNumber(String),
+    #[fail(display = "invalid json payload: {}", _0)]
+    Json(#[cause] serde_json::Error),
+}
+
+pub fn encode_json<P: Serialize>(payload: &P) -> Fallible<Vec<u8>> {
+    Ok(serde_json::to_vec(payload)?)
+}
+
+pub fn decode_json<P: DeserializeOwned>(payload: &[u8]) -> Result<P, DecodeError> {
+    serde_json::from_slice(payload).map_err(DecodeError::Json)
+}
+
+/// Encode `payload` with `envelope` as its `envelope` field, that the components not knowing about
+/// envelopes ignore
+pub fn encode_json_in_envelope<P: Serialize>(payload: &P, envelope: &Envelope) -> Fallible<Vec<u8>> {
+    Ok(serde_json::to_vec(&to_json_value(payload, Some(envelope))?)?)
+}
+
+/// Decode a json payload along with its envelope, `None` when it has none or an invalid one. The
+/// payload is only parsed once.
+pub fn decode_json_in_envelope<P: DeserializeOwned>(payload: &[u8]) -> Result<(P, Option<Envelope>), DecodeError> {
+    let mut value: Value = serde_json::from_slice(payload).map_err(DecodeError::Json)?;
+    let envelope = take_envelope(&mut value);
+    Ok((serde_json::from_value(value).map_err(DecodeError::Json)?, envelope))
+}
+
+/// Encode an audio frame, `command` is either `AudioFrame` or `ReplayResponse`
+pub fn encode_audio_frame(frame: AudioFrameMessage, command: AudioServerCommand) -> (HermesTopic, Vec<u8>) {
+    (HermesTopic::AudioServer(Some(frame.site_id), command), frame.wav_frame)
+}
+
+pub fn decode_audio_frame(topic: &HermesTopic, payload: &[u8]) -> Result<AudioFrameMessage, DecodeError> {
+    match *topic {
+        HermesTopic::AudioServer(Some(ref site_id), AudioServerCommand::AudioFrame)
+        | HermesTopic::AudioServer(Some(ref site_id), AudioServerCommand::ReplayResponse) => Ok(AudioFrameMessage {
+            site_id: site_id.to_owned(),
+            wav_frame: payload.into(),
+        }),
+        _ => Err(unexpected_topic(topic, "AudioFrameMessage")),
+    }
+}
+
+pub fn encode_play_bytes(bytes: PlayBytesMessage) -> (HermesTopic, Vec<u8>) {
+    (
+        HermesTopic::AudioServer(Some(bytes.site_id), AudioServerCommand::PlayBytes(bytes.id)),
+        bytes.wav_bytes,
+    )
+}
+
+pub fn decode_play_bytes(topic: &HermesTopic, payload: &[u8]) -> Result<PlayBytesMessage, DecodeError> {
+    if let HermesTopic::AudioServer(Some(ref site_id), AudioServerCommand::PlayBytes(ref request_id)) = *topic {
+        Ok(PlayBytesMessage {
+            site_id: site_id.to_owned(),
+            id: request_id.to_owned(),
+            wav_bytes: payload.into(),
+        })
+    } else {
+        Err(unexpected_topic(topic, "PlayBytesMessage"))
+    }
+}
+
+pub fn encode_stream_bytes(bytes: StreamBytesMessage) -> (HermesTopic, Vec<u8>) {
+    (
+        HermesTopic::AudioServer(
+            Some(bytes.site_id),
+            AudioServerCommand::StreamBytes {
+                stream_id: bytes.stream_id,
+                chunk_number: bytes.chunk_number.to_string(),
+                is_last_chunk: if bytes.is_last_chunk { "1" } else { "0" }.to_string(),
+            },
+        ),
+        bytes.bytes,
+    )
+}
+
+pub fn decode_stream_bytes(topic: &HermesTopic, payload: &[u8]) -> Result<StreamBytesMessage, DecodeError> {
+    if let HermesTopic::AudioServer(
+        Some(ref site_id),
+        AudioServerCommand::StreamBytes {
+            ref stream_id,
+            ref chunk_number,
+            ref is_last_chunk,
+        },
+    ) = *topic
+    {
+        Ok(StreamBytesMessage {
+            site_id: site_id.to_owned(),
+            stream_id: stream_id.to_owned(),
+            chunk_number: chunk_number
+                .parse()
+                .map_err(|_| DecodeError::InvalidChunkNumber(chunk_number.to_owned()))?,
+            is_last_chunk: is_last_chunk == "1",
+            bytes: payload.into(),
+        })
+    } else {
+        Err(unexpected_topic(topic, "StreamBytesMessage"))
+    }
+}
+
+pub fn encode_register_sound(sound: RegisterSoundMessage) -> (HermesTopic, Vec<u8>) {
+    (
+        HermesTopic::Tts(None, TtsCommand::RegisterSound(sound.sound_id)),
+        sound.wav_sound,
+    )
+}
+
+pub fn decode_register_sound(topic: &HermesTopic, payload: &[u8]) -> Result<RegisterSoundMessage, DecodeError> {
+    if let HermesTopic::Tts(_, TtsCommand::RegisterSound(ref sound_id)) = *topic {
+        Ok(RegisterSoundMessage {
+            sound_id: sound_id.to_owned(),
+            wav_sound: payload.into(),
+        })
+    } else {
+        Err(unexpected_topic(topic, "RegisterSoundMessage"))
+    }
+}
+
+fn unexpected_topic(topic: &HermesTopic, message: &'static str) -> DecodeError {
+    DecodeError::UnexpectedTopic {
+        topic: topic.clone(),
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hermes::hermes_utils::Example;
+
+    #[test]
+    fn binary_messages_round_trip() {
+        let frame = AudioFrameMessage::full_example();
+        let (topic, bytes) = encode_audio_frame(frame.clone(), AudioServerCommand::ReplayResponse);
+        assert_eq!(decode_audio_frame(&topic, &bytes).unwrap(), frame);
+
+        let play_bytes = PlayBytesMessage::full_example();
+        let (topic, bytes) = encode_play_bytes(play_bytes.clone());
+        assert_eq!(decode_play_bytes(&topic, &bytes).unwrap(), play_bytes);
+
+        for is_last_chunk in &[true, false] {
+            let stream_bytes = StreamBytesMessage {
+                is_last_chunk: *is_last_chunk,
+                ..StreamBytesMessage::full_example()
+            };
+            let (topic, bytes) = encode_stream_bytes(stream_bytes.clone());
+            assert_eq!(decode_stream_bytes(&topic, &bytes).unwrap(), stream_bytes);
+        }
+
+        let sound = RegisterSoundMessage::full_example();
+        let (topic, bytes) = encode_register_sound(sound.clone());
+        assert_eq!(decode_register_sound(&topic, &bytes).unwrap(), sound);
+    }
+
+    #[test]
+    fn binary_topics_are_detected() {
+        assert!(is_binary(&encode_play_bytes(PlayBytesMessage::full_example()).0));
+        assert!(is_binary(
+            &encode_register_sound(RegisterSoundMessage::full_example()).0
+        ));
+        assert!(!is_binary(&HermesTopic::Tts(None, TtsCommand::Say)));
+        assert!(!is_binary(&HermesTopic::AudioServer(
+            Some("default".into()),
+            AudioServerCommand::PlayFinished
+        )));
+    }
+
+    #[test]
+    fn binary_messages_reject_unexpected_topics() {
+        let topic = HermesTopic::Tts(None, TtsCommand::Say);
+        assert!(decode_audio_frame(&topic, &[]).is_err());
+        assert!(decode_play_bytes(&topic, &[]).is_err());
+        assert!(decode_stream_bytes(&topic, &[]).is_err());
+
+        let topic = HermesTopic::AudioServer(
+            Some("default".into()),
+            AudioServerCommand::StreamBytes {
+                stream_id: "stream".into(),
+                chunk_number: "not a number".into(),
+                is_last_chunk: "0".into(),
+            },
+        );
+        match decode_stream_bytes(&topic, &[]) {
+            Err(DecodeError::InvalidChunkNumber(chunk_number)) => assert_eq!(chunk_number, "not a number"),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn envelopes_are_optional_fields_of_the_json_payloads() {
+        let say = SayMessage::full_example();
+        let envelope = Envelope::new(MessageSource {
+            component: HermesComponent::Dialogue,
+            site_id: None,
+        });
+
+        let payload = encode_json_in_envelope(&say, &envelope).unwrap();
+        assert_eq!(decode_json::<SayMessage>(&payload).unwrap(), say);
+        let (decoded, decoded_envelope) = decode_json_in_envelope::<SayMessage>(&payload).unwrap();
+        assert_eq!(decoded, say);
+        assert_eq!(decoded_envelope, Some(envelope));
+
+        let payload = encode_json(&say).unwrap();
+        assert_eq!(
+            decode_json_in_envelope::<SayMessage>(&payload).unwrap(),
+            (say.clone(), None)
+        );
+        let invalid_envelope = br#"{"text": "hello", "siteId": "kitchen", "envelope": {"messageId": 3}}"#;
+        let (decoded, decoded_envelope) = decode_json_in_envelope::<SayMessage>(invalid_envelope).unwrap();
+        assert_eq!(decoded.text, "hello");
+        assert_eq!(decoded_envelope, None);
+        assert!(decode_json_in_envelope::<SayMessage>(b"{not json").is_err());
+    }
+
+    #[test]
+    fn garbage_from_the_network_is_rejected() {
+        let topic = HermesTopic::from_path("hermes/audioServer/x/playBytesStreaming/a/notanumber/1").unwrap();
+        assert!(decode_stream_bytes(&topic, b"bytes").is_err());
+        assert!(decode_json::<IntentMessage>(b"{not json").is_err());
+        assert!(decode_json::<IntentMessage>(&[0xff, 0xfe]).is_err());
+    }
+}
@@ -1,17 +1,22 @@
-mod topics;
+pub mod codec;
+pub mod topics;
+pub mod transport;
 
+use std::collections::HashMap;
 use std::string::ToString;
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, RwLock, Weak};
 
 use failure::{format_err, Fallible, ResultExt, SyncFailure};
 use hermes::*;
 use lazy_static::lazy_static;
 use log::*;
 
+use crate::codec::DecodeError;
 use crate::topics::*;
+use crate::transport::MqttTransport;
+pub use crate::transport::Transport;
 
-use rumqtt::PublishBuilder;
 pub use rumqtt::{MqttOptions, TlsOptions};
 
 lazy_static! {
@@ -31,142 +36,265 @@ pub fn get_mqtt_id() -> String {
     )
 }
 
+/// A function called with the name of the topic and the error when a received message can't be
+/// decoded
+pub type DecodeErrorHandler = Box<dyn Fn(&str, &DecodeError) -> () + Send + Sync>;
+
+#[derive(Default)]
+struct DecodeErrors {
+    handler: RwLock<Option<DecodeErrorHandler>>,
+}
+
+impl DecodeErrors {
+    fn report(&self, topic_name: &str, error: DecodeError) {
+        warn!("Error while decoding object on topic {:?}: {}", topic_name, error);
+        match self.handler.read() {
+            Ok(handler) => {
+                if let Some(ref handler) = *handler {
+                    handler(topic_name, &error)
+                }
+            }
+            Err(_) => error!("could not report decoding error, poisoned lock"),
+        }
+    }
+}
+
+/// The callbacks subscribed to a topic filter
+type FilterCallbacks = Mutex<Vec<(usize, Arc<dyn Fn(&str, &[u8]) -> () + Send + Sync>)>>;
+
+/// The callbacks of the subscriptions on each topic filter. The transport is subscribed to a
+/// filter when its first callback is added and unsubscribed once they are all cancelled.
+#[derive(Default)]
+struct TopicCallbacks {
+    next_id: usize,
+    filters: HashMap<String, Arc<FilterCallbacks>>,
+}
+
 struct MqttHandler {
-    mqtt_client: rumqtt::MqttClient,
+    transport: Arc<dyn Transport>,
+    callbacks: Arc<Mutex<TopicCallbacks>>,
+    decode_errors: Arc<DecodeErrors>,
+    layout: TopicLayout,
+    /// The source of the envelopes of the published messages, they have none without it
+    envelope_source: Option<MessageSource>,
 }
 
 impl MqttHandler {
     pub fn publish(&self, topic: &HermesTopic) -> Fallible<()> {
-        let topic = &*topic.as_path();
+        let topic = &*self.path(topic)?;
         debug!("Publishing on MQTT topic '{}'", topic);
-        self.mqtt_client
-            .publish(topic)
-            .and_then(PublishBuilder::send)
-            .map_err(SyncFailure::new)?;
-        Ok(())
+        self.transport.publish(topic, vec![])
     }
 
     pub fn publish_payload<P: serde::Serialize>(&self, topic: &HermesTopic, payload: P) -> Fallible<()> {
-        serde_json::to_vec(&payload).map(|p| {
-            let topic = &*topic.as_path();
-            debug!(
-                "Publishing on MQTT topic '{}', payload: {}",
-                topic,
-                if p.len() < 2048 {
-                    String::from_utf8_lossy(&p).to_string()
-                } else {
-                    format!("size = {}, start = {}", p.len(), String::from_utf8_lossy(&p[0..128]))
-                }
-            );
-            trace!("Payload: {}", String::from_utf8_lossy(&p));
-            self.mqtt_client
-                .publish(topic)
-                .map(|m| m.payload(p))
-                .and_then(PublishBuilder::send)
-                .map_err(SyncFailure::new)
-        })??;
-        Ok(())
+        let p = match self.envelope_source {
+            Some(ref source) => codec::encode_json_in_envelope(&payload, &Envelope::new(source.clone()))?,
+            None => codec::encode_json(&payload)?,
+        };
+        let topic = &*self.path(topic)?;
+        debug!(
+            "Publishing on MQTT topic '{}', payload: {}",
+            topic,
+            if p.len() < 2048 {
+                String::from_utf8_lossy(&p).to_string()
+            } else {
+                format!("size = {}, start = {}", p.len(), String::from_utf8_lossy(&p[0..128]))
+            }
+        );
+        trace!("Payload: {}", String::from_utf8_lossy(&p));
+        self.transport.publish(topic, p)
     }
 
     pub fn publish_binary_payload(&self, topic: &HermesTopic, payload: Vec<u8>) -> Fallible<()> {
-        let topic = &*topic.as_path();
+        let topic = &*self.path(topic)?;
         debug!(
             "Publishing as binary on MQTT topic '{}', with size {}",
             topic,
             payload.len()
         );
-        self.mqtt_client
-            .publish(topic)
-            .map(|m| m.payload(payload))
-            .and_then(PublishBuilder::send)
-            .map_err(SyncFailure::new)?;
-
-        Ok(())
+        self.transport.publish(topic, payload)
     }
 
-    pub fn subscribe<F>(&self, topic: &HermesTopic, handler: F) -> Fallible<()>
+    pub fn subscribe<F>(&self, topic: &HermesTopic, subscription: Subscription, handler: F) -> Fallible<()>
     where
         F: Fn() -> () + Send + Sync + 'static,
     {
         let log_level = Self::log_level(topic);
-        self.inner_subscribe(topic, move |m| {
-            log!(log_level, "Received a message on MQTT topic '{:?}'", m.topic_name);
+        self.inner_subscribe(topic, subscription, move |topic_name, _| {
+            log!(log_level, "Received a message on MQTT topic '{:?}'", topic_name);
             handler()
         })
     }
 
-    pub fn subscribe_payload<F, P>(&self, topic: &HermesTopic, handler: F) -> Fallible<()>
+    /// Subscribe to a json message, `handler` is also given its envelope
+    pub fn subscribe_payload<F, P>(&self, topic: &HermesTopic, subscription: Subscription, handler: F) -> Fallible<()>
     where
-        F: Fn(&P) -> () + Send + Sync + 'static,
+        F: Fn(&P, Option<&Envelope>) -> () + Send + Sync + 'static,
         P: serde::de::DeserializeOwned,
     {
         let log_level = Self::log_level(topic);
-        self.inner_subscribe(topic, move |m| {
+        let decode_errors = Arc::clone(&self.decode_errors);
+        self.inner_subscribe(topic, subscription, move |topic_name, payload| {
             log!(
                 log_level,
                 "Received a message on MQTT topic '{:?}', payload: {}",
-                m.topic_name,
-                if m.payload.len() < 2048 {
-                    String::from_utf8_lossy(&m.payload).to_string()
+                topic_name,
+                if payload.len() < 2048 {
+                    String::from_utf8_lossy(payload).to_string()
                 } else {
                     format!(
                         "size = {}, start = {}",
-                        m.payload.len(),
-                        String::from_utf8_lossy(&m.payload[0..128])
+                        payload.len(),
+                        String::from_utf8_lossy(&payload[0..128])
                     )
                 }
             );
-            trace!("Payload: {}", String::from_utf8_lossy(&m.payload));
-            let r = serde_json::from_slice(m.payload.as_slice());
-            match r {
-                Ok(p) => handler(&p),
-                Err(e) => warn!("Error while decoding object on topic {:?}: {}", m.topic_name, e),
+            trace!("Payload: {}", String::from_utf8_lossy(payload));
+            match codec::decode_json_in_envelope(payload) {
+                Ok((p, envelope)) => handler(&p, envelope.as_ref()),
+                Err(e) => decode_errors.report(topic_name, e),
             }
         })
     }
 
-    pub fn subscribe_binary_payload<F>(&self, topic: &HermesTopic, handler: F) -> Fallible<()>
+    /// Subscribe to a message carried partly by its topic, `decode` reads it from the parsed topic
+    /// and the payload
+    pub fn subscribe_binary_payload<F, D, P>(
+        &self,
+        topic: &HermesTopic,
+        subscription: Subscription,
+        decode: D,
+        handler: F,
+    ) -> Fallible<()>
     where
-        F: Fn(&HermesTopic, &[u8]) -> () + Send + Sync + 'static,
+        F: Fn(&P) -> () + Send + Sync + 'static,
+        D: Fn(&HermesTopic, &[u8]) -> Result<P, DecodeError> + Send + Sync + 'static,
     {
         let log_level = Self::log_level(topic);
-        self.inner_subscribe(topic, move |m| {
+        let decode_errors = Arc::clone(&self.decode_errors);
+        self.inner_subscribe(topic, subscription, move |topic_name, payload| {
             log!(
                 log_level,
                 "Received a message on MQTT topic '{:?}', payload: {}",
-                m.topic_name,
-                if m.payload.len() < 2048 {
-                    String::from_utf8_lossy(&m.payload).to_string()
+                topic_name,
+                if payload.len() < 2048 {
+                    String::from_utf8_lossy(payload).to_string()
                 } else {
                     format!(
                         "size = {}, start = {}",
-                        m.payload.len(),
-                        String::from_utf8_lossy(&m.payload[0..128])
+                        payload.len(),
+                        String::from_utf8_lossy(&payload[0..128])
                     )
                 }
             );
-            trace!("Payload: {}", String::from_utf8_lossy(&m.payload));
-            let topic = HermesTopic::from_path(&m.topic_name);
-            if let Some(topic) = topic {
-                handler(&topic, &m.payload)
-            } else {
-                error!("could not parse topic: {:?}", m.topic_name)
+            trace!("Payload: {}", String::from_utf8_lossy(payload));
+            let message = HermesTopic::from_path(topic_name)
+                .ok_or_else(|| DecodeError::UnknownTopic(topic_name.to_owned()))
+                .and_then(|topic| decode(&topic, payload));
+            match message {
+                Ok(p) => handler(&p),
+                Err(e) => decode_errors.report(topic_name, e),
             }
         })
     }
 
-    fn inner_subscribe<F>(&self, topic: &HermesTopic, callback: F) -> Fallible<()>
+    /// Subscribe to the messages of `key` on `topic`, a key also read in their payload. When the
+    /// topic doesn't carry the key, because the topic layout leaves it out or because it never
+    /// does, the payloads are checked with `has_key` instead.
+    pub fn subscribe_keyed_payload<F, K, P>(
+        &self,
+        topic: &HermesTopic,
+        key: String,
+        subscription: Subscription,
+        has_key: K,
+        handler: F,
+    ) -> Fallible<()>
+    where
+        F: Fn(&P, Option<&Envelope>) -> () + Send + Sync + 'static,
+        K: Fn(&P, &str) -> bool + Send + Sync + 'static,
+        P: serde::de::DeserializeOwned,
+    {
+        let (topic, _) = topic.clone().in_layout(self.layout);
+        if topic.identifiers().contains(&&*key) {
+            self.subscribe_payload(&topic, subscription, handler)
+        } else {
+            self.subscribe_payload(&topic, subscription, move |p, envelope| {
+                if has_key(p, &key) {
+                    handler(p, envelope)
+                }
+            })
+        }
+    }
+
+    pub fn subscribe_raw<F>(&self, topic_filter: &str, subscription: Subscription, handler: F) -> Fallible<()>
     where
-        F: Fn(&::rumqtt::Publish) -> () + Send + Sync + 'static,
+        F: Fn(&str, &[u8]) -> () + Send + Sync + 'static,
     {
-        self.mqtt_client
-            .subscribe(topic.to_string(), Box::new(callback))
-            .map_err(SyncFailure::new)?
-            .send()
-            .map_err(SyncFailure::new)?;
+        self.add_callback(topic_filter, subscription, move |topic_name, payload| {
+            trace!(
+                "Received a message on MQTT topic '{:?}', size = {}",
+                topic_name,
+                payload.len()
+            );
+            handler(topic_name, payload)
+        })
+    }
+
+    fn inner_subscribe<F>(&self, topic: &HermesTopic, subscription: Subscription, callback: F) -> Fallible<()>
+    where
+        F: Fn(&str, &[u8]) -> () + Send + Sync + 'static,
+    {
+        let (topic, _) = topic.clone().in_layout(self.layout);
+        self.add_callback(&topic.to_string(), subscription, callback)
+    }
+
+    /// Call `callback` with the messages on `topic_filter` until `subscription` is cancelled, the
+    /// transport is subscribed to the filter when it has no callback yet
+    fn add_callback<F>(&self, topic_filter: &str, subscription: Subscription, callback: F) -> Fallible<()>
+    where
+        F: Fn(&str, &[u8]) -> () + Send + Sync + 'static,
+    {
+        let mut callbacks = self.callbacks.lock().map_err(PoisonLock::from)?;
+        let id = callbacks.next_id;
+        callbacks.next_id += 1;
+        let filter_callbacks = match callbacks.filters.get(topic_filter) {
+            Some(filter_callbacks) => Arc::clone(filter_callbacks),
+            None => {
+                let filter_callbacks = Arc::new(FilterCallbacks::default());
+                let dispatched = Arc::downgrade(&filter_callbacks);
+                self.transport.subscribe(
+                    topic_filter,
+                    Box::new(move |topic_name, payload| dispatch(&dispatched, topic_name, payload)),
+                )?;
+                callbacks
+                    .filters
+                    .insert(topic_filter.to_string(), Arc::clone(&filter_callbacks));
+                filter_callbacks
+            }
+        };
+        filter_callbacks
+            .lock()
+            .map_err(PoisonLock::from)?
+            .push((id, Arc::new(callback)));
+        drop(callbacks);
+
+        let callbacks = Arc::downgrade(&self.callbacks);
+        let transport = Arc::downgrade(&self.transport);
+        let topic_filter = topic_filter.to_string();
+        subscription.on_cancel(move || {
+            if let Err(e) = remove_callback(&callbacks, &transport, &topic_filter, id) {
+                warn!("Error while unsubscribing from MQTT topic '{}': {}", topic_filter, e)
+            }
+        });
         Ok(())
     }
 
+    /// The path to publish `topic` on in the topic layout of the handler
+    fn path(&self, topic: &HermesTopic) -> Result<String, InvalidTopic> {
+        let (topic, _) = topic.clone().in_layout(self.layout);
+        topic.publish_path()
+    }
+
     fn log_level(topic: &HermesTopic) -> log::Level {
         match *topic {
             HermesTopic::AudioServer(_, AudioServerCommand::AudioFrame) => log::Level::Trace,
@@ -175,6 +303,61 @@ impl MqttHandler {
     }
 }
 
+/// Call the callbacks of a topic filter with a message received on it, they are called outside of
+/// the lock so that they can subscribe and unsubscribe
+fn dispatch(callbacks: &Weak<FilterCallbacks>, topic_name: &str, payload: &[u8]) {
+    let callbacks = match callbacks.upgrade() {
+        Some(callbacks) => callbacks,
+        // the filter was unsubscribed
+        None => return,
+    };
+    let callbacks = match callbacks.lock() {
+        Ok(callbacks) => callbacks
+            .iter()
+            .map(|(_, callback)| Arc::clone(callback))
+            .collect::<Vec<_>>(),
+        Err(_) => {
+            return error!(
+                "could not dispatch message on MQTT topic '{}', poisoned lock",
+                topic_name
+            )
+        }
+    };
+    for callback in callbacks {
+        callback(topic_name, payload)
+    }
+}
+
+/// Remove the callback `id` of `topic_filter`, unsubscribing the transport from the filter when it
+/// was the last one
+fn remove_callback(
+    callbacks: &Weak<Mutex<TopicCallbacks>>,
+    transport: &Weak<dyn Transport>,
+    topic_filter: &str,
+    id: usize,
+) -> Fallible<()> {
+    let (callbacks, transport) = match (callbacks.upgrade(), transport.upgrade()) {
+        (Some(callbacks), Some(transport)) => (callbacks, transport),
+        // the handler is gone along with its subscriptions
+        _ => return Ok(()),
+    };
+    let mut callbacks = callbacks.lock().map_err(PoisonLock::from)?;
+    let is_empty = match callbacks.filters.get(topic_filter) {
+        Some(filter_callbacks) => {
+            let mut filter_callbacks = filter_callbacks.lock().map_err(PoisonLock::from)?;
+            filter_callbacks.retain(|(it, _)| *it != id);
+            filter_callbacks.is_empty()
+        }
+        None => false,
+    };
+    if is_empty {
+        callbacks.filters.remove(topic_filter);
+        // the lock is kept so that a new subscription to the filter comes after the unsubscription
+        transport.unsubscribe(topic_filter)?;
+    }
+    Ok(())
+}
+
 pub struct MqttHermesProtocolHandler {
     name: String,
     mqtt_handler: Arc<MqttHandler>,
@@ -195,83 +378,74 @@ impl MqttHermesProtocolHandler {
             .map_err(SyncFailure::new)
             .with_context(|_| format_err!("Could not start MQTT client on {}", name))?;
 
-        let mqtt_handler = Arc::new(MqttHandler { mqtt_client });
-
-        Ok(MqttHermesProtocolHandler { name, mqtt_handler })
+        Ok(Self::new_with_transport(name, MqttTransport { mqtt_client }))
     }
-}
 
-macro_rules! s {
-    ($n:ident<$t:ty> $topic:expr; ) => {
-        fn $n(&self, handler: Callback<$t>) -> Fallible<()> {
-            self.mqtt_handler.subscribe_payload($topic, move |p| handler.call(p))
-        }
-    };
-
-    ($n:ident<$t:ty>($($a:ident: $ta:ty),*) $topic:block) => {
-        fn $n(&self, $($a: $ta),*, handler: Callback<$t>) -> Fallible<()> {
-            self.mqtt_handler.subscribe_payload($topic, move |p| handler.call(p))
-        }
-    };
-
-    ($n:ident $topic:expr; ) => {
-        fn $n(&self, handler: Callback0) -> Fallible<()> {
-            self.mqtt_handler.subscribe($topic, move || handler.call())
-        }
-    };
-}
-
-macro_rules! s_bin {
-    ($n:ident<$t:ty> $topic:block |$rt:ident, $p:ident| $decoder:block) => {
-        fn $n(&self, handler: Callback<$t>) -> Fallible<()> {
-            self.mqtt_handler.subscribe_binary_payload($topic, move |$rt, $p| handler.call(&$decoder))
-        }
-    };
-
-    ($n:ident<$t:ty>($($a:ident: $ta:ty),*) $topic:block |$rt:ident, $p:ident| $decoder:block) => {
-        fn $n(&self, $($a: $ta),*, handler: Callback<$t>) -> Fallible<()> {
-            self.mqtt_handler.subscribe_binary_payload($topic, move |$rt, $p| handler.call(&$decoder))
-        }
-    };
-}
+    /// Create a handler exchanging the hermes MQTT topics and payloads over `transport` instead of
+    /// a MQTT client, `name` describes the transport when the handler is displayed
+    pub fn new_with_transport<T: Transport + 'static>(name: String, transport: T) -> MqttHermesProtocolHandler {
+        let mqtt_handler = Arc::new(MqttHandler {
+            transport: Arc::new(transport),
+            callbacks: Arc::new(Mutex::new(TopicCallbacks::default())),
+            decode_errors: Arc::new(DecodeErrors::default()),
+            layout: TopicLayout::default(),
+            envelope_source: None,
+        });
 
-macro_rules! p {
-    ($n:ident<$t:ty> $topic:expr; ) => {
-        fn $n(&self, payload: $t) -> Fallible<()> {
-            self.mqtt_handler.publish_payload($topic, payload)
-        }
-    };
+        MqttHermesProtocolHandler { name, mqtt_handler }
+    }
 
-    ($n:ident<$t:ty>($param1:ident: $t1:ty) $topic:block ) => {
-        fn $n(&self, $param1: $t1, payload: $t) -> Fallible<()> {
-            self.mqtt_handler.publish_payload($topic, payload)
-        }
-    };
+    /// Exchange the messages on the topics of `layout` rather than on the global ones, see
+    /// `TopicLayout`. The components of an assistant must all use the same layout, and it must be
+    /// chosen before creating any facade.
+    pub fn with_topic_layout(mut self, layout: TopicLayout) -> Fallible<Self> {
+        Arc::get_mut(&mut self.mqtt_handler)
+            .ok_or_else(|| format_err!("the topic layout must be chosen before creating facades"))?
+            .layout = layout;
+        Ok(self)
+    }
 
-    ($n:ident($payload:ident: $t:ty) $topic:block ) => {
-        fn $n(&self, $payload: $t) -> Fallible<()> {
-            self.mqtt_handler.publish_payload($topic, $payload)
-        }
-    };
+    /// Add an `Envelope` from `source` to the json messages published by the facades, the
+    /// callbacks created with `Callback::with_envelope` are given the envelope of their message.
+    /// The components that don't know about envelopes ignore them. The source must be chosen
+    /// before creating any facade.
+    pub fn with_envelope(mut self, source: MessageSource) -> Fallible<Self> {
+        Arc::get_mut(&mut self.mqtt_handler)
+            .ok_or_else(|| format_err!("the envelope source must be chosen before creating facades"))?
+            .envelope_source = Some(source);
+        Ok(self)
+    }
 
-    ($n:ident $topic:expr; ) => {
-        fn $n(&self) -> Fallible<()> {
-            self.mqtt_handler.publish($topic)
-        }
-    };
-}
+    /// Subscribe to the raw MQTT messages published on topics matching `topic_filter` (MQTT
+    /// wildcards are allowed), bypassing the facades. The handler receives the topic name and the
+    /// undecoded payload. This is intended for tooling that needs to observe the bus traffic.
+    pub fn subscribe_raw<F>(&self, topic_filter: &str, handler: F) -> Fallible<()>
+    where
+        F: Fn(&str, &[u8]) -> () + Send + Sync + 'static,
+    {
+        self.mqtt_handler
+            .subscribe_raw(topic_filter, Subscription::new(), handler)
+    }
 
-macro_rules! p_bin {
-    ($n:ident($payload:ident: $t:ty) $topic:block $bytes:block ) => {
-        fn $n(&self, $payload: $t) -> Fallible<()> {
-            self.mqtt_handler.publish_binary_payload($topic, $bytes)
-        }
-    };
+    /// Call `handler` for every message received by the facades that can't be decoded, be it
+    /// because of its topic or its payload. Such messages are otherwise only logged.
+    pub fn set_decode_error_handler<F>(&self, handler: F) -> Fallible<()>
+    where
+        F: Fn(&str, &DecodeError) -> () + Send + Sync + 'static,
+    {
+        *self
+            .mqtt_handler
+            .decode_errors
+            .handler
+            .write()
+            .map_err(PoisonLock::from)? = Some(Box::new(handler));
+        Ok(())
+    }
 }
 
 macro_rules! impl_component_facades_for {
-    // cannot use s! and p! macros in the impl here because we we would need access to self
-    // to get the component... I'm sad...
+    // these are not part of the protocol description as the topics depend on the component
+    // stored in self
     ($t:ty) => {
         impl ComponentFacade for $t {
             fn publish_version_request(&self) -> Fallible<()> {
@@ -285,21 +459,24 @@ macro_rules! impl_component_facades_for {
             fn subscribe_version(&self, handler: Callback<VersionMessage>) -> Fallible<()> {
                 self.mqtt_handler.subscribe_payload(
                     &HermesTopic::Component(None, self.component, ComponentCommand::Version),
-                    move |p| handler.call(p),
+                    handler.subscription(),
+                    move |p, envelope| handler.call_with_envelope(p, envelope),
                 )
             }
 
             fn subscribe_error(&self, handler: Callback<ErrorMessage>) -> Fallible<()> {
                 self.mqtt_handler.subscribe_payload(
                     &HermesTopic::Component(None, self.component, ComponentCommand::Error),
-                    move |p| handler.call(p),
+                    handler.subscription(),
+                    move |p, envelope| handler.call_with_envelope(p, envelope),
                 )
             }
 
             fn subscribe_component_loaded(&self, handler: Callback<ComponentLoadedMessage>) -> Fallible<()> {
                 self.mqtt_handler.subscribe_payload(
                     &HermesTopic::Component(None, self.component, ComponentCommand::Loaded),
-                    move |p| handler.call(p),
+                    handler.subscription(),
+                    move |p, envelope| handler.call_with_envelope(p, envelope),
                 )
             }
         }
@@ -308,6 +485,7 @@ macro_rules! impl_component_facades_for {
             fn subscribe_version_request(&self, handler: Callback0) -> Fallible<()> {
                 self.mqtt_handler.subscribe(
                     &HermesTopic::Component(None, self.component, ComponentCommand::VersionRequest),
+                    handler.subscription(),
                     move || handler.call(),
                 )
             }
@@ -337,8 +515,8 @@ macro_rules! impl_component_facades_for {
 }
 
 macro_rules! impl_toggleable_facades_for {
-    // cannot use s! and p! macros in the impl here because we we would need access to self
-    // to get the toggle on/off topics... I'm sad...
+    // these are not part of the protocol description as the toggle on/off topics are stored in
+    // self
     ($t:ty) => {
         impl ToggleableFacade for $t {
             fn publish_toggle_on(&self) -> Fallible<()> {
@@ -353,12 +531,16 @@ macro_rules! impl_toggleable_facades_for {
         impl ToggleableBackendFacade for $t {
             fn subscribe_toggle_on(&self, handler: Callback0) -> Fallible<()> {
                 self.mqtt_handler
-                    .subscribe(&self.toggle_on_topic, move || handler.call())
+                    .subscribe(&self.toggle_on_topic, handler.subscription(), move || {
+                        handler.call()
+                    })
             }
 
             fn subscribe_toggle_off(&self, handler: Callback0) -> Fallible<()> {
                 self.mqtt_handler
-                    .subscribe(&self.toggle_off_topic, move || handler.call())
+                    .subscribe(&self.toggle_off_topic, handler.subscription(), move || {
+                        handler.call()
+                    })
             }
         }
     };
@@ -368,23 +550,51 @@ macro_rules! impl_identifiable_toggleable_facades_for {
     ($t:ty) => {
         impl IdentifiableToggleableFacade for $t {
             fn publish_toggle_on(&self, site: SiteMessage) -> Fallible<()> {
-                self.mqtt_handler.publish_payload(&self.toggle_on_topic, site)
+                let topic = self.toggle_on_topic.clone().with_site(site.site_id.clone());
+                self.mqtt_handler.publish_payload(&topic, site)
             }
 
             fn publish_toggle_off(&self, site: SiteMessage) -> Fallible<()> {
-                self.mqtt_handler.publish_payload(&self.toggle_off_topic, site)
+                let topic = self.toggle_off_topic.clone().with_site(site.site_id.clone());
+                self.mqtt_handler.publish_payload(&topic, site)
             }
         }
 
         impl IdentifiableToggleableBackendFacade for $t {
-            fn subscribe_toggle_on(&self, handler: Callback<SiteMessage>) -> Fallible<()> {
-                self.mqtt_handler
-                    .subscribe_payload(&self.toggle_on_topic, move |p| handler.call(p))
+            fn subscribe_toggle_on(&self, site_id: String, handler: Callback<SiteMessage>) -> Fallible<()> {
+                self.mqtt_handler.subscribe_keyed_payload(
+                    &self.toggle_on_topic.clone().with_site(site_id.clone()),
+                    site_id,
+                    handler.subscription(),
+                    |p: &SiteMessage, site_id| p.site_id == site_id,
+                    move |p, envelope| handler.call_with_envelope(p, envelope),
+                )
             }
 
-            fn subscribe_toggle_off(&self, handler: Callback<SiteMessage>) -> Fallible<()> {
-                self.mqtt_handler
-                    .subscribe_payload(&self.toggle_off_topic, move |p| handler.call(p))
+            fn subscribe_all_toggle_on(&self, handler: Callback<SiteMessage>) -> Fallible<()> {
+                self.mqtt_handler.subscribe_payload(
+                    &self.toggle_on_topic.clone().with_site("+".into()),
+                    handler.subscription(),
+                    move |p, envelope| handler.call_with_envelope(p, envelope),
+                )
+            }
+
+            fn subscribe_toggle_off(&self, site_id: String, handler: Callback<SiteMessage>) -> Fallible<()> {
+                self.mqtt_handler.subscribe_keyed_payload(
+                    &self.toggle_off_topic.clone().with_site(site_id.clone()),
+                    site_id,
+                    handler.subscription(),
+                    |p: &SiteMessage, site_id| p.site_id == site_id,
+                    move |p, envelope| handler.call_with_envelope(p, envelope),
+                )
+            }
+
+            fn subscribe_all_toggle_off(&self, handler: Callback<SiteMessage>) -> Fallible<()> {
+                self.mqtt_handler.subscribe_payload(
+                    &self.toggle_off_topic.clone().with_site("+".into()),
+                    handler.subscription(),
+                    move |p, envelope| handler.call_with_envelope(p, envelope),
+                )
             }
         }
     };
@@ -404,21 +614,24 @@ macro_rules! impl_identifiable_component_facades_for {
             fn subscribe_version(&self, site_id: String, handler: Callback<VersionMessage>) -> Fallible<()> {
                 self.mqtt_handler.subscribe_payload(
                     &HermesTopic::Component(Some(site_id), self.component, ComponentCommand::Version),
-                    move |p| handler.call(p),
+                    handler.subscription(),
+                    move |p, envelope| handler.call_with_envelope(p, envelope),
                 )
             }
 
             fn subscribe_error(&self, site_id: String, handler: Callback<SiteErrorMessage>) -> Fallible<()> {
                 self.mqtt_handler.subscribe_payload(
                     &HermesTopic::Component(Some(site_id), self.component, ComponentCommand::Error),
-                    move |p| handler.call(p),
+                    handler.subscription(),
+                    move |p, envelope| handler.call_with_envelope(p, envelope),
                 )
             }
 
             fn subscribe_all_error(&self, handler: Callback<SiteErrorMessage>) -> Fallible<()> {
                 self.mqtt_handler.subscribe_payload(
                     &HermesTopic::Component(Some("+".to_string()), self.component, ComponentCommand::Error),
-                    move |p| handler.call(p),
+                    handler.subscription(),
+                    move |p, envelope| handler.call_with_envelope(p, envelope),
                 )
             }
 
@@ -429,14 +642,16 @@ macro_rules! impl_identifiable_component_facades_for {
             ) -> Fallible<()> {
                 self.mqtt_handler.subscribe_payload(
                     &HermesTopic::Component(Some(site_id), self.component, ComponentCommand::Loaded),
-                    move |p| handler.call(p),
+                    handler.subscription(),
+                    move |p, envelope| handler.call_with_envelope(p, envelope),
                 )
             }
 
             fn subscribe_all_component_loaded(&self, handler: Callback<ComponentLoadedOnSiteMessage>) -> Fallible<()> {
                 self.mqtt_handler.subscribe_payload(
                     &HermesTopic::Component(Some("+".to_string()), self.component, ComponentCommand::Loaded),
-                    move |p| handler.call(p),
+                    handler.subscription(),
+                    move |p, envelope| handler.call_with_envelope(p, envelope),
                 )
             }
         }
@@ -445,6 +660,7 @@ macro_rules! impl_identifiable_component_facades_for {
             fn subscribe_version_request(&self, site_id: String, handler: Callback0) -> Fallible<()> {
                 self.mqtt_handler.subscribe(
                     &HermesTopic::Component(Some(site_id), self.component, ComponentCommand::VersionRequest),
+                    handler.subscription(),
                     move || handler.call(),
                 )
             }
@@ -501,455 +717,531 @@ impl_toggleable_facades_for!(MqttToggleableComponentFacade);
 impl_identifiable_component_facades_for!(MqttToggleableComponentFacade);
 impl_identifiable_toggleable_facades_for!(MqttToggleableComponentFacade);
 
-impl VoiceActivityFacade for MqttComponentFacade {
-    s!(subscribe_vad_up<VadUpMessage>(site_id: String) { &HermesTopic::VoiceActivity(site_id, VoiceActivityCommand::VadUp)});
-    s!(subscribe_vad_down<VadDownMessage>(site_id: String) { &HermesTopic::VoiceActivity(site_id, VoiceActivityCommand::VadDown)});
-    s!(subscribe_all_vad_up<VadUpMessage> &HermesTopic::VoiceActivity("+".into(), VoiceActivityCommand::VadUp););
-    s!(subscribe_all_vad_down<VadDownMessage> &HermesTopic::VoiceActivity("+".into(), VoiceActivityCommand::VadDown););
+/// The struct implementing the facades of a component, given whether it has a component and
+/// toggle topics in the protocol description
+macro_rules! mqtt_facade {
+    ([$component:expr] []) => {
+        MqttComponentFacade
+    };
+    ([] [$toggle_on:expr, $toggle_off:expr]) => {
+        MqttToggleableFacade
+    };
+    ([$component:expr] [$toggle_on:expr, $toggle_off:expr]) => {
+        MqttToggleableComponentFacade
+    };
 }
 
-impl VoiceActivityBackendFacade for MqttComponentFacade {
-    p!(publish_vad_up(vad_up: VadUpMessage) { &HermesTopic::VoiceActivity(vad_up.site_id.clone(), VoiceActivityCommand::VadUp)});
-    p!(publish_vad_down(vad_down: VadDownMessage) { &HermesTopic::VoiceActivity(vad_down.site_id.clone(), VoiceActivityCommand::VadDown)});
+macro_rules! new_mqtt_facade {
+    ($mqtt_handler:expr, [$component:expr] []) => {
+        Box::new(MqttComponentFacade {
+            mqtt_handler: Arc::clone($mqtt_handler),
+            component: $component,
+        })
+    };
+    ($mqtt_handler:expr, [] [$toggle_on:expr, $toggle_off:expr]) => {
+        Box::new(MqttToggleableFacade {
+            mqtt_handler: Arc::clone($mqtt_handler),
+            toggle_on_topic: $toggle_on,
+            toggle_off_topic: $toggle_off,
+        })
+    };
+    ($mqtt_handler:expr, [$component:expr] [$toggle_on:expr, $toggle_off:expr]) => {
+        Box::new(MqttToggleableComponentFacade {
+            mqtt_handler: Arc::clone($mqtt_handler),
+            component: $component,
+            toggle_on_topic: $toggle_on,
+            toggle_off_topic: $toggle_off,
+        })
+    };
 }
 
-impl HotwordFacade for MqttToggleableComponentFacade {
-    s!(subscribe_detected<HotwordDetectedMessage>(site_id: String) { &HermesTopic::Hotword(Some(site_id), HotwordCommand::Detected) });
-    s!(subscribe_all_detected<HotwordDetectedMessage> &HermesTopic::Hotword(Some("+".into()), HotwordCommand::Detected););
+macro_rules! mqtt_wildcard {
+    () => {
+        "+".to_string()
+    };
+    (on $wildcard:literal) => {
+        $wildcard.to_string()
+    };
 }
 
-impl HotwordBackendFacade for MqttToggleableComponentFacade {
-    p!(publish_detected<HotwordDetectedMessage>(site_id: String) { &HermesTopic::Hotword(Some(site_id), HotwordCommand::Detected) });
+macro_rules! mqtt_publish {
+    ([] $publish:ident $subscribe:ident [] [] [$topic:expr] []) => {
+        fn $publish(&self) -> Fallible<()> {
+            self.mqtt_handler.publish(&$topic)
+        }
+    };
+    ([$payload:ident: $payload_type:ty] $publish:ident $subscribe:ident [] $all:tt [$topic:expr] []) => {
+        fn $publish(&self, $payload: $payload_type) -> Fallible<()> {
+            self.mqtt_handler.publish_payload(&$topic, $payload)
+        }
+    };
+    (
+        [$payload:ident: $payload_type:ty]
+        $publish:ident
+        $subscribe:ident
+        [$key:ident in $($key_path:ident).+]
+        $all:tt
+        [$topic:expr]
+        []
+    ) => {
+        fn $publish(&self, $payload: $payload_type) -> Fallible<()> {
+            let topic = {
+                let $key = $payload.$($key_path).+.clone();
+                $topic
+            };
+            self.mqtt_handler.publish_payload(&topic, $payload)
+        }
+    };
+    ([$payload:ident: $payload_type:ty] $publish:ident $subscribe:ident [$key:ident] $all:tt [$topic:expr] []) => {
+        fn $publish(&self, $key: String, $payload: $payload_type) -> Fallible<()> {
+            self.mqtt_handler.publish_payload(&$topic, $payload)
+        }
+    };
+    (
+        [$payload:ident: $payload_type:ty]
+        $publish:ident
+        $subscribe:ident
+        $key:tt
+        $all:tt
+        $topic:tt
+        [$encode:expr, $decode:path]
+    ) => {
+        fn $publish(&self, $payload: $payload_type) -> Fallible<()> {
+            let (topic, bytes) = ($encode)($payload);
+            self.mqtt_handler.publish_binary_payload(&topic, bytes)
+        }
+    };
 }
 
-impl SoundFeedbackFacade for MqttToggleableFacade {}
-
-impl SoundFeedbackBackendFacade for MqttToggleableFacade {}
-
-impl AsrFacade for MqttToggleableComponentFacade {
-    p!(publish_start_listening<AsrStartListeningMessage> &HermesTopic::Asr(AsrCommand::StartListening););
-    p!(publish_stop_listening<SiteMessage> &HermesTopic::Asr(AsrCommand::StopListening););
-    p!(publish_component_reload<RequestComponentReloadMessage> &HermesTopic::Asr(AsrCommand::Reload););
-    s!(subscribe_text_captured<TextCapturedMessage> &HermesTopic::Asr(AsrCommand::TextCaptured););
-    s!(subscribe_partial_text_captured<TextCapturedMessage> &HermesTopic::Asr(AsrCommand::PartialTextCaptured););
+macro_rules! mqtt_subscription {
+    ($mqtt_handler:expr, $handler:ident, $topic:expr, []) => {
+        $mqtt_handler.subscribe_payload(&$topic, $handler.subscription(), move |p, envelope| {
+            $handler.call_with_envelope(p, envelope)
+        })
+    };
+    ($mqtt_handler:expr, $handler:ident, $topic:expr, [$encode:expr, $decode:path]) => {
+        $mqtt_handler.subscribe_binary_payload(&$topic, $handler.subscription(), $decode, move |p| $handler.call(p))
+    };
 }
 
-impl AsrBackendFacade for MqttToggleableComponentFacade {
-    s!(subscribe_start_listening<AsrStartListeningMessage> &HermesTopic::Asr(AsrCommand::StartListening););
-    s!(subscribe_stop_listening<SiteMessage> &HermesTopic::Asr(AsrCommand::StopListening););
-    s!(subscribe_component_reload<RequestComponentReloadMessage> &HermesTopic::Asr(AsrCommand::Reload););
-    p!(publish_text_captured<TextCapturedMessage> &HermesTopic::Asr(AsrCommand::TextCaptured););
-    p!(publish_partial_text_captured<TextCapturedMessage> &HermesTopic::Asr(AsrCommand::PartialTextCaptured););
+/// The subscription to a message with a key, checked on the payload when it is read from it as
+/// the topic layout may leave it out of the topic
+macro_rules! mqtt_key_subscription {
+    ($mqtt_handler:expr, $handler:ident, $key:ident, $topic:expr, $payload_type:ty, [in $($key_path:ident).+], []) => {{
+        let key = $key.clone();
+        $mqtt_handler.subscribe_keyed_payload(
+            &$topic,
+            key,
+            $handler.subscription(),
+            |p: &$payload_type, key: &str| p.$($key_path).+ == key,
+            move |p, envelope| $handler.call_with_envelope(p, envelope),
+        )
+    }};
+    ($mqtt_handler:expr, $handler:ident, $key:ident, $topic:expr, $payload_type:ty, $key_path:tt, $binary:tt) => {
+        mqtt_subscription!($mqtt_handler, $handler, $topic, $binary)
+    };
 }
 
-impl TtsFacade for MqttComponentFacade {
-    p!(publish_say<SayMessage> &HermesTopic::Tts(TtsCommand::Say););
-    s!(subscribe_say_finished<SayFinishedMessage> &HermesTopic::Tts(TtsCommand::SayFinished););
-    p_bin!(publish_register_sound(sound: RegisterSoundMessage)
-        { &HermesTopic::Tts(TtsCommand::RegisterSound(sound.sound_id)) }
-        { sound.wav_sound }
-    );
+/// Check a key subscribed to, the MQTT wildcards given alone would subscribe to every key instead
+/// of the one named by it
+fn check_key(key: &str) -> Result<(), InvalidTopic> {
+    if key == "+" || key == "#" {
+        return Err(InvalidTopic::Wildcard(key.to_string()));
+    }
+    Ok(())
 }
 
-impl TtsBackendFacade for MqttComponentFacade {
-    s!(subscribe_say<SayMessage> &HermesTopic::Tts(TtsCommand::Say););
-    p!(publish_say_finished<SayFinishedMessage> &HermesTopic::Tts(TtsCommand::SayFinished););
-    s_bin!(subscribe_register_sound<RegisterSoundMessage> { &HermesTopic::Tts(TtsCommand::RegisterSound("#".into())) }
-        |topic, bytes| {
-            if let HermesTopic::Tts(TtsCommand::RegisterSound(ref sound_id)) = *topic {
-                RegisterSoundMessage { sound_id: sound_id.to_owned(), wav_sound: bytes.into() }
-            } else {
-                unreachable!();
-            }
+macro_rules! mqtt_subscribe {
+    ([] $publish:ident $subscribe:ident [] [] [$topic:expr] []) => {
+        fn $subscribe(&self, handler: Callback0) -> Fallible<()> {
+            self.mqtt_handler.subscribe(&$topic, handler.subscription(), move || handler.call())
+        }
+    };
+    ([$payload:ident: $payload_type:ty] $publish:ident $subscribe:ident [] [] [$topic:expr] $binary:tt) => {
+        fn $subscribe(&self, handler: Callback<$payload_type>) -> Fallible<()> {
+            mqtt_subscription!(self.mqtt_handler, handler, $topic, $binary)
+        }
+    };
+    (
+        [$payload:ident: $payload_type:ty]
+        $publish:ident
+        $subscribe:ident
+        [$key:ident $($key_path:tt)*]
+        []
+        [$topic:expr]
+        $binary:tt
+    ) => {
+        fn $subscribe(&self, $key: String, handler: Callback<$payload_type>) -> Fallible<()> {
+            check_key(&$key)?;
+            mqtt_key_subscription!(self.mqtt_handler, handler, $key, $topic, $payload_type, [$($key_path)*], $binary)
+        }
+    };
+    (
+        [$payload:ident: $payload_type:ty]
+        $publish:ident
+        $subscribe:ident
+        [$key:ident $($key_path:tt)*]
+        [$subscribe_all:ident $($wildcard:tt)*]
+        [$topic:expr]
+        $binary:tt
+    ) => {
+        fn $subscribe(&self, $key: String, handler: Callback<$payload_type>) -> Fallible<()> {
+            check_key(&$key)?;
+            mqtt_key_subscription!(self.mqtt_handler, handler, $key, $topic, $payload_type, [$($key_path)*], $binary)
         }
-    );
-}
 
-impl NluFacade for MqttComponentFacade {
-    p!(publish_query<NluQueryMessage> &HermesTopic::Nlu(NluCommand::Query););
-    p!(publish_partial_query<NluSlotQueryMessage> &HermesTopic::Nlu(NluCommand::PartialQuery););
-    p!(publish_component_reload<RequestComponentReloadMessage> &HermesTopic::Nlu(NluCommand::Reload););
-    s!(subscribe_slot_parsed<NluSlotMessage> &HermesTopic::Nlu(NluCommand::SlotParsed););
-    s!(subscribe_intent_parsed<NluIntentMessage> &HermesTopic::Nlu(NluCommand::IntentParsed););
-    s!(subscribe_intent_not_recognized<NluIntentNotRecognizedMessage> &HermesTopic::Nlu(NluCommand::IntentNotRecognized););
+        fn $subscribe_all(&self, handler: Callback<$payload_type>) -> Fallible<()> {
+            let $key = mqtt_wildcard!($($wildcard)*);
+            mqtt_subscription!(self.mqtt_handler, handler, $topic, $binary)
+        }
+    };
 }
 
-impl NluBackendFacade for MqttComponentFacade {
-    s!(subscribe_query<NluQueryMessage> &HermesTopic::Nlu(NluCommand::Query););
-    s!(subscribe_partial_query<NluSlotQueryMessage> &HermesTopic::Nlu(NluCommand::PartialQuery););
-    s!(subscribe_component_reload<RequestComponentReloadMessage> &HermesTopic::Nlu(NluCommand::Reload););
-    p!(publish_slot_parsed<NluSlotMessage> &HermesTopic::Nlu(NluCommand::SlotParsed););
-    p!(publish_intent_parsed<NluIntentMessage> &HermesTopic::Nlu(NluCommand::IntentParsed););
-    p!(publish_intent_not_recognized<NluIntentNotRecognizedMessage> &HermesTopic::Nlu(NluCommand::IntentNotRecognized););
+/// The methods of a facade for a message, `$facade_side` is the side of the facade and `$side` the
+/// one publishing the message
+macro_rules! mqtt_methods {
+    (frontend frontend $($message:tt)*) => { mqtt_publish!($($message)*); };
+    (backend backend $($message:tt)*) => { mqtt_publish!($($message)*); };
+    (frontend backend $($message:tt)*) => { mqtt_subscribe!($($message)*); };
+    (backend frontend $($message:tt)*) => { mqtt_subscribe!($($message)*); };
 }
 
-impl AudioServerFacade for MqttToggleableComponentFacade {
-    s_bin!(subscribe_audio_frame<AudioFrameMessage>(site_id: String) { &HermesTopic::AudioServer(Some(site_id), AudioServerCommand::AudioFrame) }
-            |topic, bytes| {
-                if let HermesTopic::AudioServer(Some(ref site_id), AudioServerCommand::AudioFrame) = *topic {
-                    AudioFrameMessage { site_id: site_id.to_owned(), wav_frame: bytes.into() }
-                } else {
-                    unreachable!()
-                }
-            });
-    p!(publish_replay_request(message: ReplayRequestMessage) { &HermesTopic::AudioServer(Some(message.site_id.clone()), AudioServerCommand::ReplayRequest) });
-    s_bin!(subscribe_replay_response<AudioFrameMessage>(site_id: String) { &HermesTopic::AudioServer(Some(site_id), AudioServerCommand::ReplayResponse) }
-            |topic, bytes| {
-                if let HermesTopic::AudioServer(Some(ref site_id), AudioServerCommand::ReplayResponse) = *topic {
-                    AudioFrameMessage { site_id: site_id.to_owned(), wav_frame: bytes.into() }
-                } else {
-                    unreachable!()
-                }
-            });
-    p_bin!(publish_play_bytes(bytes: PlayBytesMessage)
-        { &HermesTopic::AudioServer(Some(bytes.site_id), AudioServerCommand::PlayBytes(bytes.id)) }
-        { bytes.wav_bytes });
-    s!(subscribe_play_finished<PlayFinishedMessage>(site_id: String) { &HermesTopic::AudioServer(Some(site_id), AudioServerCommand::PlayFinished) });
-    s!(subscribe_all_play_finished<PlayFinishedMessage> &HermesTopic::AudioServer(Some("+".into()), AudioServerCommand::PlayFinished););
-    p_bin!(publish_stream_bytes(stream_bytes_message: StreamBytesMessage)
-       {
-           &HermesTopic::AudioServer(
-               Some(stream_bytes_message.site_id.clone()),
-               AudioServerCommand::StreamBytes {
-                   stream_id: stream_bytes_message.stream_id.clone(),
-                   chunk_number: stream_bytes_message.chunk_number.to_string(),
-                   is_last_chunk: if stream_bytes_message.is_last_chunk { "1".to_string() } else { "0".to_string() }})
-       }
-       { stream_bytes_message.bytes });
-    s!(subscribe_stream_finished<StreamFinishedMessage>(site_id: String) { &HermesTopic::AudioServer(Some(site_id), AudioServerCommand::StreamFinished) });
-    s!(subscribe_all_stream_finished<StreamFinishedMessage> &HermesTopic::AudioServer(Some("+".into()), AudioServerCommand::StreamFinished););
-}
+macro_rules! mqtt_facades {
+    ($(
+        $component:ident {
+            facade $facade_doc:tt $facade:ident $facade_supers:tt $facade_getter:ident;
+            backend $backend_doc:tt $backend:ident $backend_supers:tt $backend_getter:ident;
+            mqtt $mqtt_component:tt $mqtt_toggle:tt $mqtt_topic:tt;
+            messages [$({ $attributes:tt $side:ident $message:ident $route:tt $($rest:tt)* })*];
+        }
+    )*) => {
+        $(
+            impl $facade for mqtt_facade!($mqtt_component $mqtt_toggle) {
+                $(mqtt_methods!(frontend $side $($rest)*);)*
+            }
 
-impl AudioServerBackendFacade for MqttToggleableComponentFacade {
-    p_bin!(publish_audio_frame(frame: AudioFrameMessage)
-        { &HermesTopic::AudioServer(Some(frame.site_id), AudioServerCommand::AudioFrame) }
-        { frame.wav_frame });
-    s!(subscribe_replay_request<ReplayRequestMessage>(site_id: String) { &HermesTopic::AudioServer(Some(site_id), AudioServerCommand::ReplayRequest) } );
-    p_bin!(publish_replay_response(frame: AudioFrameMessage)
-        { &HermesTopic::AudioServer(Some(frame.site_id), AudioServerCommand::ReplayResponse) }
-        { frame.wav_frame });
-    s_bin!(subscribe_all_play_bytes<PlayBytesMessage> { &HermesTopic::AudioServer(Some("+".into()), AudioServerCommand::PlayBytes("#".into())) }
-            |topic, bytes| {
-                if let HermesTopic::AudioServer(Some(ref site_id), AudioServerCommand::PlayBytes(ref request_id)) = *topic {
-                    PlayBytesMessage { site_id: site_id.to_owned(), id: request_id.to_owned(), wav_bytes: bytes.into() }
-                } else {
-                    unreachable!()
-                }
-            });
-    s_bin!(subscribe_play_bytes<PlayBytesMessage>(site_id: String) { &HermesTopic::AudioServer(Some(site_id), AudioServerCommand::PlayBytes("#".into())) }
-            |topic, bytes| {
-                if let HermesTopic::AudioServer(Some(ref site_id), AudioServerCommand::PlayBytes(ref request_id)) = *topic {
-                    PlayBytesMessage { site_id: site_id.to_owned(), id: request_id.to_owned(), wav_bytes: bytes.into() }
-                } else {
-                    unreachable!()
+            impl $backend for mqtt_facade!($mqtt_component $mqtt_toggle) {
+                $(mqtt_methods!(backend $side $($rest)*);)*
+            }
+        )*
+
+        impl HermesProtocolHandler for MqttHermesProtocolHandler {
+            $(
+                fn $facade_getter(&self) -> Box<dyn $facade> {
+                    new_mqtt_facade!(&self.mqtt_handler, $mqtt_component $mqtt_toggle)
                 }
-            });
-    p!(publish_play_finished(message: PlayFinishedMessage) { &HermesTopic::AudioServer(Some(message.site_id.clone()), AudioServerCommand::PlayFinished) });
-    s_bin!(subscribe_stream_bytes<StreamBytesMessage>(site_id: String)
-        {
-            &HermesTopic::AudioServer(
-                Some(site_id),
-                AudioServerCommand::StreamBytes {
-                    stream_id:"+".into(),
-                    chunk_number:"+".into(),
-                    is_last_chunk:"+".into()
+            )*
+
+            $(
+                fn $backend_getter(&self) -> Box<dyn $backend> {
+                    new_mqtt_facade!(&self.mqtt_handler, $mqtt_component $mqtt_toggle)
                 }
-            )
+            )*
         }
-        |topic, bytes| {
-            if let HermesTopic::AudioServer(Some(ref site_id), AudioServerCommand::StreamBytes {ref stream_id, ref chunk_number, ref is_last_chunk}) = *topic {
-                StreamBytesMessage {
-                    site_id: site_id.to_owned(),
-                    stream_id: stream_id.to_owned(),
-                    chunk_number: chunk_number.parse()
-                        .expect("chunk_nbr is supposed to be properly formatted"),
-                    is_last_chunk: is_last_chunk == "1",
-                    bytes: bytes.into()
-                }
-            } else {
-                unreachable!()
-            }
-            });
-    s_bin!(subscribe_all_stream_bytes<StreamBytesMessage> { &HermesTopic::AudioServer(Some("+".into()), AudioServerCommand::StreamBytes{stream_id:"+".into(), chunk_number:"+".into(), is_last_chunk:"+".into()}) }
-           |topic, bytes| {
-                if let HermesTopic::AudioServer(Some(ref site_id), AudioServerCommand::StreamBytes{ref stream_id, ref chunk_number, ref is_last_chunk}) = *topic {
-                    StreamBytesMessage {
-                        site_id: site_id.to_owned(),
-                        stream_id: stream_id.to_owned(),
-                        chunk_number: chunk_number.parse()
-                            .expect("chunk_nbr is supposed to be properly formatted"),
-                        is_last_chunk: is_last_chunk != "0",
-                        bytes: bytes.into()
-                    }
-                } else {
-                    unreachable!()
-                }
-           });
-    p!(publish_stream_finished(message: StreamFinishedMessage) { &HermesTopic::AudioServer(Some(message.site_id.clone()), AudioServerCommand::StreamFinished) });
-}
-
-impl DialogueFacade for MqttToggleableComponentFacade {
-    s!(subscribe_session_queued<SessionQueuedMessage> &HermesTopic::DialogueManager(DialogueManagerCommand::SessionQueued););
-    s!(subscribe_session_started<SessionStartedMessage> &HermesTopic::DialogueManager(DialogueManagerCommand::SessionStarted););
-    s!(subscribe_intent<IntentMessage>(intent_name: String) { &HermesTopic::Intent(intent_name) });
-    s!(subscribe_intents<IntentMessage> &HermesTopic::Intent("#".into()););
-    s!(subscribe_intent_not_recognized<IntentNotRecognizedMessage> &HermesTopic::DialogueManager(DialogueManagerCommand::IntentNotRecognized););
-    s!(subscribe_session_ended<SessionEndedMessage> &HermesTopic::DialogueManager(DialogueManagerCommand::SessionEnded););
-    p!(publish_start_session<StartSessionMessage> &HermesTopic::DialogueManager(DialogueManagerCommand::StartSession););
-    p!(publish_continue_session<ContinueSessionMessage> &HermesTopic::DialogueManager(DialogueManagerCommand::ContinueSession););
-    p!(publish_end_session<EndSessionMessage> &HermesTopic::DialogueManager(DialogueManagerCommand::EndSession););
-    p!(publish_configure<DialogueConfigureMessage> &HermesTopic::DialogueManager(DialogueManagerCommand::Configure););
+    };
 }
 
-impl DialogueBackendFacade for MqttToggleableComponentFacade {
-    p!(publish_session_queued<SessionQueuedMessage> &HermesTopic::DialogueManager(DialogueManagerCommand::SessionQueued););
-    p!(publish_session_started<SessionStartedMessage> &HermesTopic::DialogueManager(DialogueManagerCommand::SessionStarted););
-    p!(publish_intent(intent: IntentMessage) {&HermesTopic::Intent(intent.intent.intent_name.clone())});
-    p!(publish_intent_not_recognized<IntentNotRecognizedMessage> &HermesTopic::DialogueManager(DialogueManagerCommand::IntentNotRecognized););
-    p!(publish_session_ended<SessionEndedMessage> &HermesTopic::DialogueManager(DialogueManagerCommand::SessionEnded););
-    s!(subscribe_start_session<StartSessionMessage> &HermesTopic::DialogueManager(DialogueManagerCommand::StartSession););
-    s!(subscribe_continue_session<ContinueSessionMessage> &HermesTopic::DialogueManager(DialogueManagerCommand::ContinueSession););
-    s!(subscribe_end_session<EndSessionMessage> &HermesTopic::DialogueManager(DialogueManagerCommand::EndSession););
-    s!(subscribe_configure<DialogueConfigureMessage> &HermesTopic::DialogueManager(DialogueManagerCommand::Configure););
-}
+hermes_protocol!(mqtt_facades);
 
-impl InjectionFacade for MqttComponentFacade {
-    p!(publish_injection_request<InjectionRequestMessage> &HermesTopic::Injection(InjectionCommand::Perform););
-    p!(publish_injection_status_request &HermesTopic::Injection(InjectionCommand::StatusRequest););
-    p!(publish_injection_reset_request<InjectionResetRequestMessage> &HermesTopic::Injection(InjectionCommand::ResetRequest););
-    s!(subscribe_injection_status<InjectionStatusMessage> &HermesTopic::Injection(InjectionCommand::Status););
-    s!(subscribe_injection_complete<InjectionCompleteMessage> &HermesTopic::Injection(InjectionCommand::Complete););
-    s!(subscribe_injection_reset_complete<InjectionResetCompleteMessage> &HermesTopic::Injection(InjectionCommand::ResetComplete););
+impl std::fmt::Display for MqttHermesProtocolHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} (MQTT)", self.name)
+    }
 }
 
-impl InjectionBackendFacade for MqttComponentFacade {
-    s!(subscribe_injection_request<InjectionRequestMessage> &HermesTopic::Injection(InjectionCommand::Perform););
-    s!(subscribe_injection_status_request &HermesTopic::Injection(InjectionCommand::StatusRequest););
-    s!(subscribe_injection_reset_request<InjectionResetRequestMessage> &HermesTopic::Injection(InjectionCommand::ResetRequest););
-    p!(publish_injection_status<InjectionStatusMessage> &HermesTopic::Injection(InjectionCommand::Status););
-    p!(publish_injection_complete<InjectionCompleteMessage> &HermesTopic::Injection(InjectionCommand::Complete););
-    p!(publish_injection_reset_complete<InjectionResetCompleteMessage> &HermesTopic::Injection(InjectionCommand::ResetComplete););
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hermes_mqtt_test_broker::Broker;
+    use std::rc::Rc;
 
-impl MqttHermesProtocolHandler {
-    fn hotword_component(&self) -> Box<MqttToggleableComponentFacade> {
-        Box::new(MqttToggleableComponentFacade {
-            mqtt_handler: Arc::clone(&self.mqtt_handler),
-            component: Component::Hotword,
-            toggle_on_topic: HermesTopic::Hotword(None, HotwordCommand::ToggleOn),
-            toggle_off_topic: HermesTopic::Hotword(None, HotwordCommand::ToggleOff),
-        })
+    struct HandlerHolder {
+        handler: MqttHermesProtocolHandler,
+        // this code is not dead, the broker is stopped when the last handler using it is dropped
+        #[allow(dead_code)]
+        broker: Rc<Broker>,
     }
 
-    fn sound_toggleable(&self) -> Box<MqttToggleableFacade> {
-        Box::new(MqttToggleableFacade {
-            mqtt_handler: Arc::clone(&self.mqtt_handler),
-            toggle_on_topic: HermesTopic::Feedback(FeedbackCommand::Sound(SoundCommand::ToggleOn)),
-            toggle_off_topic: HermesTopic::Feedback(FeedbackCommand::Sound(SoundCommand::ToggleOff)),
-        })
+    impl std::ops::Deref for HandlerHolder {
+        type Target = MqttHermesProtocolHandler;
+        fn deref(&self) -> &MqttHermesProtocolHandler {
+            &self.handler
+        }
     }
 
-    fn asr_component(&self) -> Box<MqttToggleableComponentFacade> {
-        Box::new(MqttToggleableComponentFacade {
-            mqtt_handler: Arc::clone(&self.mqtt_handler),
-            component: Component::Asr,
-            toggle_on_topic: HermesTopic::Asr(AsrCommand::ToggleOn),
-            toggle_off_topic: HermesTopic::Asr(AsrCommand::ToggleOff),
-        })
-    }
+    fn create_handlers_in(layout: TopicLayout) -> (HandlerHolder, HandlerHolder) {
+        // the broker listens on a random free port chosen by the OS
+        let broker = Rc::new(Broker::start().expect("could not start the test broker"));
+        let broker_address = broker.address().to_string();
+        let create = |name| {
+            MqttHermesProtocolHandler::new(&broker_address)
+                .and_then(|handler| handler.with_topic_layout(layout))
+                .unwrap_or_else(|e| panic!("could not create {} client: {}", name, e))
+        };
 
-    fn dialogue_component(&self) -> Box<MqttToggleableComponentFacade> {
-        Box::new(MqttToggleableComponentFacade {
-            mqtt_handler: Arc::clone(&self.mqtt_handler),
-            component: Component::DialogueManager,
-            toggle_on_topic: HermesTopic::DialogueManager(DialogueManagerCommand::ToggleOn),
-            toggle_off_topic: HermesTopic::DialogueManager(DialogueManagerCommand::ToggleOff),
-        })
-    }
+        let handler1 = HandlerHolder {
+            handler: create("first"),
+            broker: Rc::clone(&broker),
+        };
 
-    fn audio_server_component(&self) -> Box<MqttToggleableComponentFacade> {
-        Box::new(MqttToggleableComponentFacade {
-            mqtt_handler: Arc::clone(&self.mqtt_handler),
-            component: Component::AudioServer,
-            toggle_on_topic: HermesTopic::AudioServer(None, AudioServerCommand::ToggleOn),
-            toggle_off_topic: HermesTopic::AudioServer(None, AudioServerCommand::ToggleOff),
-        })
-    }
+        let handler2 = HandlerHolder {
+            handler: create("second"),
+            broker,
+        };
 
-    fn component(&self, component: Component) -> Box<MqttComponentFacade> {
-        Box::new(MqttComponentFacade {
-            mqtt_handler: Arc::clone(&self.mqtt_handler),
-            component,
-        })
+        (handler1, handler2)
     }
-}
 
-impl HermesProtocolHandler for MqttHermesProtocolHandler {
-    fn voice_activity(&self) -> Box<dyn VoiceActivityFacade> {
-        self.component(Component::VoiceActivity)
+    fn create_handlers() -> (HandlerHolder, HandlerHolder) {
+        create_handlers_in(TopicLayout::Global)
     }
 
-    fn hotword(&self) -> Box<dyn HotwordFacade> {
-        self.hotword_component()
-    }
+    // sleep between registering the callback and sending the message to be "sure" the event
+    // arrive in the right order to the broker
+    hermes_test_suite::test_suite!(WAIT_DURATION = 200);
 
-    fn sound_feedback(&self) -> Box<dyn SoundFeedbackFacade> {
-        self.sound_toggleable()
-    }
+    mod site_scoped {
+        use super::*;
 
-    fn asr(&self) -> Box<dyn AsrFacade> {
-        self.asr_component()
-    }
+        fn create_handlers() -> (HandlerHolder, HandlerHolder) {
+            create_handlers_in(TopicLayout::SiteScoped)
+        }
 
-    fn tts(&self) -> Box<dyn TtsFacade> {
-        self.component(Component::Tts)
+        hermes_test_suite::test_suite!(WAIT_DURATION = 200);
     }
 
-    fn nlu(&self) -> Box<dyn NluFacade> {
-        self.component(Component::Nlu)
-    }
+    type Subscriptions = Arc<std::sync::Mutex<Vec<(String, crate::transport::RawCallback)>>>;
 
-    fn audio_server(&self) -> Box<dyn AudioServerFacade> {
-        self.audio_server_component()
-    }
+    /// A transport keeping the topic filters and the callbacks of the subscriptions so that
+    /// messages can be delivered by hand, the published messages are delivered to the
+    /// subscriptions whose filter is their topic
+    struct CallbacksTransport(Subscriptions);
 
-    fn dialogue(&self) -> Box<dyn DialogueFacade> {
-        self.dialogue_component()
-    }
+    impl Transport for CallbacksTransport {
+        fn publish(&self, topic: &str, payload: Vec<u8>) -> Fallible<()> {
+            for (filter, callback) in self.0.lock().unwrap().iter() {
+                if filter == topic {
+                    callback(topic, &payload)
+                }
+            }
+            Ok(())
+        }
 
-    fn injection(&self) -> Box<dyn InjectionFacade> {
-        self.component(Component::Injection)
-    }
+        fn subscribe(&self, topic_filter: &str, callback: crate::transport::RawCallback) -> Fallible<()> {
+            self.0.lock().unwrap().push((topic_filter.to_string(), callback));
+            Ok(())
+        }
 
-    fn voice_activity_backend(&self) -> Box<dyn VoiceActivityBackendFacade> {
-        self.component(Component::VoiceActivity)
+        fn unsubscribe(&self, topic_filter: &str) -> Fallible<()> {
+            self.0.lock().unwrap().retain(|(filter, _)| filter != topic_filter);
+            Ok(())
+        }
     }
 
-    fn hotword_backend(&self) -> Box<dyn HotwordBackendFacade> {
-        self.hotword_component()
+    fn handler_on_callbacks(layout: TopicLayout) -> (MqttHermesProtocolHandler, Subscriptions) {
+        let subscriptions = Subscriptions::default();
+        let handler = MqttHermesProtocolHandler::new_with_transport(
+            "test".into(),
+            CallbacksTransport(Arc::clone(&subscriptions)),
+        )
+        .with_topic_layout(layout)
+        .unwrap();
+        (handler, subscriptions)
     }
 
-    fn sound_feedback_backend(&self) -> Box<dyn SoundFeedbackBackendFacade> {
-        self.sound_toggleable()
+    fn topic_filters(subscriptions: &Subscriptions) -> Vec<String> {
+        subscriptions
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(filter, _)| filter.clone())
+            .collect()
     }
 
-    fn asr_backend(&self) -> Box<dyn AsrBackendFacade> {
-        self.asr_component()
-    }
+    #[test]
+    fn the_site_is_only_in_the_topics_of_the_site_scoped_layout() {
+        let filters = |layout| {
+            let (handler, subscriptions) = handler_on_callbacks(layout);
+            let asr = handler.asr();
+            asr.subscribe_text_captured("kitchen".into(), Callback::new(|_: &TextCapturedMessage| ()))
+                .unwrap();
+            asr.subscribe_all_text_captured(Callback::new(|_: &TextCapturedMessage| ()))
+                .unwrap();
+            handler
+                .dialogue()
+                .subscribe_start_session(Callback::new(|_: &StartSessionMessage| ()))
+                .unwrap();
+            topic_filters(&subscriptions)
+        };
 
-    fn tts_backend(&self) -> Box<dyn TtsBackendFacade> {
-        self.component(Component::Tts)
+        assert_eq!(
+            filters(TopicLayout::Global),
+            vec!["hermes/asr/textCaptured", "hermes/dialogueManager/startSession"]
+        );
+        assert_eq!(
+            filters(TopicLayout::SiteScoped),
+            vec![
+                "hermes/asr/kitchen/textCaptured",
+                "hermes/asr/+/textCaptured",
+                "hermes/dialogueManager/startSession"
+            ]
+        );
     }
 
-    fn nlu_backend(&self) -> Box<dyn NluBackendFacade> {
-        self.component(Component::Nlu)
-    }
+    #[test]
+    fn the_transport_is_subscribed_once_per_topic_filter() {
+        let (handler, subscriptions) = handler_on_callbacks(TopicLayout::Global);
+        let (tx, rx) = std::sync::mpsc::channel();
+        let tx = Arc::new(std::sync::Mutex::new(tx));
+        let subscribe = |name: &'static str| {
+            let tx = Arc::clone(&tx);
+            let callback = Callback0::new(move || tx.lock().unwrap().send(name).unwrap());
+            let subscription = callback.subscription();
+            handler
+                .injection_backend()
+                .subscribe_injection_status_request(callback)
+                .unwrap();
+            subscription
+        };
 
-    fn audio_server_backend(&self) -> Box<dyn AudioServerBackendFacade> {
-        self.audio_server_component()
-    }
+        let first = subscribe("first");
+        let second = subscribe("second");
+        assert_eq!(topic_filters(&subscriptions), vec!["hermes/injection/statusRequest"]);
 
-    fn dialogue_backend(&self) -> Box<dyn DialogueBackendFacade> {
-        self.dialogue_component()
-    }
+        first.cancel();
+        handler.injection().publish_injection_status_request().unwrap();
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), vec!["second"]);
+        assert_eq!(topic_filters(&subscriptions), vec!["hermes/injection/statusRequest"]);
 
-    fn injection_backend(&self) -> Box<dyn InjectionBackendFacade> {
-        self.component(Component::Injection)
-    }
-}
+        second.cancel();
+        assert!(topic_filters(&subscriptions).is_empty());
 
-impl std::fmt::Display for MqttHermesProtocolHandler {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "{} (MQTT)", self.name)
+        let _third = subscribe("third");
+        handler.injection().publish_injection_status_request().unwrap();
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), vec!["third"]);
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::net::{TcpListener, TcpStream};
-    use std::process::Command;
-    use std::rc::Rc;
-    use std::thread::sleep;
-    use std::time::Duration;
 
-    struct ServerHolder {
-        server: std::process::Child,
-    }
+    #[test]
+    fn the_global_layout_filters_the_sites_on_the_payloads() {
+        use hermes::hermes_utils::Example;
+
+        let (handler, subscriptions) = handler_on_callbacks(TopicLayout::Global);
+        let (tx, rx) = std::sync::mpsc::channel();
+        let tx = std::sync::Mutex::new(tx);
+        handler
+            .tts_backend()
+            .subscribe_say(
+                "kitchen".into(),
+                Callback::new(move |message: &SayMessage| tx.lock().unwrap().send(message.site_id.clone()).unwrap()),
+            )
+            .unwrap();
+
+        let subscriptions = subscriptions.lock().unwrap();
+        let (filter, callback) = &subscriptions[0];
+        assert_eq!(filter, "hermes/tts/say");
+        for site_id in &["bedroom", "kitchen", "kitchen/2"] {
+            let message = SayMessage {
+                site_id: site_id.to_string(),
+                ..SayMessage::minimal_example()
+            };
+            callback(filter, &serde_json::to_vec(&message).unwrap());
+        }
 
-    struct HandlerHolder {
-        handler: MqttHermesProtocolHandler,
-        // this code is not dead, we need this as there is a drop on server holder that will kill
-        // the child process
-        #[allow(dead_code)]
-        server: Rc<ServerHolder>,
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), vec!["kitchen".to_string()]);
     }
 
-    impl std::ops::Deref for HandlerHolder {
-        type Target = MqttHermesProtocolHandler;
-        fn deref(&self) -> &MqttHermesProtocolHandler {
-            &self.handler
-        }
+    #[test]
+    fn the_topic_layout_is_chosen_before_creating_facades() {
+        let (handler, _) = handler_on_callbacks(TopicLayout::Global);
+        let _asr = handler.asr();
+        assert!(handler.with_topic_layout(TopicLayout::SiteScoped).is_err());
     }
 
-    impl Drop for ServerHolder {
-        fn drop(&mut self) {
-            self.server.kill().unwrap();
-        }
+    #[test]
+    fn envelopes_are_given_to_the_callbacks() {
+        use hermes::hermes_utils::Example;
+
+        let (handler, subscriptions) = handler_on_callbacks(TopicLayout::default());
+        let (tx, rx) = std::sync::mpsc::channel();
+        let tx = std::sync::Mutex::new(tx);
+        handler
+            .dialogue_backend()
+            .subscribe_end_session(Callback::with_envelope(move |_: &EndSessionMessage, envelope| {
+                tx.lock().unwrap().send(envelope.cloned()).unwrap()
+            }))
+            .unwrap();
+
+        let source = MessageSource {
+            component: HermesComponent::ClientApp,
+            site_id: Some("kitchen".into()),
+        };
+        let with_envelope = MqttHermesProtocolHandler::new_with_transport(
+            "test".into(),
+            CallbacksTransport(Arc::clone(&subscriptions)),
+        )
+        .with_envelope(source.clone())
+        .unwrap();
+        with_envelope
+            .dialogue()
+            .publish_end_session(EndSessionMessage::full_example())
+            .unwrap();
+        with_envelope
+            .dialogue()
+            .publish_end_session(EndSessionMessage::full_example())
+            .unwrap();
+        handler
+            .dialogue()
+            .publish_end_session(EndSessionMessage::full_example())
+            .unwrap();
+
+        let envelopes: Vec<Option<Envelope>> = rx.try_iter().collect();
+        assert_eq!(envelopes.len(), 3);
+        let first = envelopes[0].as_ref().unwrap();
+        let second = envelopes[1].as_ref().unwrap();
+        assert_eq!(first.source, source);
+        assert_eq!(first.ontology_version, ONTOLOGY_VERSION);
+        assert_ne!(first.message_id, second.message_id);
+        assert_eq!(envelopes[2], None);
+
+        let _dialogue = with_envelope.dialogue();
+        assert!(with_envelope.with_envelope(source).is_err());
     }
 
-    fn create_handlers() -> (HandlerHolder, HandlerHolder) {
-        // get a random free port form the OS
-        let port = { TcpListener::bind("localhost:0").unwrap().local_addr().unwrap().port() };
-
-        // /usr/sbin is not in path on non login session on raspbian and it is where mosquitto is
-        // same goes for /usr/local/sbin on macos/homebrew
-        std::env::set_var(
-            "PATH",
-            format!("{}:/usr/sbin:/usr/local/sbin", std::env::var("PATH").unwrap()),
-        );
-
-        let server = Rc::new(ServerHolder {
-            server: Command::new("mosquitto")
-                .arg("-p")
-                .arg(format!("{}", port))
-                .arg("-v")
-                .spawn()
-                .expect("could not start mosquitto"),
-        });
-
-        let server_address = format!("localhost:{}", port);
-
-        // wait 'till mosquitto is accessible.
-        let server_is_live = || {
-            for _ in 0..100 {
-                if TcpStream::connect(&server_address).is_ok() {
-                    return true;
-                } else {
-                    sleep(Duration::from_millis(50));
-                }
-            }
-            false
-        };
-        assert!(
-            server_is_live(),
-            format!("can't connect to mosquitto server {}", &server_address)
+    #[test]
+    fn undecodable_messages_are_reported() {
+        let (handler, callbacks) = handler_on_callbacks(TopicLayout::default());
+        let (tx, rx) = std::sync::mpsc::channel();
+        let tx = std::sync::Mutex::new(tx);
+        handler
+            .set_decode_error_handler(move |topic, error| {
+                tx.lock().unwrap().send((topic.to_string(), error.to_string())).unwrap()
+            })
+            .unwrap();
+
+        handler
+            .audio_server_backend()
+            .subscribe_all_stream_bytes(Callback::new(|_: &StreamBytesMessage| panic!("garbage was decoded")))
+            .unwrap();
+        handler
+            .dialogue()
+            .subscribe_intents(Callback::new(|_: &IntentMessage| panic!("garbage was decoded")))
+            .unwrap();
+
+        let callbacks = callbacks.lock().unwrap();
+        callbacks[0].1("hermes/audioServer/x/playBytesStreaming/a/notanumber/1", b"bytes");
+        callbacks[0].1("hermes/audioServer/x/playBytesStreaming/a/1/1/extra", b"bytes");
+        callbacks[1].1("hermes/intent/foo", b"{not json");
+
+        let errors: Vec<(String, String)> = rx.try_iter().collect();
+        assert_eq!(errors.len(), 3, "{:?}", errors);
+        assert_eq!(
+            errors[0],
+            (
+                "hermes/audioServer/x/playBytesStreaming/a/notanumber/1".to_string(),
+                "invalid chunk number 'notanumber' in topic".to_string()
+            )
         );
-
-        let handler1 = HandlerHolder {
-            handler: MqttHermesProtocolHandler::new(&server_address).expect("could not create first client"),
-            server: Rc::clone(&server),
-        };
-
-        let handler2 = HandlerHolder {
-            handler: MqttHermesProtocolHandler::new(&server_address).expect("could not create second client"),
-            server,
-        };
-
-        (handler1, handler2)
+        assert_eq!(errors[1].0, "hermes/audioServer/x/playBytesStreaming/a/1/1/extra");
+        assert_eq!(errors[2].0, "hermes/intent/foo");
     }
-
-    // sleep 50ms between registering the callback and sending the message to be "sure" the event
-    // arrive in the right order to the mosquitto server
-    hermes_test_suite::test_suite!(WAIT_DURATION = 200);
 }
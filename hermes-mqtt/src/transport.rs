@@ -0,0 +1,58 @@
+//! The bus carrying the MQTT topics and payloads of hermes.
+//!
+//! The facades of `MqttHermesProtocolHandler` only need to publish a payload on a topic and to
+//! subscribe to a topic filter, a MQTT client is used by default but any other transport able to
+//! carry topics and payloads (a WebSocket for instance) can be plugged instead.
+
+use failure::{Fallible, SyncFailure};
+use rumqtt::PublishBuilder;
+
+/// A callback receiving the topic name and the raw payload of a message
+pub type RawCallback = Box<dyn Fn(&str, &[u8]) -> () + Send + Sync>;
+
+pub trait Transport: Send + Sync {
+    /// Publish `payload` on `topic`, the payload is empty for the messages without one
+    fn publish(&self, topic: &str, payload: Vec<u8>) -> Fallible<()>;
+
+    /// Call `callback` for every message published on a topic matching `topic_filter`, the MQTT
+    /// wildcards `+` and `#` are allowed in the filter
+    fn subscribe(&self, topic_filter: &str, callback: RawCallback) -> Fallible<()>;
+
+    /// Stop calling the callback subscribed to `topic_filter`, the handler subscribes to a filter
+    /// once and unsubscribes from it when all of its subscriptions are cancelled
+    fn unsubscribe(&self, topic_filter: &str) -> Fallible<()>;
+}
+
+pub(crate) struct MqttTransport {
+    pub mqtt_client: rumqtt::MqttClient,
+}
+
+impl Transport for MqttTransport {
+    fn publish(&self, topic: &str, payload: Vec<u8>) -> Fallible<()> {
+        self.mqtt_client
+            .publish(topic)
+            .map(|m| m.payload(payload))
+            .and_then(PublishBuilder::send)
+            .map_err(SyncFailure::new)?;
+        Ok(())
+    }
+
+    fn subscribe(&self, topic_filter: &str, callback: RawCallback) -> Fallible<()> {
+        self.mqtt_client
+            .subscribe(
+                topic_filter.to_string(),
+                Box::new(move |m: &rumqtt::Publish| callback(&m.topic_name, &m.payload)),
+            )
+            .map_err(SyncFailure::new)?
+            .send()
+            .map_err(SyncFailure::new)?;
+        Ok(())
+    }
+
+    fn unsubscribe(&self, topic_filter: &str) -> Fallible<()> {
+        self.mqtt_client
+            .unsubscribe(topic_filter.to_string())
+            .map_err(SyncFailure::new)?;
+        Ok(())
+    }
+}
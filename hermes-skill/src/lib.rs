@@ -0,0 +1,73 @@
+//! Typed intent handlers for the skills, on top of the dialogue facade.
+//!
+//! A handler is registered with `skill.on("SetTimer", |request: SetTimer| Response::end("Done"))`.
+//! The request is read from the intent with `FromIntent`, that can be derived on a structure
+//! whose fields are read from the slots with the same name. The `Response` returned by the handler
+//! continues or ends the session of the intent. The code generated by the derive only refers to
+//! this crate, which re-exports `hermes` and `failure` for it.
+
+mod response;
+
+use std::sync::Arc;
+
+use failure::Fallible;
+use hermes::*;
+use log::*;
+
+pub use crate::response::Response;
+pub use failure;
+pub use hermes;
+pub use hermes_utils_derive::FromIntent;
+
+/// A request read from an intent, see the derive of the same name
+pub trait FromIntent: Sized {
+    fn from_intent(intent: &IntentMessage) -> Fallible<Self>;
+}
+
+impl FromIntent for IntentMessage {
+    fn from_intent(intent: &IntentMessage) -> Fallible<Self> {
+        Ok(intent.clone())
+    }
+}
+
+/// The intent handlers of a skill, registered on the dialogue of a `HermesProtocolHandler`
+pub struct Skill {
+    dialogue: Arc<dyn DialogueFacade>,
+}
+
+impl Skill {
+    pub fn new(handler: &dyn HermesProtocolHandler) -> Skill {
+        Skill {
+            dialogue: handler.dialogue().into(),
+        }
+    }
+
+    /// Handle the intents named `intent_name`, which can be a glob pattern as in
+    /// `subscribe_intents_matching`. The session of an intent that can't be read as a `T` is
+    /// ended without calling the handler.
+    pub fn on<T, F>(&self, intent_name: &str, handler: F) -> Fallible<()>
+    where
+        T: FromIntent,
+        F: Fn(T) -> Response + Send + Sync + 'static,
+    {
+        let dialogue = Arc::clone(&self.dialogue);
+        self.dialogue.subscribe_intents_matching(
+            intent_name.into(),
+            Callback::new(move |intent: &IntentMessage| {
+                let response = match T::from_intent(intent) {
+                    Ok(request) => handler(request),
+                    Err(e) => {
+                        warn!(
+                            "Could not read the {} intent of session {}: {}",
+                            intent.intent.intent_name, intent.session_id, e
+                        );
+                        Response::end_silently()
+                    }
+                };
+                if let Err(e) = response.publish(&*dialogue, intent.session_id.clone()) {
+                    warn!("Could not answer the intent of session {}: {}", intent.session_id, e)
+                }
+            }),
+        )
+    }
+}
@@ -0,0 +1,63 @@
+use failure::Fallible;
+use hermes::*;
+
+/// What to do with the session of an intent once it is handled
+#[derive(Debug, Clone, PartialEq)]
+pub enum Response {
+    /// Continue the session with a `ContinueSessionMessage` having these fields
+    Continue {
+        text: String,
+        intent_filter: Option<Vec<String>>,
+        custom_data: Option<String>,
+        send_intent_not_recognized: bool,
+        slot: Option<String>,
+    },
+    /// End the session with an `EndSessionMessage` having this text
+    End { text: Option<String> },
+}
+
+impl Response {
+    /// Say `text` and wait for the answer of the user, whatever its intent
+    pub fn ask<S: Into<String>>(text: S) -> Self {
+        Response::Continue {
+            text: text.into(),
+            intent_filter: None,
+            custom_data: None,
+            send_intent_not_recognized: false,
+            slot: None,
+        }
+    }
+
+    /// Say `text` and end the session
+    pub fn end<S: Into<String>>(text: S) -> Self {
+        Response::End {
+            text: Some(text.into()),
+        }
+    }
+
+    /// End the session without saying anything
+    pub fn end_silently() -> Self {
+        Response::End { text: None }
+    }
+
+    /// Publish the message continuing or ending the session `session_id`
+    pub fn publish(self, dialogue: &dyn DialogueFacade, session_id: String) -> Fallible<()> {
+        match self {
+            Response::Continue {
+                text,
+                intent_filter,
+                custom_data,
+                send_intent_not_recognized,
+                slot,
+            } => dialogue.publish_continue_session(ContinueSessionMessage {
+                session_id,
+                text,
+                intent_filter,
+                custom_data,
+                send_intent_not_recognized,
+                slot,
+            }),
+            Response::End { text } => dialogue.publish_end_session(EndSessionMessage { session_id, text }),
+        }
+    }
+}
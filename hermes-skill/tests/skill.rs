@@ -0,0 +1,152 @@
+use std::sync::{mpsc, Mutex};
+use std::time::Duration;
+
+use chrono::prelude::*;
+use hermes::hermes_utils::Example;
+use hermes::nlu_ontology::*;
+use hermes::*;
+use hermes_inprocess::InProcessHermesProtocolHandler;
+use hermes_skill::{FromIntent, Response, Skill};
+
+#[derive(FromIntent, Debug, PartialEq)]
+struct SetTimer {
+    duration: std::time::Duration,
+    #[slot_name("timerName")]
+    name: Option<String>,
+    at: Option<DateTime<Utc>>,
+    rooms: Vec<String>,
+}
+
+fn slot(slot_name: &str, value: SlotValue) -> NluSlot {
+    NluSlot {
+        nlu_slot: Slot {
+            raw_value: "raw value".into(),
+            value,
+            alternatives: vec![],
+            range: 0..9,
+            entity: "entity".into(),
+            slot_name: slot_name.into(),
+            confidence_score: None,
+        },
+    }
+}
+
+fn intent(intent_name: &str, slots: Vec<NluSlot>) -> IntentMessage {
+    let mut intent = IntentMessage::minimal_example();
+    intent.session_id = "session".into();
+    intent.intent.intent_name = intent_name.into();
+    intent.slots = slots;
+    intent
+}
+
+fn five_minutes() -> NluSlot {
+    slot(
+        "duration",
+        SlotValue::Duration(DurationValue {
+            years: 0,
+            quarters: 0,
+            months: 0,
+            weeks: 0,
+            days: 0,
+            hours: 0,
+            minutes: 5,
+            seconds: 0,
+            precision: Precision::Exact,
+        }),
+    )
+}
+
+#[test]
+fn requests_are_derived_from_the_slots() {
+    let request = SetTimer::from_intent(&intent(
+        "SetTimer",
+        vec![
+            five_minutes(),
+            slot("rooms", SlotValue::Custom("kitchen".to_string().into())),
+            slot("timerName", SlotValue::Custom("pasta".to_string().into())),
+            slot("rooms", SlotValue::Custom("bedroom".to_string().into())),
+        ],
+    ))
+    .unwrap();
+
+    assert_eq!(
+        request,
+        SetTimer {
+            duration: std::time::Duration::from_secs(300),
+            name: Some("pasta".into()),
+            at: None,
+            rooms: vec!["kitchen".into(), "bedroom".into()],
+        }
+    );
+
+    assert!(SetTimer::from_intent(&intent("SetTimer", vec![])).is_err());
+    assert!(SetTimer::from_intent(&intent(
+        "SetTimer",
+        vec![
+            five_minutes(),
+            slot("at", SlotValue::Number(NumberValue { value: 3.0 }))
+        ]
+    ))
+    .is_err());
+}
+
+#[test]
+fn responses_continue_or_end_the_session() {
+    let handler = InProcessHermesProtocolHandler::new();
+    let skill = Skill::new(&handler);
+    skill
+        .on("SetTimer", |request: SetTimer| match request.name {
+            Some(name) => Response::end(format!("{} timer set", name)),
+            None => Response::ask("How should I call it?"),
+        })
+        .unwrap();
+
+    let (continue_tx, continue_rx) = mpsc::channel();
+    let continue_tx = Mutex::new(continue_tx);
+    handler
+        .dialogue_backend()
+        .subscribe_continue_session(Callback::new(move |m: &ContinueSessionMessage| {
+            continue_tx.lock().unwrap().send(m.clone()).unwrap()
+        }))
+        .unwrap();
+    let (end_tx, end_rx) = mpsc::channel();
+    let end_tx = Mutex::new(end_tx);
+    handler
+        .dialogue_backend()
+        .subscribe_end_session(Callback::new(move |m: &EndSessionMessage| {
+            end_tx.lock().unwrap().send(m.clone()).unwrap()
+        }))
+        .unwrap();
+
+    let dialogue_backend = handler.dialogue_backend();
+
+    dialogue_backend
+        .publish_intent(intent("SetTimer", vec![five_minutes()]))
+        .unwrap();
+    let continued = continue_rx.recv_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(continued.session_id, "session");
+    assert_eq!(continued.text, "How should I call it?");
+    assert_eq!(continued.intent_filter, None);
+
+    dialogue_backend
+        .publish_intent(intent(
+            "SetTimer",
+            vec![
+                five_minutes(),
+                slot("timerName", SlotValue::Custom("pasta".to_string().into())),
+            ],
+        ))
+        .unwrap();
+    assert_eq!(
+        end_rx.recv_timeout(Duration::from_secs(1)).unwrap(),
+        EndSessionMessage {
+            session_id: "session".into(),
+            text: Some("pasta timer set".into()),
+        }
+    );
+
+    // the duration is missing
+    dialogue_backend.publish_intent(intent("SetTimer", vec![])).unwrap();
+    assert_eq!(end_rx.recv_timeout(Duration::from_secs(1)).unwrap().text, None);
+    assert!(continue_rx.try_recv().is_err());
+}
@@ -1,3 +1,16 @@
+//! Conformance tests for `HermesProtocolHandler` implementations.
+//!
+//! Define a `create_handlers()` function returning two handlers connected to the same bus (one
+//! publishing, one subscribing) and invoke `test_suite!()` in a test module. Besides checking that
+//! every message is delivered, the suite checks that subscriptions on a site, an identifier or an
+//! intent name don't receive the messages of another one, that messages on one topic keep their
+//! order, that several subscribers on one topic all receive each message once, and that the
+//! facades of a handler restricted to a site with `for_site` fill in and filter on that site and
+//! refuse to subscribe for another one, that a cancelled subscription stops receiving while the
+//! others on its topic don't, and that the session handles of the dialogue facade follow their
+//! session. The intents subscribed to by pattern are checked the same way on every implementation,
+//! whether the pattern is filtered by the transport or by the facade.
+
 use chrono::prelude::*;
 
 pub fn now() -> DateTime<Utc> {
@@ -73,6 +86,742 @@ macro_rules! t {
     };
 }
 
+/// Check that a subscription filtering on a site, an identifier or an intent name ignores the
+/// messages published for another one. The message for the other value is published first, the one
+/// matching the subscription must then be the only one received.
+#[macro_export]
+macro_rules! t_not_received {
+    (
+        $name:ident :
+        $s_facade:ident.
+        $s:ident($($field:ident).+) <=
+        $t:ty { $other:expr } |
+        $p_facade:ident.
+        $p:ident
+    ) => {
+        #[test]
+        fn $name() {
+            let (handler_source, handler_receiver) = create_handlers();
+            let source = handler_source.$p_facade();
+            let receiver = handler_receiver.$s_facade();
+            let (tx, rx) = std::sync::mpsc::channel();
+            let tx = std::sync::Mutex::new(tx);
+            use hermes::hermes_utils::Example;
+            let message = <$t>::full_example();
+            let mut other_message = message.clone();
+            other_message.$($field).* = $other.into();
+            receiver
+                .$s(message.$($field).*.clone(),
+                    hermes::Callback::new(move |o: &$t| {
+                    tx.lock().map(|it| it.send(o.clone())).unwrap().unwrap()
+                }))
+                .unwrap();
+            std::thread::sleep(WAIT_DURATION);
+            source.$p(other_message).unwrap();
+            source.$p(message.clone()).unwrap();
+            $crate::assert_received_once!(rx, message);
+        }
+    };
+    (
+        $name:ident :
+        $s_facade:ident.
+        $s:ident $subscribe_arg:block <=
+        $p_facade:ident.
+        $p:ident $other_arg:block
+    ) => {
+        #[test]
+        fn $name() {
+            let (handler_source, handler_receiver) = create_handlers();
+            let source = handler_source.$p_facade();
+            let receiver = handler_receiver.$s_facade();
+            let (tx, rx) = std::sync::mpsc::channel();
+            let tx = std::sync::Mutex::new(tx);
+            receiver
+                .$s($subscribe_arg,
+                    hermes::Callback0::new(move || {
+                    tx.lock().map(|it| it.send(())).unwrap().unwrap()
+                }))
+                .unwrap();
+            std::thread::sleep(WAIT_DURATION);
+            source.$p($other_arg).unwrap();
+            source.$p($subscribe_arg).unwrap();
+            $crate::assert_received_once!(rx);
+        }
+    };
+    (
+        $name:ident :
+        $s_facade:ident.
+        $s:ident $subscribe_arg:block <=
+        $t:ty |
+        $p_facade:ident.
+        $p:ident $other_arg:block
+    ) => {
+        #[test]
+        fn $name() {
+            let (handler_source, handler_receiver) = create_handlers();
+            let source = handler_source.$p_facade();
+            let receiver = handler_receiver.$s_facade();
+            let (tx, rx) = std::sync::mpsc::channel();
+            let tx = std::sync::Mutex::new(tx);
+            use hermes::hermes_utils::Example;
+            let message = <$t>::full_example();
+            receiver
+                .$s($subscribe_arg,
+                    hermes::Callback::new(move |o: &$t| {
+                    tx.lock().map(|it| it.send(o.clone())).unwrap().unwrap()
+                }))
+                .unwrap();
+            std::thread::sleep(WAIT_DURATION);
+            source.$p($other_arg, message.clone()).unwrap();
+            source.$p($subscribe_arg, message.clone()).unwrap();
+            $crate::assert_received_once!(rx, message);
+        }
+    };
+}
+
+/// Check that messages published on one topic are received in the order they were published. The
+/// field between brackets is set to a different value in each message.
+#[macro_export]
+macro_rules! t_ordered {
+    (
+        $name:ident :
+        $s_facade:ident.
+        $s:ident $(($($field:ident).+))? $($subscribe_arg:block)? <=
+        $t:ty [ $($varying:ident).+ ] |
+        $p_facade:ident.
+        $p:ident
+        $($publish_arg:block)?
+    ) => {
+        #[test]
+        fn $name() {
+            let (handler_source, handler_receiver) = create_handlers();
+            let source = handler_source.$p_facade();
+            let receiver = handler_receiver.$s_facade();
+            let (tx, rx) = std::sync::mpsc::channel();
+            let tx = std::sync::Mutex::new(tx);
+            use hermes::hermes_utils::Example;
+            let message = <$t>::full_example();
+            receiver
+                .$s($(message.$($field).*.clone(),)?
+                    $($subscribe_arg,)?
+                    hermes::Callback::new(move |o: &$t| {
+                    tx.lock().map(|it| it.send(o.clone())).unwrap().unwrap()
+                }))
+                .unwrap();
+            let messages = (0..20)
+                .map(|i| {
+                    let mut message = message.clone();
+                    message.$($varying).* = format!("message {}", i).into();
+                    message
+                })
+                .collect::<Vec<_>>();
+            std::thread::sleep(WAIT_DURATION);
+            for message in &messages {
+                source.$p($($publish_arg,)? $($subscribe_arg,)? message.clone()).unwrap();
+            }
+            let received = messages
+                .iter()
+                .map(|_| {
+                    rx.recv_timeout(std::time::Duration::from_secs(1))
+                        .expect("didn't receive all the messages after one second")
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(received, messages);
+        }
+    };
+}
+
+/// Check that every subscriber on a topic receives a published message exactly once, whether it
+/// subscribed on the same handler as another subscriber or on the handler publishing the message.
+#[macro_export]
+macro_rules! t_multiple_subscribers {
+    (
+        $name:ident :
+        $s_facade:ident.
+        $s:ident $(($($field:ident).+))? $($subscribe_arg:block)? <=
+        $t:ty |
+        $p_facade:ident.
+        $p:ident
+        $($publish_arg:block)?
+    ) => {
+        #[test]
+        fn $name() {
+            let (handler_source, handler_receiver) = create_handlers();
+            let source = handler_source.$p_facade();
+            let receivers = [
+                handler_receiver.$s_facade(),
+                handler_receiver.$s_facade(),
+                handler_source.$s_facade(),
+            ];
+            use hermes::hermes_utils::Example;
+            let message = <$t>::full_example();
+            let channels = receivers
+                .iter()
+                .map(|receiver| {
+                    let (tx, rx) = std::sync::mpsc::channel();
+                    let tx = std::sync::Mutex::new(tx);
+                    receiver
+                        .$s($(message.$($field).*.clone(),)?
+                            $($subscribe_arg,)?
+                            hermes::Callback::new(move |o: &$t| {
+                            tx.lock().map(|it| it.send(o.clone())).unwrap().unwrap()
+                        }))
+                        .unwrap();
+                    rx
+                })
+                .collect::<Vec<_>>();
+            std::thread::sleep(WAIT_DURATION);
+            source.$p($($publish_arg,)? $($subscribe_arg,)? message.clone()).unwrap();
+            for rx in &channels {
+                $crate::assert_received_once!(rx, message);
+            }
+        }
+    };
+}
+
+/// Check that cancelling a subscription stops its callback without disturbing the other
+/// subscriptions on the same topic, and that the topic can be subscribed to again once all of its
+/// subscriptions are cancelled.
+#[macro_export]
+macro_rules! t_unsubscribed {
+    (
+        $name:ident :
+        $s_facade:ident.
+        $s:ident $(($($field:ident).+))? <=
+        $t:ty |
+        $p_facade:ident.
+        $p:ident
+    ) => {
+        #[test]
+        fn $name() {
+            let (handler_source, handler_receiver) = create_handlers();
+            let source = handler_source.$p_facade();
+            let receiver = handler_receiver.$s_facade();
+            use hermes::hermes_utils::Example;
+            let message = <$t>::full_example();
+            let subscribe = || {
+                let (tx, rx) = std::sync::mpsc::channel();
+                let tx = std::sync::Mutex::new(tx);
+                let callback = hermes::Callback::new(move |o: &$t| {
+                    tx.lock().map(|it| it.send(o.clone())).unwrap().unwrap()
+                });
+                let subscription = callback.subscription();
+                receiver.$s($(message.$($field).*.clone(),)? callback).unwrap();
+                (subscription, rx)
+            };
+
+            let (cancelled, cancelled_rx) = subscribe();
+            let (kept, kept_rx) = subscribe();
+            std::thread::sleep(WAIT_DURATION);
+            cancelled.cancel();
+            std::thread::sleep(WAIT_DURATION);
+            source.$p(message.clone()).unwrap();
+            $crate::assert_received_once!(kept_rx, message);
+            assert!(cancelled_rx.try_recv().is_err(), "received a message after cancelling the subscription");
+
+            kept.cancel();
+            std::thread::sleep(WAIT_DURATION);
+            let (_again, again_rx) = subscribe();
+            std::thread::sleep(WAIT_DURATION);
+            source.$p(message.clone()).unwrap();
+            $crate::assert_received_once!(again_rx, message);
+            assert!(kept_rx.try_recv().is_err(), "received a message after cancelling the subscription");
+            assert!(cancelled_rx.try_recv().is_err(), "received a message after cancelling the subscription");
+        }
+    };
+}
+
+/// Check that site ids, identifiers and intent names holding unicode or characters reserved in
+/// MQTT topics go through: a subscription on each of them receives the message published for it
+/// and only that one.
+#[macro_export]
+macro_rules! t_escaped_identifiers {
+    (
+        $name:ident :
+        $s_facade:ident.
+        $s:ident ($($field:ident).+) <=
+        $t:ty |
+        $p_facade:ident.
+        $p:ident
+    ) => {
+        #[test]
+        fn $name() {
+            let (handler_source, handler_receiver) = create_handlers();
+            let source = handler_source.$p_facade();
+            let receiver = handler_receiver.$s_facade();
+            use hermes::hermes_utils::Example;
+            let messages = ["living/room", "a+b", "#1", "100%", "%2F", "été", "客厅", "🎤"]
+                .iter()
+                .map(|identifier| {
+                    let mut message = <$t>::full_example();
+                    message.$($field).* = identifier.to_string();
+                    message
+                })
+                .collect::<Vec<_>>();
+            let channels = messages
+                .iter()
+                .map(|message| {
+                    let (tx, rx) = std::sync::mpsc::channel();
+                    let tx = std::sync::Mutex::new(tx);
+                    receiver
+                        .$s(message.$($field).*.clone(), hermes::Callback::new(move |o: &$t| {
+                            let _ = tx.lock().map(|it| it.send(o.clone()));
+                        }))
+                        .unwrap();
+                    rx
+                })
+                .collect::<Vec<_>>();
+            std::thread::sleep(WAIT_DURATION);
+            for message in &messages {
+                source.$p(message.clone()).unwrap();
+            }
+            for (rx, message) in channels.iter().zip(&messages) {
+                $crate::assert_received_once!(rx, *message);
+            }
+        }
+    };
+}
+
+/// Check the facades of a handler restricted to a site with `for_site`: the ones receiving only get
+/// the messages of their site, the ones publishing fill in the site of the message. The field
+/// between brackets is the site of the message.
+#[macro_export]
+macro_rules! t_site_scoped {
+    (
+        $name:ident :
+        for_site.
+        $s_facade:ident.
+        $s:ident $($subscribe_arg:block)? <=
+        $t:ty [$($field:ident).+] |
+        $p_facade:ident.
+        $p:ident
+        $($publish_arg:block)?
+    ) => {
+        #[test]
+        fn $name() {
+            use hermes::ForSite;
+            let (handler_source, handler_receiver) = create_handlers();
+            let scoped_receiver = handler_receiver.for_site("kitchen");
+            let source = handler_source.$p_facade();
+            let receiver = scoped_receiver.$s_facade();
+            let (tx, rx) = std::sync::mpsc::channel();
+            let tx = std::sync::Mutex::new(tx);
+            use hermes::hermes_utils::Example;
+            let mut message = <$t>::full_example();
+            message.$($field).* = "kitchen".to_string().into();
+            let mut other_message = message.clone();
+            other_message.$($field).* = "<other site>".to_string().into();
+            receiver
+                .$s($($subscribe_arg,)?
+                    hermes::Callback::new(move |o: &$t| {
+                    tx.lock().map(|it| it.send(o.clone())).unwrap().unwrap()
+                }))
+                .unwrap();
+            std::thread::sleep(WAIT_DURATION);
+            source.$p($($publish_arg,)? other_message).unwrap();
+            source.$p($($publish_arg,)? message.clone()).unwrap();
+            $crate::assert_received_once!(rx, message);
+        }
+    };
+    (
+        $name:ident :
+        $s_facade:ident.
+        $s:ident $($subscribe_arg:block)? <=
+        $t:ty [$($field:ident).+] |
+        for_site.
+        $p_facade:ident.
+        $p:ident
+        $($publish_arg:block)?
+    ) => {
+        #[test]
+        fn $name() {
+            use hermes::ForSite;
+            let (handler_source, handler_receiver) = create_handlers();
+            let scoped_source = handler_source.for_site("kitchen");
+            let source = scoped_source.$p_facade();
+            let receiver = handler_receiver.$s_facade();
+            let (tx, rx) = std::sync::mpsc::channel();
+            let tx = std::sync::Mutex::new(tx);
+            use hermes::hermes_utils::Example;
+            let message = <$t>::full_example();
+            let mut expected = message.clone();
+            expected.$($field).* = "kitchen".to_string().into();
+            receiver
+                .$s($($subscribe_arg,)?
+                    hermes::Callback::new(move |o: &$t| {
+                    tx.lock().map(|it| it.send(o.clone())).unwrap().unwrap()
+                }))
+                .unwrap();
+            std::thread::sleep(WAIT_DURATION);
+            source.$p($($publish_arg,)? message).unwrap();
+            $crate::assert_received_once!(rx, expected);
+        }
+    };
+}
+
+/// Check that the facades of a handler restricted to a site with `for_site` refuse to subscribe to a
+/// message for another site.
+#[macro_export]
+macro_rules! t_site_scoped_other_site {
+    ($name:ident : for_site.$s_facade:ident.$s:ident <= $t:ty) => {
+        #[test]
+        fn $name() {
+            use hermes::ForSite;
+            let (_handler_source, handler_receiver) = create_handlers();
+            let scoped_receiver = handler_receiver.for_site("kitchen");
+            let receiver = scoped_receiver.$s_facade();
+            let subscribed = receiver.$s("<other site>".into(), hermes::Callback::new(|_: &$t| {}));
+            assert!(subscribed.is_err());
+        }
+    };
+}
+
+/// Check the session handles of the dialogue facade: they only receive the messages of their
+/// session, release their callbacks when it ends, can be used from these callbacks and find the id
+/// of the sessions they start, even when several are started with the same custom data.
+#[macro_export]
+macro_rules! t_sessions {
+    ($name:ident) => {
+        mod $name {
+            use super::*;
+            use hermes::hermes_utils::Example;
+            use hermes::DialogueSessions;
+
+            /// A callback sending the messages it receives to the returned receiver
+            fn forward<T: Clone + Send + 'static>() -> (hermes::Callback<T>, std::sync::mpsc::Receiver<T>) {
+                let (tx, rx) = std::sync::mpsc::channel();
+                let tx = std::sync::Mutex::new(tx);
+                let callback = hermes::Callback::new(move |o: &T| {
+                    let _ = tx.lock().map(|it| it.send(o.clone()));
+                });
+                (callback, rx)
+            }
+
+            #[test]
+            fn session_only_receives_its_messages() {
+                let (handler_source, handler_receiver) = create_handlers();
+                let source = handler_source.dialogue_backend();
+                let dialogue: std::sync::Arc<dyn hermes::DialogueFacade> = handler_receiver.dialogue().into();
+                let session = dialogue.session("session").unwrap();
+                let (intents, intent_rx) = forward();
+                let (not_recognized, not_recognized_rx) = forward();
+                session.on_intent(intents).unwrap();
+                session.on_intent_not_recognized(not_recognized).unwrap();
+                std::thread::sleep(WAIT_DURATION);
+                let mut intent = IntentMessage::full_example();
+                intent.session_id = "other session".into();
+                source.publish_intent(intent.clone()).unwrap();
+                intent.session_id = "session".into();
+                source.publish_intent(intent.clone()).unwrap();
+                $crate::assert_received_once!(intent_rx, intent);
+                let mut message = IntentNotRecognizedMessage::full_example();
+                message.session_id = "other session".into();
+                source.publish_intent_not_recognized(message.clone()).unwrap();
+                message.session_id = "session".into();
+                source.publish_intent_not_recognized(message.clone()).unwrap();
+                $crate::assert_received_once!(not_recognized_rx, message);
+            }
+
+            #[test]
+            fn session_is_released_when_it_ends() {
+                let (handler_source, handler_receiver) = create_handlers();
+                let source = handler_source.dialogue_backend();
+                let dialogue: std::sync::Arc<dyn hermes::DialogueFacade> = handler_receiver.dialogue().into();
+                let session = dialogue.session("session").unwrap();
+                let (intents, intent_rx) = forward();
+                let (ended, ended_rx) = forward();
+                session.on_intent(intents).unwrap();
+                session.on_ended(ended).unwrap();
+                std::thread::sleep(WAIT_DURATION);
+                let mut session_ended = SessionEndedMessage::full_example();
+                session_ended.session_id = "session".into();
+                source.publish_session_ended(session_ended.clone()).unwrap();
+                $crate::assert_received_once!(ended_rx, session_ended);
+                assert!(session.has_ended());
+                let mut intent = IntentMessage::full_example();
+                intent.session_id = "session".into();
+                source.publish_intent(intent).unwrap();
+                let result = intent_rx.recv_timeout(WAIT_DURATION + std::time::Duration::from_millis(100));
+                assert!(result.is_err(), "received a message after the end of the session");
+                assert!(session.on_intent(forward().0).is_err());
+                assert!(session.end(EndSessionMessage::full_example()).is_err());
+            }
+
+            #[test]
+            fn session_is_continued_and_ended_from_its_callbacks() {
+                let (handler_source, handler_receiver) = create_handlers();
+                let dialogue_manager = handler_source.dialogue_backend();
+                let dialogue: std::sync::Arc<dyn hermes::DialogueFacade> = handler_receiver.dialogue().into();
+                let (continue_sessions, continue_rx) = forward();
+                let (end_sessions, end_rx) = forward();
+                dialogue_manager
+                    .subscribe_continue_session(continue_sessions)
+                    .unwrap();
+                dialogue_manager.subscribe_end_session(end_sessions).unwrap();
+                let session = dialogue.session("session").unwrap();
+                let handle = session.clone();
+                session
+                    .on_intent(hermes::Callback::new(move |intent: &IntentMessage| {
+                        if intent.input == "continue" {
+                            handle
+                                .continue_with(ContinueSessionMessage::full_example())
+                                .unwrap()
+                        } else {
+                            handle.end(EndSessionMessage::full_example()).unwrap()
+                        }
+                    }))
+                    .unwrap();
+                drop(session);
+                std::thread::sleep(WAIT_DURATION);
+                let mut intent = IntentMessage::full_example();
+                intent.session_id = "session".into();
+                intent.input = "continue".into();
+                dialogue_manager.publish_intent(intent.clone()).unwrap();
+                let continue_session: ContinueSessionMessage = continue_rx
+                    .recv_timeout(std::time::Duration::from_secs(1))
+                    .expect("didn't receive the continue session message after one second");
+                assert_eq!(continue_session.session_id, "session");
+                intent.input = "end".into();
+                dialogue_manager.publish_intent(intent).unwrap();
+                let end_session: EndSessionMessage = end_rx
+                    .recv_timeout(std::time::Duration::from_secs(1))
+                    .expect("didn't receive the end session message after one second");
+                assert_eq!(end_session.session_id, "session");
+            }
+
+            #[test]
+            fn tracked_session_finds_its_id() {
+                let (handler_source, handler_receiver) = create_handlers();
+                let dialogue_manager = handler_source.dialogue_backend();
+                let dialogue: std::sync::Arc<dyn hermes::DialogueFacade> = handler_receiver.dialogue().into();
+                let (start_sessions, start_rx) = forward();
+                let (continue_sessions, continue_rx) = forward();
+                dialogue_manager.subscribe_start_session(start_sessions).unwrap();
+                dialogue_manager
+                    .subscribe_continue_session(continue_sessions)
+                    .unwrap();
+                std::thread::sleep(WAIT_DURATION);
+                let mut start_session = StartSessionMessage::full_example();
+                start_session.custom_data = None;
+                let session = dialogue.start_session_and_track(start_session).unwrap();
+                assert!(session
+                    .continue_with(ContinueSessionMessage::full_example())
+                    .is_err());
+                let start_session: StartSessionMessage = start_rx
+                    .recv_timeout(std::time::Duration::from_secs(1))
+                    .expect("didn't receive the start session message after one second");
+                assert!(start_session.custom_data.is_some());
+                let mut other = SessionStartedMessage::full_example();
+                other.session_id = "other session".into();
+                other.custom_data = Some("other custom data".into());
+                dialogue_manager.publish_session_started(other).unwrap();
+                let mut queued = SessionQueuedMessage::full_example();
+                queued.session_id = "tracked session".into();
+                queued.custom_data = start_session.custom_data.clone();
+                dialogue_manager.publish_session_queued(queued).unwrap();
+                let deadline = std::time::Instant::now() + std::time::Duration::from_secs(1);
+                while session.session_id().is_none() && std::time::Instant::now() < deadline {
+                    std::thread::sleep(std::time::Duration::from_millis(10));
+                }
+                assert_eq!(session.session_id(), Some("tracked session".to_string()));
+                session
+                    .continue_with(ContinueSessionMessage::full_example())
+                    .unwrap();
+                let continue_session: ContinueSessionMessage = continue_rx
+                    .recv_timeout(std::time::Duration::from_secs(1))
+                    .expect("didn't receive the continue session message after one second");
+                assert_eq!(continue_session.session_id, "tracked session");
+            }
+
+            #[test]
+            fn tracked_sessions_with_the_same_custom_data_are_told_apart() {
+                let (handler_source, handler_receiver) = create_handlers();
+                let dialogue_manager = handler_source.dialogue_backend();
+                let dialogue: std::sync::Arc<dyn hermes::DialogueFacade> = handler_receiver.dialogue().into();
+                let (start_sessions, start_rx) = forward();
+                dialogue_manager.subscribe_start_session(start_sessions).unwrap();
+                std::thread::sleep(WAIT_DURATION);
+                let mut start_session = StartSessionMessage::full_example();
+                start_session.custom_data = Some("custom data".into());
+                let first = dialogue.start_session_and_track(start_session.clone()).unwrap();
+                let second = dialogue.start_session_and_track(start_session).unwrap();
+                let (intents, intent_rx) = forward();
+                first.on_intent(intents).unwrap();
+                let mut published = vec![];
+                for _ in 0..2 {
+                    let start_session: StartSessionMessage = start_rx
+                        .recv_timeout(std::time::Duration::from_secs(1))
+                        .expect("didn't receive the start session message after one second");
+                    published.push(start_session.custom_data);
+                }
+                assert_ne!(published[0], published[1]);
+                let mut started = SessionStartedMessage::full_example();
+                started.session_id = "first session".into();
+                started.custom_data = published[0].clone();
+                dialogue_manager.publish_session_started(started).unwrap();
+                let deadline = std::time::Instant::now() + std::time::Duration::from_secs(1);
+                while first.session_id().is_none() && std::time::Instant::now() < deadline {
+                    std::thread::sleep(std::time::Duration::from_millis(10));
+                }
+                assert_eq!(first.session_id(), Some("first session".to_string()));
+                assert_eq!(second.session_id(), None);
+                let mut intent = IntentMessage::full_example();
+                intent.session_id = "first session".into();
+                intent.custom_data = published[0].clone();
+                dialogue_manager.publish_intent(intent.clone()).unwrap();
+                intent.custom_data = Some("custom data".into());
+                $crate::assert_received_once!(intent_rx, intent);
+            }
+        }
+    };
+}
+
+/// Check `subscribe_intents_matching`: the intents matching the pattern are received, in the order
+/// they were published, and the others are not. The MQTT wildcards are matched as any other
+/// character in intent names, and rejected when given alone.
+#[macro_export]
+macro_rules! t_intent_patterns {
+    ($name:ident) => {
+        mod $name {
+            use super::*;
+            use hermes::hermes_utils::Example;
+            use hermes::DialogueIntentPatterns;
+
+            fn check(pattern: &str, matching: &[&str], not_matching: &[&str]) {
+                let (handler_source, handler_receiver) = create_handlers();
+                let source = handler_source.dialogue_backend();
+                let receiver = handler_receiver.dialogue();
+                let (tx, rx) = std::sync::mpsc::channel();
+                let tx = std::sync::Mutex::new(tx);
+                receiver
+                    .subscribe_intents_matching(
+                        pattern.to_string(),
+                        hermes::Callback::new(move |o: &IntentMessage| {
+                            let _ = tx.lock().map(|it| it.send(o.intent.intent_name.clone()));
+                        }),
+                    )
+                    .unwrap();
+                std::thread::sleep(WAIT_DURATION);
+                for intent_name in not_matching.iter().chain(matching) {
+                    let mut intent = IntentMessage::full_example();
+                    intent.intent.intent_name = intent_name.to_string();
+                    source.publish_intent(intent).unwrap();
+                }
+                for intent_name in matching {
+                    let result = rx.recv_timeout(std::time::Duration::from_secs(1));
+                    assert!(
+                        result.is_ok(),
+                        "didn't receive {} with {} after one second",
+                        intent_name,
+                        pattern
+                    );
+                    assert_eq!(
+                        &result.unwrap(),
+                        intent_name,
+                        "received an intent that should have been filtered out"
+                    );
+                }
+                let result = rx.recv_timeout(WAIT_DURATION + std::time::Duration::from_millis(100));
+                assert!(
+                    result.is_err(),
+                    "received an intent that should have been filtered out"
+                );
+            }
+
+            #[test]
+            fn exact_name_works() {
+                check(
+                    "user:lightsOn",
+                    &["user:lightsOn"],
+                    &["user:lightsOnAgain", "user:lights"],
+                );
+            }
+
+            #[test]
+            fn all_works() {
+                check("*", &["user:lightsOn", "weather"], &[]);
+            }
+
+            #[test]
+            fn prefix_works() {
+                check(
+                    "weather*",
+                    &["weather", "weatherForecast"],
+                    &["userWeather", "forecast"],
+                );
+            }
+
+            #[test]
+            fn namespace_works() {
+                check(
+                    "user:*",
+                    &["user:lightsOn", "user:lightsOff"],
+                    &["other:lightsOn", "user"],
+                );
+            }
+
+            #[test]
+            fn wildcards_anywhere_work() {
+                check(
+                    "*:lights??",
+                    &["user:lightsOn", "other:lightsUp"],
+                    &["user:lightsOff", "user:lightOn"],
+                );
+            }
+
+            #[test]
+            fn names_with_mqtt_wildcards_work() {
+                check("a+b", &["a+b"], &["ab", "a+bc", "a#b"]);
+                check("a#b", &["a#b"], &["ab", "a#bc", "a+b"]);
+                check("a+*", &["a+", "a+b"], &["ab", "a#b"]);
+                check("*#", &["a#", "#b#"], &["a", "#b"]);
+            }
+
+            #[test]
+            fn lone_mqtt_wildcards_are_rejected() {
+                let (_, handler_receiver) = create_handlers();
+                let receiver = handler_receiver.dialogue();
+                for name in &["+", "#"] {
+                    let result =
+                        receiver.subscribe_intent(name.to_string(), hermes::Callback::new(|_: &IntentMessage| ()));
+                    assert!(result.is_err(), "subscribed to the intent {}", name);
+                    let result = receiver
+                        .subscribe_intents_matching(name.to_string(), hermes::Callback::new(|_: &IntentMessage| ()));
+                    assert!(result.is_err(), "subscribed to the intents matching {}", name);
+                }
+            }
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! assert_received_once {
+    ($rx:ident) => {
+        let result = $rx.recv_timeout(std::time::Duration::from_secs(1));
+        assert!(result.is_ok(), "didn't receive message after one second");
+        let result = $rx.recv_timeout(WAIT_DURATION + std::time::Duration::from_millis(100));
+        assert!(
+            result.is_err(),
+            "received the message more than once, or a message that should have been filtered out"
+        );
+    };
+    ($rx:ident, $expected:expr) => {
+        let result = $rx.recv_timeout(std::time::Duration::from_secs(1));
+        assert!(result.is_ok(), "didn't receive message after one second");
+        assert_eq!(
+            result.unwrap(),
+            $expected,
+            "received a message that should have been filtered out"
+        );
+        let result = $rx.recv_timeout(WAIT_DURATION + std::time::Duration::from_millis(100));
+        assert!(
+            result.is_err(),
+            "received the message more than once, or a message that should have been filtered out"
+        );
+    };
+}
+
 #[macro_export]
 macro_rules! t_toggleable {
     ($name:ident : $f_back:ident | $f:ident) => {
@@ -92,9 +841,19 @@ macro_rules! t_identifiable_toggleable {
             mod $name {
                 use super::*;
                 t!(toggle_on_works:
-                        $f_back.subscribe_toggle_on <= SiteMessage | $f.publish_toggle_on);
+                        $f_back.subscribe_toggle_on(site_id) <= SiteMessage | $f.publish_toggle_on);
                 t!(toggle_off_works:
-                        $f_back.subscribe_toggle_off <= SiteMessage | $f.publish_toggle_off);
+                        $f_back.subscribe_toggle_off(site_id) <= SiteMessage | $f.publish_toggle_off);
+                t!(all_toggle_on_works:
+                        $f_back.subscribe_all_toggle_on <= SiteMessage | $f.publish_toggle_on);
+                t!(all_toggle_off_works:
+                        $f_back.subscribe_all_toggle_off <= SiteMessage | $f.publish_toggle_off);
+                t_not_received!(toggle_on_is_isolated:
+                        $f_back.subscribe_toggle_on(site_id) <= SiteMessage { "<other site>" } | $f.publish_toggle_on);
+                t_not_received!(toggle_off_is_isolated:
+                        $f_back.subscribe_toggle_off(site_id) <= SiteMessage { "<other site>" } | $f.publish_toggle_off);
+                t_escaped_identifiers!(toggle_on_escaped_site_ids:
+                        $f_back.subscribe_toggle_on(site_id) <= SiteMessage | $f.publish_toggle_on);
             }
         };
     }
@@ -133,10 +892,120 @@ macro_rules! t_identifiable_component {
                         $f.subscribe_component_loaded { "identifier".to_string() } <= ComponentLoadedOnSiteMessage | $f_back.publish_component_loaded );
                 t!(components_loaded_works:
                         $f.subscribe_all_component_loaded <= ComponentLoadedOnSiteMessage | $f_back.publish_component_loaded { "site_id".into() });
+                $crate::t_not_received!(version_request_is_isolated:
+                        $f_back.subscribe_version_request { "identifier".to_string() } <= $f.publish_version_request { "other_identifier".to_string() });
+                $crate::t_not_received!(version_is_isolated:
+                        $f.subscribe_version { "identifier".to_string() } <= VersionMessage | $f_back.publish_version { "other_identifier".to_string() });
+                $crate::t_not_received!(error_is_isolated:
+                        $f.subscribe_error { "identifier".to_string() } <= SiteErrorMessage | $f_back.publish_error { "other_identifier".to_string() });
             }
         };
     }
 
+/// Generate the tests of the messages of the protocol description, it is given to
+/// `hermes::hermes_protocol!` by `test_suite!`. For each message, the suite checks that it is
+/// delivered and, when it is subscribed to by site, identifier or intent name, that the
+/// subscription ignores the messages of another one and that subscribing to all of them works.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! protocol_tests {
+    ($(
+        $component:ident {
+            facade $facade_doc:tt $facade:ident $facade_supers:tt $facade_getter:ident;
+            backend $backend_doc:tt $backend:ident $backend_supers:tt $backend_getter:ident;
+            mqtt $mqtt_component:tt $mqtt_toggle:tt $mqtt_topic:tt;
+            messages [$({ $attributes:tt $side:ident $message:ident $route:tt $($rest:tt)* })*];
+        }
+    )*) => {
+        $(
+            $crate::__component_tests!($facade_getter $backend_getter $({ $side $($rest)* })*);
+        )*
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __component_tests {
+    ($facade:ident $backend:ident) => {};
+    ($facade:ident $backend:ident $({ $side:ident $($message:tt)* })+) => {
+        mod $facade {
+            use super::*;
+
+            $($crate::__message_tests!($side $facade $backend $($message)*);)+
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __message_tests {
+    (frontend $facade:ident $backend:ident $($message:tt)*) => {
+        $crate::__message_tests!(@ $backend <= $facade $($message)*);
+    };
+    (backend $facade:ident $backend:ident $($message:tt)*) => {
+        $crate::__message_tests!(@ $facade <= $backend $($message)*);
+    };
+    (@ $receiver:ident <= $source:ident [] $publish:ident $subscribe:ident $($rest:tt)*) => {
+        mod $subscribe {
+            use super::*;
+
+            $crate::t!(works: $receiver.$subscribe <= $source.$publish);
+        }
+    };
+    (
+        @ $receiver:ident <= $source:ident
+        [$payload:ident: $payload_type:ty] $publish:ident $subscribe:ident [] $($rest:tt)*
+    ) => {
+        mod $subscribe {
+            use super::*;
+
+            $crate::t!(works: $receiver.$subscribe <= $payload_type | $source.$publish);
+        }
+    };
+    (
+        @ $receiver:ident <= $source:ident
+        [$payload:ident: $payload_type:ty] $publish:ident $subscribe:ident [$key:ident] $all:tt $($rest:tt)*
+    ) => {
+        mod $subscribe {
+            use super::*;
+
+            $crate::t!(works:
+                $receiver.$subscribe { "identifier".into() } <= $payload_type | $source.$publish);
+            $crate::t_not_received!(is_isolated:
+                $receiver.$subscribe { "identifier".into() } <= $payload_type |
+                $source.$publish { "other_identifier".into() });
+            $crate::__subscribe_all_test!($receiver <= $source $payload_type, $publish { "identifier".into() } $all);
+        }
+    };
+    (
+        @ $receiver:ident <= $source:ident
+        [$payload:ident: $payload_type:ty] $publish:ident $subscribe:ident
+        [$key:ident in $($key_path:ident).+] $all:tt $($rest:tt)*
+    ) => {
+        mod $subscribe {
+            use super::*;
+
+            $crate::t!(works: $receiver.$subscribe($($key_path).+) <= $payload_type | $source.$publish);
+            $crate::t_not_received!(is_isolated:
+                $receiver.$subscribe($($key_path).+) <= $payload_type { concat!("<other ", stringify!($key), ">") } |
+                $source.$publish);
+            $crate::__subscribe_all_test!($receiver <= $source $payload_type, $publish $all);
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __subscribe_all_test {
+    ($receiver:ident <= $source:ident $payload_type:ty, $publish:ident $($publish_arg:block)? []) => {};
+    (
+        $receiver:ident <= $source:ident $payload_type:ty, $publish:ident $($publish_arg:block)?
+        [$subscribe_all:ident $($wildcard:tt)*]
+    ) => {
+        $crate::t!(all_works: $receiver.$subscribe_all <= $payload_type | $source.$publish $($publish_arg)?);
+    };
+}
+
 #[macro_export]
 macro_rules! test_suite {
     () => {
@@ -144,118 +1013,104 @@ macro_rules! test_suite {
     };
 
     (WAIT_DURATION = $wait_duration:expr) => {
-        use $crate::{t, t_identifiable_component, t_identifiable_toggleable, t_component, t_toggleable};
+        use $crate::{
+            protocol_tests, t, t_component, t_escaped_identifiers, t_identifiable_component, t_identifiable_toggleable,
+            t_intent_patterns, t_multiple_subscribers, t_not_received, t_ordered, t_sessions, t_site_scoped,
+            t_site_scoped_other_site, t_toggleable, t_unsubscribed,
+        };
         use snips_nlu_ontology::Slot;
 
         const WAIT_DURATION: std::time::Duration = std::time::Duration::from_millis($wait_duration);
 
+        hermes::hermes_protocol!(protocol_tests);
+
         t_identifiable_component!(voice_activity_identifiable_component: voice_activity_backend | voice_activity);
-        t!(voice_activity_vad_up_works:
+        t_multiple_subscribers!(voice_activity_vad_up_multiple_subscribers:
+                    voice_activity.subscribe_vad_up(site_id) <= VadUpMessage | voice_activity_backend.publish_vad_up);
+        t_escaped_identifiers!(voice_activity_vad_up_escaped_site_ids:
                     voice_activity.subscribe_vad_up(site_id) <= VadUpMessage | voice_activity_backend.publish_vad_up);
-        t!(voice_activity_vad_down_works:
-                    voice_activity.subscribe_vad_down(site_id) <= VadDownMessage | voice_activity_backend.publish_vad_down);
-        t!(voice_activity_all_vad_up_works:
-                    voice_activity.subscribe_all_vad_up <= VadUpMessage | voice_activity_backend.publish_vad_up);
-        t!(voice_activity_all_vad_down_works:
-                    voice_activity.subscribe_all_vad_down <= VadDownMessage | voice_activity_backend.publish_vad_down);
 
         t_identifiable_component!(hotword_identifiable_component: hotword_backend | hotword);
         t_identifiable_toggleable!(hotword_identifiable_toggleable: hotword_backend | hotword);
-        t!(hotword_detected_works:
+        t_multiple_subscribers!(hotword_detected_multiple_subscribers:
                     hotword.subscribe_detected { "hotword_identifier".into() } <= HotwordDetectedMessage | hotword_backend.publish_detected);
-        t!(hotword_all_detected_works:
-
-                    hotword.subscribe_all_detected <= HotwordDetectedMessage | hotword_backend.publish_detected { "hotword_identifier".into() });
+        t_site_scoped!(hotword_detected_for_site:
+                    for_site.hotword.subscribe_all_detected <= HotwordDetectedMessage [site_id] | hotword_backend.publish_detected { "hotword_identifier".into() });
+        t_site_scoped!(hotword_all_error_for_site:
+                    for_site.hotword.subscribe_all_error <= SiteErrorMessage [site_id] | hotword_backend.publish_error { "hotword_identifier".into() });
+        t_site_scoped!(hotword_error_for_site:
+                    hotword.subscribe_all_error <= SiteErrorMessage [site_id] | for_site.hotword_backend.publish_error { "hotword_identifier".into() });
+        t_site_scoped!(hotword_component_loaded_for_site:
+                    hotword.subscribe_all_component_loaded <= ComponentLoadedOnSiteMessage [site_id] | for_site.hotword_backend.publish_component_loaded { "hotword_identifier".into() });
 
         t_identifiable_toggleable!(sound_feedback_identifiable_toggleable: sound_feedback_backend | sound_feedback );
+        t_site_scoped!(sound_feedback_toggle_on_for_site:
+                    sound_feedback_backend.subscribe_toggle_on { "kitchen".into() } <= SiteMessage [site_id] | for_site.sound_feedback.publish_toggle_on);
 
         t_component!(asr_component: asr_backend | asr);
         t_toggleable!(asr_toggleable: asr_backend | asr);
-        t!(asr_text_captured_works:
-                    asr.subscribe_text_captured <= TextCapturedMessage | asr_backend.publish_text_captured);
-        t!(asr_partial_text_captured_works:
-                    asr.subscribe_partial_text_captured <= TextCapturedMessage | asr_backend.publish_partial_text_captured);
-        t!(asr_start_listening:
-                    asr_backend.subscribe_start_listening <= AsrStartListeningMessage | asr.publish_start_listening);
-        t!(asr_stop_listening:
-                    asr_backend.subscribe_stop_listening <= SiteMessage | asr.publish_stop_listening);
-        t!(asr_reload:
-                    asr_backend.subscribe_component_reload <= RequestComponentReloadMessage | asr.publish_component_reload );
+        t_ordered!(asr_text_captured_is_ordered:
+                    asr.subscribe_text_captured(site_id) <= TextCapturedMessage [text] | asr_backend.publish_text_captured);
+        t_multiple_subscribers!(asr_text_captured_multiple_subscribers:
+                    asr.subscribe_text_captured(site_id) <= TextCapturedMessage | asr_backend.publish_text_captured);
+        t_unsubscribed!(asr_text_captured_unsubscribed:
+                    asr.subscribe_text_captured(site_id) <= TextCapturedMessage | asr_backend.publish_text_captured);
 
         t_component!(tts_component: tts_backend | tts);
-        t!(tts_say_works:
-                    tts_backend.subscribe_say <= SayMessage | tts.publish_say);
-        t!(tts_say_finished_works:
-                    tts.subscribe_say_finished <= SayFinishedMessage | tts_backend.publish_say_finished);
-        t!(tts_register_sound_works:
-                    tts_backend.subscribe_register_sound <= RegisterSoundMessage | tts.publish_register_sound);
+        t_ordered!(tts_say_is_ordered:
+                    tts_backend.subscribe_say(site_id) <= SayMessage [text] | tts.publish_say);
+        t_escaped_identifiers!(tts_say_escaped_site_ids:
+                    tts_backend.subscribe_say(site_id) <= SayMessage | tts.publish_say);
+        t_site_scoped!(tts_say_for_site:
+                    tts_backend.subscribe_say { "kitchen".into() } <= SayMessage [site_id] | for_site.tts.publish_say);
+        t_site_scoped!(tts_all_say_for_site:
+                    for_site.tts_backend.subscribe_all_say <= SayMessage [site_id] | tts.publish_say);
+        t_site_scoped_other_site!(tts_say_other_site: for_site.tts_backend.subscribe_say <= SayMessage);
 
         t_component!(nlu_component: nlu_backend | nlu);
-        t!(nlu_query_works:
-                    nlu_backend.subscribe_query <= NluQueryMessage | nlu.publish_query);
-        t!(nlu_partial_query_works:
-                    nlu_backend.subscribe_partial_query <= NluSlotQueryMessage | nlu.publish_partial_query);
-        t!(nlu_slot_parsed_works:
-                    nlu.subscribe_slot_parsed <= NluSlotMessage | nlu_backend.publish_slot_parsed);
-        t!(nlu_intent_parsed_works:
-                    nlu.subscribe_intent_parsed <= NluIntentMessage | nlu_backend.publish_intent_parsed);
-        t!(nlu_intent_not_recognized_works:
-                    nlu.subscribe_intent_not_recognized <= NluIntentNotRecognizedMessage | nlu_backend.publish_intent_not_recognized);
-        t!(nlu_reload:
-                    nlu_backend.subscribe_component_reload <= RequestComponentReloadMessage | nlu.publish_component_reload );
+        t_ordered!(nlu_query_is_ordered:
+                    nlu_backend.subscribe_query <= NluQueryMessage [input] | nlu.publish_query);
 
         t_identifiable_component!(audio_server_component: audio_server_backend | audio_server);
         t_identifiable_toggleable!(audio_server_toggeable: audio_server_backend | audio_server);
-        t!(audio_server_play_bytes_works:
+        t_ordered!(audio_server_audio_frame_is_ordered:
+                    audio_server.subscribe_audio_frame(site_id) <= AudioFrameMessage [wav_frame] | audio_server_backend.publish_audio_frame);
+        t_ordered!(audio_server_play_bytes_is_ordered:
+                    audio_server_backend.subscribe_play_bytes(site_id) <= PlayBytesMessage [wav_bytes] | audio_server.publish_play_bytes);
+        t_escaped_identifiers!(audio_server_play_bytes_escaped_site_ids:
                     audio_server_backend.subscribe_play_bytes(site_id) <= PlayBytesMessage | audio_server.publish_play_bytes);
-        t!(audio_server_play_all_bytes_works:
-                    audio_server_backend.subscribe_all_play_bytes <= PlayBytesMessage | audio_server.publish_play_bytes);
-        t!(audio_server_play_finished_works:
-                    audio_server.subscribe_play_finished(site_id) <= PlayFinishedMessage | audio_server_backend.publish_play_finished);
-        t!(audio_server_all_play_finished_works:
-                    audio_server.subscribe_all_play_finished <= PlayFinishedMessage | audio_server_backend.publish_play_finished);
-        t!(audio_server_audio_frame_works:
+        t_ordered!(audio_server_stream_bytes_is_ordered:
+                    audio_server_backend.subscribe_stream_bytes(site_id) <= StreamBytesMessage [bytes] | audio_server.publish_stream_bytes);
+        t_multiple_subscribers!(audio_server_audio_frame_multiple_subscribers:
                     audio_server.subscribe_audio_frame(site_id) <= AudioFrameMessage | audio_server_backend.publish_audio_frame);
-        t!(audio_server_replay_request:
-                    audio_server_backend.subscribe_replay_request(site_id) <= ReplayRequestMessage | audio_server.publish_replay_request);
-        t!(audio_server_replay_response:
-                    audio_server.subscribe_replay_response(site_id) <= AudioFrameMessage | audio_server_backend.publish_replay_response);
+        t_site_scoped!(audio_server_all_toggle_on_for_site:
+                    for_site.audio_server_backend.subscribe_all_toggle_on <= SiteMessage [site_id] | audio_server.publish_toggle_on);
+        t_site_scoped_other_site!(audio_server_toggle_on_other_site:
+                    for_site.audio_server_backend.subscribe_toggle_on <= SiteMessage);
 
         t_component!(dialogue_component: dialogue_backend | dialogue);
         t_toggleable!(dialogue_toggleable: dialogue_backend | dialogue);
-        t!(dialogue_session_started_works:
-                    dialogue.subscribe_session_started <= SessionStartedMessage | dialogue_backend.publish_session_started);
-        t!(dialogue_session_queued_works:
-                    dialogue.subscribe_session_queued <= SessionQueuedMessage | dialogue_backend.publish_session_queued);
-        t!(dialogue_intents_works:
+        t_ordered!(dialogue_intents_is_ordered:
+                    dialogue.subscribe_intents <= IntentMessage [input] | dialogue_backend.publish_intent);
+        t_multiple_subscribers!(dialogue_intents_multiple_subscribers:
                     dialogue.subscribe_intents <= IntentMessage | dialogue_backend.publish_intent);
-        t!(dialogue_intent_works:
+        t_unsubscribed!(dialogue_intents_unsubscribed:
+                    dialogue.subscribe_intents <= IntentMessage | dialogue_backend.publish_intent);
+        t_multiple_subscribers!(dialogue_intent_multiple_subscribers:
+                    dialogue.subscribe_intent(intent.intent_name) <= IntentMessage | dialogue_backend.publish_intent);
+        t_escaped_identifiers!(dialogue_intent_escaped_intent_names:
                     dialogue.subscribe_intent(intent.intent_name) <= IntentMessage | dialogue_backend.publish_intent);
-        t!(dialogue_intent_not_recognized_works:
-                    dialogue.subscribe_intent_not_recognized <= IntentNotRecognizedMessage | dialogue_backend.publish_intent_not_recognized);
-        t!(dialogue_session_ended_works:
-                    dialogue.subscribe_session_ended <= SessionEndedMessage | dialogue_backend.publish_session_ended);
-        t!(dialogue_start_session_works:
-                    dialogue_backend.subscribe_start_session <= StartSessionMessage | dialogue.publish_start_session);
-        t!(dialogue_continue_session_works:
-                    dialogue_backend.subscribe_continue_session <= ContinueSessionMessage | dialogue.publish_continue_session);
-        t!(dialogue_end_session_works:
-                    dialogue_backend.subscribe_end_session <= EndSessionMessage | dialogue.publish_end_session);
-        t!(dialogue_configure_works:
-                    dialogue_backend.subscribe_configure <= DialogueConfigureMessage | dialogue.publish_configure);
+        t_escaped_identifiers!(dialogue_session_started_escaped_site_ids:
+                    dialogue.subscribe_session_started(site_id) <= SessionStartedMessage | dialogue_backend.publish_session_started);
+        t_ordered!(dialogue_continue_session_is_ordered:
+                    dialogue_backend.subscribe_continue_session <= ContinueSessionMessage [text] | dialogue.publish_continue_session);
+        t_site_scoped!(dialogue_start_session_for_site:
+                    dialogue_backend.subscribe_start_session <= StartSessionMessage [site_id] | for_site.dialogue.publish_start_session);
+        t_site_scoped!(dialogue_intents_for_site:
+                    for_site.dialogue.subscribe_intents <= IntentMessage [site_id] | dialogue_backend.publish_intent);
+        t_sessions!(dialogue_sessions);
+        t_intent_patterns!(dialogue_intent_patterns);
 
         t_component!(injection_component: injection_backend | injection);
-        t!(injection_request:
-                    injection_backend.subscribe_injection_request <= InjectionRequestMessage | injection.publish_injection_request);
-        t!(injection_status_request:
-                    injection_backend.subscribe_injection_status_request <= injection.publish_injection_status_request);
-        t!(injection_status:
-                    injection.subscribe_injection_status <= InjectionStatusMessage | injection_backend.publish_injection_status);
-        t!(injection_complete:
-                    injection.subscribe_injection_complete <= InjectionCompleteMessage | injection_backend.publish_injection_complete);
-        t!(injection_reset_request:
-                    injection_backend.subscribe_injection_reset_request <= InjectionResetRequestMessage | injection.publish_injection_reset_request);
-        t!(injection_reset_complete:
-                    injection.subscribe_injection_reset_complete <= InjectionResetCompleteMessage | injection_backend.publish_injection_reset_complete);
     };
 }
@@ -0,0 +1,431 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+
+use quote::{format_ident, quote};
+
+const PRIMITIVES: &[&str] = &[
+    "bool",
+    "f32",
+    "f64",
+    "i8",
+    "i16",
+    "i32",
+    "i64",
+    "isize",
+    "u8",
+    "u16",
+    "u32",
+    "u64",
+    "usize",
+    "c_char",
+    "c_schar",
+    "c_uchar",
+    "c_short",
+    "c_ushort",
+    "c_int",
+    "c_uint",
+    "c_long",
+    "c_ulong",
+    "c_longlong",
+    "c_ulonglong",
+    "c_float",
+    "c_double",
+];
+
+pub fn impl_c_repr_macro(input: &syn::DeriveInput) -> TokenStream {
+    let target_type = find_attribute(&input.attrs, "target_type")
+        .map(|attr| attr.parse_args::<syn::Type>().expect("target_type should be a type"))
+        .expect("CRepr needs a #[target_type(..)] attribute");
+
+    match &input.data {
+        syn::Data::Struct(data) if find_attribute(&input.attrs, "c_array").is_some() => {
+            impl_array(&input.ident, &target_type, data)
+        }
+        syn::Data::Struct(data) => impl_struct(&input.ident, &target_type, data),
+        syn::Data::Enum(data) => impl_enum(&input.ident, &target_type, data),
+        syn::Data::Union(_) => panic!("CRepr can't be derived for unions"),
+    }
+}
+
+fn find_attribute<'a>(attrs: &'a [syn::Attribute], name: &str) -> Option<&'a syn::Attribute> {
+    attrs.iter().find(|attr| attr.path.is_ident(name))
+}
+
+fn pointee(ty: &syn::Type) -> Option<&syn::Type> {
+    match ty {
+        syn::Type::Ptr(ptr) if ptr.mutability.is_none() => Some(&ptr.elem),
+        _ => None,
+    }
+}
+
+fn last_segment(ty: &syn::Type) -> Option<String> {
+    match ty {
+        syn::Type::Path(path) => path.path.segments.last().map(|it| it.ident.to_string()),
+        _ => None,
+    }
+}
+
+fn is_primitive(ty: &syn::Type) -> bool {
+    last_segment(ty).map_or(false, |it| PRIMITIVES.contains(&it.as_str()))
+}
+
+fn field_ident(field: &syn::Field) -> &syn::Ident {
+    field
+        .ident
+        .as_ref()
+        .expect("CRepr can only be derived for structs with named fields")
+}
+
+/// Whether the field `ident` holds the length of a `#[c_bytes]` field
+fn is_length(ident: &syn::Ident, fields: &syn::Fields) -> bool {
+    fields.iter().any(|other| {
+        find_attribute(&other.attrs, "c_bytes")
+            .and_then(|attr| attr.parse_args::<syn::Ident>().ok())
+            .map_or(false, |len| &len == ident)
+    })
+}
+
+/// The conversions of a field, as the parts of the `c_repr_of` and `as_rust` struct literals and
+/// the statements of `drop`
+struct FieldConversion {
+    c_repr: TokenStream2,
+    as_rust: TokenStream2,
+    drop: TokenStream2,
+}
+
+fn convert_field(field: &syn::Field, fields: &syn::Fields) -> FieldConversion {
+    let ident = field_ident(field);
+    let ty = &field.ty;
+    let nullable = find_attribute(&field.attrs, "nullable").is_some();
+    let null_if_empty = find_attribute(&field.attrs, "null_if_empty").is_some();
+
+    match (
+        find_attribute(&field.attrs, "c_repr_with"),
+        find_attribute(&field.attrs, "as_rust_with"),
+    ) {
+        (Some(c_repr_with), Some(as_rust_with)) => {
+            let c_repr_with = c_repr_with
+                .parse_args::<syn::Path>()
+                .expect("c_repr_with should be a path");
+            let as_rust_with = as_rust_with
+                .parse_args::<syn::Path>()
+                .expect("as_rust_with should be a path");
+            return FieldConversion {
+                c_repr: quote!(#ident: #c_repr_with(input.#ident)?),
+                as_rust: quote!(#ident: #as_rust_with(&self.#ident)?),
+                drop: quote!(),
+            };
+        }
+        (None, None) => {}
+        _ => panic!("c_repr_with and as_rust_with should be used together"),
+    }
+
+    if let Some(len) = find_attribute(&field.attrs, "c_bytes") {
+        let len = len
+            .parse_args::<syn::Ident>()
+            .expect("c_bytes should name the length field");
+        let byte = pointee(ty).expect("c_bytes fields should be pointers");
+        return FieldConversion {
+            // the length has to be taken before the bytes are moved
+            c_repr: quote!(
+                #len: input.#ident.len() as _,
+                #ident: Box::into_raw(input.#ident.into_boxed_slice()) as *const #byte
+            ),
+            as_rust: quote!(
+                #ident: unsafe { std::slice::from_raw_parts(self.#ident, self.#len as usize) }.to_vec()
+            ),
+            drop: quote!(
+                let _ = unsafe {
+                    Box::from_raw(std::slice::from_raw_parts_mut(self.#ident as *mut #byte, self.#len as usize))
+                };
+            ),
+        };
+    }
+
+    if let Some(pointee) = pointee(ty) {
+        if last_segment(pointee).as_ref().map(String::as_str) == Some("c_char") {
+            return if nullable {
+                FieldConversion {
+                    c_repr: quote!(#ident: convert_to_nullable_c_string!(input.#ident)),
+                    as_rust: quote!(#ident: create_optional_rust_string_from!(self.#ident)),
+                    drop: quote!(take_back_nullable_c_string!(self.#ident);),
+                }
+            } else {
+                FieldConversion {
+                    c_repr: quote!(#ident: convert_to_c_string!(input.#ident)),
+                    as_rust: quote!(#ident: create_rust_string_from!(self.#ident)),
+                    drop: quote!(take_back_c_string!(self.#ident);),
+                }
+            };
+        }
+
+        let borrow = quote!(unsafe { <#pointee>::raw_borrow(self.#ident) }?.as_rust()?);
+        let drop = quote!(let _ = unsafe { <#pointee>::drop_raw_pointer(self.#ident) };);
+
+        return if nullable {
+            let is_absent = match find_attribute(&field.attrs, "struct_version") {
+                Some(version) => {
+                    let version = version
+                        .parse_args::<syn::LitInt>()
+                        .expect("struct_version should be an integer");
+                    quote!(crate::struct_version() < #version || self.#ident.is_null())
+                }
+                None => quote!(self.#ident.is_null()),
+            };
+            FieldConversion {
+                c_repr: quote!(#ident: if let Some(it) = input.#ident {
+                    <#pointee>::c_repr_of(it)?.into_raw_pointer()
+                } else {
+                    std::ptr::null()
+                }),
+                as_rust: quote!(#ident: if #is_absent { None } else { Some(#borrow) }),
+                drop: quote!(if !self.#ident.is_null() { #drop }),
+            }
+        } else if null_if_empty {
+            FieldConversion {
+                c_repr: quote!(#ident: if input.#ident.is_empty() {
+                    std::ptr::null()
+                } else {
+                    <#pointee>::c_repr_of(input.#ident)?.into_raw_pointer()
+                }),
+                as_rust: quote!(#ident: if self.#ident.is_null() { Default::default() } else { #borrow }),
+                drop: quote!(if !self.#ident.is_null() { #drop }),
+            }
+        } else {
+            FieldConversion {
+                c_repr: quote!(#ident: <#pointee>::c_repr_of(input.#ident)?.into_raw_pointer()),
+                as_rust: quote!(#ident: #borrow),
+                drop,
+            }
+        };
+    }
+
+    if !is_primitive(ty) {
+        return FieldConversion {
+            c_repr: quote!(#ident: <#ty>::c_repr_of(input.#ident)?),
+            as_rust: quote!(#ident: self.#ident.as_rust()?),
+            drop: quote!(),
+        };
+    }
+
+    let (c_repr, as_rust) = if find_attribute(&field.attrs, "c_bool").is_some() {
+        (
+            quote!(#ident: if input.#ident { 1 } else { 0 }),
+            quote!(#ident: self.#ident == 1),
+        )
+    } else if let Some(sentinel) = find_attribute(&field.attrs, "none_sentinel") {
+        let sentinel = sentinel
+            .parse_args::<TokenStream2>()
+            .expect("none_sentinel should be a value");
+        (
+            quote!(#ident: input.#ident.unwrap_or(#sentinel)),
+            quote!(#ident: if self.#ident == #sentinel { None } else { Some(self.#ident) }),
+        )
+    } else if find_attribute(&field.attrs, "cast").is_some() {
+        (quote!(#ident: input.#ident as _), quote!(#ident: self.#ident as _))
+    } else {
+        (quote!(#ident: input.#ident), quote!(#ident: self.#ident))
+    };
+
+    // lengths are filled along their bytes
+    let is_length = is_length(ident, fields);
+
+    FieldConversion {
+        c_repr: if is_length { quote!() } else { c_repr },
+        as_rust: if is_length { quote!() } else { as_rust },
+        drop: quote!(),
+    }
+}
+
+fn impl_struct(struct_name: &syn::Ident, target_type: &syn::Type, data: &syn::DataStruct) -> TokenStream {
+    let conversions: Vec<_> = data
+        .fields
+        .iter()
+        .map(|field| convert_field(field, &data.fields))
+        .collect();
+
+    let c_reprs = conversions.iter().map(|it| &it.c_repr).filter(|it| !it.is_empty());
+    let as_rusts = conversions.iter().map(|it| &it.as_rust).filter(|it| !it.is_empty());
+    let drops: Vec<_> = conversions
+        .iter()
+        .map(|it| &it.drop)
+        .filter(|it| !it.is_empty())
+        .collect();
+
+    // the lengths of the byte buffers only exist on the C side
+    let target_fields = data
+        .fields
+        .iter()
+        .map(field_ident)
+        .filter(|ident| !is_length(ident, &data.fields));
+    let fields_test = format_ident!("{}_converts_every_field_of_its_target_type", struct_name);
+
+    let drop_impl = if drops.is_empty() {
+        quote!()
+    } else {
+        quote!(
+            impl Drop for #struct_name {
+                fn drop(&mut self) {
+                    #[allow(unused_imports)]
+                    use ffi_utils::*;
+                    #( #drops )*
+                }
+            }
+        )
+    };
+
+    quote!(
+        impl ffi_utils::CReprOf<#target_type> for #struct_name {
+            fn c_repr_of(input: #target_type) -> failure::Fallible<Self> {
+                #[allow(unused_imports)]
+                use failure::ResultExt;
+                #[allow(unused_imports)]
+                use ffi_utils::*;
+                // used by the nullable strings macros
+                #[allow(unused_imports)]
+                use std::ptr::null;
+                Ok(Self {
+                    #( #c_reprs, )*
+                })
+            }
+        }
+
+        impl ffi_utils::AsRust<#target_type> for #struct_name {
+            fn as_rust(&self) -> failure::Fallible<#target_type> {
+                #[allow(unused_imports)]
+                use failure::ResultExt;
+                #[allow(unused_imports)]
+                use ffi_utils::*;
+                Ok(#target_type {
+                    #( #as_rusts, )*
+                })
+            }
+        }
+
+        #drop_impl
+
+        // the C structure is declared by hand, this test stops building when the target type gains
+        // a field the structure doesn't have
+        #[cfg(test)]
+        #[test]
+        #[allow(non_snake_case)]
+        fn #fields_test() {
+            #[allow(dead_code)]
+            fn fields(input: &#target_type) {
+                let #target_type { #( #target_fields: _, )* } = input;
+            }
+        }
+    )
+    .into()
+}
+
+fn impl_array(struct_name: &syn::Ident, target_type: &syn::Type, data: &syn::DataStruct) -> TokenStream {
+    let entries = data
+        .fields
+        .iter()
+        .find(|field| pointee(&field.ty).and_then(pointee).is_some())
+        .expect("c_array structs should have a field pointing to the entries pointers");
+    let count = data
+        .fields
+        .iter()
+        .find(|field| field.ident != entries.ident)
+        .expect("c_array structs should have a count field");
+    if data.fields.len() != 2 {
+        panic!("c_array structs should only have an entries and a count field")
+    }
+
+    let entry_type = pointee(&entries.ty).and_then(pointee).unwrap();
+    let entries = field_ident(entries);
+    let count = field_ident(count);
+
+    quote!(
+        impl ffi_utils::CReprOf<#target_type> for #struct_name {
+            fn c_repr_of(input: #target_type) -> failure::Fallible<Self> {
+                use failure::ResultExt;
+                use ffi_utils::*;
+                Ok(Self {
+                    #count: input.len() as _,
+                    #entries: Box::into_raw(
+                        input
+                            .into_iter()
+                            .map(|e| <#entry_type>::c_repr_of(e).map(RawPointerConverter::into_raw_pointer))
+                            .collect::<failure::Fallible<Vec<_>>>()
+                            .context("Could not convert array to C Repr")?
+                            .into_boxed_slice(),
+                    ) as *const *const _,
+                })
+            }
+        }
+
+        impl ffi_utils::AsRust<#target_type> for #struct_name {
+            fn as_rust(&self) -> failure::Fallible<#target_type> {
+                use ffi_utils::*;
+                let mut result = Vec::with_capacity(self.#count as usize);
+                if self.#count > 0 {
+                    for e in unsafe { std::slice::from_raw_parts(self.#entries, self.#count as usize) } {
+                        result.push(unsafe { <#entry_type>::raw_borrow(*e) }?.as_rust()?);
+                    }
+                }
+                Ok(result)
+            }
+        }
+
+        impl Drop for #struct_name {
+            fn drop(&mut self) {
+                use ffi_utils::*;
+                unsafe {
+                    let entries = Box::from_raw(std::slice::from_raw_parts_mut(
+                        self.#entries as *mut *mut #entry_type,
+                        self.#count as usize,
+                    ));
+                    for e in entries.iter() {
+                        let _ = <#entry_type>::drop_raw_pointer(*e);
+                    }
+                }
+            }
+        }
+    )
+    .into()
+}
+
+fn impl_enum(enum_name: &syn::Ident, target_type: &syn::Type, data: &syn::DataEnum) -> TokenStream {
+    let variants: Vec<_> = data
+        .variants
+        .iter()
+        .map(|variant| {
+            let target_variant = find_attribute(&variant.attrs, "target_variant")
+                .map(|attr| {
+                    attr.parse_args::<syn::Ident>()
+                        .expect("target_variant should be an ident")
+                })
+                .expect("CRepr enum variants need a #[target_variant(..)] attribute");
+            (&variant.ident, target_variant)
+        })
+        .collect();
+
+    let c_reprs = variants
+        .iter()
+        .map(|(variant, target_variant)| quote!(#target_type::#target_variant => #enum_name::#variant));
+    let as_rusts = variants
+        .iter()
+        .map(|(variant, target_variant)| quote!(#enum_name::#variant => #target_type::#target_variant));
+
+    quote!(
+        impl ffi_utils::CReprOf<#target_type> for #enum_name {
+            fn c_repr_of(input: #target_type) -> failure::Fallible<Self> {
+                Ok(match input {
+                    #( #c_reprs, )*
+                })
+            }
+        }
+
+        impl ffi_utils::AsRust<#target_type> for #enum_name {
+            fn as_rust(&self) -> failure::Fallible<#target_type> {
+                Ok(match self {
+                    #( #as_rusts, )*
+                })
+            }
+        }
+    )
+    .into()
+}
@@ -0,0 +1,68 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+
+use quote::quote;
+
+pub fn impl_from_intent_macro(input: &syn::DeriveInput) -> TokenStream {
+    let struct_name = &input.ident;
+
+    let fields = match &input.data {
+        syn::Data::Struct(syn::DataStruct {
+            fields: syn::Fields::Named(fields),
+            ..
+        }) => &fields.named,
+        _ => panic!("FromIntent can only be derived for structs with named fields"),
+    };
+
+    let fields: Vec<_> = fields.iter().map(convert_field).collect();
+
+    quote!(
+        impl ::hermes_skill::FromIntent for #struct_name {
+            fn from_intent(intent: &::hermes_skill::hermes::IntentMessage) -> ::hermes_skill::failure::Fallible<Self> {
+                Ok(Self {
+                    #(#fields,)*
+                })
+            }
+        }
+    )
+    .into()
+}
+
+/// The outer type of `ty` when it is an `Option` or a `Vec`
+fn wrapper(ty: &syn::Type) -> Option<String> {
+    match ty {
+        syn::Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .filter(|it| match it.arguments {
+                syn::PathArguments::AngleBracketed(_) => true,
+                _ => false,
+            })
+            .map(|it| it.ident.to_string())
+            .filter(|it| it == "Option" || it == "Vec"),
+        _ => None,
+    }
+}
+
+/// The field of the struct literal, read from the slots named after the field or after its
+/// `#[slot_name(..)]`
+fn convert_field(field: &syn::Field) -> TokenStream2 {
+    let ident = field.ident.as_ref().expect("field should have an ident");
+    let slot_name = field
+        .attrs
+        .iter()
+        .find(|attr| attr.path.is_ident("slot_name"))
+        .map(|attr| {
+            attr.parse_args::<syn::LitStr>()
+                .expect("slot_name should be a string")
+                .value()
+        })
+        .unwrap_or_else(|| ident.to_string());
+
+    match wrapper(&field.ty).as_ref().map(String::as_str) {
+        Some("Option") => quote!(#ident: intent.optional_slot(#slot_name)?),
+        Some("Vec") => quote!(#ident: intent.all_slots(#slot_name)?),
+        _ => quote!(#ident: intent.slot(#slot_name)?),
+    }
+}
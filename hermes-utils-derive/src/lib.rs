@@ -6,6 +6,9 @@ use syn;
 
 use quote::quote;
 
+mod c_repr;
+mod from_intent;
+
 #[proc_macro_derive(Example, attributes(example_value))]
 pub fn example_derive(token_stream: TokenStream) -> TokenStream {
     let ast = syn::parse(token_stream).unwrap();
@@ -56,3 +59,58 @@ fn impl_example_macro(input: &syn::DeriveInput) -> TokenStream {
     )
     .into()
 }
+
+/// Derive `CReprOf`, `AsRust` and `Drop` for a `#[repr(C)]` structure of hermes-ffi from the type
+/// given in `#[target_type(..)]`, which should have the same field names. The conversions are
+/// chosen from the C types of the fields:
+/// - `*const c_char` are strings, `#[nullable]` ones map to `Option<String>`
+/// - other `*const T` are boxed `T`, `#[nullable]` ones map to an `Option` and `#[null_if_empty]`
+///   ones to a collection that is null when empty. A `#[struct_version(n)]` nullable field is only
+///   read from the structures of version `n` and later
+/// - `#[c_bytes(len_field)]` `*const u8` hold a `Vec<u8>` whose length is in `len_field`
+/// - primitives are copied, `#[c_bool]` ones map to a `bool`, `#[cast]` ones are converted with
+///   `as` and `#[none_sentinel(value)]` ones map to an `Option` that is `None` for `value`
+/// - other types are converted with their own `CReprOf` and `AsRust`
+/// - `#[c_repr_with(path)]` and `#[as_rust_with(path)]` give the conversion functions of fields
+///   that fit none of the above
+///
+/// `#[c_array]` structures hold a pointer to the entries pointers and their count and map to a
+/// `Vec`, fieldless enums map their variants to the ones given in `#[target_variant(..)]`.
+///
+/// The C structure itself stays declared by hand: its field order, C types and docs are the ABI
+/// and the headers cbindgen writes, and they must not move when the ontology type is reordered.
+/// A test is generated along the conversions for each structure, which stops building when the
+/// target type gains a field the structure doesn't have.
+#[proc_macro_derive(
+    CRepr,
+    attributes(
+        target_type,
+        c_array,
+        target_variant,
+        nullable,
+        null_if_empty,
+        struct_version,
+        c_bytes,
+        c_bool,
+        cast,
+        none_sentinel,
+        c_repr_with,
+        as_rust_with
+    )
+)]
+pub fn c_repr_derive(token_stream: TokenStream) -> TokenStream {
+    let ast = syn::parse(token_stream).unwrap();
+    c_repr::impl_c_repr_macro(&ast)
+}
+
+/// Derive `hermes_skill::FromIntent` for a structure whose fields are read from the slots of the
+/// intent having the same name, or the name given in `#[slot_name(..)]`. The values are converted
+/// with `hermes::FromSlotValue` and the type of the field tells how many slots are expected:
+/// - `Option<T>` fields are `None` when the intent has no such slot
+/// - `Vec<T>` fields hold the values of all the slots with this name, for the multi-valued slots
+/// - the other fields need the intent to have the slot
+#[proc_macro_derive(FromIntent, attributes(slot_name))]
+pub fn from_intent_derive(token_stream: TokenStream) -> TokenStream {
+    let ast = syn::parse(token_stream).unwrap();
+    from_intent::impl_from_intent_macro(&ast)
+}
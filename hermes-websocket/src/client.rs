@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use failure::{format_err, Fallible};
+use hermes::*;
+use hermes_mqtt::codec;
+use hermes_mqtt::topics::*;
+use hermes_mqtt::transport::{RawCallback, Transport};
+use hermes_mqtt::MqttHermesProtocolHandler;
+use log::*;
+use tungstenite::protocol::Role;
+use tungstenite::Message;
+
+use crate::connection::{Connection, SharedStream};
+use crate::protocol::*;
+
+/// The topic filter and the callback of each subscription id
+type Subscriptions = Mutex<HashMap<u32, (String, Arc<RawCallback>)>>;
+
+/// A `HermesProtocolHandler` connected to a `WebSocketServer`, the connection is closed when the
+/// handler is dropped
+pub struct WebSocketHermesProtocolHandler {
+    url: String,
+    connection: Arc<Connection>,
+    handler: MqttHermesProtocolHandler,
+}
+
+impl WebSocketHermesProtocolHandler {
+    /// Connect to the server listening at `url`, for instance `ws://localhost:9001`
+    pub fn new(url: &str) -> Fallible<WebSocketHermesProtocolHandler> {
+        let parsed = url::Url::parse(url)?;
+        if parsed.scheme() != "ws" {
+            return Err(format_err!(
+                "unsupported WebSocket url {}, only ws:// is supported",
+                url
+            ));
+        }
+        let address = parsed
+            .socket_addrs(|| Some(80))?
+            .into_iter()
+            .next()
+            .ok_or_else(|| format_err!("could not resolve {}", url))?;
+        let stream = TcpStream::connect(address)?;
+        let (socket, _) = tungstenite::client(parsed, SharedStream::new(stream)?)
+            .map_err(|e| format_err!("handshake with {} failed: {}", url, e))?;
+
+        let subscriptions: Arc<Subscriptions> = Arc::new(Mutex::new(HashMap::new()));
+        let dispatch_subscriptions = Arc::clone(&subscriptions);
+        let connection = Connection::spawn(
+            format!("hermes-websocket-{}", url),
+            socket,
+            Role::Client,
+            move |_, frame| {
+                if let Err(e) = dispatch(&dispatch_subscriptions, frame) {
+                    warn!("Error while handling a frame from the WebSocket server: {}", e);
+                }
+            },
+            || debug!("Disconnected from the WebSocket server"),
+        )?;
+
+        let transport = ClientTransport {
+            connection: Arc::clone(&connection),
+            subscriptions,
+            next_subscription: AtomicUsize::new(0),
+        };
+
+        Ok(WebSocketHermesProtocolHandler {
+            url: url.to_string(),
+            connection,
+            handler: MqttHermesProtocolHandler::new_with_transport(url.to_string(), transport),
+        })
+    }
+}
+
+impl Drop for WebSocketHermesProtocolHandler {
+    fn drop(&mut self) {
+        self.connection.close();
+    }
+}
+
+fn dispatch(subscriptions: &Subscriptions, frame: Message) -> Fallible<()> {
+    match frame {
+        Message::Text(text) => match serde_json::from_str(&text)? {
+            ServerFrame::Message {
+                subscription,
+                topic,
+                payload,
+            } => deliver(subscriptions, subscription, &topic, &raw_payload(payload.as_ref())?),
+            ServerFrame::Error { subscription, message } => {
+                match subscription {
+                    Some(id) => warn!("The WebSocket server rejected subscription {}: {}", id, message),
+                    None => warn!("The WebSocket server reported an error: {}", message),
+                }
+                Ok(())
+            }
+        },
+        Message::Binary(bytes) => {
+            let (subscription, topic, payload) = decode_binary_message(&bytes)?;
+            deliver(subscriptions, subscription, topic, payload)
+        }
+        _ => Ok(()),
+    }
+}
+
+fn deliver(subscriptions: &Subscriptions, subscription: u32, topic: &str, payload: &[u8]) -> Fallible<()> {
+    // the callback is called outside of the lock so that it can subscribe again
+    let callback = subscriptions
+        .lock()
+        .map_err(|_| format_err!("poisoned subscriptions"))?
+        .get(&subscription)
+        .map(|(_, callback)| Arc::clone(callback));
+    match callback {
+        Some(callback) => callback(topic, payload),
+        // the messages sent by the server before it received the unsubscription
+        None => debug!("Dropping a message for the cancelled subscription {}", subscription),
+    }
+    Ok(())
+}
+
+/// The transport of the client, sending the MQTT topics and payloads to the server
+struct ClientTransport {
+    connection: Arc<Connection>,
+    subscriptions: Arc<Subscriptions>,
+    next_subscription: AtomicUsize,
+}
+
+impl Transport for ClientTransport {
+    fn publish(&self, topic: &str, payload: Vec<u8>) -> Fallible<()> {
+        let binary = HermesTopic::from_path(topic).map_or(false, |topic| codec::is_binary(&topic));
+        let frame = if binary {
+            Message::Binary(encode_binary_publish(topic, &payload)?)
+        } else {
+            Message::Text(serde_json::to_string(&ClientFrame::Publish {
+                topic: topic.to_string(),
+                payload: json_payload(&payload)?,
+            })?)
+        };
+        self.connection.send(frame)
+    }
+
+    fn subscribe(&self, topic_filter: &str, callback: RawCallback) -> Fallible<()> {
+        let id = self.next_subscription.fetch_add(1, Ordering::SeqCst) as u32;
+        self.subscriptions
+            .lock()
+            .map_err(|_| format_err!("poisoned subscriptions"))?
+            .insert(id, (topic_filter.to_string(), Arc::new(callback)));
+        let frame = ClientFrame::Subscribe {
+            id,
+            topic: topic_filter.to_string(),
+        };
+        self.connection.send(Message::Text(serde_json::to_string(&frame)?))
+    }
+
+    fn unsubscribe(&self, topic_filter: &str) -> Fallible<()> {
+        let ids: Vec<u32> = {
+            let mut subscriptions = self
+                .subscriptions
+                .lock()
+                .map_err(|_| format_err!("poisoned subscriptions"))?;
+            let ids = subscriptions
+                .iter()
+                .filter(|(_, (filter, _))| filter == topic_filter)
+                .map(|(id, _)| *id)
+                .collect();
+            subscriptions.retain(|_, (filter, _)| filter != topic_filter);
+            ids
+        };
+        for id in ids {
+            let frame = ClientFrame::Unsubscribe { id };
+            self.connection.send(Message::Text(serde_json::to_string(&frame)?))?;
+        }
+        Ok(())
+    }
+}
+
+/// The facades are the ones of the MQTT handler publishing and subscribing through the connection
+macro_rules! websocket_facades {
+    ($(
+        $component:ident {
+            facade $facade_doc:tt $facade:ident $facade_supers:tt $facade_getter:ident;
+            backend $backend_doc:tt $backend:ident $backend_supers:tt $backend_getter:ident;
+            $($description:tt)*
+        }
+    )*) => {
+        impl HermesProtocolHandler for WebSocketHermesProtocolHandler {
+            $(
+                fn $facade_getter(&self) -> Box<dyn $facade> {
+                    self.handler.$facade_getter()
+                }
+            )*
+
+            $(
+                fn $backend_getter(&self) -> Box<dyn $backend> {
+                    self.handler.$backend_getter()
+                }
+            )*
+        }
+    };
+}
+
+hermes_protocol!(websocket_facades);
+
+impl std::fmt::Display for WebSocketHermesProtocolHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} (WebSocket)", self.url)
+    }
+}
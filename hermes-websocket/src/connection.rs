@@ -0,0 +1,186 @@
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use failure::{format_err, Fallible};
+use log::*;
+use tungstenite::protocol::Role;
+use tungstenite::{Message, WebSocket};
+
+/// How long to wait for the peer to acknowledge the closing of the connection
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// A TCP stream shared by the reading and the writing side of a connection. tungstenite gives
+/// whole frames to `write`, they are written at once so that the frames of both sides, including
+/// the pongs tungstenite sends on its own while reading, are never interleaved.
+pub struct SharedStream {
+    stream: TcpStream,
+    write_lock: Arc<Mutex<()>>,
+}
+
+impl SharedStream {
+    pub fn new(stream: TcpStream) -> Fallible<SharedStream> {
+        stream.set_nodelay(true)?;
+        Ok(SharedStream {
+            stream,
+            write_lock: Arc::new(Mutex::new(())),
+        })
+    }
+
+    fn try_clone(&self) -> io::Result<SharedStream> {
+        Ok(SharedStream {
+            stream: self.stream.try_clone()?,
+            write_lock: Arc::clone(&self.write_lock),
+        })
+    }
+}
+
+impl Read for SharedStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.stream.read(buf)
+    }
+}
+
+impl Write for SharedStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let _lock = self
+            .write_lock
+            .lock()
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "poisoned WebSocket stream"))?;
+        self.stream.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+enum Outgoing {
+    Frame(Message),
+    Close,
+    /// The reading side stopped, the peer closed the connection or it failed
+    Stopped,
+}
+
+/// A WebSocket connection served by two threads, one blocked reading the frames of the peer and
+/// one writing the frames sent, so that neither of them has to poll the socket.
+pub struct Connection {
+    outgoing: Mutex<Sender<Outgoing>>,
+    closed: AtomicBool,
+}
+
+impl Connection {
+    /// Start serving `socket`, `role` being the side of the connection this end is on.
+    /// `on_message` is called on the reading thread with every text and binary frame received, and
+    /// `on_closed` once when the connection is closed.
+    pub fn spawn<F, C>(
+        name: String,
+        mut socket: WebSocket<SharedStream>,
+        role: Role,
+        mut on_message: F,
+        on_closed: C,
+    ) -> Fallible<Arc<Connection>>
+    where
+        F: FnMut(&Arc<Connection>, Message) + Send + 'static,
+        C: FnOnce() + Send + 'static,
+    {
+        let write_stream = socket.get_ref().try_clone()?;
+        let shutdown_stream = write_stream.stream.try_clone()?;
+        let writer = WebSocket::from_raw_socket(write_stream, role, None);
+
+        let (sender, receiver) = mpsc::channel();
+        let connection = Arc::new(Connection {
+            outgoing: Mutex::new(sender.clone()),
+            closed: AtomicBool::new(false),
+        });
+
+        let writer_name = name.clone();
+        thread::Builder::new().name(format!("{}-writer", name)).spawn(move || {
+            if let Err(e) = write(writer, &receiver) {
+                debug!("WebSocket connection {} stopped writing: {}", writer_name, e);
+                // wake up the reading side, it is blocked until the peer sends something
+                let _ = shutdown_stream.shutdown(Shutdown::Both);
+            }
+        })?;
+
+        let thread_connection = Arc::clone(&connection);
+        thread::Builder::new().name(name.clone()).spawn(move || {
+            if let Err(e) = read(&mut socket, |message| on_message(&thread_connection, message)) {
+                debug!("WebSocket connection {} stopped: {}", name, e);
+            }
+            thread_connection.closed.store(true, Ordering::SeqCst);
+            let _ = sender.send(Outgoing::Stopped);
+            on_closed();
+        })?;
+
+        Ok(connection)
+    }
+
+    pub fn send(&self, frame: Message) -> Fallible<()> {
+        if self.is_closed() {
+            return Err(format_err!("the WebSocket connection is closed"));
+        }
+        self.outgoing
+            .lock()
+            .map_err(|_| format_err!("poisoned WebSocket connection"))?
+            .send(Outgoing::Frame(frame))
+            .map_err(|_| format_err!("the WebSocket connection is closed"))
+    }
+
+    /// Start the closing handshake, the frames sent before are written first
+    pub fn close(&self) {
+        if let Ok(outgoing) = self.outgoing.lock() {
+            let _ = outgoing.send(Outgoing::Close);
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+}
+
+fn read<F>(socket: &mut WebSocket<SharedStream>, mut on_message: F) -> Fallible<()>
+where
+    F: FnMut(Message),
+{
+    loop {
+        match socket.read_message() {
+            Ok(frame @ Message::Text(_)) | Ok(frame @ Message::Binary(_)) => on_message(frame),
+            // pings, pongs and close frames are handled by tungstenite
+            Ok(_) => {}
+            Err(tungstenite::Error::ConnectionClosed) | Err(tungstenite::Error::AlreadyClosed) => return Ok(()),
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+fn write(mut socket: WebSocket<SharedStream>, outgoing: &Receiver<Outgoing>) -> Fallible<()> {
+    loop {
+        match outgoing.recv() {
+            Ok(Outgoing::Frame(frame)) => socket.write_message(frame)?,
+            Ok(Outgoing::Close) => break,
+            Ok(Outgoing::Stopped) | Err(_) => return Ok(()),
+        }
+    }
+
+    // the reading side stops once the peer acknowledges the closing
+    socket.close(None)?;
+    let deadline = Instant::now() + CLOSE_TIMEOUT;
+    loop {
+        let now = Instant::now();
+        if now >= deadline {
+            return Err(format_err!(
+                "the peer did not acknowledge the closing of the connection"
+            ));
+        }
+        match outgoing.recv_timeout(deadline - now) {
+            Ok(Outgoing::Stopped) | Err(RecvTimeoutError::Disconnected) => return Ok(()),
+            Ok(_) | Err(RecvTimeoutError::Timeout) => {}
+        }
+    }
+}
@@ -0,0 +1,92 @@
+//! Hermes over WebSockets.
+//!
+//! `WebSocketServer` exposes any `HermesProtocolHandler` to WebSocket clients, for instance to let
+//! a browser talk to an in-process bus, and `WebSocketHermesProtocolHandler` is the
+//! `HermesProtocolHandler` of such a client. The frames exchanged are described in `protocol`.
+
+mod client;
+mod connection;
+pub mod protocol;
+mod server;
+
+pub use crate::client::WebSocketHermesProtocolHandler;
+pub use crate::server::WebSocketServer;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hermes_inprocess::InProcessHermesProtocolHandler;
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    struct HandlerHolder {
+        handler: WebSocketHermesProtocolHandler,
+        // this code is not dead, the server is stopped when the last handler using it is dropped
+        #[allow(dead_code)]
+        server: Rc<WebSocketServer>,
+    }
+
+    impl std::ops::Deref for HandlerHolder {
+        type Target = WebSocketHermesProtocolHandler;
+        fn deref(&self) -> &WebSocketHermesProtocolHandler {
+            &self.handler
+        }
+    }
+
+    fn create_handlers() -> (HandlerHolder, HandlerHolder) {
+        // the server listens on a random free port chosen by the OS
+        let server = Rc::new(
+            WebSocketServer::bind("127.0.0.1:0", Arc::new(InProcessHermesProtocolHandler::new()))
+                .expect("could not start server"),
+        );
+        let url = format!("ws://{}", server.address());
+
+        let handler1 = HandlerHolder {
+            handler: WebSocketHermesProtocolHandler::new(&url).expect("could not create first client"),
+            server: Rc::clone(&server),
+        };
+
+        let handler2 = HandlerHolder {
+            handler: WebSocketHermesProtocolHandler::new(&url).expect("could not create second client"),
+            server,
+        };
+
+        (handler1, handler2)
+    }
+
+    hermes_test_suite::test_suite!(WAIT_DURATION = 200);
+
+    #[test]
+    fn envelopes_are_forwarded_to_the_clients() {
+        use hermes::hermes_utils::Example;
+        use hermes::*;
+        use std::time::Duration;
+
+        let source = MessageSource {
+            component: HermesComponent::Dialogue,
+            site_id: None,
+        };
+        let served = Arc::new(InProcessHermesProtocolHandler::new().with_envelope(source.clone()));
+        let server = WebSocketServer::bind("127.0.0.1:0", Arc::clone(&served) as Arc<dyn HermesProtocolHandler>)
+            .expect("could not start server");
+        let client = WebSocketHermesProtocolHandler::new(&format!("ws://{}", server.address())).unwrap();
+        let (tx, rx) = std::sync::mpsc::channel();
+        let tx = std::sync::Mutex::new(tx);
+        client
+            .dialogue()
+            .subscribe_intents(Callback::with_envelope(move |_: &IntentMessage, envelope| {
+                tx.lock().unwrap().send(envelope.cloned()).unwrap()
+            }))
+            .unwrap();
+        // the server subscribes once it has received the subscription frame
+        std::thread::sleep(Duration::from_millis(200));
+
+        served
+            .dialogue_backend()
+            .publish_intent(IntentMessage::full_example())
+            .unwrap();
+
+        let envelope = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(envelope.map(|it| it.source), Some(source));
+    }
+}
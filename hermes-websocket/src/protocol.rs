@@ -0,0 +1,183 @@
+//! The frames exchanged between the WebSocket clients and the server.
+//!
+//! Messages are identified by their MQTT topic and their payload is encoded as it would be over
+//! MQTT. Json messages travel in text frames, wrapped in a json envelope, while the audio and the
+//! sounds travel in binary frames prefixed by their topic:
+//!
+//! - a client publishes with `{"type": "publish", "topic": "hermes/tts/say", "payload": {...}}`,
+//!   or a binary frame made of the topic length (big endian `u16`), the topic and the raw payload
+//! - a client subscribes with `{"type": "subscribe", "id": 1, "topic": "hermes/intent/#"}`, using
+//!   the topic filters of the MQTT implementation, and unsubscribes with
+//!   `{"type": "unsubscribe", "id": 1}`
+//! - the server forwards each message with the id of the subscription it matches, in
+//!   `{"type": "message", "subscription": 1, "topic": "hermes/intent/lightsOn", "payload": {...}}`
+//!   or in a binary frame made of the subscription id (big endian `u32`), the topic length, the
+//!   topic and the raw payload. The payload of a json message has the `hermes::Envelope` of the
+//!   message in its `envelope` field when it has one, as over MQTT, the binary frames carry none.
+//! - the server reports a subscription or a publication it could not handle with
+//!   `{"type": "error", "subscription": 1, "message": "..."}`
+
+use std::convert::TryInto;
+
+use failure::{format_err, Fallible};
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ClientFrame {
+    Subscribe {
+        id: u32,
+        topic: String,
+    },
+    Unsubscribe {
+        id: u32,
+    },
+    Publish {
+        topic: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        payload: Option<Value>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ServerFrame {
+    Message {
+        subscription: u32,
+        topic: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        payload: Option<Value>,
+    },
+    Error {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        subscription: Option<u32>,
+        message: String,
+    },
+}
+
+/// Wrap a json payload as encoded for MQTT in an envelope, an empty payload has no value
+pub fn json_payload(payload: &[u8]) -> Fallible<Option<Value>> {
+    if payload.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(serde_json::from_slice(payload)?))
+    }
+}
+
+/// Get back the payload as encoded for MQTT from the value of an envelope
+pub fn raw_payload(payload: Option<&Value>) -> Fallible<Vec<u8>> {
+    match payload {
+        Some(value) => Ok(serde_json::to_vec(value)?),
+        None => Ok(vec![]),
+    }
+}
+
+pub fn encode_binary_publish(topic: &str, payload: &[u8]) -> Fallible<Vec<u8>> {
+    let mut frame = Vec::with_capacity(2 + topic.len() + payload.len());
+    write_topic(&mut frame, topic)?;
+    frame.extend_from_slice(payload);
+    Ok(frame)
+}
+
+pub fn decode_binary_publish(frame: &[u8]) -> Fallible<(&str, &[u8])> {
+    read_topic(frame)
+}
+
+pub fn encode_binary_message(subscription: u32, topic: &str, payload: &[u8]) -> Fallible<Vec<u8>> {
+    let mut frame = Vec::with_capacity(6 + topic.len() + payload.len());
+    frame.extend_from_slice(&subscription.to_be_bytes());
+    write_topic(&mut frame, topic)?;
+    frame.extend_from_slice(payload);
+    Ok(frame)
+}
+
+pub fn decode_binary_message(frame: &[u8]) -> Fallible<(u32, &str, &[u8])> {
+    if frame.len() < 4 {
+        return Err(format_err!("binary frame too short to hold a subscription id"));
+    }
+    let subscription = u32::from_be_bytes(frame[0..4].try_into()?);
+    let (topic, payload) = read_topic(&frame[4..])?;
+    Ok((subscription, topic, payload))
+}
+
+fn write_topic(frame: &mut Vec<u8>, topic: &str) -> Fallible<()> {
+    if topic.len() > u16::max_value() as usize {
+        return Err(format_err!("topic too long for a binary frame: {}", topic));
+    }
+    frame.extend_from_slice(&(topic.len() as u16).to_be_bytes());
+    frame.extend_from_slice(topic.as_bytes());
+    Ok(())
+}
+
+fn read_topic(frame: &[u8]) -> Fallible<(&str, &[u8])> {
+    if frame.len() < 2 {
+        return Err(format_err!("binary frame too short to hold a topic"));
+    }
+    let len = u16::from_be_bytes(frame[0..2].try_into()?) as usize;
+    if frame.len() < 2 + len {
+        return Err(format_err!("binary frame too short for its topic of {} bytes", len));
+    }
+    let topic = std::str::from_utf8(&frame[2..2 + len])?;
+    Ok((topic, &frame[2 + len..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_frames_use_a_type_tag() {
+        let frame = ClientFrame::Subscribe {
+            id: 3,
+            topic: "hermes/intent/#".into(),
+        };
+        assert_eq!(
+            serde_json::to_string(&frame).unwrap(),
+            r#"{"type":"subscribe","id":3,"topic":"hermes/intent/#"}"#
+        );
+
+        let frame: ClientFrame = serde_json::from_str(r#"{"type":"unsubscribe","id":3}"#).unwrap();
+        assert_eq!(frame, ClientFrame::Unsubscribe { id: 3 });
+
+        let frame: ClientFrame = serde_json::from_str(r#"{"type":"publish","topic":"hermes/asr/toggleOn"}"#).unwrap();
+        assert_eq!(
+            frame,
+            ClientFrame::Publish {
+                topic: "hermes/asr/toggleOn".into(),
+                payload: None
+            }
+        );
+    }
+
+    #[test]
+    fn payloads_round_trip_through_envelopes() {
+        assert_eq!(json_payload(b"").unwrap(), None);
+        let payload = br#"{"siteId":"kitchen"}"#;
+        let value = json_payload(payload).unwrap();
+        assert_eq!(raw_payload(value.as_ref()).unwrap(), payload.to_vec());
+        assert!(json_payload(b"RIFF").is_err());
+    }
+
+    #[test]
+    fn binary_frames_round_trip() {
+        let frame = encode_binary_publish("hermes/audioServer/kitchen/audioFrame", &[1, 2, 3]).unwrap();
+        assert_eq!(
+            decode_binary_publish(&frame).unwrap(),
+            ("hermes/audioServer/kitchen/audioFrame", &[1u8, 2, 3][..])
+        );
+
+        let frame = encode_binary_message(7, "hermes/audioServer/kitchen/audioFrame", &[]).unwrap();
+        assert_eq!(
+            decode_binary_message(&frame).unwrap(),
+            (7, "hermes/audioServer/kitchen/audioFrame", &[][..])
+        );
+    }
+
+    #[test]
+    fn truncated_binary_frames_are_rejected() {
+        assert!(decode_binary_publish(&[0]).is_err());
+        assert!(decode_binary_publish(&[0, 10, b'h']).is_err());
+        assert!(decode_binary_message(&[0, 0, 1]).is_err());
+    }
+}
@@ -0,0 +1,784 @@
+use std::collections::HashMap;
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::thread;
+
+use failure::{format_err, Fallible};
+use hermes::envelope::to_json_value;
+use hermes::*;
+use hermes_mqtt::codec;
+use hermes_mqtt::topics::{self, *};
+use hermes_mqtt::transport::{RawCallback, Transport};
+use hermes_mqtt::MqttHermesProtocolHandler;
+use log::*;
+use tungstenite::protocol::Role;
+use tungstenite::Message;
+
+use crate::connection::{Connection, SharedStream};
+use crate::protocol::*;
+
+/// A server exposing a `HermesProtocolHandler` to WebSocket clients, it is stopped and all its
+/// clients are disconnected when dropped
+pub struct WebSocketServer {
+    address: SocketAddr,
+    state: Arc<State>,
+}
+
+struct State {
+    running: AtomicBool,
+    handler: Arc<dyn HermesProtocolHandler>,
+    next_client_id: AtomicUsize,
+    clients: Mutex<HashMap<usize, Arc<Connection>>>,
+}
+
+impl WebSocketServer {
+    /// Serve `handler` to the clients connecting on `address`
+    pub fn bind<A: ToSocketAddrs>(address: A, handler: Arc<dyn HermesProtocolHandler>) -> Fallible<WebSocketServer> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        let state = Arc::new(State {
+            running: AtomicBool::new(true),
+            handler,
+            next_client_id: AtomicUsize::new(0),
+            clients: Mutex::new(HashMap::new()),
+        });
+
+        let accept_state = Arc::clone(&state);
+        thread::Builder::new()
+            .name(format!("hermes-websocket-{}", address.port()))
+            .spawn(move || accept(&listener, &accept_state))?;
+
+        info!("Serving {} over WebSocket on {}", state.handler, address);
+        Ok(WebSocketServer { address, state })
+    }
+
+    /// The address the server listens on
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+}
+
+impl Drop for WebSocketServer {
+    fn drop(&mut self) {
+        self.state.running.store(false, Ordering::SeqCst);
+        // wake up the accept loop so that it notices the server is stopped
+        let _ = TcpStream::connect(self.address);
+        for client in self.state.clients.lock().unwrap().values() {
+            client.close();
+        }
+    }
+}
+
+fn accept(listener: &TcpListener, state: &Arc<State>) {
+    for stream in listener.incoming() {
+        if !state.running.load(Ordering::SeqCst) {
+            break;
+        }
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                warn!("Could not accept a WebSocket connection: {}", e);
+                continue;
+            }
+        };
+        let id = state.next_client_id.fetch_add(1, Ordering::SeqCst);
+        let state = Arc::clone(state);
+        // the handshake is done on its own thread so that a slow client does not block the others
+        let spawned = thread::Builder::new()
+            .name(format!("hermes-websocket-handshake-{}", id))
+            .spawn(move || {
+                if let Err(e) = connect(&state, id, stream) {
+                    warn!("Could not open WebSocket connection {}: {}", id, e);
+                }
+            });
+        if let Err(e) = spawned {
+            warn!("Could not spawn a WebSocket handshake thread: {}", e);
+        }
+    }
+}
+
+fn connect(state: &Arc<State>, id: usize, stream: TcpStream) -> Fallible<()> {
+    let peer = stream.peer_addr()?;
+    let socket = tungstenite::accept(SharedStream::new(stream)?)
+        .map_err(|e| format_err!("handshake with {} failed: {}", peer, e))?;
+    debug!("WebSocket client {} connected from {}", id, peer);
+
+    let handler = Arc::clone(&state.handler);
+    // a weak reference, the state owns its clients
+    let closed_state = Arc::downgrade(state);
+    let subscriptions: Arc<Subscriptions> = Arc::new(Mutex::new(HashMap::new()));
+    let closed_subscriptions = Arc::clone(&subscriptions);
+    let mut clients = state.clients.lock().unwrap();
+    let connection = Connection::spawn(
+        format!("hermes-websocket-client-{}", id),
+        socket,
+        Role::Server,
+        move |connection, frame| receive(&*handler, &subscriptions, connection, peer, frame),
+        move || {
+            debug!("WebSocket client {} disconnected", id);
+            for (_, subscription) in closed_subscriptions.lock().unwrap().drain() {
+                subscription.cancel()
+            }
+            if let Some(state) = closed_state.upgrade() {
+                state.clients.lock().unwrap().remove(&id);
+            }
+        },
+    )?;
+    clients.insert(id, connection);
+    Ok(())
+}
+
+/// The subscriptions made on the handler for a client, by the id the client gave them
+type Subscriptions = Mutex<HashMap<u32, Subscription>>;
+
+fn receive(
+    handler: &dyn HermesProtocolHandler,
+    subscriptions: &Subscriptions,
+    connection: &Arc<Connection>,
+    peer: SocketAddr,
+    frame: Message,
+) {
+    let (subscription, result) = match frame {
+        Message::Text(text) => match serde_json::from_str(&text) {
+            Ok(ClientFrame::Subscribe { id, topic }) => {
+                let sink = SubscriptionSink {
+                    name: format!("WebSocket client {}", peer),
+                    connection: Arc::downgrade(connection),
+                    subscription: id,
+                    envelope: Default::default(),
+                    forwarding: Default::default(),
+                };
+                let result = parse(&topic)
+                    .and_then(|filter| subscribe(handler, &filter, &sink))
+                    .map(|subscription| {
+                        // a client reusing an id replaces its subscription
+                        if let Some(previous) = subscriptions.lock().unwrap().insert(id, subscription) {
+                            previous.cancel()
+                        }
+                    });
+                (Some(id), result)
+            }
+            Ok(ClientFrame::Unsubscribe { id }) => {
+                if let Some(subscription) = subscriptions.lock().unwrap().remove(&id) {
+                    subscription.cancel()
+                }
+                (Some(id), Ok(()))
+            }
+            Ok(ClientFrame::Publish { topic, payload }) => (
+                None,
+                parse(&topic).and_then(|topic| publish(handler, &topic, &raw_payload(payload.as_ref())?)),
+            ),
+            Err(e) => (None, Err(format_err!("invalid frame: {}", e))),
+        },
+        Message::Binary(bytes) => (
+            None,
+            decode_binary_publish(&bytes).and_then(|(topic, payload)| publish(handler, &parse(topic)?, payload)),
+        ),
+        _ => (None, Ok(())),
+    };
+
+    if let Err(e) = result {
+        warn!("Error while handling a frame from WebSocket client {}: {}", peer, e);
+        let frame = ServerFrame::Error {
+            subscription,
+            message: e.to_string(),
+        };
+        if let Err(e) = serde_json::to_string(&frame)
+            .map_err(failure::Error::from)
+            .and_then(|frame| connection.send(Message::Text(frame)))
+        {
+            debug!("Could not report an error to WebSocket client {}: {}", peer, e);
+        }
+    }
+}
+
+fn parse(topic: &str) -> Fallible<HermesTopic> {
+    HermesTopic::from_path(topic).ok_or_else(|| format_err!("unknown topic {}", topic))
+}
+
+/// The transport on which the messages of one subscription are republished to forward them to the
+/// client. The client dispatches them according to the subscription id, the topic of the frames is
+/// only informative. The connection is only borrowed, the messages are dropped once the client is
+/// gone.
+#[derive(Clone)]
+struct SubscriptionSink {
+    name: String,
+    connection: Weak<Connection>,
+    subscription: u32,
+    /// The envelope of the message being forwarded, added to its json payload
+    envelope: Arc<Mutex<Option<Envelope>>>,
+    /// Held while a message is forwarded, so that the messages don't swap their envelopes
+    forwarding: Arc<Mutex<()>>,
+}
+
+impl SubscriptionSink {
+    /// The handler republishing the messages of the subscription, created once for it
+    fn handler(&self) -> MqttHermesProtocolHandler {
+        MqttHermesProtocolHandler::new_with_transport(self.name.clone(), self.clone())
+    }
+
+    /// Republish with `publish` a message received with `envelope`
+    fn forward<F: FnOnce() -> Fallible<()>>(&self, envelope: Option<&Envelope>, publish: F) -> Fallible<()> {
+        let _forwarding = self.forwarding.lock().unwrap();
+        *self.envelope.lock().unwrap() = envelope.cloned();
+        let result = publish();
+        *self.envelope.lock().unwrap() = None;
+        result
+    }
+}
+
+impl Transport for SubscriptionSink {
+    fn publish(&self, topic: &str, payload: Vec<u8>) -> Fallible<()> {
+        let connection = match self.connection.upgrade().filter(|it| !it.is_closed()) {
+            Some(connection) => connection,
+            None => return Ok(()),
+        };
+        let binary = HermesTopic::from_path(topic).map_or(false, |topic| codec::is_binary(&topic));
+        let frame = if binary {
+            Message::Binary(encode_binary_message(self.subscription, topic, &payload)?)
+        } else {
+            let envelope = self.envelope.lock().unwrap().clone();
+            let payload = match (json_payload(&payload)?, envelope) {
+                (Some(payload), Some(envelope)) => Some(to_json_value(&payload, Some(&envelope))?),
+                (payload, _) => payload,
+            };
+            Message::Text(serde_json::to_string(&ServerFrame::Message {
+                subscription: self.subscription,
+                topic: topic.to_string(),
+                payload,
+            })?)
+        };
+        connection.send(frame)
+    }
+
+    fn subscribe(&self, topic_filter: &str, _callback: RawCallback) -> Fallible<()> {
+        Err(format_err!(
+            "can't subscribe to {} on a WebSocket subscription",
+            topic_filter
+        ))
+    }
+
+    fn unsubscribe(&self, topic_filter: &str) -> Fallible<()> {
+        Err(format_err!(
+            "can't unsubscribe from {} on a WebSocket subscription",
+            topic_filter
+        ))
+    }
+}
+
+fn is_wildcard(id: &str) -> bool {
+    id == "+" || id == "#"
+}
+
+fn is_all_sites(site_id: &Option<String>) -> bool {
+    site_id.as_ref().map_or(true, |it| is_wildcard(it))
+}
+
+fn unsupported(filter: &HermesTopic) -> failure::Error {
+    format_err!("subscribing to {} for all the sites is not supported", filter)
+}
+
+fn log_error(result: Fallible<()>) {
+    if let Err(e) = result {
+        warn!("Could not forward a message to a WebSocket client: {}", e)
+    }
+}
+
+/// Subscribe on `handler` with `$s`, republishing each message and its envelope on `sink` with
+/// `$p`, and give the subscription. The facade of `sink` is created once for the subscription. The
+/// callback moves what `$p_arg` uses before the arguments of `$s` are evaluated, they can't share a
+/// binding. The type of the messages is only known once the callback is given to `$s`, it is
+/// spelled out for `$p_arg` to use them.
+macro_rules! forward {
+    ($handler:ident.$s_facade:ident().$s:ident($($s_arg:expr),*) => $sink:ident.$p_facade:ident().$p:ident) => {{
+        let sink = $sink.clone();
+        let facade = $sink.handler().$p_facade();
+        let callback = Callback::with_envelope(move |message, envelope| {
+            log_error(sink.forward(envelope, || facade.$p(Clone::clone(message))))
+        });
+        let subscription = callback.subscription();
+        $handler.$s_facade().$s($($s_arg,)* callback).map(|_| subscription)
+    }};
+    ($handler:ident.$s_facade:ident().$s:ident($($s_arg:expr),*) => $sink:ident.$p_facade:ident().$p:ident |$it:ident: $it_type:ty| $p_arg:expr) => {{
+        let sink = $sink.clone();
+        let facade = $sink.handler().$p_facade();
+        let callback = Callback::with_envelope(move |$it: &$it_type, envelope| {
+            log_error(sink.forward(envelope, || facade.$p($p_arg, $it.clone())))
+        });
+        let subscription = callback.subscription();
+        $handler.$s_facade().$s($($s_arg,)* callback).map(|_| subscription)
+    }};
+    ($handler:ident.$s_facade:ident().$s:ident($($s_arg:expr),*) => $sink:ident.$p_facade:ident().$p:ident()) => {{
+        let facade = $sink.handler().$p_facade();
+        let callback = Callback0::new(move || log_error(facade.$p()));
+        let subscription = callback.subscription();
+        $handler.$s_facade().$s($($s_arg,)* callback).map(|_| subscription)
+    }};
+}
+
+macro_rules! forward_component {
+    ($handler:ident, $sink:ident, $facade:ident, $backend:ident, $command:expr) => {
+        match $command {
+            ComponentCommand::VersionRequest => {
+                forward!($handler.$backend().subscribe_version_request() => $sink.$facade().publish_version_request())
+            }
+            ComponentCommand::Version => forward!($handler.$facade().subscribe_version() => $sink.$backend().publish_version),
+            ComponentCommand::Error => forward!($handler.$facade().subscribe_error() => $sink.$backend().publish_error),
+            ComponentCommand::Loaded => {
+                forward!($handler.$facade().subscribe_component_loaded() => $sink.$backend().publish_component_loaded)
+            }
+        }
+    };
+    ($handler:ident, $sink:ident, $facade:ident, $backend:ident, $site_id:expr, $command:expr, $filter:expr) => {{
+        let site_id = $site_id.to_string();
+        let published_site_id = site_id.clone();
+        match $command {
+            ComponentCommand::VersionRequest if is_wildcard(&site_id) => Err(unsupported($filter)),
+            ComponentCommand::VersionRequest => {
+                let facade = $sink.handler().$facade();
+                let callback = Callback0::new(move || log_error(facade.publish_version_request(published_site_id.clone())));
+                let subscription = callback.subscription();
+                $handler
+                    .$backend()
+                    .subscribe_version_request(site_id, callback)
+                    .map(|_| subscription)
+            }
+            ComponentCommand::Version if is_wildcard(&site_id) => Err(unsupported($filter)),
+            ComponentCommand::Version => forward!($handler.$facade().subscribe_version(site_id.clone())
+                => $sink.$backend().publish_version |it: VersionMessage| published_site_id.clone()),
+            ComponentCommand::Error if is_wildcard(&site_id) => forward!($handler.$facade().subscribe_all_error()
+                => $sink.$backend().publish_error |it: SiteErrorMessage| it.site_id.clone()),
+            ComponentCommand::Error => forward!($handler.$facade().subscribe_error(site_id.clone())
+                => $sink.$backend().publish_error |it: SiteErrorMessage| published_site_id.clone()),
+            ComponentCommand::Loaded if is_wildcard(&site_id) => {
+                forward!($handler.$facade().subscribe_all_component_loaded()
+                    => $sink.$backend().publish_component_loaded |it: ComponentLoadedOnSiteMessage| it.site_id.clone())
+            }
+            ComponentCommand::Loaded => forward!($handler.$facade().subscribe_component_loaded(site_id.clone())
+                => $sink.$backend().publish_component_loaded |it: ComponentLoadedOnSiteMessage| published_site_id.clone()),
+        }
+    }};
+}
+
+/// The pattern of the command of a message, whatever the identifiers written after its path
+macro_rules! command_pattern {
+    ($command:ident $message:ident []) => {
+        $command::$message
+    };
+    ($command:ident $message:ident [command $path:literal]) => {
+        $command::$message
+    };
+    ($command:ident $message:ident [command $path:literal ($arg:ident)]) => {
+        $command::$message(_)
+    };
+    ($command:ident $message:ident [command $path:literal { $($field:ident),+ }]) => {
+        $command::$message { .. }
+    };
+}
+
+/// The pattern of the topics a message is published on, for any key
+macro_rules! message_topic {
+    ($decl:tt $message:ident [topic $own_topic:ident]) => {
+        HermesTopic::$own_topic(_)
+    };
+    ([$topic:ident [] $command:ident] $message:ident $route:tt) => {
+        HermesTopic::$topic(command_pattern!($command $message $route))
+    };
+    ([$topic:ident [$site:ty] $command:ident] $message:ident $route:tt) => {
+        HermesTopic::$topic(_, command_pattern!($command $message $route))
+    };
+}
+
+/// Forward the messages published with `$publish` for the key of `$filter`. The frontend messages
+/// are received on the backend facade and republished on the facade, the backend ones the other way
+/// around. `None` when the key of `$filter` is left to `subscribe`.
+macro_rules! forward_message {
+    (frontend [$facade:ident $backend:ident] $($rest:tt)*) => {
+        forward_message!(@forward $backend $facade $($rest)*)
+    };
+    (backend [$facade:ident $backend:ident] $($rest:tt)*) => {
+        forward_message!(@forward $facade $backend $($rest)*)
+    };
+    (@forward $from:ident $to:ident $handler:ident $filter:ident $sink:ident [] $publish:ident $subscribe:ident [] []) => {
+        match $filter.key() {
+            None => Some(forward!($handler.$from().$subscribe() => $sink.$to().$publish())),
+            Some(_) => None,
+        }
+    };
+    (
+        @forward $from:ident $to:ident $handler:ident $filter:ident $sink:ident [$($payload:tt)+]
+        $publish:ident $subscribe:ident [] []
+    ) => {
+        match $filter.key() {
+            None => Some(forward!($handler.$from().$subscribe() => $sink.$to().$publish)),
+            Some(_) => None,
+        }
+    };
+    (
+        @forward $from:ident $to:ident $handler:ident $filter:ident $sink:ident $payload:tt
+        $publish:ident $subscribe:ident [$key:ident in $($key_path:ident).+] [$subscribe_all:ident $(on $wildcard:literal)?]
+    ) => {
+        match $filter.key() {
+            Some(id) if !is_wildcard(id) => {
+                Some(forward!($handler.$from().$subscribe(id.to_string()) => $sink.$to().$publish))
+            }
+            _ => Some(forward!($handler.$from().$subscribe_all() => $sink.$to().$publish)),
+        }
+    };
+    (
+        @forward $from:ident $to:ident $handler:ident $filter:ident $sink:ident $payload:tt
+        $publish:ident $subscribe:ident [$key:ident in $($key_path:ident).+] []
+    ) => {
+        match $filter.key() {
+            Some(id) if !is_wildcard(id) => {
+                Some(forward!($handler.$from().$subscribe(id.to_string()) => $sink.$to().$publish))
+            }
+            _ => Some(Err(unsupported($filter))),
+        }
+    };
+    // the key is given to the publish method, it can only be republished for one key
+    (
+        @forward $from:ident $to:ident $handler:ident $filter:ident $sink:ident [$payload:ident: $payload_type:ident]
+        $publish:ident $subscribe:ident [$key:ident] $subscribe_all:tt
+    ) => {
+        match $filter.key() {
+            Some(id) if !is_wildcard(id) => {
+                let published_id = id.to_string();
+                Some(forward!($handler.$from().$subscribe(id.to_string())
+                    => $sink.$to().$publish |it: $payload_type| published_id.clone()))
+            }
+            _ => None,
+        }
+    };
+}
+
+/// Forward the messages of a component published on `$filter`
+macro_rules! forward_messages {
+    (
+        $handler:ident, $filter:ident, $sink:ident, $decl:tt $getters:tt
+        $({ $side:ident $message:ident $route:tt $($message_rest:tt)* })*
+    ) => {
+        $(
+            if let message_topic!($decl $message $route) = *$filter {
+                return forward_message!($side $getters $handler $filter $sink $($message_rest)*);
+            }
+        )*
+    };
+}
+
+/// Subscribe to the messages of the components described by `hermes::hermes_protocol!`
+macro_rules! websocket_forwarding {
+    ($(
+        $component:ident {
+            facade $facade_doc:tt $facade:ident $facade_supers:tt $facade_getter:ident;
+            backend $backend_doc:tt $backend:ident $backend_supers:tt $backend_getter:ident;
+            mqtt $mqtt_component:tt $mqtt_toggle:tt $mqtt_topic:tt;
+            messages [$({
+                $attributes:tt $side:ident $message:ident $route:tt
+                $payload:tt $publish:ident $subscribe:ident $key:tt $subscribe_all:tt $($rest:tt)*
+            })*];
+        }
+    )*) => {
+        /// Subscribe on `handler` to the message published on `filter` and republish it on `sink`,
+        /// `None` if `filter` is not the topic of a message or is left to `subscribe`
+        fn subscribe_message(
+            handler: &dyn HermesProtocolHandler,
+            filter: &HermesTopic,
+            sink: &SubscriptionSink,
+        ) -> Option<Fallible<Subscription>> {
+            $(
+                forward_messages!(
+                    handler, filter, sink, $mqtt_topic [$facade_getter $backend_getter]
+                    $({ $side $message $route $payload $publish $subscribe $key $subscribe_all })*
+                );
+            )*
+            None
+        }
+    };
+}
+
+hermes::hermes_protocol!(websocket_forwarding);
+
+/// Subscribe on `handler` to the messages published on the topics matching `filter`, a topic
+/// filter as used by the MQTT implementation, and republish them on `sink` until the subscription
+/// given back is cancelled
+fn subscribe(
+    handler: &dyn HermesProtocolHandler,
+    filter: &HermesTopic,
+    sink: &SubscriptionSink,
+) -> Fallible<Subscription> {
+    use self::HermesTopic::*;
+
+    if let Some(subscription) = subscribe_message(handler, filter, sink) {
+        return subscription;
+    }
+
+    match filter {
+        Hotword(site_id, HotwordCommand::ToggleOn) if is_all_sites(site_id) => {
+            forward!(handler.hotword_backend().subscribe_all_toggle_on() => sink.hotword().publish_toggle_on)
+        }
+        Hotword(Some(site_id), HotwordCommand::ToggleOn) => {
+            forward!(handler.hotword_backend().subscribe_toggle_on(site_id.clone()) => sink.hotword().publish_toggle_on)
+        }
+        Hotword(site_id, HotwordCommand::ToggleOff) if is_all_sites(site_id) => {
+            forward!(handler.hotword_backend().subscribe_all_toggle_off() => sink.hotword().publish_toggle_off)
+        }
+        Hotword(Some(site_id), HotwordCommand::ToggleOff) => {
+            forward!(handler.hotword_backend().subscribe_toggle_off(site_id.clone()) => sink.hotword().publish_toggle_off)
+        }
+        Hotword(Some(id), HotwordCommand::Detected) if is_wildcard(id) => {
+            forward!(handler.hotword().subscribe_all_detected()
+            => sink.hotword_backend().publish_detected |it: HotwordDetectedMessage| it.site_id.clone())
+        }
+
+        Feedback(FeedbackCommand::Sound(SoundCommand::ToggleOn)) => {
+            forward!(handler.sound_feedback_backend().subscribe_all_toggle_on() => sink.sound_feedback().publish_toggle_on)
+        }
+        Feedback(FeedbackCommand::Sound(SoundCommand::ToggleOff)) => {
+            forward!(handler.sound_feedback_backend().subscribe_all_toggle_off() => sink.sound_feedback().publish_toggle_off)
+        }
+
+        Asr(None, AsrCommand::ToggleOn) => {
+            forward!(handler.asr_backend().subscribe_toggle_on() => sink.asr().publish_toggle_on())
+        }
+        Asr(None, AsrCommand::ToggleOff) => {
+            forward!(handler.asr_backend().subscribe_toggle_off() => sink.asr().publish_toggle_off())
+        }
+
+        AudioServer(site_id, AudioServerCommand::ToggleOn) if is_all_sites(site_id) => {
+            forward!(handler.audio_server_backend().subscribe_all_toggle_on() => sink.audio_server().publish_toggle_on)
+        }
+        AudioServer(Some(site_id), AudioServerCommand::ToggleOn) => {
+            forward!(handler.audio_server_backend().subscribe_toggle_on(site_id.clone()) => sink.audio_server().publish_toggle_on)
+        }
+        AudioServer(site_id, AudioServerCommand::ToggleOff) if is_all_sites(site_id) => {
+            forward!(handler.audio_server_backend().subscribe_all_toggle_off() => sink.audio_server().publish_toggle_off)
+        }
+        AudioServer(Some(site_id), AudioServerCommand::ToggleOff) => {
+            forward!(handler.audio_server_backend().subscribe_toggle_off(site_id.clone()) => sink.audio_server().publish_toggle_off)
+        }
+
+        DialogueManager(None, DialogueManagerCommand::ToggleOn) => {
+            forward!(handler.dialogue_backend().subscribe_toggle_on() => sink.dialogue().publish_toggle_on())
+        }
+        DialogueManager(None, DialogueManagerCommand::ToggleOff) => {
+            forward!(handler.dialogue_backend().subscribe_toggle_off() => sink.dialogue().publish_toggle_off())
+        }
+
+        Component(None, topics::Component::Asr, command) => {
+            forward_component!(handler, sink, asr, asr_backend, command)
+        }
+        Component(None, topics::Component::Tts, command) => {
+            forward_component!(handler, sink, tts, tts_backend, command)
+        }
+        Component(None, topics::Component::Nlu, command) => {
+            forward_component!(handler, sink, nlu, nlu_backend, command)
+        }
+        Component(None, topics::Component::DialogueManager, command) => {
+            forward_component!(handler, sink, dialogue, dialogue_backend, command)
+        }
+        Component(None, topics::Component::Injection, command) => {
+            forward_component!(handler, sink, injection, injection_backend, command)
+        }
+        Component(Some(site_id), topics::Component::VoiceActivity, command) => {
+            forward_component!(
+                handler,
+                sink,
+                voice_activity,
+                voice_activity_backend,
+                site_id,
+                command,
+                filter
+            )
+        }
+        Component(Some(site_id), topics::Component::Hotword, command) => {
+            forward_component!(handler, sink, hotword, hotword_backend, site_id, command, filter)
+        }
+        Component(Some(site_id), topics::Component::AudioServer, command) => {
+            forward_component!(
+                handler,
+                sink,
+                audio_server,
+                audio_server_backend,
+                site_id,
+                command,
+                filter
+            )
+        }
+
+        _ => Err(format_err!("no messages are published on {}", filter)),
+    }
+}
+
+/// Publish on `handler` a message received from a client, the payload is encoded as for MQTT
+fn publish(handler: &dyn HermesProtocolHandler, topic: &HermesTopic, payload: &[u8]) -> Fallible<()> {
+    use self::HermesTopic::*;
+
+    macro_rules! json {
+        () => {
+            codec::decode_json(payload)?
+        };
+    }
+
+    macro_rules! component {
+        ($facade:ident, $backend:ident, $command:expr) => {
+            match $command {
+                ComponentCommand::VersionRequest => handler.$facade().publish_version_request(),
+                ComponentCommand::Version => handler.$backend().publish_version(json!()),
+                ComponentCommand::Error => handler.$backend().publish_error(json!()),
+                ComponentCommand::Loaded => handler.$backend().publish_component_loaded(json!()),
+            }
+        };
+        ($facade:ident, $backend:ident, $site_id:expr, $command:expr) => {
+            match $command {
+                ComponentCommand::VersionRequest => handler.$facade().publish_version_request($site_id),
+                ComponentCommand::Version => handler.$backend().publish_version($site_id, json!()),
+                ComponentCommand::Error => handler.$backend().publish_error($site_id, json!()),
+                ComponentCommand::Loaded => handler.$backend().publish_component_loaded($site_id, json!()),
+            }
+        };
+    }
+
+    match topic {
+        VoiceActivity(_, VoiceActivityCommand::VadUp) => handler.voice_activity_backend().publish_vad_up(json!()),
+        VoiceActivity(_, VoiceActivityCommand::VadDown) => handler.voice_activity_backend().publish_vad_down(json!()),
+
+        Hotword(_, HotwordCommand::ToggleOn) => handler.hotword().publish_toggle_on(json!()),
+        Hotword(_, HotwordCommand::ToggleOff) => handler.hotword().publish_toggle_off(json!()),
+        Hotword(Some(id), HotwordCommand::Detected) => handler.hotword_backend().publish_detected(id.clone(), json!()),
+
+        Feedback(FeedbackCommand::Sound(SoundCommand::ToggleOn)) => handler.sound_feedback().publish_toggle_on(json!()),
+        Feedback(FeedbackCommand::Sound(SoundCommand::ToggleOff)) => {
+            handler.sound_feedback().publish_toggle_off(json!())
+        }
+
+        Asr(_, AsrCommand::ToggleOn) => handler.asr().publish_toggle_on(),
+        Asr(_, AsrCommand::ToggleOff) => handler.asr().publish_toggle_off(),
+        Asr(_, AsrCommand::StartListening) => handler.asr().publish_start_listening(json!()),
+        Asr(_, AsrCommand::StopListening) => handler.asr().publish_stop_listening(json!()),
+        Asr(_, AsrCommand::Reload) => handler.asr().publish_component_reload(json!()),
+        Asr(_, AsrCommand::TextCaptured) => handler.asr_backend().publish_text_captured(json!()),
+        Asr(_, AsrCommand::PartialTextCaptured) => handler.asr_backend().publish_partial_text_captured(json!()),
+
+        Tts(_, TtsCommand::Say) => handler.tts().publish_say(json!()),
+        Tts(_, TtsCommand::SayFinished) => handler.tts_backend().publish_say_finished(json!()),
+        Tts(_, TtsCommand::RegisterSound(_)) => handler
+            .tts()
+            .publish_register_sound(codec::decode_register_sound(topic, payload)?),
+
+        Nlu(NluCommand::Query) => handler.nlu().publish_query(json!()),
+        Nlu(NluCommand::PartialQuery) => handler.nlu().publish_partial_query(json!()),
+        Nlu(NluCommand::Reload) => handler.nlu().publish_component_reload(json!()),
+        Nlu(NluCommand::SlotParsed) => handler.nlu_backend().publish_slot_parsed(json!()),
+        Nlu(NluCommand::IntentParsed) => handler.nlu_backend().publish_intent_parsed(json!()),
+        Nlu(NluCommand::IntentNotRecognized) => handler.nlu_backend().publish_intent_not_recognized(json!()),
+
+        Intent(_) => handler.dialogue_backend().publish_intent(json!()),
+
+        AudioServer(_, AudioServerCommand::ToggleOn) => handler.audio_server().publish_toggle_on(json!()),
+        AudioServer(_, AudioServerCommand::ToggleOff) => handler.audio_server().publish_toggle_off(json!()),
+        AudioServer(Some(_), AudioServerCommand::AudioFrame) => handler
+            .audio_server_backend()
+            .publish_audio_frame(codec::decode_audio_frame(topic, payload)?),
+        AudioServer(Some(_), AudioServerCommand::ReplayRequest) => {
+            handler.audio_server().publish_replay_request(json!())
+        }
+        AudioServer(Some(_), AudioServerCommand::ReplayResponse) => handler
+            .audio_server_backend()
+            .publish_replay_response(codec::decode_audio_frame(topic, payload)?),
+        AudioServer(Some(_), AudioServerCommand::PlayBytes(_)) => handler
+            .audio_server()
+            .publish_play_bytes(codec::decode_play_bytes(topic, payload)?),
+        AudioServer(Some(_), AudioServerCommand::PlayFinished) => {
+            handler.audio_server_backend().publish_play_finished(json!())
+        }
+        AudioServer(Some(_), AudioServerCommand::StreamBytes { .. }) => handler
+            .audio_server()
+            .publish_stream_bytes(codec::decode_stream_bytes(topic, payload)?),
+        AudioServer(Some(_), AudioServerCommand::StreamFinished) => {
+            handler.audio_server_backend().publish_stream_finished(json!())
+        }
+
+        DialogueManager(_, DialogueManagerCommand::ToggleOn) => handler.dialogue().publish_toggle_on(),
+        DialogueManager(_, DialogueManagerCommand::ToggleOff) => handler.dialogue().publish_toggle_off(),
+        DialogueManager(_, DialogueManagerCommand::StartSession) => handler.dialogue().publish_start_session(json!()),
+        DialogueManager(_, DialogueManagerCommand::ContinueSession) => {
+            handler.dialogue().publish_continue_session(json!())
+        }
+        DialogueManager(_, DialogueManagerCommand::EndSession) => handler.dialogue().publish_end_session(json!()),
+        DialogueManager(_, DialogueManagerCommand::Configure) => handler.dialogue().publish_configure(json!()),
+        DialogueManager(_, DialogueManagerCommand::SessionQueued) => {
+            handler.dialogue_backend().publish_session_queued(json!())
+        }
+        DialogueManager(_, DialogueManagerCommand::SessionStarted) => {
+            handler.dialogue_backend().publish_session_started(json!())
+        }
+        DialogueManager(_, DialogueManagerCommand::SessionEnded) => {
+            handler.dialogue_backend().publish_session_ended(json!())
+        }
+        DialogueManager(_, DialogueManagerCommand::IntentNotRecognized) => {
+            handler.dialogue_backend().publish_intent_not_recognized(json!())
+        }
+
+        Injection(InjectionCommand::Perform) => handler.injection().publish_injection_request(json!()),
+        Injection(InjectionCommand::StatusRequest) => handler.injection().publish_injection_status_request(),
+        Injection(InjectionCommand::ResetRequest) => handler.injection().publish_injection_reset_request(json!()),
+        Injection(InjectionCommand::Status) => handler.injection_backend().publish_injection_status(json!()),
+        Injection(InjectionCommand::Complete) => handler.injection_backend().publish_injection_complete(json!()),
+        Injection(InjectionCommand::ResetComplete) => {
+            handler.injection_backend().publish_injection_reset_complete(json!())
+        }
+
+        Component(None, topics::Component::Asr, command) => component!(asr, asr_backend, command),
+        Component(None, topics::Component::Tts, command) => component!(tts, tts_backend, command),
+        Component(None, topics::Component::Nlu, command) => component!(nlu, nlu_backend, command),
+        Component(None, topics::Component::DialogueManager, command) => component!(dialogue, dialogue_backend, command),
+        Component(None, topics::Component::Injection, command) => component!(injection, injection_backend, command),
+        Component(Some(site_id), topics::Component::VoiceActivity, command) => {
+            component!(voice_activity, voice_activity_backend, site_id.clone(), command)
+        }
+        Component(Some(site_id), topics::Component::Hotword, command) => {
+            component!(hotword, hotword_backend, site_id.clone(), command)
+        }
+        Component(Some(site_id), topics::Component::AudioServer, command) => {
+            component!(audio_server, audio_server_backend, site_id.clone(), command)
+        }
+
+        _ => Err(format_err!("no messages can be published on {}", topic)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use hermes_inprocess::InProcessHermesProtocolHandler;
+
+    use super::*;
+    use crate::WebSocketHermesProtocolHandler;
+
+    fn wait_until<F: Fn() -> bool>(condition: F) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !condition() {
+            assert!(Instant::now() < deadline, "timed out");
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn disconnected_clients_are_forgotten() {
+        let server = WebSocketServer::bind("127.0.0.1:0", Arc::new(InProcessHermesProtocolHandler::new())).unwrap();
+        let clients = || server.state.clients.lock().unwrap().len();
+
+        let client = WebSocketHermesProtocolHandler::new(&format!("ws://{}", server.address())).unwrap();
+        client
+            .dialogue()
+            .subscribe_intents(Callback::new(|_: &IntentMessage| ()))
+            .unwrap();
+        wait_until(|| clients() == 1);
+
+        drop(client);
+        wait_until(|| clients() == 0);
+    }
+}
@@ -0,0 +1,114 @@
+//! Metadata on who sent a message and when.
+
+use chrono::prelude::*;
+use failure::Fallible;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::session::unique_id;
+use crate::HermesComponent;
+
+/// The version of the ontology of this crate, given in the envelopes it creates
+pub const ONTOLOGY_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The metadata a protocol handler can add to the messages it publishes, the callbacks created
+/// with `Callback::with_envelope` are given it. The messages don't have to carry one, the ones
+/// coming from older components don't.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Envelope {
+    /// A unique id of the message, a message received twice has the same id both times
+    pub message_id: String,
+    /// When the message was published
+    pub sent_at: DateTime<Utc>,
+    /// The component that published the message
+    pub source: MessageSource,
+    /// The version of the ontology of the component that published the message
+    pub ontology_version: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageSource {
+    pub component: HermesComponent,
+    /// The site the component serves, if it serves a single one
+    pub site_id: Option<String>,
+}
+
+impl Envelope {
+    /// The envelope of a message published now by `source`
+    pub fn new(source: MessageSource) -> Self {
+        Self {
+            message_id: unique_id("hermes-message"),
+            sent_at: Utc::now(),
+            source,
+            ontology_version: ONTOLOGY_VERSION.to_string(),
+        }
+    }
+}
+
+/// The json of `message` with `envelope` as its `envelope` field, which the components not knowing
+/// about envelopes ignore. Only the messages serialized as json objects can carry one.
+pub fn to_json_value<P: Serialize>(message: &P, envelope: Option<&Envelope>) -> Fallible<Value> {
+    let mut value = serde_json::to_value(message)?;
+    if let Some(envelope) = envelope {
+        match value {
+            Value::Object(ref mut fields) => {
+                fields.insert("envelope".into(), serde_json::to_value(envelope)?);
+            }
+            _ => bail!("only a json object can carry an envelope"),
+        }
+    }
+    Ok(value)
+}
+
+/// Take the `envelope` field out of the json of a message, `None` when it has none or an invalid
+/// one
+pub fn take_envelope(message: &mut Value) -> Option<Envelope> {
+    match message {
+        Value::Object(fields) => serde_json::from_value(fields.remove("envelope")?).ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn envelope() -> Envelope {
+        Envelope::new(MessageSource {
+            component: HermesComponent::Hotword,
+            site_id: Some("kitchen".into()),
+        })
+    }
+
+    #[test]
+    fn envelopes_are_serialized_in_camel_case() {
+        let json = serde_json::to_value(&envelope()).unwrap();
+        assert_eq!(json["source"]["component"], "hotword");
+        assert_eq!(json["source"]["siteId"], "kitchen");
+        assert_eq!(json["ontologyVersion"], ONTOLOGY_VERSION);
+        assert!(json["messageId"].is_string());
+        assert!(json["sentAt"].is_string());
+    }
+
+    #[test]
+    fn envelopes_are_a_field_of_the_json_messages() {
+        let envelope = envelope();
+        let site = crate::SiteMessage {
+            site_id: "kitchen".into(),
+            session_id: None,
+        };
+
+        let mut json = to_json_value(&site, Some(&envelope)).unwrap();
+        assert_eq!(json["siteId"], "kitchen");
+        assert_eq!(take_envelope(&mut json), Some(envelope.clone()));
+        assert_eq!(json, to_json_value(&site, None).unwrap());
+        assert_eq!(take_envelope(&mut json), None);
+
+        let mut json = serde_json::json!({ "siteId": "kitchen", "envelope": { "messageId": 3 } });
+        assert_eq!(take_envelope(&mut json), None);
+        assert!(json.get("envelope").is_none());
+        assert!(to_json_value(&"kitchen", Some(&envelope)).is_err());
+    }
+}
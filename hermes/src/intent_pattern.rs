@@ -0,0 +1,130 @@
+//! Subscriptions to the intents whose name matches a pattern.
+
+use failure::Fallible;
+
+use crate::*;
+
+/// A glob pattern on intent names, `*` matches any sequence of characters and `?` any single one
+#[derive(Debug, Clone, PartialEq)]
+pub enum IntentPattern {
+    /// A pattern without wildcard, matching one intent name
+    Exact(String),
+    /// `*`, matching every intent name
+    All,
+    /// Any other pattern
+    Glob(Vec<char>),
+}
+
+impl IntentPattern {
+    pub fn new(pattern: &str) -> Self {
+        if !pattern.contains(|c: char| c == '*' || c == '?') {
+            IntentPattern::Exact(pattern.to_string())
+        } else if pattern.chars().all(|c| c == '*') {
+            IntentPattern::All
+        } else {
+            IntentPattern::Glob(pattern.chars().collect())
+        }
+    }
+
+    pub fn matches(&self, intent_name: &str) -> bool {
+        match *self {
+            IntentPattern::Exact(ref name) => name == intent_name,
+            IntentPattern::All => true,
+            IntentPattern::Glob(ref pattern) => glob_matches(pattern, &intent_name.chars().collect::<Vec<_>>()),
+        }
+    }
+}
+
+/// Match `name` against `pattern`, going back to the last `*` on a mismatch to let it take one
+/// more character
+fn glob_matches(pattern: &[char], name: &[char]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    let mut last_star = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some(&'*') => {
+                last_star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match last_star {
+                Some((star, taken)) => {
+                    last_star = Some((star, taken + 1));
+                    p = star + 1;
+                    n = taken + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Subscribe to the intents of the dialogue by pattern, see `IntentPattern`
+pub trait DialogueIntentPatterns {
+    /// Subscribe to the intents whose name matches the glob `pattern`. A pattern without wildcard
+    /// is subscribed to with `subscribe_intent` and `*` with `subscribe_intents`, so that MQTT
+    /// filters them on the broker, the other patterns receive all the intents and filter them.
+    /// `+` and `#` are matched as any other character, but can't be the whole pattern.
+    fn subscribe_intents_matching(&self, pattern: String, handler: Callback<IntentMessage>) -> Fallible<()>;
+}
+
+impl<D: DialogueFacade + ?Sized> DialogueIntentPatterns for D {
+    fn subscribe_intents_matching(&self, pattern: String, handler: Callback<IntentMessage>) -> Fallible<()> {
+        match IntentPattern::new(&pattern) {
+            IntentPattern::Exact(intent_name) => self.subscribe_intent(intent_name, handler),
+            IntentPattern::All => self.subscribe_intents(handler),
+            pattern => self.subscribe_intents(Callback::with_envelope(move |intent: &IntentMessage, envelope| {
+                if pattern.matches(&intent.intent.intent_name) {
+                    handler.call_with_envelope(intent, envelope)
+                }
+            })),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patterns_without_wildcard_are_exact() {
+        assert_eq!(
+            IntentPattern::new("user:lights"),
+            IntentPattern::Exact("user:lights".into())
+        );
+        assert_eq!(IntentPattern::new("**"), IntentPattern::All);
+        assert!(!IntentPattern::new("lights").matches("lightsOn"));
+    }
+
+    #[test]
+    fn glob_patterns_match() {
+        let cases = [
+            ("weather*", "weather", true),
+            ("weather*", "weatherForecast", true),
+            ("weather*", "userWeather", false),
+            ("user:*", "user:turnOn", true),
+            ("user:*", "other:turnOn", false),
+            ("*On", "user:turnOn", true),
+            ("*On", "user:turnOff", false),
+            ("*:turn*", "user:turnOff", true),
+            ("*a*b", "xaybzb", true),
+            ("*a*b", "xaybzc", false),
+            ("turn?n", "turnOn", true),
+            ("turn?n", "turnOOn", false),
+            ("?*", "", false),
+        ];
+        for &(pattern, name, expected) in cases.iter() {
+            assert_eq!(
+                IntentPattern::new(pattern).matches(name),
+                expected,
+                "{} on {}",
+                pattern,
+                name
+            );
+        }
+    }
+}
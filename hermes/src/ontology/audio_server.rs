@@ -154,6 +154,8 @@ pub struct StreamFinishedMessage {
 
 impl<'de> HermesMessage<'de> for PlayFinishedMessage {}
 
+impl<'de> HermesMessage<'de> for StreamFinishedMessage {}
+
 fn as_base64<S>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error>
 where
     S: serde::Serializer,
@@ -0,0 +1,582 @@
+//! The declarative description of the hermes protocol.
+//!
+//! Every component is described once here: its facades, how it appears over MQTT and the messages
+//! it exchanges. The facade traits, the MQTT topics, the MQTT, in-process and WebSocket
+//! implementations, the symbols of the FFI and the conformance test suite are all generated from
+//! this description, so adding a message only takes a new entry below.
+//!
+//! The description is given to a macro of the caller's choosing with `hermes_protocol!(callback)`.
+//! The callback receives, for each component:
+//!
+//! ```text
+//! Name {
+//!     facade [#[doc]...] FacadeTrait [SuperTrait + ...] facade_getter;
+//!     backend [#[doc]...] BackendFacadeTrait [SuperTrait + ...] backend_getter;
+//!     mqtt [component expression] [toggle on topic, toggle off topic] [Topic [SiteType] Command];
+//!     messages [
+//!         { [attributes] side MessageName [route] [payload: PayloadType] publish_method
+//!           subscribe_method [key (in path.to.key)] [subscribe_all_method (on "wildcard")] [topic]
+//!           [encoder, decoder] }
+//!         ...
+//!     ];
+//! }
+//! ```
+//!
+//! where `side` is `frontend` for the messages published by the facade and received by the
+//! backend, and `backend` for the other way around. A message with a key is received either for
+//! one key, by giving it to the subscribe method, or for all of them with the subscribe all method.
+//! The key is read from the payload when its path is given, otherwise it is given alongside the
+//! payload to the publish method. The topic is the MQTT topic of the message, built from the key
+//! if any. The site of the ASR, TTS and dialogue manager topics is only written in the site-scoped
+//! topic layout of the MQTT implementation, the global layout leaves it in the payload. The
+//! encoder and decoder are the MQTT codec functions of binary messages, the others are sent as
+//! JSON. The payload type is a name in scope of the `hermes` crate root, the FFI derives the name
+//! of its C structure from it. The attributes are `quiet`, for the messages too frequent to be
+//! logged, and `site`, for the messages whose payload has a `site_id` that a site-scoped handler
+//! fills in and filters on.
+//!
+//! The topics of a component are the `Topic` variant of `HermesTopic`, holding the site when its
+//! type is given and a value of the `Command` enum. The command of a message is the variant of
+//! that enum named after it, written in the topic as its name with the first letter lowercased,
+//! unless the route of the message is `command "path"`, optionally followed by the identifiers
+//! written after the path as `(identifier)` or `{ identifier, ... }`. The route `topic Variant`
+//! gives the message a variant of `HermesTopic` of its own, holding its key. The toggles of a
+//! component are its `ToggleOn` and `ToggleOff` commands.
+//!
+//! The facades shared by the components (`ComponentFacade`, `ToggleableFacade` and their
+//! identifiable and backend variants) are described apart with `hermes_shared_facades!(callback)`,
+//! they are implemented once for all the components by each transport. The callback receives, for
+//! each of them:
+//!
+//! ```text
+//! [#[doc]...] Trait [
+//!     fn method(&self, argument: Type, ...);
+//!     ...
+//! ]
+//! ```
+//!
+//! where every method returns a `Fallible<()>`.
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __hermes_protocol_parse {
+    (
+        $callback:ident;
+        $(
+            component $component:ident {
+                $(#[$facade_doc:meta])*
+                facade $facade:ident: $facade_super:ident $(+ $facade_supers:ident)* = $facade_getter:ident;
+                $(#[$backend_doc:meta])*
+                backend $backend:ident: $backend_super:ident $(+ $backend_supers:ident)* = $backend_getter:ident;
+                mqtt {
+                    $(topic $topic_variant:ident $(($topic_site:ty))? $topic_command:ident;)?
+                    $(component $mqtt_component:expr;)?
+                    $(toggle $toggle_on:expr, $toggle_off:expr;)?
+                }
+                $(
+                    $(#[$attribute:ident])*
+                    $side:ident $message:ident $(($payload:ident: $payload_type:ident))? {
+                        publish $publish:ident;
+                        subscribe $subscribe:ident
+                            $(by $key:ident $(in $($key_path:ident).+)?)?
+                            $(, all $subscribe_all:ident $(on $wildcard:literal)?)?;
+                        mqtt $topic:expr $(, binary $encode:expr, $decode:path)?;
+                        $(topic $own_topic:ident;)?
+                        $(command $command_path:literal $(($command_arg:ident))? $({ $($command_field:ident),+ })?;)?
+                    }
+                )*
+            }
+        )*
+    ) => {
+        $callback! {
+            $(
+                $component {
+                    facade [$(#[$facade_doc])*] $facade [$facade_super $(+ $facade_supers)*] $facade_getter;
+                    backend [$(#[$backend_doc])*] $backend [$backend_super $(+ $backend_supers)*] $backend_getter;
+                    mqtt [$($mqtt_component)?] [$($toggle_on, $toggle_off)?]
+                        [$($topic_variant [$($topic_site)?] $topic_command)?];
+                    messages [$(
+                        {
+                            [$($attribute)*] $side $message
+                            [
+                                $(topic $own_topic)?
+                                $(command $command_path $(($command_arg))? $({ $($command_field),+ })?)?
+                            ]
+                            [$($payload: $payload_type)?] $publish $subscribe
+                            [$($key $(in $($key_path).+)?)?] [$($subscribe_all $(on $wildcard)?)?]
+                            [$topic] [$($encode, $decode)?]
+                        }
+                    )*];
+                }
+            )*
+        }
+    };
+}
+
+/// Give the description of the protocol to the macro `$callback`, see the module documentation
+/// for the format it receives
+#[macro_export]
+macro_rules! hermes_protocol {
+    ($callback:ident) => {
+        $crate::__hermes_protocol_parse! {
+            $callback;
+
+            component VoiceActivity {
+                /// Facade used to interact with the voice activity component
+                facade VoiceActivityFacade: IdentifiableComponentFacade = voice_activity;
+                /// Facade the voice activity component must use to publish its results
+                backend VoiceActivityBackendFacade: IdentifiableComponentBackendFacade = voice_activity_backend;
+                mqtt {
+                    topic VoiceActivity(String) VoiceActivityCommand;
+                    component Component::VoiceActivity;
+                }
+
+                #[site]
+                backend VadUp(vad_up: VadUpMessage) {
+                    publish publish_vad_up;
+                    subscribe subscribe_vad_up by site_id in site_id, all subscribe_all_vad_up;
+                    mqtt HermesTopic::VoiceActivity(site_id, VoiceActivityCommand::VadUp);
+                }
+
+                #[site]
+                backend VadDown(vad_down: VadDownMessage) {
+                    publish publish_vad_down;
+                    subscribe subscribe_vad_down by site_id in site_id, all subscribe_all_vad_down;
+                    mqtt HermesTopic::VoiceActivity(site_id, VoiceActivityCommand::VadDown);
+                }
+            }
+
+            component Hotword {
+                /// The facade to interact with the hotword component
+                facade HotwordFacade: IdentifiableComponentFacade + IdentifiableToggleableFacade = hotword;
+                /// The facade the hotword feature must use receive its orders and publish detected hotwords
+                backend HotwordBackendFacade: IdentifiableComponentBackendFacade + IdentifiableToggleableBackendFacade = hotword_backend;
+                mqtt {
+                    topic Hotword(Option<String>) HotwordCommand;
+                    component Component::Hotword;
+                    toggle HermesTopic::Hotword(None, HotwordCommand::ToggleOn),
+                        HermesTopic::Hotword(None, HotwordCommand::ToggleOff);
+                }
+
+                #[site]
+                backend Detected(message: HotwordDetectedMessage) {
+                    publish publish_detected;
+                    subscribe subscribe_detected by hotword_id, all subscribe_all_detected;
+                    mqtt HermesTopic::Hotword(Some(hotword_id), HotwordCommand::Detected);
+                }
+            }
+
+            component SoundFeedback {
+                /// The facade used to toggle on and of the sound feedback at a specific site
+                facade SoundFeedbackFacade: IdentifiableToggleableFacade = sound_feedback;
+                /// The facade a component that manages sound feedback must use to receive its orders
+                backend SoundFeedbackBackendFacade: IdentifiableToggleableBackendFacade = sound_feedback_backend;
+                mqtt {
+                    toggle HermesTopic::Feedback(FeedbackCommand::Sound(SoundCommand::ToggleOn)),
+                        HermesTopic::Feedback(FeedbackCommand::Sound(SoundCommand::ToggleOff));
+                }
+            }
+
+            component Asr {
+                /// The facade to interact with the automatic speech recognition component
+                facade AsrFacade: ComponentFacade + ToggleableFacade = asr;
+                /// The facade the automatic speech recognition must use to receive its orders and publish
+                /// recognized text
+                backend AsrBackendFacade: ComponentBackendFacade + ToggleableBackendFacade = asr_backend;
+                mqtt {
+                    topic Asr(Option<String>) AsrCommand;
+                    component Component::Asr;
+                    toggle HermesTopic::Asr(None, AsrCommand::ToggleOn), HermesTopic::Asr(None, AsrCommand::ToggleOff);
+                }
+
+                #[site]
+                frontend StartListening(start: AsrStartListeningMessage) {
+                    publish publish_start_listening;
+                    subscribe subscribe_start_listening by site_id in site_id, all subscribe_all_start_listening;
+                    mqtt HermesTopic::Asr(Some(site_id), AsrCommand::StartListening);
+                }
+
+                #[site]
+                frontend StopListening(site: SiteMessage) {
+                    publish publish_stop_listening;
+                    subscribe subscribe_stop_listening by site_id in site_id, all subscribe_all_stop_listening;
+                    mqtt HermesTopic::Asr(Some(site_id), AsrCommand::StopListening);
+                }
+
+                frontend Reload(component_reload: RequestComponentReloadMessage) {
+                    publish publish_component_reload;
+                    subscribe subscribe_component_reload;
+                    mqtt HermesTopic::Asr(None, AsrCommand::Reload);
+                }
+
+                #[site]
+                backend TextCaptured(text_captured: TextCapturedMessage) {
+                    publish publish_text_captured;
+                    subscribe subscribe_text_captured by site_id in site_id, all subscribe_all_text_captured;
+                    mqtt HermesTopic::Asr(Some(site_id), AsrCommand::TextCaptured);
+                }
+
+                #[site]
+                backend PartialTextCaptured(text_captured: TextCapturedMessage) {
+                    publish publish_partial_text_captured;
+                    subscribe subscribe_partial_text_captured by site_id in site_id, all subscribe_all_partial_text_captured;
+                    mqtt HermesTopic::Asr(Some(site_id), AsrCommand::PartialTextCaptured);
+                }
+            }
+
+            component Tts {
+                /// The facade to interact with the text to speech component
+                facade TtsFacade: ComponentFacade = tts;
+                /// The facade the text to speech must use to receive its orders and advertise when it has finished
+                backend TtsBackendFacade: ComponentBackendFacade = tts_backend;
+                mqtt {
+                    topic Tts(Option<String>) TtsCommand;
+                    component Component::Tts;
+                }
+
+                #[site]
+                frontend Say(to_say: SayMessage) {
+                    publish publish_say;
+                    subscribe subscribe_say by site_id in site_id, all subscribe_all_say;
+                    mqtt HermesTopic::Tts(Some(site_id), TtsCommand::Say);
+                }
+
+                backend SayFinished(status: SayFinishedMessage) {
+                    publish publish_say_finished;
+                    subscribe subscribe_say_finished;
+                    mqtt HermesTopic::Tts(None, TtsCommand::SayFinished);
+                }
+
+                frontend RegisterSound(sound: RegisterSoundMessage) {
+                    publish publish_register_sound;
+                    subscribe subscribe_register_sound;
+                    mqtt HermesTopic::Tts(None, TtsCommand::RegisterSound("#".into())),
+                        binary codec::encode_register_sound, codec::decode_register_sound;
+                    command "registerSound" (sound_id);
+                }
+            }
+
+            component Nlu {
+                /// The facade to interact with the natural language understanding component
+                facade NluFacade: ComponentFacade = nlu;
+                /// The facade the natural language understanding must use to receive its orders and publish
+                /// its results
+                backend NluBackendFacade: ComponentBackendFacade = nlu_backend;
+                mqtt {
+                    topic Nlu NluCommand;
+                    component Component::Nlu;
+                }
+
+                frontend Query(query: NluQueryMessage) {
+                    publish publish_query;
+                    subscribe subscribe_query;
+                    mqtt HermesTopic::Nlu(NluCommand::Query);
+                }
+
+                frontend PartialQuery(query: NluSlotQueryMessage) {
+                    publish publish_partial_query;
+                    subscribe subscribe_partial_query;
+                    mqtt HermesTopic::Nlu(NluCommand::PartialQuery);
+                }
+
+                frontend Reload(component_reload: RequestComponentReloadMessage) {
+                    publish publish_component_reload;
+                    subscribe subscribe_component_reload;
+                    mqtt HermesTopic::Nlu(NluCommand::Reload);
+                }
+
+                backend SlotParsed(slot: NluSlotMessage) {
+                    publish publish_slot_parsed;
+                    subscribe subscribe_slot_parsed;
+                    mqtt HermesTopic::Nlu(NluCommand::SlotParsed);
+                }
+
+                backend IntentParsed(intent: NluIntentMessage) {
+                    publish publish_intent_parsed;
+                    subscribe subscribe_intent_parsed;
+                    mqtt HermesTopic::Nlu(NluCommand::IntentParsed);
+                }
+
+                backend IntentNotRecognized(status: NluIntentNotRecognizedMessage) {
+                    publish publish_intent_not_recognized;
+                    subscribe subscribe_intent_not_recognized;
+                    mqtt HermesTopic::Nlu(NluCommand::IntentNotRecognized);
+                }
+            }
+
+            component AudioServer {
+                /// The facade to interact with the audio server
+                facade AudioServerFacade: IdentifiableComponentFacade + IdentifiableToggleableFacade = audio_server;
+                /// The facade the audio server must use to receive its orders and advertise when it has finished
+                backend AudioServerBackendFacade: IdentifiableComponentBackendFacade + IdentifiableToggleableBackendFacade = audio_server_backend;
+                mqtt {
+                    topic AudioServer(Option<String>) AudioServerCommand;
+                    component Component::AudioServer;
+                    toggle HermesTopic::AudioServer(None, AudioServerCommand::ToggleOn),
+                        HermesTopic::AudioServer(None, AudioServerCommand::ToggleOff);
+                }
+
+                #[site]
+                frontend PlayBytes(bytes: PlayBytesMessage) {
+                    publish publish_play_bytes;
+                    subscribe subscribe_play_bytes by site_id in site_id, all subscribe_all_play_bytes;
+                    mqtt HermesTopic::AudioServer(Some(site_id), AudioServerCommand::PlayBytes("#".into())),
+                        binary codec::encode_play_bytes, codec::decode_play_bytes;
+                    command "playBytes" (id);
+                }
+
+                #[site]
+                backend PlayFinished(status: PlayFinishedMessage) {
+                    publish publish_play_finished;
+                    subscribe subscribe_play_finished by site_id in site_id, all subscribe_all_play_finished;
+                    mqtt HermesTopic::AudioServer(Some(site_id), AudioServerCommand::PlayFinished);
+                }
+
+                #[quiet]
+                #[site]
+                backend AudioFrame(frame: AudioFrameMessage) {
+                    publish publish_audio_frame;
+                    subscribe subscribe_audio_frame by site_id in site_id, all subscribe_all_audio_frame;
+                    mqtt HermesTopic::AudioServer(Some(site_id), AudioServerCommand::AudioFrame),
+                        binary |frame| codec::encode_audio_frame(frame, AudioServerCommand::AudioFrame),
+                        codec::decode_audio_frame;
+                }
+
+                #[site]
+                frontend ReplayRequest(request: ReplayRequestMessage) {
+                    publish publish_replay_request;
+                    subscribe subscribe_replay_request by site_id in site_id;
+                    mqtt HermesTopic::AudioServer(Some(site_id), AudioServerCommand::ReplayRequest);
+                }
+
+                #[quiet]
+                #[site]
+                backend ReplayResponse(frame: AudioFrameMessage) {
+                    publish publish_replay_response;
+                    subscribe subscribe_replay_response by site_id in site_id;
+                    mqtt HermesTopic::AudioServer(Some(site_id), AudioServerCommand::ReplayResponse),
+                        binary |frame| codec::encode_audio_frame(frame, AudioServerCommand::ReplayResponse),
+                        codec::decode_audio_frame;
+                }
+
+                #[site]
+                frontend StreamBytes(bytes: StreamBytesMessage) {
+                    publish publish_stream_bytes;
+                    subscribe subscribe_stream_bytes by site_id in site_id, all subscribe_all_stream_bytes;
+                    mqtt HermesTopic::AudioServer(
+                        Some(site_id),
+                        AudioServerCommand::StreamBytes {
+                            stream_id: "+".into(),
+                            chunk_number: "+".into(),
+                            is_last_chunk: "+".into(),
+                        }
+                    ), binary codec::encode_stream_bytes, codec::decode_stream_bytes;
+                    command "playBytesStreaming" { stream_id, chunk_number, is_last_chunk };
+                }
+
+                #[site]
+                backend StreamFinished(status: StreamFinishedMessage) {
+                    publish publish_stream_finished;
+                    subscribe subscribe_stream_finished by site_id in site_id, all subscribe_all_stream_finished;
+                    mqtt HermesTopic::AudioServer(Some(site_id), AudioServerCommand::StreamFinished);
+                }
+            }
+
+            component Dialogue {
+                /// The facade to use to interact with the dialogue manager, this is the principal interface that a
+                /// lambda should use
+                facade DialogueFacade: ComponentFacade + ToggleableFacade = dialogue;
+                /// The facade the dialogue manager must use to interact with the lambdas
+                backend DialogueBackendFacade: ComponentBackendFacade + ToggleableBackendFacade = dialogue_backend;
+                mqtt {
+                    topic DialogueManager(Option<String>) DialogueManagerCommand;
+                    component Component::DialogueManager;
+                    toggle HermesTopic::DialogueManager(None, DialogueManagerCommand::ToggleOn),
+                        HermesTopic::DialogueManager(None, DialogueManagerCommand::ToggleOff);
+                }
+
+                #[site]
+                backend SessionQueued(status: SessionQueuedMessage) {
+                    publish publish_session_queued;
+                    subscribe subscribe_session_queued by site_id in site_id, all subscribe_all_session_queued;
+                    mqtt HermesTopic::DialogueManager(Some(site_id), DialogueManagerCommand::SessionQueued);
+                }
+
+                #[site]
+                backend SessionStarted(status: SessionStartedMessage) {
+                    publish publish_session_started;
+                    subscribe subscribe_session_started by site_id in site_id, all subscribe_all_session_started;
+                    mqtt HermesTopic::DialogueManager(Some(site_id), DialogueManagerCommand::SessionStarted);
+                }
+
+                #[site]
+                backend Intent(intent: IntentMessage) {
+                    publish publish_intent;
+                    subscribe subscribe_intent by intent_name in intent.intent_name, all subscribe_intents on "#";
+                    mqtt HermesTopic::Intent(intent_name);
+                    topic Intent;
+                }
+
+                #[site]
+                backend IntentNotRecognized(intent_not_recognized: IntentNotRecognizedMessage) {
+                    publish publish_intent_not_recognized;
+                    subscribe subscribe_intent_not_recognized by site_id in site_id, all subscribe_all_intent_not_recognized;
+                    mqtt HermesTopic::DialogueManager(Some(site_id), DialogueManagerCommand::IntentNotRecognized);
+                }
+
+                #[site]
+                backend SessionEnded(status: SessionEndedMessage) {
+                    publish publish_session_ended;
+                    subscribe subscribe_session_ended by site_id in site_id, all subscribe_all_session_ended;
+                    mqtt HermesTopic::DialogueManager(Some(site_id), DialogueManagerCommand::SessionEnded);
+                }
+
+                #[site]
+                frontend StartSession(start_session: StartSessionMessage) {
+                    publish publish_start_session;
+                    subscribe subscribe_start_session;
+                    mqtt HermesTopic::DialogueManager(None, DialogueManagerCommand::StartSession);
+                }
+
+                frontend ContinueSession(continue_session: ContinueSessionMessage) {
+                    publish publish_continue_session;
+                    subscribe subscribe_continue_session;
+                    mqtt HermesTopic::DialogueManager(None, DialogueManagerCommand::ContinueSession);
+                }
+
+                frontend EndSession(end_session: EndSessionMessage) {
+                    publish publish_end_session;
+                    subscribe subscribe_end_session;
+                    mqtt HermesTopic::DialogueManager(None, DialogueManagerCommand::EndSession);
+                }
+
+                #[site]
+                frontend Configure(config: DialogueConfigureMessage) {
+                    publish publish_configure;
+                    subscribe subscribe_configure;
+                    mqtt HermesTopic::DialogueManager(None, DialogueManagerCommand::Configure);
+                }
+            }
+
+            component Injection {
+                /// The facade to interact with the injection component
+                facade InjectionFacade: ComponentFacade = injection;
+                /// The facade the injecter must use to receive its orders and advertise when it has finished
+                backend InjectionBackendFacade: ComponentBackendFacade = injection_backend;
+                mqtt {
+                    topic Injection InjectionCommand;
+                    component Component::Injection;
+                }
+
+                frontend Perform(request: InjectionRequestMessage) {
+                    publish publish_injection_request;
+                    subscribe subscribe_injection_request;
+                    mqtt HermesTopic::Injection(InjectionCommand::Perform);
+                }
+
+                frontend StatusRequest {
+                    publish publish_injection_status_request;
+                    subscribe subscribe_injection_status_request;
+                    mqtt HermesTopic::Injection(InjectionCommand::StatusRequest);
+                }
+
+                frontend ResetRequest(request: InjectionResetRequestMessage) {
+                    publish publish_injection_reset_request;
+                    subscribe subscribe_injection_reset_request;
+                    mqtt HermesTopic::Injection(InjectionCommand::ResetRequest);
+                    command "reset/perform";
+                }
+
+                backend Status(status: InjectionStatusMessage) {
+                    publish publish_injection_status;
+                    subscribe subscribe_injection_status;
+                    mqtt HermesTopic::Injection(InjectionCommand::Status);
+                }
+
+                backend Complete(message: InjectionCompleteMessage) {
+                    publish publish_injection_complete;
+                    subscribe subscribe_injection_complete;
+                    mqtt HermesTopic::Injection(InjectionCommand::Complete);
+                }
+
+                backend ResetComplete(message: InjectionResetCompleteMessage) {
+                    publish publish_injection_reset_complete;
+                    subscribe subscribe_injection_reset_complete;
+                    mqtt HermesTopic::Injection(InjectionCommand::ResetComplete);
+                    command "reset/complete";
+                }
+            }
+        }
+    };
+}
+
+/// Give the description of the facades shared by the components to the macro `$callback`, see the
+/// module documentation for the format it receives
+#[macro_export]
+macro_rules! hermes_shared_facades {
+    ($callback:ident) => {
+        $callback! {
+            /// A generic facade used to interact with a component
+            ComponentFacade [
+                fn publish_version_request(&self);
+                fn subscribe_version(&self, handler: Callback<VersionMessage>);
+                fn subscribe_error(&self, handler: Callback<ErrorMessage>);
+                fn subscribe_component_loaded(&self, handler: Callback<ComponentLoadedMessage>);
+            ]
+
+            /// A generic facade used to interact with a component
+            IdentifiableComponentFacade [
+                fn publish_version_request(&self, id: String);
+                fn subscribe_version(&self, id: String, handler: Callback<VersionMessage>);
+                fn subscribe_error(&self, id: String, handler: Callback<SiteErrorMessage>);
+                fn subscribe_all_error(&self, handler: Callback<SiteErrorMessage>);
+                fn subscribe_component_loaded(&self, id: String, handler: Callback<ComponentLoadedOnSiteMessage>);
+                fn subscribe_all_component_loaded(&self, handler: Callback<ComponentLoadedOnSiteMessage>);
+            ]
+
+            /// A generic facade all components must use to publish their errors and versions (when requested)
+            ComponentBackendFacade [
+                fn subscribe_version_request(&self, handler: Callback0);
+                fn publish_version(&self, version: VersionMessage);
+                fn publish_error(&self, error: ErrorMessage);
+                fn publish_component_loaded(&self, component_loaded: ComponentLoadedMessage);
+            ]
+
+            /// A generic facade all components must use to publish their errors and versions (when requested)
+            IdentifiableComponentBackendFacade [
+                fn subscribe_version_request(&self, id: String, handler: Callback0);
+                fn publish_version(&self, id: String, version: VersionMessage);
+                fn publish_error(&self, id: String, error: SiteErrorMessage);
+                fn publish_component_loaded(&self, id: String, component_loaded: ComponentLoadedOnSiteMessage);
+            ]
+
+            /// A facade to interact with a component that can be toggled on an off at a specific site
+            ToggleableFacade [
+                fn publish_toggle_on(&self);
+                fn publish_toggle_off(&self);
+            ]
+
+            /// The facade a component that can be toggled on an off at a specific site must use to receive
+            /// its orders
+            ToggleableBackendFacade [
+                fn subscribe_toggle_on(&self, handler: Callback0);
+                fn subscribe_toggle_off(&self, handler: Callback0);
+            ]
+
+            /// A facade to interact with a component that can be toggled on an off at a specific site
+            IdentifiableToggleableFacade [
+                fn publish_toggle_on(&self, site: SiteMessage);
+                fn publish_toggle_off(&self, site: SiteMessage);
+            ]
+
+            /// The facade a component that can be toggled on an off at a specific site must use to receive
+            /// its orders, either for one site or for all of them
+            IdentifiableToggleableBackendFacade [
+                fn subscribe_toggle_on(&self, site_id: String, handler: Callback<SiteMessage>);
+                fn subscribe_all_toggle_on(&self, handler: Callback<SiteMessage>);
+                fn subscribe_toggle_off(&self, site_id: String, handler: Callback<SiteMessage>);
+                fn subscribe_all_toggle_off(&self, handler: Callback<SiteMessage>);
+            ]
+        }
+    };
+}
@@ -0,0 +1,358 @@
+//! Handles on the sessions of the dialogue.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use failure::{bail, format_err, Fallible};
+
+use crate::*;
+
+static NEXT_UNIQUE_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// A handle on a dialogue session, get one with `session` or `start_session_and_track` on a
+/// dialogue facade shared in an `Arc`.
+///
+/// The callbacks registered on the handle only receive the messages of its session. They are
+/// released when the session ends, after the ones given to `on_ended` are called, and the
+/// subscriptions of the handle are cancelled along with them. The handle can be cloned and moved
+/// into these callbacks to continue or end the session from them, such a clone lives until the
+/// session ends. Once the last clone is dropped a tracked session isn't looked for anymore, and if
+/// its id wasn't found yet its callbacks are released.
+pub struct Session<D: ?Sized> {
+    dialogue: Arc<D>,
+    state: Arc<SessionState>,
+    release: Arc<Release>,
+}
+
+impl<D: ?Sized> Clone for Session<D> {
+    fn clone(&self) -> Self {
+        Self {
+            dialogue: Arc::clone(&self.dialogue),
+            state: Arc::clone(&self.state),
+            release: Arc::clone(&self.release),
+        }
+    }
+}
+
+/// Shared by the clones of a handle, stops tracking its session when the last one is dropped
+struct Release(Arc<SessionState>);
+
+impl Drop for Release {
+    fn drop(&mut self) {
+        cancel(&self.0.tracking);
+        // nothing can find the id of the session anymore, its callbacks would never be called
+        if self.0.session_id.lock().map(|it| it.is_none()).unwrap_or(true) {
+            self.0.cancel_subscriptions()
+        }
+    }
+}
+
+/// Start and follow the sessions of the dialogue, see `Session`
+pub trait DialogueSessions {
+    /// The dialogue facade the handles publish on
+    type Dialogue: ?Sized;
+
+    /// A handle on the session `session_id`
+    fn session<S: Into<String>>(&self, session_id: S) -> Fallible<Session<Self::Dialogue>>;
+
+    /// Start a session and give a handle on it. Its id is the one of the `SessionStartedMessage`
+    /// or `SessionQueuedMessage` having the custom data of the published `start_session`, which is
+    /// replaced by a unique correlation id. The callbacks of the handle get the messages with the
+    /// custom data of `start_session` back, the other subscribers see the correlation id. The
+    /// handle doesn't receive anything until the session is started or queued.
+    fn start_session_and_track(&self, start_session: StartSessionMessage) -> Fallible<Session<Self::Dialogue>>;
+}
+
+impl<D: DialogueFacade + ?Sized> DialogueSessions for Arc<D> {
+    type Dialogue = D;
+
+    fn session<S: Into<String>>(&self, session_id: S) -> Fallible<Session<D>> {
+        Session::new(Arc::clone(self), Some(session_id.into()), None)
+    }
+
+    fn start_session_and_track(&self, mut start_session: StartSessionMessage) -> Fallible<Session<D>> {
+        let correlation = Correlation {
+            id: unique_id("hermes-session"),
+            custom_data: start_session.custom_data.take(),
+        };
+        start_session.custom_data = Some(correlation.id.clone());
+        // dropped without a session id on failure, the handle then cancels its subscriptions
+        let session = Session::new(Arc::clone(self), None, Some(correlation))?;
+        session.track()?;
+        self.publish_start_session(start_session)?;
+        Ok(session)
+    }
+}
+
+/// An id that no other process or call gives, starting with `prefix`
+pub(crate) fn unique_id(prefix: &str) -> String {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|it| it.as_nanos())
+        .unwrap_or(0);
+    format!(
+        "{}-{}-{}-{}",
+        prefix,
+        std::process::id(),
+        timestamp,
+        NEXT_UNIQUE_ID.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+impl<D: DialogueFacade + ?Sized> Session<D> {
+    fn new(dialogue: Arc<D>, session_id: Option<String>, correlation: Option<Correlation>) -> Fallible<Self> {
+        let state = Arc::new(SessionState {
+            session_id: Mutex::new(session_id),
+            correlation,
+            callbacks: Mutex::new(Some(SessionCallbacks::default())),
+            subscriptions: Mutex::new(vec![]),
+            tracking: Mutex::new(vec![]),
+        });
+        let session = Self {
+            dialogue,
+            release: Arc::new(Release(Arc::clone(&state))),
+            state,
+        };
+        if let Err(e) = session.subscribe() {
+            session.state.cancel_subscriptions();
+            return Err(e);
+        }
+        Ok(session)
+    }
+
+    /// Subscribe to the messages of the session
+    fn subscribe(&self) -> Fallible<()> {
+        let intent_state = Arc::clone(&self.state);
+        let intents = Callback::with_envelope(move |intent: &IntentMessage, envelope| {
+            let callbacks = intent_state.callbacks(&intent.session_id, |it| &it.intent);
+            if !callbacks.is_empty() {
+                let intent = intent_state.with_custom_data(intent, |it| &mut it.custom_data);
+                for callback in callbacks {
+                    callback.call_with_envelope(&intent, envelope)
+                }
+            }
+        });
+        keep(&self.state.subscriptions, intents.subscription())?;
+        self.dialogue.subscribe_intents(intents)?;
+
+        let intent_not_recognized_state = Arc::clone(&self.state);
+        let intents_not_recognized = Callback::with_envelope(move |message: &IntentNotRecognizedMessage, envelope| {
+            let state = &intent_not_recognized_state;
+            let callbacks = state.callbacks(&message.session_id, |it| &it.intent_not_recognized);
+            if !callbacks.is_empty() {
+                let message = state.with_custom_data(message, |it| &mut it.custom_data);
+                for callback in callbacks {
+                    callback.call_with_envelope(&message, envelope)
+                }
+            }
+        });
+        keep(&self.state.subscriptions, intents_not_recognized.subscription())?;
+        self.dialogue
+            .subscribe_all_intent_not_recognized(intents_not_recognized)?;
+
+        let ended_state = Arc::clone(&self.state);
+        let ended = Callback::with_envelope(move |ended: &SessionEndedMessage, envelope| {
+            let callbacks = ended_state.end(&ended.session_id);
+            if !callbacks.is_empty() {
+                let ended = ended_state.with_custom_data(ended, |it| &mut it.custom_data);
+                for callback in callbacks {
+                    callback.call_with_envelope(&ended, envelope)
+                }
+            }
+        });
+        keep(&self.state.subscriptions, ended.subscription())?;
+        self.dialogue.subscribe_all_session_ended(ended)
+    }
+
+    /// Look for the id of a tracked session in the started and queued messages
+    fn track(&self) -> Fallible<()> {
+        let state = Arc::clone(&self.state);
+        let started = Callback::new(move |started: &SessionStartedMessage| {
+            state.resolve(&started.session_id, &started.custom_data)
+        });
+        keep(&self.state.tracking, started.subscription())?;
+        self.dialogue.subscribe_all_session_started(started)?;
+
+        let state = Arc::clone(&self.state);
+        let queued =
+            Callback::new(move |queued: &SessionQueuedMessage| state.resolve(&queued.session_id, &queued.custom_data));
+        keep(&self.state.tracking, queued.subscription())?;
+        self.dialogue.subscribe_all_session_queued(queued)
+    }
+
+    /// The id of the session, `None` while a tracked session isn't started or queued
+    pub fn session_id(&self) -> Option<String> {
+        self.state.session_id.lock().ok()?.clone()
+    }
+
+    pub fn has_ended(&self) -> bool {
+        self.state.callbacks.lock().map(|it| it.is_none()).unwrap_or(true)
+    }
+
+    pub fn on_intent(&self, handler: Callback<IntentMessage>) -> Fallible<()> {
+        self.state.register(handler, |it| &mut it.intent)
+    }
+
+    pub fn on_intent_not_recognized(&self, handler: Callback<IntentNotRecognizedMessage>) -> Fallible<()> {
+        self.state.register(handler, |it| &mut it.intent_not_recognized)
+    }
+
+    pub fn on_ended(&self, handler: Callback<SessionEndedMessage>) -> Fallible<()> {
+        self.state.register(handler, |it| &mut it.ended)
+    }
+
+    /// Continue the session, the session id of `continue_session` is replaced by the one of the
+    /// session
+    pub fn continue_with(&self, mut continue_session: ContinueSessionMessage) -> Fallible<()> {
+        continue_session.session_id = self.current_session_id()?;
+        self.dialogue.publish_continue_session(continue_session)
+    }
+
+    /// End the session, the session id of `end_session` is replaced by the one of the session
+    pub fn end(&self, mut end_session: EndSessionMessage) -> Fallible<()> {
+        end_session.session_id = self.current_session_id()?;
+        self.dialogue.publish_end_session(end_session)
+    }
+
+    fn current_session_id(&self) -> Fallible<String> {
+        if self.has_ended() {
+            bail!("the session has ended")
+        }
+        self.state
+            .session_id
+            .lock()
+            .map_err(PoisonLock::from)?
+            .clone()
+            .ok_or_else(|| format_err!("the session isn't started yet"))
+    }
+}
+
+/// The callbacks registered on a session
+#[derive(Default)]
+struct SessionCallbacks {
+    intent: Vec<Arc<Callback<IntentMessage>>>,
+    intent_not_recognized: Vec<Arc<Callback<IntentNotRecognizedMessage>>>,
+    ended: Vec<Arc<Callback<SessionEndedMessage>>>,
+}
+
+/// How a tracked session is told apart: the id published as its custom data, and the custom data
+/// given when starting it
+struct Correlation {
+    id: String,
+    custom_data: Option<String>,
+}
+
+/// The state of a session shared by its handle and its subscriptions
+struct SessionState {
+    session_id: Mutex<Option<String>>,
+    /// `None` unless the session is tracked
+    correlation: Option<Correlation>,
+    /// `None` once the session has ended
+    callbacks: Mutex<Option<SessionCallbacks>>,
+    /// The subscriptions of the handle, cancelled when the session ends
+    subscriptions: Mutex<Vec<Subscription>>,
+    /// The subscriptions looking for the id of a tracked session, cancelled once it is found
+    tracking: Mutex<Vec<Subscription>>,
+}
+
+fn keep(subscriptions: &Mutex<Vec<Subscription>>, subscription: Subscription) -> Fallible<()> {
+    subscriptions.lock().map_err(PoisonLock::from)?.push(subscription);
+    Ok(())
+}
+
+/// Cancel `subscriptions`, once the lock is released since the protocol handlers may call back
+fn cancel(subscriptions: &Mutex<Vec<Subscription>>) {
+    let cancelled = match subscriptions.lock() {
+        Ok(mut subscriptions) => subscriptions.drain(..).collect(),
+        Err(_) => vec![],
+    };
+    for subscription in cancelled {
+        subscription.cancel()
+    }
+}
+
+impl SessionState {
+    fn is_session(&self, session_id: &str) -> bool {
+        self.session_id
+            .lock()
+            .map(|it| it.as_ref().map(|it| it == session_id).unwrap_or(false))
+            .unwrap_or(false)
+    }
+
+    /// Set the id of a tracked session, the first started or queued message carrying its
+    /// correlation id wins
+    fn resolve(&self, session_id: &str, custom_data: &Option<String>) {
+        let correlation_id = self.correlation.as_ref().map(|it| &it.id);
+        if correlation_id.is_none() || custom_data.as_ref() != correlation_id {
+            return;
+        }
+        if let Ok(mut current) = self.session_id.lock() {
+            if current.is_none() {
+                *current = Some(session_id.to_string())
+            }
+        }
+        cancel(&self.tracking)
+    }
+
+    /// `message` with the custom data given when starting a tracked session instead of its
+    /// correlation id
+    fn with_custom_data<T: Clone>(&self, message: &T, custom_data: fn(&mut T) -> &mut Option<String>) -> T {
+        let mut message = message.clone();
+        if let Some(ref correlation) = self.correlation {
+            let field = custom_data(&mut message);
+            if field.as_ref() == Some(&correlation.id) {
+                *field = correlation.custom_data.clone()
+            }
+        }
+        message
+    }
+
+    fn register<T>(
+        &self,
+        handler: Callback<T>,
+        select: fn(&mut SessionCallbacks) -> &mut Vec<Arc<Callback<T>>>,
+    ) -> Fallible<()> {
+        match *self.callbacks.lock().map_err(PoisonLock::from)? {
+            Some(ref mut callbacks) => select(callbacks).push(Arc::new(handler)),
+            None => bail!("the session has ended"),
+        }
+        Ok(())
+    }
+
+    /// The callbacks to call with a message of the session `session_id`, they are called once the
+    /// lock is released so that they can register other callbacks
+    fn callbacks<T>(
+        &self,
+        session_id: &str,
+        select: fn(&SessionCallbacks) -> &Vec<Arc<Callback<T>>>,
+    ) -> Vec<Arc<Callback<T>>> {
+        if !self.is_session(session_id) {
+            return vec![];
+        }
+        match self.callbacks.lock() {
+            Ok(callbacks) => callbacks.as_ref().map(|it| select(it).clone()).unwrap_or_default(),
+            Err(_) => vec![],
+        }
+    }
+
+    /// Release the callbacks and cancel the subscriptions if `session_id` is the one of the
+    /// session, giving back the callbacks to call with the end of the session
+    fn end(&self, session_id: &str) -> Vec<Arc<Callback<SessionEndedMessage>>> {
+        if !self.is_session(session_id) {
+            return vec![];
+        }
+        // the other callbacks are dropped once the lock is released, they may hold the last handle
+        let callbacks = match self.callbacks.lock() {
+            Ok(mut callbacks) => callbacks.take(),
+            Err(_) => None,
+        };
+        self.cancel_subscriptions();
+        callbacks.map(|it| it.ended).unwrap_or_default()
+    }
+
+    fn cancel_subscriptions(&self) {
+        cancel(&self.tracking);
+        cancel(&self.subscriptions)
+    }
+}
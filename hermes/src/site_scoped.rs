@@ -0,0 +1,427 @@
+//! Views of a protocol handler restricted to one site.
+
+use std::fmt;
+
+use failure::Fallible;
+
+use crate::*;
+
+/// A protocol handler restricted to one site, get one with `for_site` on any handler.
+///
+/// Its facades fill in the site of the messages they publish and only deliver the messages of
+/// their site to the subscribers. The messages keyed by site are subscribed to for the site alone,
+/// so that a site-scoped MQTT topic layout doesn't even send the others, subscribing to them for
+/// another site is an error. The messages carrying a site in another way, as the errors and the
+/// component loaded messages of the identifiable components, are filtered on their payload. An
+/// optional site, as in `StartSessionMessage`, only matches when it is given. The messages that
+/// don't belong to a site and the other component messages are left as they are.
+pub struct SiteScopedHandler<'a, H: ?Sized> {
+    handler: &'a H,
+    site_id: String,
+}
+
+impl<'a, H: ?Sized> SiteScopedHandler<'a, H> {
+    /// The site this handler is restricted to
+    pub fn site_id(&self) -> &str {
+        &self.site_id
+    }
+}
+
+impl<'a, H: HermesProtocolHandler + ?Sized> fmt::Display for SiteScopedHandler<'a, H> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} for site {}", self.handler, self.site_id)
+    }
+}
+
+/// Restrict a protocol handler to one site, see `SiteScopedHandler`
+pub trait ForSite {
+    fn for_site<S: Into<String>>(&self, site_id: S) -> SiteScopedHandler<'_, Self>;
+}
+
+impl<H: HermesProtocolHandler + ?Sized> ForSite for H {
+    fn for_site<S: Into<String>>(&self, site_id: S) -> SiteScopedHandler<'_, Self> {
+        SiteScopedHandler {
+            handler: self,
+            site_id: site_id.into(),
+        }
+    }
+}
+
+/// The `site_id` field of a message, some messages only have an optional one
+trait SiteId {
+    fn set_site(&mut self, site_id: &str);
+    fn is_site(&self, site_id: &str) -> bool;
+}
+
+impl SiteId for String {
+    fn set_site(&mut self, site_id: &str) {
+        *self = site_id.to_string();
+    }
+
+    fn is_site(&self, site_id: &str) -> bool {
+        self == site_id
+    }
+}
+
+impl SiteId for Option<String> {
+    fn set_site(&mut self, site_id: &str) {
+        *self = Some(site_id.to_string());
+    }
+
+    fn is_site(&self, site_id: &str) -> bool {
+        self.as_ref().map(|it| it == site_id).unwrap_or(false)
+    }
+}
+
+/// A facade of the wrapped handler and the site it is restricted to
+struct SiteScoped<F: ?Sized> {
+    facade: Box<F>,
+    site_id: String,
+}
+
+impl<F: ?Sized> SiteScoped<F> {
+    /// Only call `handler` with the messages whose site, read by `site`, is the one of the facade
+    fn on_site<T, S>(&self, handler: Callback<T>, site: fn(&T) -> &S) -> Callback<T>
+    where
+        T: 'static,
+        S: SiteId + 'static,
+    {
+        let site_id = self.site_id.clone();
+        Callback::with_envelope(move |payload: &T, envelope| {
+            if site(payload).is_site(&site_id) {
+                handler.call_with_envelope(payload, envelope)
+            }
+        })
+    }
+
+    /// Fails when `site_id` isn't the site of the facade
+    fn check_site(&self, site_id: &str) -> Fallible<()> {
+        if site_id != self.site_id {
+            bail!(
+                "this facade is restricted to the site {}, not {}",
+                self.site_id,
+                site_id
+            )
+        }
+        Ok(())
+    }
+}
+
+impl<F: ComponentFacade + ?Sized> ComponentFacade for SiteScoped<F> {
+    fn publish_version_request(&self) -> Fallible<()> {
+        self.facade.publish_version_request()
+    }
+
+    fn subscribe_version(&self, handler: Callback<VersionMessage>) -> Fallible<()> {
+        self.facade.subscribe_version(handler)
+    }
+
+    fn subscribe_error(&self, handler: Callback<ErrorMessage>) -> Fallible<()> {
+        self.facade.subscribe_error(handler)
+    }
+
+    fn subscribe_component_loaded(&self, handler: Callback<ComponentLoadedMessage>) -> Fallible<()> {
+        self.facade.subscribe_component_loaded(handler)
+    }
+}
+
+impl<F: IdentifiableComponentFacade + ?Sized> IdentifiableComponentFacade for SiteScoped<F> {
+    fn publish_version_request(&self, id: String) -> Fallible<()> {
+        self.facade.publish_version_request(id)
+    }
+
+    fn subscribe_version(&self, id: String, handler: Callback<VersionMessage>) -> Fallible<()> {
+        self.facade.subscribe_version(id, handler)
+    }
+
+    fn subscribe_error(&self, id: String, handler: Callback<SiteErrorMessage>) -> Fallible<()> {
+        let handler = self.on_site(handler, |it: &SiteErrorMessage| &it.site_id);
+        self.facade.subscribe_error(id, handler)
+    }
+
+    fn subscribe_all_error(&self, handler: Callback<SiteErrorMessage>) -> Fallible<()> {
+        let handler = self.on_site(handler, |it: &SiteErrorMessage| &it.site_id);
+        self.facade.subscribe_all_error(handler)
+    }
+
+    fn subscribe_component_loaded(&self, id: String, handler: Callback<ComponentLoadedOnSiteMessage>) -> Fallible<()> {
+        let handler = self.on_site(handler, |it: &ComponentLoadedOnSiteMessage| &it.site_id);
+        self.facade.subscribe_component_loaded(id, handler)
+    }
+
+    fn subscribe_all_component_loaded(&self, handler: Callback<ComponentLoadedOnSiteMessage>) -> Fallible<()> {
+        let handler = self.on_site(handler, |it: &ComponentLoadedOnSiteMessage| &it.site_id);
+        self.facade.subscribe_all_component_loaded(handler)
+    }
+}
+
+impl<F: ComponentBackendFacade + ?Sized> ComponentBackendFacade for SiteScoped<F> {
+    fn subscribe_version_request(&self, handler: Callback0) -> Fallible<()> {
+        self.facade.subscribe_version_request(handler)
+    }
+
+    fn publish_version(&self, version: VersionMessage) -> Fallible<()> {
+        self.facade.publish_version(version)
+    }
+
+    fn publish_error(&self, error: ErrorMessage) -> Fallible<()> {
+        self.facade.publish_error(error)
+    }
+
+    fn publish_component_loaded(&self, component_loaded: ComponentLoadedMessage) -> Fallible<()> {
+        self.facade.publish_component_loaded(component_loaded)
+    }
+}
+
+impl<F: IdentifiableComponentBackendFacade + ?Sized> IdentifiableComponentBackendFacade for SiteScoped<F> {
+    fn subscribe_version_request(&self, id: String, handler: Callback0) -> Fallible<()> {
+        self.facade.subscribe_version_request(id, handler)
+    }
+
+    fn publish_version(&self, id: String, version: VersionMessage) -> Fallible<()> {
+        self.facade.publish_version(id, version)
+    }
+
+    fn publish_error(&self, id: String, mut error: SiteErrorMessage) -> Fallible<()> {
+        error.site_id.set_site(&self.site_id);
+        self.facade.publish_error(id, error)
+    }
+
+    fn publish_component_loaded(&self, id: String, mut component_loaded: ComponentLoadedOnSiteMessage) -> Fallible<()> {
+        component_loaded.site_id.set_site(&self.site_id);
+        self.facade.publish_component_loaded(id, component_loaded)
+    }
+}
+
+impl<F: ToggleableFacade + ?Sized> ToggleableFacade for SiteScoped<F> {
+    fn publish_toggle_on(&self) -> Fallible<()> {
+        self.facade.publish_toggle_on()
+    }
+
+    fn publish_toggle_off(&self) -> Fallible<()> {
+        self.facade.publish_toggle_off()
+    }
+}
+
+impl<F: ToggleableBackendFacade + ?Sized> ToggleableBackendFacade for SiteScoped<F> {
+    fn subscribe_toggle_on(&self, handler: Callback0) -> Fallible<()> {
+        self.facade.subscribe_toggle_on(handler)
+    }
+
+    fn subscribe_toggle_off(&self, handler: Callback0) -> Fallible<()> {
+        self.facade.subscribe_toggle_off(handler)
+    }
+}
+
+impl<F: IdentifiableToggleableFacade + ?Sized> IdentifiableToggleableFacade for SiteScoped<F> {
+    fn publish_toggle_on(&self, mut site: SiteMessage) -> Fallible<()> {
+        site.site_id.set_site(&self.site_id);
+        self.facade.publish_toggle_on(site)
+    }
+
+    fn publish_toggle_off(&self, mut site: SiteMessage) -> Fallible<()> {
+        site.site_id.set_site(&self.site_id);
+        self.facade.publish_toggle_off(site)
+    }
+}
+
+impl<F: IdentifiableToggleableBackendFacade + ?Sized> IdentifiableToggleableBackendFacade for SiteScoped<F> {
+    fn subscribe_toggle_on(&self, site_id: String, handler: Callback<SiteMessage>) -> Fallible<()> {
+        self.check_site(&site_id)?;
+        self.facade.subscribe_toggle_on(site_id, handler)
+    }
+
+    fn subscribe_all_toggle_on(&self, handler: Callback<SiteMessage>) -> Fallible<()> {
+        self.facade.subscribe_toggle_on(self.site_id.clone(), handler)
+    }
+
+    fn subscribe_toggle_off(&self, site_id: String, handler: Callback<SiteMessage>) -> Fallible<()> {
+        self.check_site(&site_id)?;
+        self.facade.subscribe_toggle_off(site_id, handler)
+    }
+
+    fn subscribe_all_toggle_off(&self, handler: Callback<SiteMessage>) -> Fallible<()> {
+        self.facade.subscribe_toggle_off(self.site_id.clone(), handler)
+    }
+}
+
+/// The publish method of a message, the messages with the `site` attribute get the site of the
+/// facade
+macro_rules! site_scoped_publish {
+    ([] [] $publish:ident $($rest:tt)*) => {
+        fn $publish(&self) -> Fallible<()> {
+            self.facade.$publish()
+        }
+    };
+    ([] [$payload:ident: $payload_type:ty] $publish:ident $subscribe:ident [$key:ident] $($rest:tt)*) => {
+        fn $publish(&self, $key: String, $payload: $payload_type) -> Fallible<()> {
+            self.facade.$publish($key, $payload)
+        }
+    };
+    ([] [$payload:ident: $payload_type:ty] $publish:ident $($rest:tt)*) => {
+        fn $publish(&self, $payload: $payload_type) -> Fallible<()> {
+            self.facade.$publish($payload)
+        }
+    };
+    ([site $($attribute:ident)*] [$payload:ident: $payload_type:ty] $publish:ident $subscribe:ident [$key:ident] $($rest:tt)*) => {
+        fn $publish(&self, $key: String, mut $payload: $payload_type) -> Fallible<()> {
+            $payload.site_id.set_site(&self.site_id);
+            self.facade.$publish($key, $payload)
+        }
+    };
+    ([site $($attribute:ident)*] [$payload:ident: $payload_type:ty] $publish:ident $($rest:tt)*) => {
+        fn $publish(&self, mut $payload: $payload_type) -> Fallible<()> {
+            $payload.site_id.set_site(&self.site_id);
+            self.facade.$publish($payload)
+        }
+    };
+    ([$attribute:ident $($attributes:ident)*] $($message:tt)*) => {
+        site_scoped_publish!([$($attributes)*] $($message)*);
+    };
+}
+
+/// The subscribe methods of a message, the messages with the `site` attribute are only delivered
+/// for the site of the facade
+macro_rules! site_scoped_subscribe {
+    ([] [] $publish:ident $subscribe:ident $($rest:tt)*) => {
+        fn $subscribe(&self, handler: Callback0) -> Fallible<()> {
+            self.facade.$subscribe(handler)
+        }
+    };
+    ([] [$payload:ident: $payload_type:ty] $publish:ident $subscribe:ident [] $($rest:tt)*) => {
+        fn $subscribe(&self, handler: Callback<$payload_type>) -> Fallible<()> {
+            self.facade.$subscribe(handler)
+        }
+    };
+    ([] [$payload:ident: $payload_type:ty] $publish:ident $subscribe:ident [$key:ident $($key_path:tt)*] [] $($rest:tt)*) => {
+        fn $subscribe(&self, $key: String, handler: Callback<$payload_type>) -> Fallible<()> {
+            self.facade.$subscribe($key, handler)
+        }
+    };
+    (
+        []
+        [$payload:ident: $payload_type:ty]
+        $publish:ident
+        $subscribe:ident
+        [$key:ident $($key_path:tt)*]
+        [$subscribe_all:ident $($wildcard:tt)*]
+        $($rest:tt)*
+    ) => {
+        fn $subscribe(&self, $key: String, handler: Callback<$payload_type>) -> Fallible<()> {
+            self.facade.$subscribe($key, handler)
+        }
+
+        fn $subscribe_all(&self, handler: Callback<$payload_type>) -> Fallible<()> {
+            self.facade.$subscribe_all(handler)
+        }
+    };
+    ([site $($attribute:ident)*] [$payload:ident: $payload_type:ty] $publish:ident $subscribe:ident [] $($rest:tt)*) => {
+        fn $subscribe(&self, handler: Callback<$payload_type>) -> Fallible<()> {
+            self.facade.$subscribe(self.on_site(handler, |it: &$payload_type| &it.site_id))
+        }
+    };
+    (
+        [site $($attribute:ident)*]
+        [$payload:ident: $payload_type:ty]
+        $publish:ident
+        $subscribe:ident
+        [site_id $($key_path:tt)*]
+        $all:tt
+        $($rest:tt)*
+    ) => {
+        fn $subscribe(&self, site_id: String, handler: Callback<$payload_type>) -> Fallible<()> {
+            self.check_site(&site_id)?;
+            self.facade.$subscribe(site_id, handler)
+        }
+
+        site_scoped_subscribe_all!($subscribe [$payload_type] $all);
+    };
+    (
+        [site $($attribute:ident)*]
+        [$payload:ident: $payload_type:ty]
+        $publish:ident
+        $subscribe:ident
+        [$key:ident $($key_path:tt)*]
+        $all:tt
+        $($rest:tt)*
+    ) => {
+        fn $subscribe(&self, $key: String, handler: Callback<$payload_type>) -> Fallible<()> {
+            self.facade.$subscribe($key, self.on_site(handler, |it: &$payload_type| &it.site_id))
+        }
+
+        site_scoped_subscribe_all!([$payload_type] $all);
+    };
+    ([$attribute:ident $($attributes:ident)*] $($message:tt)*) => {
+        site_scoped_subscribe!([$($attributes)*] $($message)*);
+    };
+}
+
+/// The subscribe all method of a message with a site, the messages keyed by site are subscribed
+/// to for the site of the facade alone
+macro_rules! site_scoped_subscribe_all {
+    ($subscribe:ident [$payload_type:ty] []) => {};
+    ($subscribe:ident [$payload_type:ty] [$subscribe_all:ident $($wildcard:tt)*]) => {
+        fn $subscribe_all(&self, handler: Callback<$payload_type>) -> Fallible<()> {
+            self.facade.$subscribe(self.site_id.clone(), handler)
+        }
+    };
+    ([$payload_type:ty] []) => {};
+    ([$payload_type:ty] [$subscribe_all:ident $($wildcard:tt)*]) => {
+        fn $subscribe_all(&self, handler: Callback<$payload_type>) -> Fallible<()> {
+            self.facade
+                .$subscribe_all(self.on_site(handler, |it: &$payload_type| &it.site_id))
+        }
+    };
+}
+
+/// The methods of a facade for a message, `$facade_side` is the side of the facade and `$side` the
+/// one publishing the message
+macro_rules! site_scoped_methods {
+    (frontend frontend $($message:tt)*) => { site_scoped_publish!($($message)*); };
+    (backend backend $($message:tt)*) => { site_scoped_publish!($($message)*); };
+    (frontend backend $($message:tt)*) => { site_scoped_subscribe!($($message)*); };
+    (backend frontend $($message:tt)*) => { site_scoped_subscribe!($($message)*); };
+}
+
+macro_rules! site_scoped_facades {
+    ($(
+        $component:ident {
+            facade $facade_doc:tt $facade:ident $facade_supers:tt $facade_getter:ident;
+            backend $backend_doc:tt $backend:ident $backend_supers:tt $backend_getter:ident;
+            mqtt $mqtt_component:tt $mqtt_toggle:tt $mqtt_topic:tt;
+            messages [$({ $attributes:tt $side:ident $message:ident $route:tt $($rest:tt)* })*];
+        }
+    )*) => {
+        $(
+            impl $facade for SiteScoped<dyn $facade> {
+                $(site_scoped_methods!(frontend $side $attributes $($rest)*);)*
+            }
+
+            impl $backend for SiteScoped<dyn $backend> {
+                $(site_scoped_methods!(backend $side $attributes $($rest)*);)*
+            }
+        )*
+
+        impl<'a, H: HermesProtocolHandler + ?Sized> HermesProtocolHandler for SiteScopedHandler<'a, H> {
+            $(
+                fn $facade_getter(&self) -> Box<dyn $facade> {
+                    Box::new(SiteScoped {
+                        facade: self.handler.$facade_getter(),
+                        site_id: self.site_id.clone(),
+                    })
+                }
+            )*
+
+            $(
+                fn $backend_getter(&self) -> Box<dyn $backend> {
+                    Box::new(SiteScoped {
+                        facade: self.handler.$backend_getter(),
+                        site_id: self.site_id.clone(),
+                    })
+                }
+            )*
+        }
+    };
+}
+
+hermes_protocol!(site_scoped_facades);
@@ -0,0 +1,479 @@
+//! Typed values of the slots of the intents.
+
+use std::convert::TryFrom;
+
+use chrono::prelude::*;
+
+use crate::nlu_ontology::*;
+use crate::{IntentMessage, NluSlot};
+
+/// Why a slot value can't be read as some type
+#[derive(Debug, Clone, PartialEq, Fail)]
+pub enum SlotValueError {
+    /// The value is of another kind
+    #[fail(display = "a {} value is not a {}", found, expected)]
+    Mismatch {
+        found: &'static str,
+        expected: &'static str,
+    },
+    /// The value has a fitting kind but can't be converted, like a fractional number to an integer
+    #[fail(display = "{} can't be read as a {}", value, expected)]
+    Invalid { value: String, expected: &'static str },
+}
+
+/// Why a slot of an intent can't be read as some type
+#[derive(Debug, Clone, PartialEq, Fail)]
+pub enum SlotError {
+    #[fail(display = "the intent has no {} slot", _0)]
+    Missing(String),
+    #[fail(display = "the {} slot is invalid: {}", slot_name, error)]
+    Value {
+        slot_name: String,
+        #[cause]
+        error: SlotValueError,
+    },
+}
+
+/// A type a slot value can be read as. The slots can be read as any of these types with
+/// `TryFrom<&NluSlot>` or the `slot` methods of `IntentMessage`.
+pub trait FromSlotValue: Sized {
+    fn from_slot_value(value: &SlotValue) -> Result<Self, SlotValueError>;
+}
+
+impl NluSlot {
+    pub fn value<T: FromSlotValue>(&self) -> Result<T, SlotError> {
+        T::from_slot_value(&self.nlu_slot.value).map_err(|error| self.error(error))
+    }
+
+    /// The other values the NLU considered for the slot, the most likely first
+    pub fn alternatives<T: FromSlotValue>(&self) -> Result<Vec<T>, SlotError> {
+        self.nlu_slot
+            .alternatives
+            .iter()
+            .map(|it| T::from_slot_value(it).map_err(|error| self.error(error)))
+            .collect()
+    }
+
+    fn error(&self, error: SlotValueError) -> SlotError {
+        SlotError::Value {
+            slot_name: self.nlu_slot.slot_name.clone(),
+            error,
+        }
+    }
+}
+
+impl IntentMessage {
+    /// The slots named `slot_name`, in the order of the input
+    pub fn nlu_slots<'a>(&'a self, slot_name: &'a str) -> impl Iterator<Item = &'a NluSlot> + 'a {
+        self.slots.iter().filter(move |it| it.nlu_slot.slot_name == slot_name)
+    }
+
+    /// The value of the first slot named `slot_name`, failing when there is none
+    pub fn slot<T: FromSlotValue>(&self, slot_name: &str) -> Result<T, SlotError> {
+        self.optional_slot(slot_name)?
+            .ok_or_else(|| SlotError::Missing(slot_name.to_string()))
+    }
+
+    /// The value of the first slot named `slot_name`, if any
+    pub fn optional_slot<T: FromSlotValue>(&self, slot_name: &str) -> Result<Option<T>, SlotError> {
+        self.nlu_slots(slot_name).next().map(NluSlot::value).transpose()
+    }
+
+    /// The values of all the slots named `slot_name`, as found in a multi-valued slot
+    pub fn all_slots<T: FromSlotValue>(&self, slot_name: &str) -> Result<Vec<T>, SlotError> {
+        self.nlu_slots(slot_name).map(NluSlot::value).collect()
+    }
+}
+
+fn kind(value: &SlotValue) -> &'static str {
+    match value {
+        SlotValue::Custom(_) => "custom",
+        SlotValue::Number(_) => "number",
+        SlotValue::Ordinal(_) => "ordinal",
+        SlotValue::Percentage(_) => "percentage",
+        SlotValue::InstantTime(_) => "instant time",
+        SlotValue::TimeInterval(_) => "time interval",
+        SlotValue::AmountOfMoney(_) => "amount of money",
+        SlotValue::Temperature(_) => "temperature",
+        SlotValue::Duration(_) => "duration",
+        SlotValue::MusicAlbum(_) => "music album",
+        SlotValue::MusicArtist(_) => "music artist",
+        SlotValue::MusicTrack(_) => "music track",
+        SlotValue::City(_) => "city",
+        SlotValue::Country(_) => "country",
+        SlotValue::Region(_) => "region",
+    }
+}
+
+fn mismatch(value: &SlotValue, expected: &'static str) -> SlotValueError {
+    SlotValueError::Mismatch {
+        found: kind(value),
+        expected,
+    }
+}
+
+fn invalid<V: ToString>(value: V, expected: &'static str) -> SlotValueError {
+    SlotValueError::Invalid {
+        value: value.to_string(),
+        expected,
+    }
+}
+
+/// The values of the variants of `SlotValue`, as they are
+macro_rules! from_slot_value_variant {
+    ($($variant:ident($t:ty) $expected:expr;)*) => {
+        $(
+            impl FromSlotValue for $t {
+                fn from_slot_value(value: &SlotValue) -> Result<Self, SlotValueError> {
+                    match value {
+                        SlotValue::$variant(it) => Ok(it.clone()),
+                        other => Err(mismatch(other, $expected)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+from_slot_value_variant! {
+    Number(NumberValue) "number";
+    Ordinal(OrdinalValue) "ordinal";
+    Percentage(PercentageValue) "percentage";
+    InstantTime(InstantTimeValue) "instant time";
+    TimeInterval(TimeIntervalValue) "time interval";
+    AmountOfMoney(AmountOfMoneyValue) "amount of money";
+    Temperature(TemperatureValue) "temperature";
+    Duration(DurationValue) "duration";
+}
+
+impl FromSlotValue for SlotValue {
+    fn from_slot_value(value: &SlotValue) -> Result<Self, SlotValueError> {
+        Ok(value.clone())
+    }
+}
+
+impl FromSlotValue for String {
+    /// The custom values and the names of the music and places
+    fn from_slot_value(value: &SlotValue) -> Result<Self, SlotValueError> {
+        match value {
+            SlotValue::Custom(it)
+            | SlotValue::MusicAlbum(it)
+            | SlotValue::MusicArtist(it)
+            | SlotValue::MusicTrack(it)
+            | SlotValue::City(it)
+            | SlotValue::Country(it)
+            | SlotValue::Region(it) => Ok(it.value.clone()),
+            other => Err(mismatch(other, "string")),
+        }
+    }
+}
+
+impl FromSlotValue for f64 {
+    /// The numbers, ordinals, percentages, temperatures and amounts of money
+    fn from_slot_value(value: &SlotValue) -> Result<Self, SlotValueError> {
+        match value {
+            SlotValue::Number(it) => Ok(it.value),
+            SlotValue::Ordinal(it) => Ok(it.value as f64),
+            SlotValue::Percentage(it) => Ok(it.value),
+            SlotValue::Temperature(it) => Ok(it.value.into()),
+            SlotValue::AmountOfMoney(it) => Ok(it.value.into()),
+            other => Err(mismatch(other, "number")),
+        }
+    }
+}
+
+impl FromSlotValue for f32 {
+    fn from_slot_value(value: &SlotValue) -> Result<Self, SlotValueError> {
+        f64::from_slot_value(value).map(|it| it as f32)
+    }
+}
+
+impl FromSlotValue for i64 {
+    /// The ordinals and the numbers without a fractional part
+    fn from_slot_value(value: &SlotValue) -> Result<Self, SlotValueError> {
+        match value {
+            SlotValue::Number(it) if it.value.fract() == 0.0 => Ok(it.value as i64),
+            SlotValue::Number(it) => Err(invalid(it.value, "integer")),
+            SlotValue::Ordinal(it) => Ok(it.value),
+            other => Err(mismatch(other, "integer")),
+        }
+    }
+}
+
+macro_rules! from_slot_value_as_i64 {
+    ($($t:ty)*) => {
+        $(
+            impl FromSlotValue for $t {
+                fn from_slot_value(value: &SlotValue) -> Result<Self, SlotValueError> {
+                    let value = i64::from_slot_value(value)?;
+                    <$t>::try_from(value).map_err(|_| invalid(value, stringify!($t)))
+                }
+            }
+        )*
+    };
+}
+
+from_slot_value_as_i64!(i8 i16 i32 u8 u16 u32 u64 usize);
+
+fn parse_date_time(value: &str) -> Result<DateTime<FixedOffset>, SlotValueError> {
+    DateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S %:z").map_err(|_| invalid(value, "date time"))
+}
+
+impl FromSlotValue for DateTime<FixedOffset> {
+    /// The instant times and the start of the time intervals, or their end for the intervals
+    /// without a start, in the time zone they were given in
+    fn from_slot_value(value: &SlotValue) -> Result<Self, SlotValueError> {
+        match value {
+            SlotValue::InstantTime(it) => parse_date_time(&it.value),
+            SlotValue::TimeInterval(it) => match it.from.as_ref().or(it.to.as_ref()) {
+                Some(bound) => parse_date_time(bound),
+                None => Err(invalid("an unbounded time interval", "date time")),
+            },
+            other => Err(mismatch(other, "date time")),
+        }
+    }
+}
+
+impl FromSlotValue for DateTime<Utc> {
+    fn from_slot_value(value: &SlotValue) -> Result<Self, SlotValueError> {
+        DateTime::<FixedOffset>::from_slot_value(value).map(|it| it.with_timezone(&Utc))
+    }
+}
+
+impl FromSlotValue for DateTime<Local> {
+    fn from_slot_value(value: &SlotValue) -> Result<Self, SlotValueError> {
+        DateTime::<FixedOffset>::from_slot_value(value).map(|it| it.with_timezone(&Local))
+    }
+}
+
+impl FromSlotValue for chrono::Duration {
+    /// The durations, the months are taken as 30 days and the years as 365 days. The ones too long
+    /// for a `chrono::Duration` are invalid.
+    fn from_slot_value(value: &SlotValue) -> Result<Self, SlotValueError> {
+        match value {
+            SlotValue::Duration(it) => checked_duration(it).ok_or_else(|| invalid(format!("{:?}", it), "duration")),
+            other => Err(mismatch(other, "duration")),
+        }
+    }
+}
+
+/// The `chrono::Duration` of a duration value, `None` when it overflows
+fn checked_duration(value: &DurationValue) -> Option<chrono::Duration> {
+    let months = value.quarters.checked_mul(3)?.checked_add(value.months)?;
+    let days = value
+        .years
+        .checked_mul(365)?
+        .checked_add(months.checked_mul(30)?)?
+        .checked_add(value.weeks.checked_mul(7)?)?
+        .checked_add(value.days)?;
+    chrono::Duration::try_days(days)?
+        .checked_add(&chrono::Duration::try_hours(value.hours)?)?
+        .checked_add(&chrono::Duration::try_minutes(value.minutes)?)?
+        .checked_add(&chrono::Duration::try_seconds(value.seconds)?)
+}
+
+impl FromSlotValue for std::time::Duration {
+    /// The durations, as the `chrono::Duration` ones
+    fn from_slot_value(value: &SlotValue) -> Result<Self, SlotValueError> {
+        let duration = chrono::Duration::from_slot_value(value)?;
+        duration.to_std().map_err(|_| invalid(duration, "duration"))
+    }
+}
+
+macro_rules! try_from_nlu_slot {
+    ($($t:ty,)*) => {
+        $(
+            impl TryFrom<&NluSlot> for $t {
+                type Error = SlotError;
+
+                fn try_from(slot: &NluSlot) -> Result<Self, SlotError> {
+                    slot.value()
+                }
+            }
+        )*
+    };
+}
+
+try_from_nlu_slot!(
+    SlotValue,
+    NumberValue,
+    OrdinalValue,
+    PercentageValue,
+    InstantTimeValue,
+    TimeIntervalValue,
+    AmountOfMoneyValue,
+    TemperatureValue,
+    DurationValue,
+    String,
+    f64,
+    f32,
+    i64,
+    i8,
+    i16,
+    i32,
+    u8,
+    u16,
+    u32,
+    u64,
+    usize,
+    DateTime<FixedOffset>,
+    DateTime<Utc>,
+    DateTime<Local>,
+    chrono::Duration,
+    std::time::Duration,
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hermes_utils::Example;
+
+    fn slot(slot_name: &str, value: SlotValue) -> NluSlot {
+        NluSlot {
+            nlu_slot: Slot {
+                raw_value: "raw value".into(),
+                value,
+                alternatives: vec![],
+                range: 0..9,
+                entity: "entity".into(),
+                slot_name: slot_name.into(),
+                confidence_score: None,
+            },
+        }
+    }
+
+    fn number(value: f64) -> SlotValue {
+        SlotValue::Number(NumberValue { value })
+    }
+
+    fn instant_time(value: &str) -> SlotValue {
+        SlotValue::InstantTime(InstantTimeValue {
+            value: value.into(),
+            grain: Grain::Minute,
+            precision: Precision::Exact,
+        })
+    }
+
+    #[test]
+    fn numbers_are_converted() {
+        assert_eq!(i32::from_slot_value(&number(3.0)), Ok(3));
+        assert_eq!(
+            i32::from_slot_value(&number(3.5)),
+            Err(SlotValueError::Invalid {
+                value: "3.5".into(),
+                expected: "integer"
+            })
+        );
+        assert_eq!(
+            u8::from_slot_value(&number(-1.0)),
+            Err(SlotValueError::Invalid {
+                value: "-1".into(),
+                expected: "u8"
+            })
+        );
+        assert_eq!(f64::from_slot_value(&number(3.5)), Ok(3.5));
+        assert_eq!(
+            f64::from_slot_value(&SlotValue::Temperature(TemperatureValue {
+                value: 21.5,
+                unit: Some("celsius".into())
+            })),
+            Ok(21.5)
+        );
+        assert_eq!(
+            f64::from_slot_value(&SlotValue::Custom("kitchen".to_string().into())),
+            Err(SlotValueError::Mismatch {
+                found: "custom",
+                expected: "number"
+            })
+        );
+    }
+
+    #[test]
+    fn times_are_converted() {
+        assert_eq!(
+            DateTime::<Utc>::from_slot_value(&instant_time("2019-01-06 09:30:00 +01:00")),
+            Ok(Utc.with_ymd_and_hms(2019, 1, 6, 8, 30, 0).unwrap())
+        );
+        assert!(DateTime::<Utc>::from_slot_value(&instant_time("tomorrow")).is_err());
+        assert_eq!(
+            DateTime::<Utc>::from_slot_value(&SlotValue::TimeInterval(TimeIntervalValue {
+                from: None,
+                to: Some("2019-01-07 00:00:00 +01:00".into()),
+            })),
+            Ok(Utc.with_ymd_and_hms(2019, 1, 6, 23, 0, 0).unwrap())
+        );
+
+        let duration = SlotValue::Duration(DurationValue {
+            years: 0,
+            quarters: 0,
+            months: 0,
+            weeks: 1,
+            days: 0,
+            hours: 2,
+            minutes: 0,
+            seconds: 30,
+            precision: Precision::Exact,
+        });
+        assert_eq!(
+            std::time::Duration::from_slot_value(&duration),
+            Ok(std::time::Duration::from_secs(7 * 24 * 3600 + 2 * 3600 + 30))
+        );
+    }
+
+    #[test]
+    fn too_long_durations_are_invalid() {
+        let duration = SlotValue::Duration(DurationValue {
+            years: i64::MAX,
+            quarters: 0,
+            months: 0,
+            weeks: 0,
+            days: 0,
+            hours: 0,
+            minutes: 0,
+            seconds: 0,
+            precision: Precision::Approximate,
+        });
+        match chrono::Duration::from_slot_value(&duration) {
+            Err(SlotValueError::Invalid { expected, .. }) => assert_eq!(expected, "duration"),
+            other => panic!("{:?} should be invalid", other),
+        }
+        assert!(std::time::Duration::from_slot_value(&duration).is_err());
+    }
+
+    #[test]
+    fn slots_are_converted() {
+        let mut kitchen = slot("room", SlotValue::Custom("kitchen".to_string().into()));
+        kitchen.nlu_slot.alternatives = vec![SlotValue::Custom("chicken".to_string().into())];
+
+        assert_eq!(String::try_from(&kitchen), Ok("kitchen".into()));
+        assert_eq!(kitchen.alternatives::<String>(), Ok(vec!["chicken".into()]));
+        assert_eq!(
+            f64::try_from(&kitchen),
+            Err(SlotError::Value {
+                slot_name: "room".into(),
+                error: SlotValueError::Mismatch {
+                    found: "custom",
+                    expected: "number"
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn intent_slots_are_found_by_name() {
+        let mut intent = IntentMessage::minimal_example();
+        intent.slots = vec![
+            slot("count", number(1.0)),
+            slot("room", SlotValue::Custom("kitchen".to_string().into())),
+            slot("count", number(2.0)),
+        ];
+
+        assert_eq!(intent.slot::<i32>("count"), Ok(1));
+        assert_eq!(intent.slot::<i32>("missing"), Err(SlotError::Missing("missing".into())));
+        assert!(intent.slot::<i32>("room").is_err());
+        assert_eq!(intent.optional_slot::<i32>("missing"), Ok(None));
+        assert!(intent.optional_slot::<i32>("room").is_err());
+        assert_eq!(intent.all_slots::<i32>("count"), Ok(vec![1, 2]));
+        assert_eq!(intent.all_slots::<i32>("missing"), Ok(vec![]));
+    }
+}
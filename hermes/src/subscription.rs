@@ -0,0 +1,95 @@
+//! Cancel the subscriptions made with a callback.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// A handle on the subscription made with a callback, get one with the `subscription` method of
+/// the callback before giving it to a facade.
+///
+/// Cancelling it stops the callback from being called and lets the protocol handler unregister
+/// it, the clones of a handle all cancel the same subscription.
+#[derive(Clone, Default)]
+pub struct Subscription {
+    state: Arc<SubscriptionState>,
+}
+
+#[derive(Default)]
+struct SubscriptionState {
+    cancelled: AtomicBool,
+    on_cancel: Mutex<Vec<Box<dyn FnOnce() + Send>>>,
+}
+
+impl Subscription {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop the callback and unregister it, does nothing if the subscription is already cancelled
+    pub fn cancel(&self) {
+        if self.state.cancelled.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let hooks = self
+            .state
+            .on_cancel
+            .lock()
+            .map(|mut it| it.drain(..).collect::<Vec<_>>())
+            .unwrap_or_default();
+        for hook in hooks {
+            hook()
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Run `hook` when the subscription is cancelled, right away if it already is. This is how the
+    /// protocol handlers unregister the callbacks.
+    pub fn on_cancel<F: FnOnce() + Send + 'static>(&self, hook: F) {
+        if let Ok(mut hooks) = self.state.on_cancel.lock() {
+            if !self.is_cancelled() {
+                hooks.push(Box::new(hook));
+                return;
+            }
+        }
+        hook()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use super::*;
+
+    #[test]
+    fn the_hooks_run_once_when_cancelled() {
+        let subscription = Subscription::new();
+        let count = Arc::new(AtomicUsize::new(0));
+        let hook_count = Arc::clone(&count);
+        subscription.on_cancel(move || {
+            hook_count.fetch_add(1, Ordering::SeqCst);
+        });
+        assert_eq!(count.load(Ordering::SeqCst), 0);
+
+        subscription.clone().cancel();
+        subscription.cancel();
+
+        assert!(subscription.is_cancelled());
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn the_hooks_added_after_cancel_run_right_away() {
+        let subscription = Subscription::new();
+        subscription.cancel();
+        let count = Arc::new(AtomicUsize::new(0));
+        let hook_count = Arc::clone(&count);
+        subscription.on_cancel(move || {
+            hook_count.fetch_add(1, Ordering::SeqCst);
+        });
+
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+}
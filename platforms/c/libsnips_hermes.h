@@ -6,6 +6,15 @@
 #include <stdint.h>
 #include <stdlib.h>
 
+/**
+ * The latest version of the layout of the structures. A version only appends fields at the end
+ * of the structures of the previous one:
+ * - 1: the initial layout
+ * - 2: `speaker_hypotheses` in `CTextCapturedMessage`, `CIntentMessage` and
+ * `CIntentNotRecognizedMessage`
+ */
+#define SNIPS_HERMES_STRUCT_VERSION 2
+
 /**
  * Enum representing the grain of a resolved date related value
  */
@@ -195,6 +204,15 @@ typedef enum {
   SNIPS_SLOT_VALUE_TYPE_REGION = 15,
 } SNIPS_SLOT_VALUE_TYPE;
 
+/**
+ * A token identifying a subscription, returned by the `hermes_*_subscribe_*_with_subscription`
+ * functions through their last parameter when it isn't null. It must be given to
+ * `hermes_unsubscribe` or `hermes_drop_subscription`.
+ */
+typedef struct {
+  const void *subscription;
+} CSubscription;
+
 typedef struct {
   const void *handler;
   void *user_data;
@@ -471,6 +489,16 @@ typedef struct {
   int count;
 } CAsrTokenDoubleArray;
 
+typedef struct {
+  const char *name;
+  float confidence;
+} CSpeakerId;
+
+typedef struct {
+  const CSpeakerId *const *entries;
+  int count;
+} CSpeakerIdArray;
+
 typedef struct {
   /**
    * The session identifier in which this intent was detected
@@ -509,6 +537,11 @@ typedef struct {
    * Confidence of the asr capture, this value is optional. Any value not in [0,1] should be ignored.
    */
   float asr_confidence;
+  /**
+   * Nullable, only read from the structures given by the C side from the version 2 of the
+   * structures, see `hermes_set_struct_version`
+   */
+  const CSpeakerIdArray *speaker_hypotheses;
 } CIntentMessage;
 
 typedef struct {
@@ -536,6 +569,11 @@ typedef struct {
    * Expresses the confidence that no intent was found
    */
   float confidence_score;
+  /**
+   * Nullable, only read from the structures given by the C side from the version 2 of the
+   * structures, see `hermes_set_struct_version`
+   */
+  const CSpeakerIdArray *speaker_hypotheses;
 } CIntentNotRecognizedMessage;
 
 typedef struct {
@@ -892,6 +930,8 @@ typedef struct {
   SNIPS_PRECISION precision;
 } CDurationValue;
 
+SNIPS_RESULT hermes_destroy_in_process_protocol_handler(CProtocolHandler *handler);
+
 SNIPS_RESULT hermes_destroy_mqtt_protocol_handler(CProtocolHandler *handler);
 
 SNIPS_RESULT hermes_dialogue_publish_configure(const CDialogueFacade *facade,
@@ -906,6 +946,10 @@ SNIPS_RESULT hermes_dialogue_publish_end_session(const CDialogueFacade *facade,
 SNIPS_RESULT hermes_dialogue_publish_start_session(const CDialogueFacade *facade,
                                                    const CStartSessionMessage *message);
 
+SNIPS_RESULT hermes_dialogue_publish_toggle_off(const CDialogueFacade *facade);
+
+SNIPS_RESULT hermes_dialogue_publish_toggle_on(const CDialogueFacade *facade);
+
 SNIPS_RESULT hermes_dialogue_subscribe_intent(const CDialogueFacade *facade,
                                               const char *intent_name,
                                               void (*handler)(const CIntentMessage*, void*));
@@ -913,18 +957,84 @@ SNIPS_RESULT hermes_dialogue_subscribe_intent(const CDialogueFacade *facade,
 SNIPS_RESULT hermes_dialogue_subscribe_intent_not_recognized(const CDialogueFacade *facade,
                                                              void (*handler)(const CIntentNotRecognizedMessage*, void*));
 
+SNIPS_RESULT hermes_dialogue_subscribe_intent_not_recognized_for_site(const CDialogueFacade *facade,
+                                                                      const char *site_id,
+                                                                      void (*handler)(const CIntentNotRecognizedMessage*, void*));
+
+SNIPS_RESULT hermes_dialogue_subscribe_intent_not_recognized_for_site_with_subscription(const CDialogueFacade *facade,
+                                                                                        const char *site_id,
+                                                                                        void (*handler)(const CIntentNotRecognizedMessage*, void*),
+                                                                                        const CSubscription **subscription);
+
+SNIPS_RESULT hermes_dialogue_subscribe_intent_not_recognized_with_subscription(const CDialogueFacade *facade,
+                                                                               void (*handler)(const CIntentNotRecognizedMessage*, void*),
+                                                                               const CSubscription **subscription);
+
+SNIPS_RESULT hermes_dialogue_subscribe_intent_with_subscription(const CDialogueFacade *facade,
+                                                                const char *intent_name,
+                                                                void (*handler)(const CIntentMessage*, void*),
+                                                                const CSubscription **subscription);
+
 SNIPS_RESULT hermes_dialogue_subscribe_intents(const CDialogueFacade *facade,
                                                void (*handler)(const CIntentMessage*, void*));
 
+SNIPS_RESULT hermes_dialogue_subscribe_intents_with_subscription(const CDialogueFacade *facade,
+                                                                 void (*handler)(const CIntentMessage*, void*),
+                                                                 const CSubscription **subscription);
+
 SNIPS_RESULT hermes_dialogue_subscribe_session_ended(const CDialogueFacade *facade,
                                                      void (*handler)(const CSessionEndedMessage*, void*));
 
+SNIPS_RESULT hermes_dialogue_subscribe_session_ended_for_site(const CDialogueFacade *facade,
+                                                              const char *site_id,
+                                                              void (*handler)(const CSessionEndedMessage*, void*));
+
+SNIPS_RESULT hermes_dialogue_subscribe_session_ended_for_site_with_subscription(const CDialogueFacade *facade,
+                                                                                const char *site_id,
+                                                                                void (*handler)(const CSessionEndedMessage*, void*),
+                                                                                const CSubscription **subscription);
+
+SNIPS_RESULT hermes_dialogue_subscribe_session_ended_with_subscription(const CDialogueFacade *facade,
+                                                                       void (*handler)(const CSessionEndedMessage*, void*),
+                                                                       const CSubscription **subscription);
+
 SNIPS_RESULT hermes_dialogue_subscribe_session_queued(const CDialogueFacade *facade,
                                                       void (*handler)(const CSessionQueuedMessage*, void*));
 
+SNIPS_RESULT hermes_dialogue_subscribe_session_queued_for_site(const CDialogueFacade *facade,
+                                                               const char *site_id,
+                                                               void (*handler)(const CSessionQueuedMessage*, void*));
+
+SNIPS_RESULT hermes_dialogue_subscribe_session_queued_for_site_with_subscription(const CDialogueFacade *facade,
+                                                                                 const char *site_id,
+                                                                                 void (*handler)(const CSessionQueuedMessage*, void*),
+                                                                                 const CSubscription **subscription);
+
+SNIPS_RESULT hermes_dialogue_subscribe_session_queued_with_subscription(const CDialogueFacade *facade,
+                                                                        void (*handler)(const CSessionQueuedMessage*, void*),
+                                                                        const CSubscription **subscription);
+
 SNIPS_RESULT hermes_dialogue_subscribe_session_started(const CDialogueFacade *facade,
                                                        void (*handler)(const CSessionStartedMessage*, void*));
 
+SNIPS_RESULT hermes_dialogue_subscribe_session_started_for_site(const CDialogueFacade *facade,
+                                                                const char *site_id,
+                                                                void (*handler)(const CSessionStartedMessage*, void*));
+
+SNIPS_RESULT hermes_dialogue_subscribe_session_started_for_site_with_subscription(const CDialogueFacade *facade,
+                                                                                  const char *site_id,
+                                                                                  void (*handler)(const CSessionStartedMessage*, void*),
+                                                                                  const CSubscription **subscription);
+
+SNIPS_RESULT hermes_dialogue_subscribe_session_started_with_subscription(const CDialogueFacade *facade,
+                                                                         void (*handler)(const CSessionStartedMessage*, void*),
+                                                                         const CSubscription **subscription);
+
+/**
+ * Call the queued callbacks of all the handlers, without waiting
+ */
+SNIPS_RESULT hermes_dispatch_pending(void);
+
 SNIPS_RESULT hermes_drop_dialogue_facade(const CDialogueFacade *cstruct);
 
 SNIPS_RESULT hermes_drop_error_message(const CErrorMessage *cstruct);
@@ -949,6 +1059,8 @@ SNIPS_RESULT hermes_drop_session_started_message(const CSessionStartedMessage *c
 
 SNIPS_RESULT hermes_drop_sound_feedback_facade(const CSoundFeedbackFacade *cstruct);
 
+SNIPS_RESULT hermes_drop_subscription(const CSubscription *cstruct);
+
 SNIPS_RESULT hermes_drop_tts_facade(const CTtsFacade *cstruct);
 
 SNIPS_RESULT hermes_drop_version_message(const CVersionMessage *cstruct);
@@ -972,18 +1084,59 @@ SNIPS_RESULT hermes_injection_publish_injection_status_request(const CInjectionF
 SNIPS_RESULT hermes_injection_subscribe_injection_complete(const CInjectionFacade *facade,
                                                            void (*handler)(const CInjectionCompleteMessage*, void*));
 
+SNIPS_RESULT hermes_injection_subscribe_injection_complete_with_subscription(const CInjectionFacade *facade,
+                                                                             void (*handler)(const CInjectionCompleteMessage*, void*),
+                                                                             const CSubscription **subscription);
+
 SNIPS_RESULT hermes_injection_subscribe_injection_reset_complete(const CInjectionFacade *facade,
                                                                  void (*handler)(const CInjectionResetCompleteMessage*, void*));
 
+SNIPS_RESULT hermes_injection_subscribe_injection_reset_complete_with_subscription(const CInjectionFacade *facade,
+                                                                                   void (*handler)(const CInjectionResetCompleteMessage*, void*),
+                                                                                   const CSubscription **subscription);
+
 SNIPS_RESULT hermes_injection_subscribe_injection_status(const CInjectionFacade *facade,
                                                          void (*handler)(const CInjectionStatusMessage*, void*));
 
+SNIPS_RESULT hermes_injection_subscribe_injection_status_with_subscription(const CInjectionFacade *facade,
+                                                                           void (*handler)(const CInjectionStatusMessage*, void*),
+                                                                           const CSubscription **subscription);
+
+/**
+ * Call the queued callbacks of `handler`, waiting up to `timeout_ms` milliseconds for one
+ * if there are none. A negative timeout waits as long as needed.
+ */
+SNIPS_RESULT hermes_poll(const CProtocolHandler *handler, int timeout_ms);
+
 SNIPS_RESULT hermes_protocol_handler_dialogue_facade(const CProtocolHandler *handler,
                                                      const CDialogueFacade **facade);
 
+/**
+ * Give a file descriptor that is readable while callbacks of `handler` are queued, so that
+ * hosts can wait for them in their own event loop. The queue must be enabled first and the
+ * descriptor stays owned by the handler. Only available on unix.
+ */
+SNIPS_RESULT hermes_protocol_handler_dispatch_fd(const CProtocolHandler *handler, int *fd);
+
+/**
+ * Queue the callbacks of the subscriptions made through `handler` and its facades instead
+ * of calling them on the thread of the communication layer. They are then called by
+ * `hermes_poll` or `hermes_dispatch_pending`, on the thread of the caller.
+ */
+SNIPS_RESULT hermes_protocol_handler_enable_queued_dispatch(const CProtocolHandler *handler);
+
 SNIPS_RESULT hermes_protocol_handler_injection_facade(const CProtocolHandler *handler,
                                                       const CInjectionFacade **facade);
 
+/**
+ * Create a handler delivering the messages inside the process, without a broker. The
+ * handlers created with the same non null `bus_name` share their bus as long as one of
+ * them is alive, a null `bus_name` gives a handler with a bus of its own.
+ */
+SNIPS_RESULT hermes_protocol_handler_new_in_process(const CProtocolHandler **handler,
+                                                    const char *bus_name,
+                                                    void *user_data);
+
 SNIPS_RESULT hermes_protocol_handler_new_mqtt(const CProtocolHandler **handler,
                                               const char *broker_address,
                                               void *user_data);
@@ -992,12 +1145,21 @@ SNIPS_RESULT hermes_protocol_handler_new_mqtt_with_options(const CProtocolHandle
                                                            const CMqttOptions *mqtt_options,
                                                            void *user_data);
 
+/**
+ * Set the function called with the `user_data` given at the creation of `handler` once the
+ * handler, its facades and its subscriptions are all gone
+ */
+SNIPS_RESULT hermes_protocol_handler_set_user_data_destructor(const CProtocolHandler *handler,
+                                                              void (*destructor)(void*));
+
 SNIPS_RESULT hermes_protocol_handler_sound_feedback_facade(const CProtocolHandler *handler,
                                                            const CSoundFeedbackFacade **facade);
 
 SNIPS_RESULT hermes_protocol_handler_tts_facade(const CProtocolHandler *handler,
                                                 const CTtsFacade **facade);
 
+SNIPS_RESULT hermes_set_struct_version(unsigned int version);
+
 SNIPS_RESULT hermes_sound_feedback_publish_toggle_off(const CSoundFeedbackFacade *facade,
                                                       const CSiteMessage *message);
 
@@ -1007,4 +1169,10 @@ SNIPS_RESULT hermes_sound_feedback_publish_toggle_on(const CSoundFeedbackFacade
 SNIPS_RESULT hermes_tts_publish_register_sound(const CTtsFacade *facade,
                                                const CRegisterSoundMessage *message);
 
+/**
+ * Unregister the handler of `subscription` from the protocol handler and release the token.
+ * The `user_data` of the handler isn't given to it anymore once this returns.
+ */
+SNIPS_RESULT hermes_unsubscribe(const CSubscription *subscription);
+
 #endif /* LIB_HERMES_H_ */
@@ -6,6 +6,15 @@
 #include <stdint.h>
 #include <stdlib.h>
 
+/**
+ * The latest version of the layout of the structures. A version only appends fields at the end
+ * of the structures of the previous one:
+ * - 1: the initial layout
+ * - 2: `speaker_hypotheses` in `CTextCapturedMessage`, `CIntentMessage` and
+ * `CIntentNotRecognizedMessage`
+ */
+#define SNIPS_HERMES_STRUCT_VERSION 2
+
 /**
  * Enum representing the grain of a resolved date related value
  */
@@ -218,6 +227,16 @@ typedef struct {
   int count;
 } CAsrTokenArray;
 
+typedef struct {
+  const char *name;
+  float confidence;
+} CSpeakerId;
+
+typedef struct {
+  const CSpeakerId *const *entries;
+  int count;
+} CSpeakerIdArray;
+
 typedef struct {
   const char *text;
   /**
@@ -231,6 +250,11 @@ typedef struct {
    * Nullable
    */
   const char *session_id;
+  /**
+   * Nullable, only read from the structures given by the C side from the version 2 of the
+   * structures, see `hermes_set_struct_version`
+   */
+  const CSpeakerIdArray *speaker_hypotheses;
 } CTextCapturedMessage;
 
 typedef struct {
@@ -242,6 +266,10 @@ typedef struct {
   int64_t start_signal_ms;
 } CAsrStartListeningMessage;
 
+typedef struct {
+  const char *id;
+} CRequestComponentReloadMessage;
+
 typedef struct {
   const char *site_id;
   /**
@@ -271,6 +299,11 @@ typedef struct {
   const char *site_id;
 } CPlayFinishedMessage;
 
+typedef struct {
+  const char *id;
+  const char *site_id;
+} CStreamFinishedMessage;
+
 typedef struct {
   const char *id;
   const uint8_t *wav_bytes;
@@ -278,11 +311,38 @@ typedef struct {
   const char *site_id;
 } CPlayBytesMessage;
 
+typedef struct {
+  const char *stream_id;
+  const uint8_t *bytes;
+  int bytes_len;
+  const char *site_id;
+  uint32_t chunk_number;
+  /**
+   * A boolean to indicate if this is the last chunk of the stream, true = 1, false = 0
+   */
+  unsigned char is_last_chunk;
+} CStreamBytesMessage;
+
+typedef struct {
+  const char *request_id;
+  int64_t start_at_ms;
+  const char *site_id;
+} CReplayRequestMessage;
+
 typedef struct {
   const void *facade;
   void *user_data;
 } CAudioServerFacade;
 
+/**
+ * A token identifying a subscription, returned by the `hermes_*_subscribe_*_with_subscription`
+ * functions through their last parameter when it isn't null. It must be given to
+ * `hermes_unsubscribe` or `hermes_drop_subscription`.
+ */
+typedef struct {
+  const void *subscription;
+} CSubscription;
+
 typedef struct {
   const void *handler;
   void *user_data;
@@ -453,6 +513,11 @@ typedef struct {
    * Confidence of the asr capture, this value is optional. Any value not in [0,1] should be ignored.
    */
   float asr_confidence;
+  /**
+   * Nullable, only read from the structures given by the C side from the version 2 of the
+   * structures, see `hermes_set_struct_version`
+   */
+  const CSpeakerIdArray *speaker_hypotheses;
 } CIntentMessage;
 
 typedef struct {
@@ -480,6 +545,11 @@ typedef struct {
    * Expresses the confidence that no intent was found
    */
   float confidence_score;
+  /**
+   * Nullable, only read from the structures given by the C side from the version 2 of the
+   * structures, see `hermes_set_struct_version`
+   */
+  const CSpeakerIdArray *speaker_hypotheses;
 } CIntentNotRecognizedMessage;
 
 typedef struct {
@@ -712,6 +782,11 @@ typedef struct {
   void *user_data;
 } CHotwordFacade;
 
+typedef struct {
+  const void *facade;
+  void *user_data;
+} CInjectionBackendFacade;
+
 typedef struct {
   const char *request_id;
 } CInjectionCompleteMessage;
@@ -920,12 +995,32 @@ typedef struct {
   void *user_data;
 } CTtsFacade;
 
+typedef struct {
+  const char *site_id;
+  int64_t signal_ms;
+} CVadDownMessage;
+
+typedef struct {
+  const char *site_id;
+  int64_t signal_ms;
+} CVadUpMessage;
+
 typedef struct {
   uint64_t major;
   uint64_t minor;
   uint64_t patch;
 } CVersionMessage;
 
+typedef struct {
+  const void *facade;
+  void *user_data;
+} CVoiceActivityBackendFacade;
+
+typedef struct {
+  const void *facade;
+  void *user_data;
+} CVoiceActivityFacade;
+
 /**
  * A struct representing the configuration of the MQTT client
  */
@@ -1118,51 +1213,263 @@ SNIPS_RESULT hermes_asr_backend_publish_partial_text_captured(const CAsrBackendF
 SNIPS_RESULT hermes_asr_backend_publish_text_captured(const CAsrBackendFacade *facade,
                                                       const CTextCapturedMessage *message);
 
+SNIPS_RESULT hermes_asr_backend_subscribe_component_reload(const CAsrBackendFacade *facade,
+                                                           void (*handler)(const CRequestComponentReloadMessage*, void*));
+
+SNIPS_RESULT hermes_asr_backend_subscribe_component_reload_with_subscription(const CAsrBackendFacade *facade,
+                                                                             void (*handler)(const CRequestComponentReloadMessage*, void*),
+                                                                             const CSubscription **subscription);
+
 SNIPS_RESULT hermes_asr_backend_subscribe_start_listening(const CAsrBackendFacade *facade,
                                                           void (*handler)(const CAsrStartListeningMessage*, void*));
 
+SNIPS_RESULT hermes_asr_backend_subscribe_start_listening_for_site(const CAsrBackendFacade *facade,
+                                                                   const char *site_id,
+                                                                   void (*handler)(const CAsrStartListeningMessage*, void*));
+
+SNIPS_RESULT hermes_asr_backend_subscribe_start_listening_for_site_with_subscription(const CAsrBackendFacade *facade,
+                                                                                     const char *site_id,
+                                                                                     void (*handler)(const CAsrStartListeningMessage*, void*),
+                                                                                     const CSubscription **subscription);
+
+SNIPS_RESULT hermes_asr_backend_subscribe_start_listening_with_subscription(const CAsrBackendFacade *facade,
+                                                                            void (*handler)(const CAsrStartListeningMessage*, void*),
+                                                                            const CSubscription **subscription);
+
 SNIPS_RESULT hermes_asr_backend_subscribe_stop_listening(const CAsrBackendFacade *facade,
                                                          void (*handler)(const CSiteMessage*, void*));
 
+SNIPS_RESULT hermes_asr_backend_subscribe_stop_listening_for_site(const CAsrBackendFacade *facade,
+                                                                  const char *site_id,
+                                                                  void (*handler)(const CSiteMessage*, void*));
+
+SNIPS_RESULT hermes_asr_backend_subscribe_stop_listening_for_site_with_subscription(const CAsrBackendFacade *facade,
+                                                                                    const char *site_id,
+                                                                                    void (*handler)(const CSiteMessage*, void*),
+                                                                                    const CSubscription **subscription);
+
+SNIPS_RESULT hermes_asr_backend_subscribe_stop_listening_with_subscription(const CAsrBackendFacade *facade,
+                                                                           void (*handler)(const CSiteMessage*, void*),
+                                                                           const CSubscription **subscription);
+
+SNIPS_RESULT hermes_asr_backend_subscribe_toggle_off(const CAsrBackendFacade *facade,
+                                                     void (*handler)(void*));
+
+SNIPS_RESULT hermes_asr_backend_subscribe_toggle_off_with_subscription(const CAsrBackendFacade *facade,
+                                                                       void (*handler)(void*),
+                                                                       const CSubscription **subscription);
+
+SNIPS_RESULT hermes_asr_backend_subscribe_toggle_on(const CAsrBackendFacade *facade,
+                                                    void (*handler)(void*));
+
+SNIPS_RESULT hermes_asr_backend_subscribe_toggle_on_with_subscription(const CAsrBackendFacade *facade,
+                                                                      void (*handler)(void*),
+                                                                      const CSubscription **subscription);
+
+SNIPS_RESULT hermes_asr_publish_component_reload(const CAsrFacade *facade,
+                                                 const CRequestComponentReloadMessage *message);
+
 SNIPS_RESULT hermes_asr_publish_start_listening(const CAsrFacade *facade,
                                                 const CAsrStartListeningMessage *message);
 
 SNIPS_RESULT hermes_asr_publish_stop_listening(const CAsrFacade *facade,
                                                const CSiteMessage *message);
 
+SNIPS_RESULT hermes_asr_publish_toggle_off(const CAsrFacade *facade);
+
+SNIPS_RESULT hermes_asr_publish_toggle_on(const CAsrFacade *facade);
+
 SNIPS_RESULT hermes_asr_subscribe_partial_text_captured(const CAsrFacade *facade,
                                                         void (*handler)(const CTextCapturedMessage*, void*));
 
+SNIPS_RESULT hermes_asr_subscribe_partial_text_captured_for_site(const CAsrFacade *facade,
+                                                                 const char *site_id,
+                                                                 void (*handler)(const CTextCapturedMessage*, void*));
+
+SNIPS_RESULT hermes_asr_subscribe_partial_text_captured_for_site_with_subscription(const CAsrFacade *facade,
+                                                                                   const char *site_id,
+                                                                                   void (*handler)(const CTextCapturedMessage*, void*),
+                                                                                   const CSubscription **subscription);
+
+SNIPS_RESULT hermes_asr_subscribe_partial_text_captured_with_subscription(const CAsrFacade *facade,
+                                                                          void (*handler)(const CTextCapturedMessage*, void*),
+                                                                          const CSubscription **subscription);
+
 SNIPS_RESULT hermes_asr_subscribe_text_captured(const CAsrFacade *facade,
                                                 void (*handler)(const CTextCapturedMessage*, void*));
 
+SNIPS_RESULT hermes_asr_subscribe_text_captured_for_site(const CAsrFacade *facade,
+                                                         const char *site_id,
+                                                         void (*handler)(const CTextCapturedMessage*, void*));
+
+SNIPS_RESULT hermes_asr_subscribe_text_captured_for_site_with_subscription(const CAsrFacade *facade,
+                                                                           const char *site_id,
+                                                                           void (*handler)(const CTextCapturedMessage*, void*),
+                                                                           const CSubscription **subscription);
+
+SNIPS_RESULT hermes_asr_subscribe_text_captured_with_subscription(const CAsrFacade *facade,
+                                                                  void (*handler)(const CTextCapturedMessage*, void*),
+                                                                  const CSubscription **subscription);
+
 SNIPS_RESULT hermes_audio_server_backend_publish_audio_frame(const CAudioServerBackendFacade *facade,
                                                              const CAudioFrameMessage *message);
 
 SNIPS_RESULT hermes_audio_server_backend_publish_play_finished(const CAudioServerBackendFacade *facade,
                                                                const CPlayFinishedMessage *message);
 
+SNIPS_RESULT hermes_audio_server_backend_publish_replay_response(const CAudioServerBackendFacade *facade,
+                                                                 const CAudioFrameMessage *message);
+
+SNIPS_RESULT hermes_audio_server_backend_publish_stream_finished(const CAudioServerBackendFacade *facade,
+                                                                 const CStreamFinishedMessage *message);
+
 SNIPS_RESULT hermes_audio_server_backend_subscribe_all_play_bytes(const CAudioServerBackendFacade *facade,
                                                                   void (*handler)(const CPlayBytesMessage*, void*));
 
+SNIPS_RESULT hermes_audio_server_backend_subscribe_all_play_bytes_with_subscription(const CAudioServerBackendFacade *facade,
+                                                                                    void (*handler)(const CPlayBytesMessage*, void*),
+                                                                                    const CSubscription **subscription);
+
+SNIPS_RESULT hermes_audio_server_backend_subscribe_all_stream_bytes(const CAudioServerBackendFacade *facade,
+                                                                    void (*handler)(const CStreamBytesMessage*, void*));
+
+SNIPS_RESULT hermes_audio_server_backend_subscribe_all_stream_bytes_with_subscription(const CAudioServerBackendFacade *facade,
+                                                                                      void (*handler)(const CStreamBytesMessage*, void*),
+                                                                                      const CSubscription **subscription);
+
+SNIPS_RESULT hermes_audio_server_backend_subscribe_all_toggle_off(const CAudioServerBackendFacade *facade,
+                                                                  void (*handler)(const CSiteMessage*, void*));
+
+SNIPS_RESULT hermes_audio_server_backend_subscribe_all_toggle_off_with_subscription(const CAudioServerBackendFacade *facade,
+                                                                                    void (*handler)(const CSiteMessage*, void*),
+                                                                                    const CSubscription **subscription);
+
+SNIPS_RESULT hermes_audio_server_backend_subscribe_all_toggle_on(const CAudioServerBackendFacade *facade,
+                                                                 void (*handler)(const CSiteMessage*, void*));
+
+SNIPS_RESULT hermes_audio_server_backend_subscribe_all_toggle_on_with_subscription(const CAudioServerBackendFacade *facade,
+                                                                                   void (*handler)(const CSiteMessage*, void*),
+                                                                                   const CSubscription **subscription);
+
 SNIPS_RESULT hermes_audio_server_backend_subscribe_play_bytes(const CAudioServerBackendFacade *facade,
                                                               const char *site_id,
                                                               void (*handler)(const CPlayBytesMessage*, void*));
 
+SNIPS_RESULT hermes_audio_server_backend_subscribe_play_bytes_with_subscription(const CAudioServerBackendFacade *facade,
+                                                                                const char *site_id,
+                                                                                void (*handler)(const CPlayBytesMessage*, void*),
+                                                                                const CSubscription **subscription);
+
+SNIPS_RESULT hermes_audio_server_backend_subscribe_replay_request(const CAudioServerBackendFacade *facade,
+                                                                  const char *site_id,
+                                                                  void (*handler)(const CReplayRequestMessage*, void*));
+
+SNIPS_RESULT hermes_audio_server_backend_subscribe_replay_request_with_subscription(const CAudioServerBackendFacade *facade,
+                                                                                    const char *site_id,
+                                                                                    void (*handler)(const CReplayRequestMessage*, void*),
+                                                                                    const CSubscription **subscription);
+
+SNIPS_RESULT hermes_audio_server_backend_subscribe_stream_bytes(const CAudioServerBackendFacade *facade,
+                                                                const char *site_id,
+                                                                void (*handler)(const CStreamBytesMessage*, void*));
+
+SNIPS_RESULT hermes_audio_server_backend_subscribe_stream_bytes_with_subscription(const CAudioServerBackendFacade *facade,
+                                                                                  const char *site_id,
+                                                                                  void (*handler)(const CStreamBytesMessage*, void*),
+                                                                                  const CSubscription **subscription);
+
+SNIPS_RESULT hermes_audio_server_backend_subscribe_toggle_off(const CAudioServerBackendFacade *facade,
+                                                              const char *site_id,
+                                                              void (*handler)(const CSiteMessage*, void*));
+
+SNIPS_RESULT hermes_audio_server_backend_subscribe_toggle_off_with_subscription(const CAudioServerBackendFacade *facade,
+                                                                                const char *site_id,
+                                                                                void (*handler)(const CSiteMessage*, void*),
+                                                                                const CSubscription **subscription);
+
+SNIPS_RESULT hermes_audio_server_backend_subscribe_toggle_on(const CAudioServerBackendFacade *facade,
+                                                             const char *site_id,
+                                                             void (*handler)(const CSiteMessage*, void*));
+
+SNIPS_RESULT hermes_audio_server_backend_subscribe_toggle_on_with_subscription(const CAudioServerBackendFacade *facade,
+                                                                               const char *site_id,
+                                                                               void (*handler)(const CSiteMessage*, void*),
+                                                                               const CSubscription **subscription);
+
 SNIPS_RESULT hermes_audio_server_publish_play_bytes(const CAudioServerFacade *facade,
                                                     const CPlayBytesMessage *message);
 
+SNIPS_RESULT hermes_audio_server_publish_replay_request(const CAudioServerFacade *facade,
+                                                        const CReplayRequestMessage *message);
+
+SNIPS_RESULT hermes_audio_server_publish_stream_bytes(const CAudioServerFacade *facade,
+                                                      const CStreamBytesMessage *message);
+
+SNIPS_RESULT hermes_audio_server_publish_toggle_off(const CAudioServerFacade *facade,
+                                                    const CSiteMessage *message);
+
+SNIPS_RESULT hermes_audio_server_publish_toggle_on(const CAudioServerFacade *facade,
+                                                   const CSiteMessage *message);
+
+SNIPS_RESULT hermes_audio_server_subscribe_all_audio_frame(const CAudioServerFacade *facade,
+                                                           void (*handler)(const CAudioFrameMessage*, void*));
+
+SNIPS_RESULT hermes_audio_server_subscribe_all_audio_frame_with_subscription(const CAudioServerFacade *facade,
+                                                                             void (*handler)(const CAudioFrameMessage*, void*),
+                                                                             const CSubscription **subscription);
+
 SNIPS_RESULT hermes_audio_server_subscribe_all_play_finished(const CAudioServerFacade *facade,
                                                              void (*handler)(const CPlayFinishedMessage*, void*));
 
+SNIPS_RESULT hermes_audio_server_subscribe_all_play_finished_with_subscription(const CAudioServerFacade *facade,
+                                                                               void (*handler)(const CPlayFinishedMessage*, void*),
+                                                                               const CSubscription **subscription);
+
+SNIPS_RESULT hermes_audio_server_subscribe_all_stream_finished(const CAudioServerFacade *facade,
+                                                               void (*handler)(const CStreamFinishedMessage*, void*));
+
+SNIPS_RESULT hermes_audio_server_subscribe_all_stream_finished_with_subscription(const CAudioServerFacade *facade,
+                                                                                 void (*handler)(const CStreamFinishedMessage*, void*),
+                                                                                 const CSubscription **subscription);
+
 SNIPS_RESULT hermes_audio_server_subscribe_audio_frame(const CAudioServerFacade *facade,
                                                        const char *site_id,
                                                        void (*handler)(const CAudioFrameMessage*, void*));
 
+SNIPS_RESULT hermes_audio_server_subscribe_audio_frame_with_subscription(const CAudioServerFacade *facade,
+                                                                         const char *site_id,
+                                                                         void (*handler)(const CAudioFrameMessage*, void*),
+                                                                         const CSubscription **subscription);
+
 SNIPS_RESULT hermes_audio_server_subscribe_play_finished(const CAudioServerFacade *facade,
                                                          const char *site_id,
                                                          void (*handler)(const CPlayFinishedMessage*, void*));
 
+SNIPS_RESULT hermes_audio_server_subscribe_play_finished_with_subscription(const CAudioServerFacade *facade,
+                                                                           const char *site_id,
+                                                                           void (*handler)(const CPlayFinishedMessage*, void*),
+                                                                           const CSubscription **subscription);
+
+SNIPS_RESULT hermes_audio_server_subscribe_replay_response(const CAudioServerFacade *facade,
+                                                           const char *site_id,
+                                                           void (*handler)(const CAudioFrameMessage*, void*));
+
+SNIPS_RESULT hermes_audio_server_subscribe_replay_response_with_subscription(const CAudioServerFacade *facade,
+                                                                             const char *site_id,
+                                                                             void (*handler)(const CAudioFrameMessage*, void*),
+                                                                             const CSubscription **subscription);
+
+SNIPS_RESULT hermes_audio_server_subscribe_stream_finished(const CAudioServerFacade *facade,
+                                                           const char *site_id,
+                                                           void (*handler)(const CStreamFinishedMessage*, void*));
+
+SNIPS_RESULT hermes_audio_server_subscribe_stream_finished_with_subscription(const CAudioServerFacade *facade,
+                                                                             const char *site_id,
+                                                                             void (*handler)(const CStreamFinishedMessage*, void*),
+                                                                             const CSubscription **subscription);
+
+SNIPS_RESULT hermes_destroy_in_process_protocol_handler(CProtocolHandler *handler);
+
 SNIPS_RESULT hermes_destroy_mqtt_protocol_handler(CProtocolHandler *handler);
 
 SNIPS_RESULT hermes_dialogue_backend_publish_intent(const CDialogueBackendFacade *facade,
@@ -1183,15 +1490,45 @@ SNIPS_RESULT hermes_dialogue_backend_publish_session_started(const CDialogueBack
 SNIPS_RESULT hermes_dialogue_backend_subscribe_configure(const CDialogueBackendFacade *facade,
                                                          void (*handler)(const CDialogueConfigureMessage*, void*));
 
+SNIPS_RESULT hermes_dialogue_backend_subscribe_configure_with_subscription(const CDialogueBackendFacade *facade,
+                                                                           void (*handler)(const CDialogueConfigureMessage*, void*),
+                                                                           const CSubscription **subscription);
+
 SNIPS_RESULT hermes_dialogue_backend_subscribe_continue_session(const CDialogueBackendFacade *facade,
                                                                 void (*handler)(const CContinueSessionMessage*, void*));
 
+SNIPS_RESULT hermes_dialogue_backend_subscribe_continue_session_with_subscription(const CDialogueBackendFacade *facade,
+                                                                                  void (*handler)(const CContinueSessionMessage*, void*),
+                                                                                  const CSubscription **subscription);
+
 SNIPS_RESULT hermes_dialogue_backend_subscribe_end_session(const CDialogueBackendFacade *facade,
                                                            void (*handler)(const CEndSessionMessage*, void*));
 
+SNIPS_RESULT hermes_dialogue_backend_subscribe_end_session_with_subscription(const CDialogueBackendFacade *facade,
+                                                                             void (*handler)(const CEndSessionMessage*, void*),
+                                                                             const CSubscription **subscription);
+
 SNIPS_RESULT hermes_dialogue_backend_subscribe_start_session(const CDialogueBackendFacade *facade,
                                                              void (*handler)(const CStartSessionMessage*, void*));
 
+SNIPS_RESULT hermes_dialogue_backend_subscribe_start_session_with_subscription(const CDialogueBackendFacade *facade,
+                                                                               void (*handler)(const CStartSessionMessage*, void*),
+                                                                               const CSubscription **subscription);
+
+SNIPS_RESULT hermes_dialogue_backend_subscribe_toggle_off(const CDialogueBackendFacade *facade,
+                                                          void (*handler)(void*));
+
+SNIPS_RESULT hermes_dialogue_backend_subscribe_toggle_off_with_subscription(const CDialogueBackendFacade *facade,
+                                                                            void (*handler)(void*),
+                                                                            const CSubscription **subscription);
+
+SNIPS_RESULT hermes_dialogue_backend_subscribe_toggle_on(const CDialogueBackendFacade *facade,
+                                                         void (*handler)(void*));
+
+SNIPS_RESULT hermes_dialogue_backend_subscribe_toggle_on_with_subscription(const CDialogueBackendFacade *facade,
+                                                                           void (*handler)(void*),
+                                                                           const CSubscription **subscription);
+
 SNIPS_RESULT hermes_dialogue_publish_configure(const CDialogueFacade *facade,
                                                const CDialogueConfigureMessage *message);
 
@@ -1204,6 +1541,10 @@ SNIPS_RESULT hermes_dialogue_publish_end_session(const CDialogueFacade *facade,
 SNIPS_RESULT hermes_dialogue_publish_start_session(const CDialogueFacade *facade,
                                                    const CStartSessionMessage *message);
 
+SNIPS_RESULT hermes_dialogue_publish_toggle_off(const CDialogueFacade *facade);
+
+SNIPS_RESULT hermes_dialogue_publish_toggle_on(const CDialogueFacade *facade);
+
 SNIPS_RESULT hermes_dialogue_subscribe_intent(const CDialogueFacade *facade,
                                               const char *intent_name,
                                               void (*handler)(const CIntentMessage*, void*));
@@ -1211,18 +1552,84 @@ SNIPS_RESULT hermes_dialogue_subscribe_intent(const CDialogueFacade *facade,
 SNIPS_RESULT hermes_dialogue_subscribe_intent_not_recognized(const CDialogueFacade *facade,
                                                              void (*handler)(const CIntentNotRecognizedMessage*, void*));
 
+SNIPS_RESULT hermes_dialogue_subscribe_intent_not_recognized_for_site(const CDialogueFacade *facade,
+                                                                      const char *site_id,
+                                                                      void (*handler)(const CIntentNotRecognizedMessage*, void*));
+
+SNIPS_RESULT hermes_dialogue_subscribe_intent_not_recognized_for_site_with_subscription(const CDialogueFacade *facade,
+                                                                                        const char *site_id,
+                                                                                        void (*handler)(const CIntentNotRecognizedMessage*, void*),
+                                                                                        const CSubscription **subscription);
+
+SNIPS_RESULT hermes_dialogue_subscribe_intent_not_recognized_with_subscription(const CDialogueFacade *facade,
+                                                                               void (*handler)(const CIntentNotRecognizedMessage*, void*),
+                                                                               const CSubscription **subscription);
+
+SNIPS_RESULT hermes_dialogue_subscribe_intent_with_subscription(const CDialogueFacade *facade,
+                                                                const char *intent_name,
+                                                                void (*handler)(const CIntentMessage*, void*),
+                                                                const CSubscription **subscription);
+
 SNIPS_RESULT hermes_dialogue_subscribe_intents(const CDialogueFacade *facade,
                                                void (*handler)(const CIntentMessage*, void*));
 
+SNIPS_RESULT hermes_dialogue_subscribe_intents_with_subscription(const CDialogueFacade *facade,
+                                                                 void (*handler)(const CIntentMessage*, void*),
+                                                                 const CSubscription **subscription);
+
 SNIPS_RESULT hermes_dialogue_subscribe_session_ended(const CDialogueFacade *facade,
                                                      void (*handler)(const CSessionEndedMessage*, void*));
 
+SNIPS_RESULT hermes_dialogue_subscribe_session_ended_for_site(const CDialogueFacade *facade,
+                                                              const char *site_id,
+                                                              void (*handler)(const CSessionEndedMessage*, void*));
+
+SNIPS_RESULT hermes_dialogue_subscribe_session_ended_for_site_with_subscription(const CDialogueFacade *facade,
+                                                                                const char *site_id,
+                                                                                void (*handler)(const CSessionEndedMessage*, void*),
+                                                                                const CSubscription **subscription);
+
+SNIPS_RESULT hermes_dialogue_subscribe_session_ended_with_subscription(const CDialogueFacade *facade,
+                                                                       void (*handler)(const CSessionEndedMessage*, void*),
+                                                                       const CSubscription **subscription);
+
 SNIPS_RESULT hermes_dialogue_subscribe_session_queued(const CDialogueFacade *facade,
                                                       void (*handler)(const CSessionQueuedMessage*, void*));
 
+SNIPS_RESULT hermes_dialogue_subscribe_session_queued_for_site(const CDialogueFacade *facade,
+                                                               const char *site_id,
+                                                               void (*handler)(const CSessionQueuedMessage*, void*));
+
+SNIPS_RESULT hermes_dialogue_subscribe_session_queued_for_site_with_subscription(const CDialogueFacade *facade,
+                                                                                 const char *site_id,
+                                                                                 void (*handler)(const CSessionQueuedMessage*, void*),
+                                                                                 const CSubscription **subscription);
+
+SNIPS_RESULT hermes_dialogue_subscribe_session_queued_with_subscription(const CDialogueFacade *facade,
+                                                                        void (*handler)(const CSessionQueuedMessage*, void*),
+                                                                        const CSubscription **subscription);
+
 SNIPS_RESULT hermes_dialogue_subscribe_session_started(const CDialogueFacade *facade,
                                                        void (*handler)(const CSessionStartedMessage*, void*));
 
+SNIPS_RESULT hermes_dialogue_subscribe_session_started_for_site(const CDialogueFacade *facade,
+                                                                const char *site_id,
+                                                                void (*handler)(const CSessionStartedMessage*, void*));
+
+SNIPS_RESULT hermes_dialogue_subscribe_session_started_for_site_with_subscription(const CDialogueFacade *facade,
+                                                                                  const char *site_id,
+                                                                                  void (*handler)(const CSessionStartedMessage*, void*),
+                                                                                  const CSubscription **subscription);
+
+SNIPS_RESULT hermes_dialogue_subscribe_session_started_with_subscription(const CDialogueFacade *facade,
+                                                                         void (*handler)(const CSessionStartedMessage*, void*),
+                                                                         const CSubscription **subscription);
+
+/**
+ * Call the queued callbacks of all the handlers, without waiting
+ */
+SNIPS_RESULT hermes_dispatch_pending(void);
+
 SNIPS_RESULT hermes_drop_asr_backend_facade(const CAsrBackendFacade *cstruct);
 
 SNIPS_RESULT hermes_drop_asr_facade(const CAsrFacade *cstruct);
@@ -1251,6 +1658,8 @@ SNIPS_RESULT hermes_drop_hotword_detected_message(const CHotwordDetectedMessage
 
 SNIPS_RESULT hermes_drop_hotword_facade(const CHotwordFacade *cstruct);
 
+SNIPS_RESULT hermes_drop_injection_backend_facade(const CInjectionBackendFacade *cstruct);
+
 SNIPS_RESULT hermes_drop_injection_complete_message(const CInjectionCompleteMessage *cstruct);
 
 SNIPS_RESULT hermes_drop_injection_facade(const CInjectionFacade *cstruct);
@@ -1287,6 +1696,10 @@ SNIPS_RESULT hermes_drop_play_finished_message(const CPlayFinishedMessage *cstru
 
 SNIPS_RESULT hermes_drop_register_sound_message(const CRegisterSoundMessage *cstruct);
 
+SNIPS_RESULT hermes_drop_replay_request_message(const CReplayRequestMessage *cstruct);
+
+SNIPS_RESULT hermes_drop_request_component_reload_message(const CRequestComponentReloadMessage *cstruct);
+
 SNIPS_RESULT hermes_drop_say_finished_message(const CSayFinishedMessage *cstruct);
 
 SNIPS_RESULT hermes_drop_say_message(const CSayMessage *cstruct);
@@ -1305,14 +1718,28 @@ SNIPS_RESULT hermes_drop_sound_feedback_facade(const CSoundFeedbackFacade *cstru
 
 SNIPS_RESULT hermes_drop_start_session_message(const CStartSessionMessage *cstruct);
 
+SNIPS_RESULT hermes_drop_stream_bytes_message(const CStreamBytesMessage *cstruct);
+
+SNIPS_RESULT hermes_drop_stream_finished_message(const CStreamFinishedMessage *cstruct);
+
+SNIPS_RESULT hermes_drop_subscription(const CSubscription *cstruct);
+
 SNIPS_RESULT hermes_drop_text_captured_message(const CTextCapturedMessage *cstruct);
 
 SNIPS_RESULT hermes_drop_tts_backend_facade(const CTtsBackendFacade *cstruct);
 
 SNIPS_RESULT hermes_drop_tts_facade(const CTtsFacade *cstruct);
 
+SNIPS_RESULT hermes_drop_vad_down_message(const CVadDownMessage *cstruct);
+
+SNIPS_RESULT hermes_drop_vad_up_message(const CVadUpMessage *cstruct);
+
 SNIPS_RESULT hermes_drop_version_message(const CVersionMessage *cstruct);
 
+SNIPS_RESULT hermes_drop_voice_activity_backend_facade(const CVoiceActivityBackendFacade *cstruct);
+
+SNIPS_RESULT hermes_drop_voice_activity_facade(const CVoiceActivityFacade *cstruct);
+
 SNIPS_RESULT hermes_enable_debug_logs(void);
 
 /**
@@ -1325,13 +1752,90 @@ SNIPS_RESULT hermes_hotword_backend_publish_detected(const CHotwordBackendFacade
                                                      const char *hotword_id,
                                                      const CHotwordDetectedMessage *message);
 
+SNIPS_RESULT hermes_hotword_backend_subscribe_all_toggle_off(const CHotwordBackendFacade *facade,
+                                                             void (*handler)(const CSiteMessage*, void*));
+
+SNIPS_RESULT hermes_hotword_backend_subscribe_all_toggle_off_with_subscription(const CHotwordBackendFacade *facade,
+                                                                               void (*handler)(const CSiteMessage*, void*),
+                                                                               const CSubscription **subscription);
+
+SNIPS_RESULT hermes_hotword_backend_subscribe_all_toggle_on(const CHotwordBackendFacade *facade,
+                                                            void (*handler)(const CSiteMessage*, void*));
+
+SNIPS_RESULT hermes_hotword_backend_subscribe_all_toggle_on_with_subscription(const CHotwordBackendFacade *facade,
+                                                                              void (*handler)(const CSiteMessage*, void*),
+                                                                              const CSubscription **subscription);
+
+SNIPS_RESULT hermes_hotword_backend_subscribe_toggle_off(const CHotwordBackendFacade *facade,
+                                                         const char *site_id,
+                                                         void (*handler)(const CSiteMessage*, void*));
+
+SNIPS_RESULT hermes_hotword_backend_subscribe_toggle_off_with_subscription(const CHotwordBackendFacade *facade,
+                                                                           const char *site_id,
+                                                                           void (*handler)(const CSiteMessage*, void*),
+                                                                           const CSubscription **subscription);
+
+SNIPS_RESULT hermes_hotword_backend_subscribe_toggle_on(const CHotwordBackendFacade *facade,
+                                                        const char *site_id,
+                                                        void (*handler)(const CSiteMessage*, void*));
+
+SNIPS_RESULT hermes_hotword_backend_subscribe_toggle_on_with_subscription(const CHotwordBackendFacade *facade,
+                                                                          const char *site_id,
+                                                                          void (*handler)(const CSiteMessage*, void*),
+                                                                          const CSubscription **subscription);
+
+SNIPS_RESULT hermes_hotword_publish_toggle_off(const CHotwordFacade *facade,
+                                               const CSiteMessage *message);
+
+SNIPS_RESULT hermes_hotword_publish_toggle_on(const CHotwordFacade *facade,
+                                              const CSiteMessage *message);
+
 SNIPS_RESULT hermes_hotword_subscribe_all_detected(const CHotwordFacade *facade,
                                                    void (*handler)(const CHotwordDetectedMessage*, void*));
 
+SNIPS_RESULT hermes_hotword_subscribe_all_detected_with_subscription(const CHotwordFacade *facade,
+                                                                     void (*handler)(const CHotwordDetectedMessage*, void*),
+                                                                     const CSubscription **subscription);
+
 SNIPS_RESULT hermes_hotword_subscribe_detected(const CHotwordFacade *facade,
                                                const char *hotword_id,
                                                void (*handler)(const CHotwordDetectedMessage*, void*));
 
+SNIPS_RESULT hermes_hotword_subscribe_detected_with_subscription(const CHotwordFacade *facade,
+                                                                 const char *hotword_id,
+                                                                 void (*handler)(const CHotwordDetectedMessage*, void*),
+                                                                 const CSubscription **subscription);
+
+SNIPS_RESULT hermes_injection_backend_publish_injection_complete(const CInjectionBackendFacade *facade,
+                                                                 const CInjectionCompleteMessage *message);
+
+SNIPS_RESULT hermes_injection_backend_publish_injection_reset_complete(const CInjectionBackendFacade *facade,
+                                                                       const CInjectionResetCompleteMessage *message);
+
+SNIPS_RESULT hermes_injection_backend_publish_injection_status(const CInjectionBackendFacade *facade,
+                                                               const CInjectionStatusMessage *message);
+
+SNIPS_RESULT hermes_injection_backend_subscribe_injection_request(const CInjectionBackendFacade *facade,
+                                                                  void (*handler)(const CInjectionRequestMessage*, void*));
+
+SNIPS_RESULT hermes_injection_backend_subscribe_injection_request_with_subscription(const CInjectionBackendFacade *facade,
+                                                                                    void (*handler)(const CInjectionRequestMessage*, void*),
+                                                                                    const CSubscription **subscription);
+
+SNIPS_RESULT hermes_injection_backend_subscribe_injection_reset_request(const CInjectionBackendFacade *facade,
+                                                                        void (*handler)(const CInjectionResetRequestMessage*, void*));
+
+SNIPS_RESULT hermes_injection_backend_subscribe_injection_reset_request_with_subscription(const CInjectionBackendFacade *facade,
+                                                                                          void (*handler)(const CInjectionResetRequestMessage*, void*),
+                                                                                          const CSubscription **subscription);
+
+SNIPS_RESULT hermes_injection_backend_subscribe_injection_status_request(const CInjectionBackendFacade *facade,
+                                                                         void (*handler)(void*));
+
+SNIPS_RESULT hermes_injection_backend_subscribe_injection_status_request_with_subscription(const CInjectionBackendFacade *facade,
+                                                                                           void (*handler)(void*),
+                                                                                           const CSubscription **subscription);
+
 SNIPS_RESULT hermes_injection_publish_injection_request(const CInjectionFacade *facade,
                                                         const CInjectionRequestMessage *message);
 
@@ -1343,12 +1847,24 @@ SNIPS_RESULT hermes_injection_publish_injection_status_request(const CInjectionF
 SNIPS_RESULT hermes_injection_subscribe_injection_complete(const CInjectionFacade *facade,
                                                            void (*handler)(const CInjectionCompleteMessage*, void*));
 
+SNIPS_RESULT hermes_injection_subscribe_injection_complete_with_subscription(const CInjectionFacade *facade,
+                                                                             void (*handler)(const CInjectionCompleteMessage*, void*),
+                                                                             const CSubscription **subscription);
+
 SNIPS_RESULT hermes_injection_subscribe_injection_reset_complete(const CInjectionFacade *facade,
                                                                  void (*handler)(const CInjectionResetCompleteMessage*, void*));
 
+SNIPS_RESULT hermes_injection_subscribe_injection_reset_complete_with_subscription(const CInjectionFacade *facade,
+                                                                                   void (*handler)(const CInjectionResetCompleteMessage*, void*),
+                                                                                   const CSubscription **subscription);
+
 SNIPS_RESULT hermes_injection_subscribe_injection_status(const CInjectionFacade *facade,
                                                          void (*handler)(const CInjectionStatusMessage*, void*));
 
+SNIPS_RESULT hermes_injection_subscribe_injection_status_with_subscription(const CInjectionFacade *facade,
+                                                                           void (*handler)(const CInjectionStatusMessage*, void*),
+                                                                           const CSubscription **subscription);
+
 SNIPS_RESULT hermes_nlu_backend_publish_intent_not_recognized(const CNluBackendFacade *facade,
                                                               const CNluIntentNotRecognizedMessage *message);
 
@@ -1358,12 +1874,30 @@ SNIPS_RESULT hermes_nlu_backend_publish_intent_parsed(const CNluBackendFacade *f
 SNIPS_RESULT hermes_nlu_backend_publish_slot_parsed(const CNluBackendFacade *facade,
                                                     const CNluSlotMessage *message);
 
+SNIPS_RESULT hermes_nlu_backend_subscribe_component_reload(const CNluBackendFacade *facade,
+                                                           void (*handler)(const CRequestComponentReloadMessage*, void*));
+
+SNIPS_RESULT hermes_nlu_backend_subscribe_component_reload_with_subscription(const CNluBackendFacade *facade,
+                                                                             void (*handler)(const CRequestComponentReloadMessage*, void*),
+                                                                             const CSubscription **subscription);
+
 SNIPS_RESULT hermes_nlu_backend_subscribe_partial_query(const CNluBackendFacade *facade,
                                                         void (*handler)(const CNluSlotQueryMessage*, void*));
 
+SNIPS_RESULT hermes_nlu_backend_subscribe_partial_query_with_subscription(const CNluBackendFacade *facade,
+                                                                          void (*handler)(const CNluSlotQueryMessage*, void*),
+                                                                          const CSubscription **subscription);
+
 SNIPS_RESULT hermes_nlu_backend_subscribe_query(const CNluBackendFacade *facade,
                                                 void (*handler)(const CNluQueryMessage*, void*));
 
+SNIPS_RESULT hermes_nlu_backend_subscribe_query_with_subscription(const CNluBackendFacade *facade,
+                                                                  void (*handler)(const CNluQueryMessage*, void*),
+                                                                  const CSubscription **subscription);
+
+SNIPS_RESULT hermes_nlu_publish_component_reload(const CNluFacade *facade,
+                                                 const CRequestComponentReloadMessage *message);
+
 SNIPS_RESULT hermes_nlu_publish_partial_query(const CNluFacade *facade,
                                               const CNluSlotQueryMessage *message);
 
@@ -1372,12 +1906,30 @@ SNIPS_RESULT hermes_nlu_publish_query(const CNluFacade *facade, const CNluQueryM
 SNIPS_RESULT hermes_nlu_subscribe_intent_not_recognized(const CNluFacade *facade,
                                                         void (*handler)(const CNluIntentNotRecognizedMessage*, void*));
 
+SNIPS_RESULT hermes_nlu_subscribe_intent_not_recognized_with_subscription(const CNluFacade *facade,
+                                                                          void (*handler)(const CNluIntentNotRecognizedMessage*, void*),
+                                                                          const CSubscription **subscription);
+
 SNIPS_RESULT hermes_nlu_subscribe_intent_parsed(const CNluFacade *facade,
                                                 void (*handler)(const CNluIntentMessage*, void*));
 
+SNIPS_RESULT hermes_nlu_subscribe_intent_parsed_with_subscription(const CNluFacade *facade,
+                                                                  void (*handler)(const CNluIntentMessage*, void*),
+                                                                  const CSubscription **subscription);
+
 SNIPS_RESULT hermes_nlu_subscribe_slot_parsed(const CNluFacade *facade,
                                               void (*handler)(const CNluSlotMessage*, void*));
 
+SNIPS_RESULT hermes_nlu_subscribe_slot_parsed_with_subscription(const CNluFacade *facade,
+                                                                void (*handler)(const CNluSlotMessage*, void*),
+                                                                const CSubscription **subscription);
+
+/**
+ * Call the queued callbacks of `handler`, waiting up to `timeout_ms` milliseconds for one
+ * if there are none. A negative timeout waits as long as needed.
+ */
+SNIPS_RESULT hermes_poll(const CProtocolHandler *handler, int timeout_ms);
+
 SNIPS_RESULT hermes_protocol_handler_asr_backend_facade(const CProtocolHandler *handler,
                                                         const CAsrBackendFacade **facade);
 
@@ -1396,15 +1948,41 @@ SNIPS_RESULT hermes_protocol_handler_dialogue_backend_facade(const CProtocolHand
 SNIPS_RESULT hermes_protocol_handler_dialogue_facade(const CProtocolHandler *handler,
                                                      const CDialogueFacade **facade);
 
+/**
+ * Give a file descriptor that is readable while callbacks of `handler` are queued, so that
+ * hosts can wait for them in their own event loop. The queue must be enabled first and the
+ * descriptor stays owned by the handler. Only available on unix.
+ */
+SNIPS_RESULT hermes_protocol_handler_dispatch_fd(const CProtocolHandler *handler, int *fd);
+
+/**
+ * Queue the callbacks of the subscriptions made through `handler` and its facades instead
+ * of calling them on the thread of the communication layer. They are then called by
+ * `hermes_poll` or `hermes_dispatch_pending`, on the thread of the caller.
+ */
+SNIPS_RESULT hermes_protocol_handler_enable_queued_dispatch(const CProtocolHandler *handler);
+
 SNIPS_RESULT hermes_protocol_handler_hotword_backend_facade(const CProtocolHandler *handler,
                                                             const CHotwordBackendFacade **facade);
 
 SNIPS_RESULT hermes_protocol_handler_hotword_facade(const CProtocolHandler *handler,
                                                     const CHotwordFacade **facade);
 
+SNIPS_RESULT hermes_protocol_handler_injection_backend_facade(const CProtocolHandler *handler,
+                                                              const CInjectionBackendFacade **facade);
+
 SNIPS_RESULT hermes_protocol_handler_injection_facade(const CProtocolHandler *handler,
                                                       const CInjectionFacade **facade);
 
+/**
+ * Create a handler delivering the messages inside the process, without a broker. The
+ * handlers created with the same non null `bus_name` share their bus as long as one of
+ * them is alive, a null `bus_name` gives a handler with a bus of its own.
+ */
+SNIPS_RESULT hermes_protocol_handler_new_in_process(const CProtocolHandler **handler,
+                                                    const char *bus_name,
+                                                    void *user_data);
+
 SNIPS_RESULT hermes_protocol_handler_new_mqtt(const CProtocolHandler **handler,
                                               const char *broker_address,
                                               void *user_data);
@@ -1419,6 +1997,13 @@ SNIPS_RESULT hermes_protocol_handler_nlu_backend_facade(const CProtocolHandler *
 SNIPS_RESULT hermes_protocol_handler_nlu_facade(const CProtocolHandler *handler,
                                                 const CNluFacade **facade);
 
+/**
+ * Set the function called with the `user_data` given at the creation of `handler` once the
+ * handler, its facades and its subscriptions are all gone
+ */
+SNIPS_RESULT hermes_protocol_handler_set_user_data_destructor(const CProtocolHandler *handler,
+                                                              void (*destructor)(void*));
+
 SNIPS_RESULT hermes_protocol_handler_sound_feedback_backend_facade(const CProtocolHandler *handler,
                                                                    const CSoundFeedbackBackendFacade **facade);
 
@@ -1431,6 +2016,46 @@ SNIPS_RESULT hermes_protocol_handler_tts_backend_facade(const CProtocolHandler *
 SNIPS_RESULT hermes_protocol_handler_tts_facade(const CProtocolHandler *handler,
                                                 const CTtsFacade **facade);
 
+SNIPS_RESULT hermes_protocol_handler_voice_activity_backend_facade(const CProtocolHandler *handler,
+                                                                   const CVoiceActivityBackendFacade **facade);
+
+SNIPS_RESULT hermes_protocol_handler_voice_activity_facade(const CProtocolHandler *handler,
+                                                           const CVoiceActivityFacade **facade);
+
+SNIPS_RESULT hermes_set_struct_version(unsigned int version);
+
+SNIPS_RESULT hermes_sound_feedback_backend_subscribe_all_toggle_off(const CSoundFeedbackBackendFacade *facade,
+                                                                    void (*handler)(const CSiteMessage*, void*));
+
+SNIPS_RESULT hermes_sound_feedback_backend_subscribe_all_toggle_off_with_subscription(const CSoundFeedbackBackendFacade *facade,
+                                                                                      void (*handler)(const CSiteMessage*, void*),
+                                                                                      const CSubscription **subscription);
+
+SNIPS_RESULT hermes_sound_feedback_backend_subscribe_all_toggle_on(const CSoundFeedbackBackendFacade *facade,
+                                                                   void (*handler)(const CSiteMessage*, void*));
+
+SNIPS_RESULT hermes_sound_feedback_backend_subscribe_all_toggle_on_with_subscription(const CSoundFeedbackBackendFacade *facade,
+                                                                                     void (*handler)(const CSiteMessage*, void*),
+                                                                                     const CSubscription **subscription);
+
+SNIPS_RESULT hermes_sound_feedback_backend_subscribe_toggle_off(const CSoundFeedbackBackendFacade *facade,
+                                                                const char *site_id,
+                                                                void (*handler)(const CSiteMessage*, void*));
+
+SNIPS_RESULT hermes_sound_feedback_backend_subscribe_toggle_off_with_subscription(const CSoundFeedbackBackendFacade *facade,
+                                                                                  const char *site_id,
+                                                                                  void (*handler)(const CSiteMessage*, void*),
+                                                                                  const CSubscription **subscription);
+
+SNIPS_RESULT hermes_sound_feedback_backend_subscribe_toggle_on(const CSoundFeedbackBackendFacade *facade,
+                                                               const char *site_id,
+                                                               void (*handler)(const CSiteMessage*, void*));
+
+SNIPS_RESULT hermes_sound_feedback_backend_subscribe_toggle_on_with_subscription(const CSoundFeedbackBackendFacade *facade,
+                                                                                 const char *site_id,
+                                                                                 void (*handler)(const CSiteMessage*, void*),
+                                                                                 const CSubscription **subscription);
+
 SNIPS_RESULT hermes_sound_feedback_publish_toggle_off(const CSoundFeedbackFacade *facade,
                                                       const CSiteMessage *message);
 
@@ -1443,9 +2068,26 @@ SNIPS_RESULT hermes_tts_backend_publish_say_finished(const CTtsBackendFacade *fa
 SNIPS_RESULT hermes_tts_backend_subscribe_register_sound(const CTtsBackendFacade *facade,
                                                          void (*handler)(const CRegisterSoundMessage*, void*));
 
+SNIPS_RESULT hermes_tts_backend_subscribe_register_sound_with_subscription(const CTtsBackendFacade *facade,
+                                                                           void (*handler)(const CRegisterSoundMessage*, void*),
+                                                                           const CSubscription **subscription);
+
 SNIPS_RESULT hermes_tts_backend_subscribe_say(const CTtsBackendFacade *facade,
                                               void (*handler)(const CSayMessage*, void*));
 
+SNIPS_RESULT hermes_tts_backend_subscribe_say_for_site(const CTtsBackendFacade *facade,
+                                                       const char *site_id,
+                                                       void (*handler)(const CSayMessage*, void*));
+
+SNIPS_RESULT hermes_tts_backend_subscribe_say_for_site_with_subscription(const CTtsBackendFacade *facade,
+                                                                         const char *site_id,
+                                                                         void (*handler)(const CSayMessage*, void*),
+                                                                         const CSubscription **subscription);
+
+SNIPS_RESULT hermes_tts_backend_subscribe_say_with_subscription(const CTtsBackendFacade *facade,
+                                                                void (*handler)(const CSayMessage*, void*),
+                                                                const CSubscription **subscription);
+
 SNIPS_RESULT hermes_tts_publish_register_sound(const CTtsFacade *facade,
                                                const CRegisterSoundMessage *message);
 
@@ -1454,4 +2096,52 @@ SNIPS_RESULT hermes_tts_publish_say(const CTtsFacade *facade, const CSayMessage
 SNIPS_RESULT hermes_tts_subscribe_say_finished(const CTtsFacade *facade,
                                                void (*handler)(const CSayFinishedMessage*, void*));
 
+SNIPS_RESULT hermes_tts_subscribe_say_finished_with_subscription(const CTtsFacade *facade,
+                                                                 void (*handler)(const CSayFinishedMessage*, void*),
+                                                                 const CSubscription **subscription);
+
+/**
+ * Unregister the handler of `subscription` from the protocol handler and release the token.
+ * The `user_data` of the handler isn't given to it anymore once this returns.
+ */
+SNIPS_RESULT hermes_unsubscribe(const CSubscription *subscription);
+
+SNIPS_RESULT hermes_voice_activity_backend_publish_vad_down(const CVoiceActivityBackendFacade *facade,
+                                                            const CVadDownMessage *message);
+
+SNIPS_RESULT hermes_voice_activity_backend_publish_vad_up(const CVoiceActivityBackendFacade *facade,
+                                                          const CVadUpMessage *message);
+
+SNIPS_RESULT hermes_voice_activity_subscribe_all_vad_down(const CVoiceActivityFacade *facade,
+                                                          void (*handler)(const CVadDownMessage*, void*));
+
+SNIPS_RESULT hermes_voice_activity_subscribe_all_vad_down_with_subscription(const CVoiceActivityFacade *facade,
+                                                                            void (*handler)(const CVadDownMessage*, void*),
+                                                                            const CSubscription **subscription);
+
+SNIPS_RESULT hermes_voice_activity_subscribe_all_vad_up(const CVoiceActivityFacade *facade,
+                                                        void (*handler)(const CVadUpMessage*, void*));
+
+SNIPS_RESULT hermes_voice_activity_subscribe_all_vad_up_with_subscription(const CVoiceActivityFacade *facade,
+                                                                          void (*handler)(const CVadUpMessage*, void*),
+                                                                          const CSubscription **subscription);
+
+SNIPS_RESULT hermes_voice_activity_subscribe_vad_down(const CVoiceActivityFacade *facade,
+                                                      const char *site_id,
+                                                      void (*handler)(const CVadDownMessage*, void*));
+
+SNIPS_RESULT hermes_voice_activity_subscribe_vad_down_with_subscription(const CVoiceActivityFacade *facade,
+                                                                        const char *site_id,
+                                                                        void (*handler)(const CVadDownMessage*, void*),
+                                                                        const CSubscription **subscription);
+
+SNIPS_RESULT hermes_voice_activity_subscribe_vad_up(const CVoiceActivityFacade *facade,
+                                                    const char *site_id,
+                                                    void (*handler)(const CVadUpMessage*, void*));
+
+SNIPS_RESULT hermes_voice_activity_subscribe_vad_up_with_subscription(const CVoiceActivityFacade *facade,
+                                                                      const char *site_id,
+                                                                      void (*handler)(const CVadUpMessage*, void*),
+                                                                      const CSubscription **subscription);
+
 #endif /* LIB_HERMES_H_ */
@@ -20,31 +20,110 @@ typedef enum {
   SNIPS_RESULT_KO = 1,
 } SNIPS_RESULT;
 
+/**
+ * A token identifying a subscription, returned by the `hermes_*_subscribe_*_with_subscription`
+ * functions through their last parameter when it isn't null. It must be given to
+ * `hermes_unsubscribe` or `hermes_drop_subscription`.
+ */
+typedef struct {
+  const void *subscription;
+} CSubscription;
+
 typedef struct {
   const void *handler;
   void *user_data;
 } CProtocolHandler;
 
+typedef struct {
+  const void *facade;
+  void *user_data;
+} CAsrBackendFacade;
+
+typedef struct {
+  const void *facade;
+  void *user_data;
+} CAsrFacade;
+
+typedef struct {
+  const void *facade;
+  void *user_data;
+} CAudioServerBackendFacade;
+
+typedef struct {
+  const void *facade;
+  void *user_data;
+} CAudioServerFacade;
+
+typedef struct {
+  const void *facade;
+  void *user_data;
+} CDialogueBackendFacade;
+
 typedef struct {
   const void *facade;
   void *user_data;
 } CDialogueFacade;
 
+typedef struct {
+  const void *facade;
+  void *user_data;
+} CHotwordBackendFacade;
+
+typedef struct {
+  const void *facade;
+  void *user_data;
+} CHotwordFacade;
+
+typedef struct {
+  const void *facade;
+  void *user_data;
+} CInjectionBackendFacade;
+
 typedef struct {
   const void *facade;
   void *user_data;
 } CInjectionFacade;
 
+typedef struct {
+  const void *facade;
+  void *user_data;
+} CNluBackendFacade;
+
+typedef struct {
+  const void *facade;
+  void *user_data;
+} CNluFacade;
+
+typedef struct {
+  const void *facade;
+  void *user_data;
+} CSoundFeedbackBackendFacade;
+
 typedef struct {
   const void *facade;
   void *user_data;
 } CSoundFeedbackFacade;
 
+typedef struct {
+  const void *facade;
+  void *user_data;
+} CTtsBackendFacade;
+
 typedef struct {
   const void *facade;
   void *user_data;
 } CTtsFacade;
 
+typedef struct {
+  const void *facade;
+  void *user_data;
+} CVoiceActivityBackendFacade;
+
+typedef struct {
+  const void *facade;
+  void *user_data;
+} CVoiceActivityFacade;
+
 /**
  * A struct representing the configuration of the MQTT client
  */
@@ -88,8 +167,470 @@ typedef struct {
   unsigned char tls_disable_root_store;
 } CMqttOptions;
 
+SNIPS_RESULT hermes_asr_backend_publish_component_loaded_json(const CAsrBackendFacade *facade,
+                                                              const char *message);
+
+SNIPS_RESULT hermes_asr_backend_publish_error_json(const CAsrBackendFacade *facade,
+                                                   const char *message);
+
+SNIPS_RESULT hermes_asr_backend_publish_partial_text_captured_json(const CAsrBackendFacade *facade,
+                                                                   const char *message);
+
+/**
+ * Deprecated, use `hermes_asr_backend_subscribe_start_listening_json`
+ */
+SNIPS_RESULT hermes_asr_backend_publish_start_listening_json(const CAsrBackendFacade *facade,
+                                                             void (*handler)(const char*, void*));
+
+/**
+ * Deprecated, use `hermes_asr_backend_subscribe_stop_listening_json`
+ */
+SNIPS_RESULT hermes_asr_backend_publish_stop_listening_json(const CAsrBackendFacade *facade,
+                                                            void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_asr_backend_publish_text_captured_json(const CAsrBackendFacade *facade,
+                                                           const char *message);
+
+SNIPS_RESULT hermes_asr_backend_publish_version_json(const CAsrBackendFacade *facade,
+                                                     const char *message);
+
+SNIPS_RESULT hermes_asr_backend_subscribe_component_reload_json(const CAsrBackendFacade *facade,
+                                                                void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_asr_backend_subscribe_component_reload_json_with_subscription(const CAsrBackendFacade *facade,
+                                                                                  void (*handler)(const char*, void*),
+                                                                                  const CSubscription **subscription);
+
+/**
+ * Deprecated, use `hermes_asr_backend_publish_partial_text_captured_json`
+ */
+SNIPS_RESULT hermes_asr_backend_subscribe_partial_text_captured_json(const CAsrBackendFacade *facade,
+                                                                     const char *message);
+
+SNIPS_RESULT hermes_asr_backend_subscribe_start_listening_for_site_json(const CAsrBackendFacade *facade,
+                                                                        const char *site_id,
+                                                                        void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_asr_backend_subscribe_start_listening_for_site_json_with_subscription(const CAsrBackendFacade *facade,
+                                                                                          const char *site_id,
+                                                                                          void (*handler)(const char*, void*),
+                                                                                          const CSubscription **subscription);
+
+SNIPS_RESULT hermes_asr_backend_subscribe_start_listening_json(const CAsrBackendFacade *facade,
+                                                               void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_asr_backend_subscribe_start_listening_json_with_subscription(const CAsrBackendFacade *facade,
+                                                                                 void (*handler)(const char*, void*),
+                                                                                 const CSubscription **subscription);
+
+SNIPS_RESULT hermes_asr_backend_subscribe_stop_listening_for_site_json(const CAsrBackendFacade *facade,
+                                                                       const char *site_id,
+                                                                       void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_asr_backend_subscribe_stop_listening_for_site_json_with_subscription(const CAsrBackendFacade *facade,
+                                                                                         const char *site_id,
+                                                                                         void (*handler)(const char*, void*),
+                                                                                         const CSubscription **subscription);
+
+SNIPS_RESULT hermes_asr_backend_subscribe_stop_listening_json(const CAsrBackendFacade *facade,
+                                                              void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_asr_backend_subscribe_stop_listening_json_with_subscription(const CAsrBackendFacade *facade,
+                                                                                void (*handler)(const char*, void*),
+                                                                                const CSubscription **subscription);
+
+/**
+ * Deprecated, use `hermes_asr_backend_publish_text_captured_json`
+ */
+SNIPS_RESULT hermes_asr_backend_subscribe_text_captured_json(const CAsrBackendFacade *facade,
+                                                             const char *message);
+
+SNIPS_RESULT hermes_asr_backend_subscribe_toggle_off_json(const CAsrBackendFacade *facade,
+                                                          void (*handler)(void*));
+
+SNIPS_RESULT hermes_asr_backend_subscribe_toggle_off_json_with_subscription(const CAsrBackendFacade *facade,
+                                                                            void (*handler)(void*),
+                                                                            const CSubscription **subscription);
+
+SNIPS_RESULT hermes_asr_backend_subscribe_toggle_on_json(const CAsrBackendFacade *facade,
+                                                         void (*handler)(void*));
+
+SNIPS_RESULT hermes_asr_backend_subscribe_toggle_on_json_with_subscription(const CAsrBackendFacade *facade,
+                                                                           void (*handler)(void*),
+                                                                           const CSubscription **subscription);
+
+SNIPS_RESULT hermes_asr_backend_subscribe_version_request_json(const CAsrBackendFacade *facade,
+                                                               void (*handler)(void*));
+
+SNIPS_RESULT hermes_asr_backend_subscribe_version_request_json_with_subscription(const CAsrBackendFacade *facade,
+                                                                                 void (*handler)(void*),
+                                                                                 const CSubscription **subscription);
+
+SNIPS_RESULT hermes_asr_publish_component_reload_json(const CAsrFacade *facade,
+                                                      const char *message);
+
+SNIPS_RESULT hermes_asr_publish_start_listening_json(const CAsrFacade *facade, const char *message);
+
+SNIPS_RESULT hermes_asr_publish_stop_listening_json(const CAsrFacade *facade, const char *message);
+
+SNIPS_RESULT hermes_asr_publish_toggle_off_json(const CAsrFacade *facade);
+
+SNIPS_RESULT hermes_asr_publish_toggle_on_json(const CAsrFacade *facade);
+
+SNIPS_RESULT hermes_asr_publish_version_request_json(const CAsrFacade *facade);
+
+SNIPS_RESULT hermes_asr_subscribe_component_loaded_json(const CAsrFacade *facade,
+                                                        void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_asr_subscribe_component_loaded_json_with_subscription(const CAsrFacade *facade,
+                                                                          void (*handler)(const char*, void*),
+                                                                          const CSubscription **subscription);
+
+SNIPS_RESULT hermes_asr_subscribe_error_json(const CAsrFacade *facade,
+                                             void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_asr_subscribe_error_json_with_subscription(const CAsrFacade *facade,
+                                                               void (*handler)(const char*, void*),
+                                                               const CSubscription **subscription);
+
+SNIPS_RESULT hermes_asr_subscribe_partial_text_captured_for_site_json(const CAsrFacade *facade,
+                                                                      const char *site_id,
+                                                                      void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_asr_subscribe_partial_text_captured_for_site_json_with_subscription(const CAsrFacade *facade,
+                                                                                        const char *site_id,
+                                                                                        void (*handler)(const char*, void*),
+                                                                                        const CSubscription **subscription);
+
+SNIPS_RESULT hermes_asr_subscribe_partial_text_captured_json(const CAsrFacade *facade,
+                                                             void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_asr_subscribe_partial_text_captured_json_with_subscription(const CAsrFacade *facade,
+                                                                               void (*handler)(const char*, void*),
+                                                                               const CSubscription **subscription);
+
+SNIPS_RESULT hermes_asr_subscribe_text_captured_for_site_json(const CAsrFacade *facade,
+                                                              const char *site_id,
+                                                              void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_asr_subscribe_text_captured_for_site_json_with_subscription(const CAsrFacade *facade,
+                                                                                const char *site_id,
+                                                                                void (*handler)(const char*, void*),
+                                                                                const CSubscription **subscription);
+
+SNIPS_RESULT hermes_asr_subscribe_text_captured_json(const CAsrFacade *facade,
+                                                     void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_asr_subscribe_text_captured_json_with_subscription(const CAsrFacade *facade,
+                                                                       void (*handler)(const char*, void*),
+                                                                       const CSubscription **subscription);
+
+SNIPS_RESULT hermes_asr_subscribe_version_json(const CAsrFacade *facade,
+                                               void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_asr_subscribe_version_json_with_subscription(const CAsrFacade *facade,
+                                                                 void (*handler)(const char*, void*),
+                                                                 const CSubscription **subscription);
+
+SNIPS_RESULT hermes_audio_server_backend_publish_audio_frame_json(const CAudioServerBackendFacade *facade,
+                                                                  const char *message);
+
+SNIPS_RESULT hermes_audio_server_backend_publish_component_loaded_json(const CAudioServerBackendFacade *facade,
+                                                                       const char *id,
+                                                                       const char *message);
+
+SNIPS_RESULT hermes_audio_server_backend_publish_error_json(const CAudioServerBackendFacade *facade,
+                                                            const char *id,
+                                                            const char *message);
+
+SNIPS_RESULT hermes_audio_server_backend_publish_play_finished_json(const CAudioServerBackendFacade *facade,
+                                                                    const char *message);
+
+SNIPS_RESULT hermes_audio_server_backend_publish_replay_response_json(const CAudioServerBackendFacade *facade,
+                                                                      const char *message);
+
+SNIPS_RESULT hermes_audio_server_backend_publish_stream_finished_json(const CAudioServerBackendFacade *facade,
+                                                                      const char *message);
+
+SNIPS_RESULT hermes_audio_server_backend_publish_version_json(const CAudioServerBackendFacade *facade,
+                                                              const char *id,
+                                                              const char *message);
+
+SNIPS_RESULT hermes_audio_server_backend_subscribe_all_play_bytes_json(const CAudioServerBackendFacade *facade,
+                                                                       void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_audio_server_backend_subscribe_all_play_bytes_json_with_subscription(const CAudioServerBackendFacade *facade,
+                                                                                         void (*handler)(const char*, void*),
+                                                                                         const CSubscription **subscription);
+
+SNIPS_RESULT hermes_audio_server_backend_subscribe_all_stream_bytes_json(const CAudioServerBackendFacade *facade,
+                                                                         void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_audio_server_backend_subscribe_all_stream_bytes_json_with_subscription(const CAudioServerBackendFacade *facade,
+                                                                                           void (*handler)(const char*, void*),
+                                                                                           const CSubscription **subscription);
+
+SNIPS_RESULT hermes_audio_server_backend_subscribe_all_toggle_off_json(const CAudioServerBackendFacade *facade,
+                                                                       void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_audio_server_backend_subscribe_all_toggle_off_json_with_subscription(const CAudioServerBackendFacade *facade,
+                                                                                         void (*handler)(const char*, void*),
+                                                                                         const CSubscription **subscription);
+
+SNIPS_RESULT hermes_audio_server_backend_subscribe_all_toggle_on_json(const CAudioServerBackendFacade *facade,
+                                                                      void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_audio_server_backend_subscribe_all_toggle_on_json_with_subscription(const CAudioServerBackendFacade *facade,
+                                                                                        void (*handler)(const char*, void*),
+                                                                                        const CSubscription **subscription);
+
+SNIPS_RESULT hermes_audio_server_backend_subscribe_play_bytes_json(const CAudioServerBackendFacade *facade,
+                                                                   const char *site_id,
+                                                                   void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_audio_server_backend_subscribe_play_bytes_json_with_subscription(const CAudioServerBackendFacade *facade,
+                                                                                     const char *site_id,
+                                                                                     void (*handler)(const char*, void*),
+                                                                                     const CSubscription **subscription);
+
+SNIPS_RESULT hermes_audio_server_backend_subscribe_replay_request_json(const CAudioServerBackendFacade *facade,
+                                                                       const char *site_id,
+                                                                       void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_audio_server_backend_subscribe_replay_request_json_with_subscription(const CAudioServerBackendFacade *facade,
+                                                                                         const char *site_id,
+                                                                                         void (*handler)(const char*, void*),
+                                                                                         const CSubscription **subscription);
+
+SNIPS_RESULT hermes_audio_server_backend_subscribe_stream_bytes_json(const CAudioServerBackendFacade *facade,
+                                                                     const char *site_id,
+                                                                     void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_audio_server_backend_subscribe_stream_bytes_json_with_subscription(const CAudioServerBackendFacade *facade,
+                                                                                       const char *site_id,
+                                                                                       void (*handler)(const char*, void*),
+                                                                                       const CSubscription **subscription);
+
+SNIPS_RESULT hermes_audio_server_backend_subscribe_toggle_off_json(const CAudioServerBackendFacade *facade,
+                                                                   const char *site_id,
+                                                                   void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_audio_server_backend_subscribe_toggle_off_json_with_subscription(const CAudioServerBackendFacade *facade,
+                                                                                     const char *site_id,
+                                                                                     void (*handler)(const char*, void*),
+                                                                                     const CSubscription **subscription);
+
+SNIPS_RESULT hermes_audio_server_backend_subscribe_toggle_on_json(const CAudioServerBackendFacade *facade,
+                                                                  const char *site_id,
+                                                                  void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_audio_server_backend_subscribe_toggle_on_json_with_subscription(const CAudioServerBackendFacade *facade,
+                                                                                    const char *site_id,
+                                                                                    void (*handler)(const char*, void*),
+                                                                                    const CSubscription **subscription);
+
+SNIPS_RESULT hermes_audio_server_backend_subscribe_version_request_json(const CAudioServerBackendFacade *facade,
+                                                                        const char *id,
+                                                                        void (*handler)(void*));
+
+SNIPS_RESULT hermes_audio_server_backend_subscribe_version_request_json_with_subscription(const CAudioServerBackendFacade *facade,
+                                                                                          const char *id,
+                                                                                          void (*handler)(void*),
+                                                                                          const CSubscription **subscription);
+
+SNIPS_RESULT hermes_audio_server_publish_play_bytes_json(const CAudioServerFacade *facade,
+                                                         const char *message);
+
+SNIPS_RESULT hermes_audio_server_publish_replay_request_json(const CAudioServerFacade *facade,
+                                                             const char *message);
+
+SNIPS_RESULT hermes_audio_server_publish_stream_bytes_json(const CAudioServerFacade *facade,
+                                                           const char *message);
+
+SNIPS_RESULT hermes_audio_server_publish_toggle_off_json(const CAudioServerFacade *facade,
+                                                         const char *message);
+
+SNIPS_RESULT hermes_audio_server_publish_toggle_on_json(const CAudioServerFacade *facade,
+                                                        const char *message);
+
+SNIPS_RESULT hermes_audio_server_publish_version_request_json(const CAudioServerFacade *facade,
+                                                              const char *id);
+
+SNIPS_RESULT hermes_audio_server_subscribe_all_audio_frame_json(const CAudioServerFacade *facade,
+                                                                void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_audio_server_subscribe_all_audio_frame_json_with_subscription(const CAudioServerFacade *facade,
+                                                                                  void (*handler)(const char*, void*),
+                                                                                  const CSubscription **subscription);
+
+SNIPS_RESULT hermes_audio_server_subscribe_all_component_loaded_json(const CAudioServerFacade *facade,
+                                                                     void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_audio_server_subscribe_all_component_loaded_json_with_subscription(const CAudioServerFacade *facade,
+                                                                                       void (*handler)(const char*, void*),
+                                                                                       const CSubscription **subscription);
+
+SNIPS_RESULT hermes_audio_server_subscribe_all_error_json(const CAudioServerFacade *facade,
+                                                          void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_audio_server_subscribe_all_error_json_with_subscription(const CAudioServerFacade *facade,
+                                                                            void (*handler)(const char*, void*),
+                                                                            const CSubscription **subscription);
+
+SNIPS_RESULT hermes_audio_server_subscribe_all_play_finished_json(const CAudioServerFacade *facade,
+                                                                  void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_audio_server_subscribe_all_play_finished_json_with_subscription(const CAudioServerFacade *facade,
+                                                                                    void (*handler)(const char*, void*),
+                                                                                    const CSubscription **subscription);
+
+SNIPS_RESULT hermes_audio_server_subscribe_all_stream_finished_json(const CAudioServerFacade *facade,
+                                                                    void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_audio_server_subscribe_all_stream_finished_json_with_subscription(const CAudioServerFacade *facade,
+                                                                                      void (*handler)(const char*, void*),
+                                                                                      const CSubscription **subscription);
+
+SNIPS_RESULT hermes_audio_server_subscribe_audio_frame_json(const CAudioServerFacade *facade,
+                                                            const char *site_id,
+                                                            void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_audio_server_subscribe_audio_frame_json_with_subscription(const CAudioServerFacade *facade,
+                                                                              const char *site_id,
+                                                                              void (*handler)(const char*, void*),
+                                                                              const CSubscription **subscription);
+
+SNIPS_RESULT hermes_audio_server_subscribe_component_loaded_json(const CAudioServerFacade *facade,
+                                                                 const char *id,
+                                                                 void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_audio_server_subscribe_component_loaded_json_with_subscription(const CAudioServerFacade *facade,
+                                                                                   const char *id,
+                                                                                   void (*handler)(const char*, void*),
+                                                                                   const CSubscription **subscription);
+
+SNIPS_RESULT hermes_audio_server_subscribe_error_json(const CAudioServerFacade *facade,
+                                                      const char *id,
+                                                      void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_audio_server_subscribe_error_json_with_subscription(const CAudioServerFacade *facade,
+                                                                        const char *id,
+                                                                        void (*handler)(const char*, void*),
+                                                                        const CSubscription **subscription);
+
+SNIPS_RESULT hermes_audio_server_subscribe_play_finished_json(const CAudioServerFacade *facade,
+                                                              const char *site_id,
+                                                              void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_audio_server_subscribe_play_finished_json_with_subscription(const CAudioServerFacade *facade,
+                                                                                const char *site_id,
+                                                                                void (*handler)(const char*, void*),
+                                                                                const CSubscription **subscription);
+
+SNIPS_RESULT hermes_audio_server_subscribe_replay_response_json(const CAudioServerFacade *facade,
+                                                                const char *site_id,
+                                                                void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_audio_server_subscribe_replay_response_json_with_subscription(const CAudioServerFacade *facade,
+                                                                                  const char *site_id,
+                                                                                  void (*handler)(const char*, void*),
+                                                                                  const CSubscription **subscription);
+
+SNIPS_RESULT hermes_audio_server_subscribe_stream_finished_json(const CAudioServerFacade *facade,
+                                                                const char *site_id,
+                                                                void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_audio_server_subscribe_stream_finished_json_with_subscription(const CAudioServerFacade *facade,
+                                                                                  const char *site_id,
+                                                                                  void (*handler)(const char*, void*),
+                                                                                  const CSubscription **subscription);
+
+SNIPS_RESULT hermes_audio_server_subscribe_version_json(const CAudioServerFacade *facade,
+                                                        const char *id,
+                                                        void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_audio_server_subscribe_version_json_with_subscription(const CAudioServerFacade *facade,
+                                                                          const char *id,
+                                                                          void (*handler)(const char*, void*),
+                                                                          const CSubscription **subscription);
+
+SNIPS_RESULT hermes_destroy_in_process_protocol_handler(CProtocolHandler *handler);
+
 SNIPS_RESULT hermes_destroy_mqtt_protocol_handler(CProtocolHandler *handler);
 
+SNIPS_RESULT hermes_dialogue_backend_publish_component_loaded_json(const CDialogueBackendFacade *facade,
+                                                                   const char *message);
+
+SNIPS_RESULT hermes_dialogue_backend_publish_error_json(const CDialogueBackendFacade *facade,
+                                                        const char *message);
+
+SNIPS_RESULT hermes_dialogue_backend_publish_intent_json(const CDialogueBackendFacade *facade,
+                                                         const char *message);
+
+SNIPS_RESULT hermes_dialogue_backend_publish_intent_not_recognized_json(const CDialogueBackendFacade *facade,
+                                                                        const char *message);
+
+SNIPS_RESULT hermes_dialogue_backend_publish_session_ended_json(const CDialogueBackendFacade *facade,
+                                                                const char *message);
+
+SNIPS_RESULT hermes_dialogue_backend_publish_session_queued_json(const CDialogueBackendFacade *facade,
+                                                                 const char *message);
+
+SNIPS_RESULT hermes_dialogue_backend_publish_session_started_json(const CDialogueBackendFacade *facade,
+                                                                  const char *message);
+
+SNIPS_RESULT hermes_dialogue_backend_publish_version_json(const CDialogueBackendFacade *facade,
+                                                          const char *message);
+
+SNIPS_RESULT hermes_dialogue_backend_subscribe_configure_json(const CDialogueBackendFacade *facade,
+                                                              void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_dialogue_backend_subscribe_configure_json_with_subscription(const CDialogueBackendFacade *facade,
+                                                                                void (*handler)(const char*, void*),
+                                                                                const CSubscription **subscription);
+
+SNIPS_RESULT hermes_dialogue_backend_subscribe_continue_session_json(const CDialogueBackendFacade *facade,
+                                                                     void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_dialogue_backend_subscribe_continue_session_json_with_subscription(const CDialogueBackendFacade *facade,
+                                                                                       void (*handler)(const char*, void*),
+                                                                                       const CSubscription **subscription);
+
+SNIPS_RESULT hermes_dialogue_backend_subscribe_end_session_json(const CDialogueBackendFacade *facade,
+                                                                void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_dialogue_backend_subscribe_end_session_json_with_subscription(const CDialogueBackendFacade *facade,
+                                                                                  void (*handler)(const char*, void*),
+                                                                                  const CSubscription **subscription);
+
+SNIPS_RESULT hermes_dialogue_backend_subscribe_start_session_json(const CDialogueBackendFacade *facade,
+                                                                  void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_dialogue_backend_subscribe_start_session_json_with_subscription(const CDialogueBackendFacade *facade,
+                                                                                    void (*handler)(const char*, void*),
+                                                                                    const CSubscription **subscription);
+
+SNIPS_RESULT hermes_dialogue_backend_subscribe_toggle_off_json(const CDialogueBackendFacade *facade,
+                                                               void (*handler)(void*));
+
+SNIPS_RESULT hermes_dialogue_backend_subscribe_toggle_off_json_with_subscription(const CDialogueBackendFacade *facade,
+                                                                                 void (*handler)(void*),
+                                                                                 const CSubscription **subscription);
+
+SNIPS_RESULT hermes_dialogue_backend_subscribe_toggle_on_json(const CDialogueBackendFacade *facade,
+                                                              void (*handler)(void*));
+
+SNIPS_RESULT hermes_dialogue_backend_subscribe_toggle_on_json_with_subscription(const CDialogueBackendFacade *facade,
+                                                                                void (*handler)(void*),
+                                                                                const CSubscription **subscription);
+
+SNIPS_RESULT hermes_dialogue_backend_subscribe_version_request_json(const CDialogueBackendFacade *facade,
+                                                                    void (*handler)(void*));
+
+SNIPS_RESULT hermes_dialogue_backend_subscribe_version_request_json_with_subscription(const CDialogueBackendFacade *facade,
+                                                                                      void (*handler)(void*),
+                                                                                      const CSubscription **subscription);
+
 SNIPS_RESULT hermes_dialogue_publish_configure_json(const CDialogueFacade *facade,
                                                     const char *message);
 
@@ -102,33 +643,156 @@ SNIPS_RESULT hermes_dialogue_publish_end_session_json(const CDialogueFacade *fac
 SNIPS_RESULT hermes_dialogue_publish_start_session_json(const CDialogueFacade *facade,
                                                         const char *message);
 
+SNIPS_RESULT hermes_dialogue_publish_toggle_off_json(const CDialogueFacade *facade);
+
+SNIPS_RESULT hermes_dialogue_publish_toggle_on_json(const CDialogueFacade *facade);
+
+SNIPS_RESULT hermes_dialogue_publish_version_request_json(const CDialogueFacade *facade);
+
+SNIPS_RESULT hermes_dialogue_subscribe_component_loaded_json(const CDialogueFacade *facade,
+                                                             void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_dialogue_subscribe_component_loaded_json_with_subscription(const CDialogueFacade *facade,
+                                                                               void (*handler)(const char*, void*),
+                                                                               const CSubscription **subscription);
+
+SNIPS_RESULT hermes_dialogue_subscribe_error_json(const CDialogueFacade *facade,
+                                                  void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_dialogue_subscribe_error_json_with_subscription(const CDialogueFacade *facade,
+                                                                    void (*handler)(const char*, void*),
+                                                                    const CSubscription **subscription);
+
 SNIPS_RESULT hermes_dialogue_subscribe_intent_json(const CDialogueFacade *facade,
                                                    const char *intent_name,
                                                    void (*handler)(const char*, void*));
 
+SNIPS_RESULT hermes_dialogue_subscribe_intent_json_with_subscription(const CDialogueFacade *facade,
+                                                                     const char *intent_name,
+                                                                     void (*handler)(const char*, void*),
+                                                                     const CSubscription **subscription);
+
+SNIPS_RESULT hermes_dialogue_subscribe_intent_not_recognized_for_site_json(const CDialogueFacade *facade,
+                                                                           const char *site_id,
+                                                                           void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_dialogue_subscribe_intent_not_recognized_for_site_json_with_subscription(const CDialogueFacade *facade,
+                                                                                             const char *site_id,
+                                                                                             void (*handler)(const char*, void*),
+                                                                                             const CSubscription **subscription);
+
 SNIPS_RESULT hermes_dialogue_subscribe_intent_not_recognized_json(const CDialogueFacade *facade,
                                                                   void (*handler)(const char*, void*));
 
+SNIPS_RESULT hermes_dialogue_subscribe_intent_not_recognized_json_with_subscription(const CDialogueFacade *facade,
+                                                                                    void (*handler)(const char*, void*),
+                                                                                    const CSubscription **subscription);
+
 SNIPS_RESULT hermes_dialogue_subscribe_intents_json(const CDialogueFacade *facade,
                                                     void (*handler)(const char*, void*));
 
+SNIPS_RESULT hermes_dialogue_subscribe_intents_json_with_subscription(const CDialogueFacade *facade,
+                                                                      void (*handler)(const char*, void*),
+                                                                      const CSubscription **subscription);
+
+SNIPS_RESULT hermes_dialogue_subscribe_session_ended_for_site_json(const CDialogueFacade *facade,
+                                                                   const char *site_id,
+                                                                   void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_dialogue_subscribe_session_ended_for_site_json_with_subscription(const CDialogueFacade *facade,
+                                                                                     const char *site_id,
+                                                                                     void (*handler)(const char*, void*),
+                                                                                     const CSubscription **subscription);
+
 SNIPS_RESULT hermes_dialogue_subscribe_session_ended_json(const CDialogueFacade *facade,
                                                           void (*handler)(const char*, void*));
 
+SNIPS_RESULT hermes_dialogue_subscribe_session_ended_json_with_subscription(const CDialogueFacade *facade,
+                                                                            void (*handler)(const char*, void*),
+                                                                            const CSubscription **subscription);
+
+SNIPS_RESULT hermes_dialogue_subscribe_session_queued_for_site_json(const CDialogueFacade *facade,
+                                                                    const char *site_id,
+                                                                    void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_dialogue_subscribe_session_queued_for_site_json_with_subscription(const CDialogueFacade *facade,
+                                                                                      const char *site_id,
+                                                                                      void (*handler)(const char*, void*),
+                                                                                      const CSubscription **subscription);
+
 SNIPS_RESULT hermes_dialogue_subscribe_session_queued_json(const CDialogueFacade *facade,
                                                            void (*handler)(const char*, void*));
 
+SNIPS_RESULT hermes_dialogue_subscribe_session_queued_json_with_subscription(const CDialogueFacade *facade,
+                                                                             void (*handler)(const char*, void*),
+                                                                             const CSubscription **subscription);
+
+SNIPS_RESULT hermes_dialogue_subscribe_session_started_for_site_json(const CDialogueFacade *facade,
+                                                                     const char *site_id,
+                                                                     void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_dialogue_subscribe_session_started_for_site_json_with_subscription(const CDialogueFacade *facade,
+                                                                                       const char *site_id,
+                                                                                       void (*handler)(const char*, void*),
+                                                                                       const CSubscription **subscription);
+
 SNIPS_RESULT hermes_dialogue_subscribe_session_started_json(const CDialogueFacade *facade,
                                                             void (*handler)(const char*, void*));
 
+SNIPS_RESULT hermes_dialogue_subscribe_session_started_json_with_subscription(const CDialogueFacade *facade,
+                                                                              void (*handler)(const char*, void*),
+                                                                              const CSubscription **subscription);
+
+SNIPS_RESULT hermes_dialogue_subscribe_version_json(const CDialogueFacade *facade,
+                                                    void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_dialogue_subscribe_version_json_with_subscription(const CDialogueFacade *facade,
+                                                                      void (*handler)(const char*, void*),
+                                                                      const CSubscription **subscription);
+
+/**
+ * Call the queued callbacks of all the handlers, without waiting
+ */
+SNIPS_RESULT hermes_dispatch_pending(void);
+
+SNIPS_RESULT hermes_drop_asr_backend_facade(const CAsrBackendFacade *cstruct);
+
+SNIPS_RESULT hermes_drop_asr_facade(const CAsrFacade *cstruct);
+
+SNIPS_RESULT hermes_drop_audio_server_backend_facade(const CAudioServerBackendFacade *cstruct);
+
+SNIPS_RESULT hermes_drop_audio_server_facade(const CAudioServerFacade *cstruct);
+
+SNIPS_RESULT hermes_drop_dialogue_backend_facade(const CDialogueBackendFacade *cstruct);
+
 SNIPS_RESULT hermes_drop_dialogue_facade(const CDialogueFacade *cstruct);
 
+SNIPS_RESULT hermes_drop_hotword_backend_facade(const CHotwordBackendFacade *cstruct);
+
+SNIPS_RESULT hermes_drop_hotword_facade(const CHotwordFacade *cstruct);
+
+SNIPS_RESULT hermes_drop_injection_backend_facade(const CInjectionBackendFacade *cstruct);
+
 SNIPS_RESULT hermes_drop_injection_facade(const CInjectionFacade *cstruct);
 
+SNIPS_RESULT hermes_drop_nlu_backend_facade(const CNluBackendFacade *cstruct);
+
+SNIPS_RESULT hermes_drop_nlu_facade(const CNluFacade *cstruct);
+
+SNIPS_RESULT hermes_drop_sound_feedback_backend_facade(const CSoundFeedbackBackendFacade *cstruct);
+
 SNIPS_RESULT hermes_drop_sound_feedback_facade(const CSoundFeedbackFacade *cstruct);
 
+SNIPS_RESULT hermes_drop_subscription(const CSubscription *cstruct);
+
+SNIPS_RESULT hermes_drop_tts_backend_facade(const CTtsBackendFacade *cstruct);
+
 SNIPS_RESULT hermes_drop_tts_facade(const CTtsFacade *cstruct);
 
+SNIPS_RESULT hermes_drop_voice_activity_backend_facade(const CVoiceActivityBackendFacade *cstruct);
+
+SNIPS_RESULT hermes_drop_voice_activity_facade(const CVoiceActivityFacade *cstruct);
+
 SNIPS_RESULT hermes_enable_debug_logs(void);
 
 /**
@@ -137,6 +801,175 @@ SNIPS_RESULT hermes_enable_debug_logs(void);
  */
 SNIPS_RESULT hermes_get_last_error(const char **error);
 
+SNIPS_RESULT hermes_hotword_backend_publish_component_loaded_json(const CHotwordBackendFacade *facade,
+                                                                  const char *id,
+                                                                  const char *message);
+
+SNIPS_RESULT hermes_hotword_backend_publish_detected_json(const CHotwordBackendFacade *facade,
+                                                          const char *hotword_id,
+                                                          const char *message);
+
+SNIPS_RESULT hermes_hotword_backend_publish_error_json(const CHotwordBackendFacade *facade,
+                                                       const char *id,
+                                                       const char *message);
+
+SNIPS_RESULT hermes_hotword_backend_publish_version_json(const CHotwordBackendFacade *facade,
+                                                         const char *id,
+                                                         const char *message);
+
+SNIPS_RESULT hermes_hotword_backend_subscribe_all_toggle_off_json(const CHotwordBackendFacade *facade,
+                                                                  void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_hotword_backend_subscribe_all_toggle_off_json_with_subscription(const CHotwordBackendFacade *facade,
+                                                                                    void (*handler)(const char*, void*),
+                                                                                    const CSubscription **subscription);
+
+SNIPS_RESULT hermes_hotword_backend_subscribe_all_toggle_on_json(const CHotwordBackendFacade *facade,
+                                                                 void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_hotword_backend_subscribe_all_toggle_on_json_with_subscription(const CHotwordBackendFacade *facade,
+                                                                                   void (*handler)(const char*, void*),
+                                                                                   const CSubscription **subscription);
+
+SNIPS_RESULT hermes_hotword_backend_subscribe_toggle_off_json(const CHotwordBackendFacade *facade,
+                                                              const char *site_id,
+                                                              void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_hotword_backend_subscribe_toggle_off_json_with_subscription(const CHotwordBackendFacade *facade,
+                                                                                const char *site_id,
+                                                                                void (*handler)(const char*, void*),
+                                                                                const CSubscription **subscription);
+
+SNIPS_RESULT hermes_hotword_backend_subscribe_toggle_on_json(const CHotwordBackendFacade *facade,
+                                                             const char *site_id,
+                                                             void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_hotword_backend_subscribe_toggle_on_json_with_subscription(const CHotwordBackendFacade *facade,
+                                                                               const char *site_id,
+                                                                               void (*handler)(const char*, void*),
+                                                                               const CSubscription **subscription);
+
+SNIPS_RESULT hermes_hotword_backend_subscribe_version_request_json(const CHotwordBackendFacade *facade,
+                                                                   const char *id,
+                                                                   void (*handler)(void*));
+
+SNIPS_RESULT hermes_hotword_backend_subscribe_version_request_json_with_subscription(const CHotwordBackendFacade *facade,
+                                                                                     const char *id,
+                                                                                     void (*handler)(void*),
+                                                                                     const CSubscription **subscription);
+
+SNIPS_RESULT hermes_hotword_publish_toggle_off_json(const CHotwordFacade *facade,
+                                                    const char *message);
+
+SNIPS_RESULT hermes_hotword_publish_toggle_on_json(const CHotwordFacade *facade,
+                                                   const char *message);
+
+SNIPS_RESULT hermes_hotword_publish_version_request_json(const CHotwordFacade *facade,
+                                                         const char *id);
+
+SNIPS_RESULT hermes_hotword_subscribe_all_component_loaded_json(const CHotwordFacade *facade,
+                                                                void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_hotword_subscribe_all_component_loaded_json_with_subscription(const CHotwordFacade *facade,
+                                                                                  void (*handler)(const char*, void*),
+                                                                                  const CSubscription **subscription);
+
+SNIPS_RESULT hermes_hotword_subscribe_all_detected_json(const CHotwordFacade *facade,
+                                                        void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_hotword_subscribe_all_detected_json_with_subscription(const CHotwordFacade *facade,
+                                                                          void (*handler)(const char*, void*),
+                                                                          const CSubscription **subscription);
+
+SNIPS_RESULT hermes_hotword_subscribe_all_error_json(const CHotwordFacade *facade,
+                                                     void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_hotword_subscribe_all_error_json_with_subscription(const CHotwordFacade *facade,
+                                                                       void (*handler)(const char*, void*),
+                                                                       const CSubscription **subscription);
+
+SNIPS_RESULT hermes_hotword_subscribe_component_loaded_json(const CHotwordFacade *facade,
+                                                            const char *id,
+                                                            void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_hotword_subscribe_component_loaded_json_with_subscription(const CHotwordFacade *facade,
+                                                                              const char *id,
+                                                                              void (*handler)(const char*, void*),
+                                                                              const CSubscription **subscription);
+
+SNIPS_RESULT hermes_hotword_subscribe_detected_json(const CHotwordFacade *facade,
+                                                    const char *hotword_id,
+                                                    void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_hotword_subscribe_detected_json_with_subscription(const CHotwordFacade *facade,
+                                                                      const char *hotword_id,
+                                                                      void (*handler)(const char*, void*),
+                                                                      const CSubscription **subscription);
+
+SNIPS_RESULT hermes_hotword_subscribe_error_json(const CHotwordFacade *facade,
+                                                 const char *id,
+                                                 void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_hotword_subscribe_error_json_with_subscription(const CHotwordFacade *facade,
+                                                                   const char *id,
+                                                                   void (*handler)(const char*, void*),
+                                                                   const CSubscription **subscription);
+
+SNIPS_RESULT hermes_hotword_subscribe_version_json(const CHotwordFacade *facade,
+                                                   const char *id,
+                                                   void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_hotword_subscribe_version_json_with_subscription(const CHotwordFacade *facade,
+                                                                     const char *id,
+                                                                     void (*handler)(const char*, void*),
+                                                                     const CSubscription **subscription);
+
+SNIPS_RESULT hermes_injection_backend_publish_component_loaded_json(const CInjectionBackendFacade *facade,
+                                                                    const char *message);
+
+SNIPS_RESULT hermes_injection_backend_publish_error_json(const CInjectionBackendFacade *facade,
+                                                         const char *message);
+
+SNIPS_RESULT hermes_injection_backend_publish_injection_complete_json(const CInjectionBackendFacade *facade,
+                                                                      const char *message);
+
+SNIPS_RESULT hermes_injection_backend_publish_injection_reset_complete_json(const CInjectionBackendFacade *facade,
+                                                                            const char *message);
+
+SNIPS_RESULT hermes_injection_backend_publish_injection_status_json(const CInjectionBackendFacade *facade,
+                                                                    const char *message);
+
+SNIPS_RESULT hermes_injection_backend_publish_version_json(const CInjectionBackendFacade *facade,
+                                                           const char *message);
+
+SNIPS_RESULT hermes_injection_backend_subscribe_injection_request_json(const CInjectionBackendFacade *facade,
+                                                                       void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_injection_backend_subscribe_injection_request_json_with_subscription(const CInjectionBackendFacade *facade,
+                                                                                         void (*handler)(const char*, void*),
+                                                                                         const CSubscription **subscription);
+
+SNIPS_RESULT hermes_injection_backend_subscribe_injection_reset_request_json(const CInjectionBackendFacade *facade,
+                                                                             void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_injection_backend_subscribe_injection_reset_request_json_with_subscription(const CInjectionBackendFacade *facade,
+                                                                                               void (*handler)(const char*, void*),
+                                                                                               const CSubscription **subscription);
+
+SNIPS_RESULT hermes_injection_backend_subscribe_injection_status_request_json(const CInjectionBackendFacade *facade,
+                                                                              void (*handler)(void*));
+
+SNIPS_RESULT hermes_injection_backend_subscribe_injection_status_request_json_with_subscription(const CInjectionBackendFacade *facade,
+                                                                                                void (*handler)(void*),
+                                                                                                const CSubscription **subscription);
+
+SNIPS_RESULT hermes_injection_backend_subscribe_version_request_json(const CInjectionBackendFacade *facade,
+                                                                     void (*handler)(void*));
+
+SNIPS_RESULT hermes_injection_backend_subscribe_version_request_json_with_subscription(const CInjectionBackendFacade *facade,
+                                                                                       void (*handler)(void*),
+                                                                                       const CSubscription **subscription);
+
 SNIPS_RESULT hermes_injection_publish_injection_request_json(const CInjectionFacade *facade,
                                                              const char *message);
 
@@ -145,21 +978,206 @@ SNIPS_RESULT hermes_injection_publish_injection_reset_request_json(const CInject
 
 SNIPS_RESULT hermes_injection_publish_injection_status_request_json(const CInjectionFacade *facade);
 
+SNIPS_RESULT hermes_injection_publish_version_request_json(const CInjectionFacade *facade);
+
+SNIPS_RESULT hermes_injection_subscribe_component_loaded_json(const CInjectionFacade *facade,
+                                                              void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_injection_subscribe_component_loaded_json_with_subscription(const CInjectionFacade *facade,
+                                                                                void (*handler)(const char*, void*),
+                                                                                const CSubscription **subscription);
+
+SNIPS_RESULT hermes_injection_subscribe_error_json(const CInjectionFacade *facade,
+                                                   void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_injection_subscribe_error_json_with_subscription(const CInjectionFacade *facade,
+                                                                     void (*handler)(const char*, void*),
+                                                                     const CSubscription **subscription);
+
 SNIPS_RESULT hermes_injection_subscribe_injection_complete_json(const CInjectionFacade *facade,
                                                                 void (*handler)(const char*, void*));
 
+SNIPS_RESULT hermes_injection_subscribe_injection_complete_json_with_subscription(const CInjectionFacade *facade,
+                                                                                  void (*handler)(const char*, void*),
+                                                                                  const CSubscription **subscription);
+
 SNIPS_RESULT hermes_injection_subscribe_injection_reset_complete_json(const CInjectionFacade *facade,
                                                                       void (*handler)(const char*, void*));
 
+SNIPS_RESULT hermes_injection_subscribe_injection_reset_complete_json_with_subscription(const CInjectionFacade *facade,
+                                                                                        void (*handler)(const char*, void*),
+                                                                                        const CSubscription **subscription);
+
 SNIPS_RESULT hermes_injection_subscribe_injection_status_json(const CInjectionFacade *facade,
                                                               void (*handler)(const char*, void*));
 
+SNIPS_RESULT hermes_injection_subscribe_injection_status_json_with_subscription(const CInjectionFacade *facade,
+                                                                                void (*handler)(const char*, void*),
+                                                                                const CSubscription **subscription);
+
+SNIPS_RESULT hermes_injection_subscribe_version_json(const CInjectionFacade *facade,
+                                                     void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_injection_subscribe_version_json_with_subscription(const CInjectionFacade *facade,
+                                                                       void (*handler)(const char*, void*),
+                                                                       const CSubscription **subscription);
+
+SNIPS_RESULT hermes_nlu_backend_publish_component_loaded_json(const CNluBackendFacade *facade,
+                                                              const char *message);
+
+SNIPS_RESULT hermes_nlu_backend_publish_error_json(const CNluBackendFacade *facade,
+                                                   const char *message);
+
+SNIPS_RESULT hermes_nlu_backend_publish_intent_not_recognized_json(const CNluBackendFacade *facade,
+                                                                   const char *message);
+
+SNIPS_RESULT hermes_nlu_backend_publish_intent_parsed_json(const CNluBackendFacade *facade,
+                                                           const char *message);
+
+SNIPS_RESULT hermes_nlu_backend_publish_slot_parsed_json(const CNluBackendFacade *facade,
+                                                         const char *message);
+
+SNIPS_RESULT hermes_nlu_backend_publish_version_json(const CNluBackendFacade *facade,
+                                                     const char *message);
+
+SNIPS_RESULT hermes_nlu_backend_subscribe_component_reload_json(const CNluBackendFacade *facade,
+                                                                void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_nlu_backend_subscribe_component_reload_json_with_subscription(const CNluBackendFacade *facade,
+                                                                                  void (*handler)(const char*, void*),
+                                                                                  const CSubscription **subscription);
+
+SNIPS_RESULT hermes_nlu_backend_subscribe_partial_query_json(const CNluBackendFacade *facade,
+                                                             void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_nlu_backend_subscribe_partial_query_json_with_subscription(const CNluBackendFacade *facade,
+                                                                               void (*handler)(const char*, void*),
+                                                                               const CSubscription **subscription);
+
+SNIPS_RESULT hermes_nlu_backend_subscribe_query_json(const CNluBackendFacade *facade,
+                                                     void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_nlu_backend_subscribe_query_json_with_subscription(const CNluBackendFacade *facade,
+                                                                       void (*handler)(const char*, void*),
+                                                                       const CSubscription **subscription);
+
+SNIPS_RESULT hermes_nlu_backend_subscribe_version_request_json(const CNluBackendFacade *facade,
+                                                               void (*handler)(void*));
+
+SNIPS_RESULT hermes_nlu_backend_subscribe_version_request_json_with_subscription(const CNluBackendFacade *facade,
+                                                                                 void (*handler)(void*),
+                                                                                 const CSubscription **subscription);
+
+SNIPS_RESULT hermes_nlu_publish_component_reload_json(const CNluFacade *facade,
+                                                      const char *message);
+
+SNIPS_RESULT hermes_nlu_publish_partial_query_json(const CNluFacade *facade, const char *message);
+
+SNIPS_RESULT hermes_nlu_publish_query_json(const CNluFacade *facade, const char *message);
+
+SNIPS_RESULT hermes_nlu_publish_version_request_json(const CNluFacade *facade);
+
+SNIPS_RESULT hermes_nlu_subscribe_component_loaded_json(const CNluFacade *facade,
+                                                        void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_nlu_subscribe_component_loaded_json_with_subscription(const CNluFacade *facade,
+                                                                          void (*handler)(const char*, void*),
+                                                                          const CSubscription **subscription);
+
+SNIPS_RESULT hermes_nlu_subscribe_error_json(const CNluFacade *facade,
+                                             void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_nlu_subscribe_error_json_with_subscription(const CNluFacade *facade,
+                                                               void (*handler)(const char*, void*),
+                                                               const CSubscription **subscription);
+
+SNIPS_RESULT hermes_nlu_subscribe_intent_not_recognized_json(const CNluFacade *facade,
+                                                             void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_nlu_subscribe_intent_not_recognized_json_with_subscription(const CNluFacade *facade,
+                                                                               void (*handler)(const char*, void*),
+                                                                               const CSubscription **subscription);
+
+SNIPS_RESULT hermes_nlu_subscribe_intent_parsed_json(const CNluFacade *facade,
+                                                     void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_nlu_subscribe_intent_parsed_json_with_subscription(const CNluFacade *facade,
+                                                                       void (*handler)(const char*, void*),
+                                                                       const CSubscription **subscription);
+
+SNIPS_RESULT hermes_nlu_subscribe_slot_parsed_json(const CNluFacade *facade,
+                                                   void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_nlu_subscribe_slot_parsed_json_with_subscription(const CNluFacade *facade,
+                                                                     void (*handler)(const char*, void*),
+                                                                     const CSubscription **subscription);
+
+SNIPS_RESULT hermes_nlu_subscribe_version_json(const CNluFacade *facade,
+                                               void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_nlu_subscribe_version_json_with_subscription(const CNluFacade *facade,
+                                                                 void (*handler)(const char*, void*),
+                                                                 const CSubscription **subscription);
+
+/**
+ * Call the queued callbacks of `handler`, waiting up to `timeout_ms` milliseconds for one
+ * if there are none. A negative timeout waits as long as needed.
+ */
+SNIPS_RESULT hermes_poll(const CProtocolHandler *handler, int timeout_ms);
+
+SNIPS_RESULT hermes_protocol_handler_asr_backend_facade(const CProtocolHandler *handler,
+                                                        const CAsrBackendFacade **facade);
+
+SNIPS_RESULT hermes_protocol_handler_asr_facade(const CProtocolHandler *handler,
+                                                const CAsrFacade **facade);
+
+SNIPS_RESULT hermes_protocol_handler_audio_server_backend_facade(const CProtocolHandler *handler,
+                                                                 const CAudioServerBackendFacade **facade);
+
+SNIPS_RESULT hermes_protocol_handler_audio_server_facade(const CProtocolHandler *handler,
+                                                         const CAudioServerFacade **facade);
+
+SNIPS_RESULT hermes_protocol_handler_dialogue_backend_facade(const CProtocolHandler *handler,
+                                                             const CDialogueBackendFacade **facade);
+
 SNIPS_RESULT hermes_protocol_handler_dialogue_facade(const CProtocolHandler *handler,
                                                      const CDialogueFacade **facade);
 
+/**
+ * Give a file descriptor that is readable while callbacks of `handler` are queued, so that
+ * hosts can wait for them in their own event loop. The queue must be enabled first and the
+ * descriptor stays owned by the handler. Only available on unix.
+ */
+SNIPS_RESULT hermes_protocol_handler_dispatch_fd(const CProtocolHandler *handler, int *fd);
+
+/**
+ * Queue the callbacks of the subscriptions made through `handler` and its facades instead
+ * of calling them on the thread of the communication layer. They are then called by
+ * `hermes_poll` or `hermes_dispatch_pending`, on the thread of the caller.
+ */
+SNIPS_RESULT hermes_protocol_handler_enable_queued_dispatch(const CProtocolHandler *handler);
+
+SNIPS_RESULT hermes_protocol_handler_hotword_backend_facade(const CProtocolHandler *handler,
+                                                            const CHotwordBackendFacade **facade);
+
+SNIPS_RESULT hermes_protocol_handler_hotword_facade(const CProtocolHandler *handler,
+                                                    const CHotwordFacade **facade);
+
+SNIPS_RESULT hermes_protocol_handler_injection_backend_facade(const CProtocolHandler *handler,
+                                                              const CInjectionBackendFacade **facade);
+
 SNIPS_RESULT hermes_protocol_handler_injection_facade(const CProtocolHandler *handler,
                                                       const CInjectionFacade **facade);
 
+/**
+ * Create a handler delivering the messages inside the process, without a broker. The
+ * handlers created with the same non null `bus_name` share their bus as long as one of
+ * them is alive, a null `bus_name` gives a handler with a bus of its own.
+ */
+SNIPS_RESULT hermes_protocol_handler_new_in_process(const CProtocolHandler **handler,
+                                                    const char *bus_name,
+                                                    void *user_data);
+
 SNIPS_RESULT hermes_protocol_handler_new_mqtt(const CProtocolHandler **handler,
                                               const char *broker_address,
                                               void *user_data);
@@ -168,18 +1186,258 @@ SNIPS_RESULT hermes_protocol_handler_new_mqtt_with_options(const CProtocolHandle
                                                            const CMqttOptions *mqtt_options,
                                                            void *user_data);
 
+SNIPS_RESULT hermes_protocol_handler_nlu_backend_facade(const CProtocolHandler *handler,
+                                                        const CNluBackendFacade **facade);
+
+SNIPS_RESULT hermes_protocol_handler_nlu_facade(const CProtocolHandler *handler,
+                                                const CNluFacade **facade);
+
+/**
+ * Set the function called with the `user_data` given at the creation of `handler` once the
+ * handler, its facades and its subscriptions are all gone
+ */
+SNIPS_RESULT hermes_protocol_handler_set_user_data_destructor(const CProtocolHandler *handler,
+                                                              void (*destructor)(void*));
+
+SNIPS_RESULT hermes_protocol_handler_sound_feedback_backend_facade(const CProtocolHandler *handler,
+                                                                   const CSoundFeedbackBackendFacade **facade);
+
 SNIPS_RESULT hermes_protocol_handler_sound_feedback_facade(const CProtocolHandler *handler,
                                                            const CSoundFeedbackFacade **facade);
 
+SNIPS_RESULT hermes_protocol_handler_tts_backend_facade(const CProtocolHandler *handler,
+                                                        const CTtsBackendFacade **facade);
+
 SNIPS_RESULT hermes_protocol_handler_tts_facade(const CProtocolHandler *handler,
                                                 const CTtsFacade **facade);
 
+SNIPS_RESULT hermes_protocol_handler_voice_activity_backend_facade(const CProtocolHandler *handler,
+                                                                   const CVoiceActivityBackendFacade **facade);
+
+SNIPS_RESULT hermes_protocol_handler_voice_activity_facade(const CProtocolHandler *handler,
+                                                           const CVoiceActivityFacade **facade);
+
+SNIPS_RESULT hermes_sound_feedback_backend_subscribe_all_toggle_off_json(const CSoundFeedbackBackendFacade *facade,
+                                                                         void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_sound_feedback_backend_subscribe_all_toggle_off_json_with_subscription(const CSoundFeedbackBackendFacade *facade,
+                                                                                           void (*handler)(const char*, void*),
+                                                                                           const CSubscription **subscription);
+
+SNIPS_RESULT hermes_sound_feedback_backend_subscribe_all_toggle_on_json(const CSoundFeedbackBackendFacade *facade,
+                                                                        void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_sound_feedback_backend_subscribe_all_toggle_on_json_with_subscription(const CSoundFeedbackBackendFacade *facade,
+                                                                                          void (*handler)(const char*, void*),
+                                                                                          const CSubscription **subscription);
+
+SNIPS_RESULT hermes_sound_feedback_backend_subscribe_toggle_off_json(const CSoundFeedbackBackendFacade *facade,
+                                                                     const char *site_id,
+                                                                     void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_sound_feedback_backend_subscribe_toggle_off_json_with_subscription(const CSoundFeedbackBackendFacade *facade,
+                                                                                       const char *site_id,
+                                                                                       void (*handler)(const char*, void*),
+                                                                                       const CSubscription **subscription);
+
+SNIPS_RESULT hermes_sound_feedback_backend_subscribe_toggle_on_json(const CSoundFeedbackBackendFacade *facade,
+                                                                    const char *site_id,
+                                                                    void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_sound_feedback_backend_subscribe_toggle_on_json_with_subscription(const CSoundFeedbackBackendFacade *facade,
+                                                                                      const char *site_id,
+                                                                                      void (*handler)(const char*, void*),
+                                                                                      const CSubscription **subscription);
+
 SNIPS_RESULT hermes_sound_feedback_publish_toggle_off_json(const CSoundFeedbackFacade *facade,
                                                            const char *message);
 
 SNIPS_RESULT hermes_sound_feedback_publish_toggle_on_json(const CSoundFeedbackFacade *facade,
                                                           const char *message);
 
+SNIPS_RESULT hermes_tts_backend_publish_component_loaded_json(const CTtsBackendFacade *facade,
+                                                              const char *message);
+
+SNIPS_RESULT hermes_tts_backend_publish_error_json(const CTtsBackendFacade *facade,
+                                                   const char *message);
+
+SNIPS_RESULT hermes_tts_backend_publish_say_finished_json(const CTtsBackendFacade *facade,
+                                                          const char *message);
+
+SNIPS_RESULT hermes_tts_backend_publish_version_json(const CTtsBackendFacade *facade,
+                                                     const char *message);
+
+SNIPS_RESULT hermes_tts_backend_subscribe_register_sound_json(const CTtsBackendFacade *facade,
+                                                              void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_tts_backend_subscribe_register_sound_json_with_subscription(const CTtsBackendFacade *facade,
+                                                                                void (*handler)(const char*, void*),
+                                                                                const CSubscription **subscription);
+
+SNIPS_RESULT hermes_tts_backend_subscribe_say_for_site_json(const CTtsBackendFacade *facade,
+                                                            const char *site_id,
+                                                            void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_tts_backend_subscribe_say_for_site_json_with_subscription(const CTtsBackendFacade *facade,
+                                                                              const char *site_id,
+                                                                              void (*handler)(const char*, void*),
+                                                                              const CSubscription **subscription);
+
+SNIPS_RESULT hermes_tts_backend_subscribe_say_json(const CTtsBackendFacade *facade,
+                                                   void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_tts_backend_subscribe_say_json_with_subscription(const CTtsBackendFacade *facade,
+                                                                     void (*handler)(const char*, void*),
+                                                                     const CSubscription **subscription);
+
+SNIPS_RESULT hermes_tts_backend_subscribe_version_request_json(const CTtsBackendFacade *facade,
+                                                               void (*handler)(void*));
+
+SNIPS_RESULT hermes_tts_backend_subscribe_version_request_json_with_subscription(const CTtsBackendFacade *facade,
+                                                                                 void (*handler)(void*),
+                                                                                 const CSubscription **subscription);
+
 SNIPS_RESULT hermes_tts_publish_register_sound_json(const CTtsFacade *facade, const char *message);
 
+SNIPS_RESULT hermes_tts_publish_say_json(const CTtsFacade *facade, const char *message);
+
+SNIPS_RESULT hermes_tts_publish_version_request_json(const CTtsFacade *facade);
+
+SNIPS_RESULT hermes_tts_subscribe_component_loaded_json(const CTtsFacade *facade,
+                                                        void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_tts_subscribe_component_loaded_json_with_subscription(const CTtsFacade *facade,
+                                                                          void (*handler)(const char*, void*),
+                                                                          const CSubscription **subscription);
+
+SNIPS_RESULT hermes_tts_subscribe_error_json(const CTtsFacade *facade,
+                                             void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_tts_subscribe_error_json_with_subscription(const CTtsFacade *facade,
+                                                               void (*handler)(const char*, void*),
+                                                               const CSubscription **subscription);
+
+SNIPS_RESULT hermes_tts_subscribe_say_finished_json(const CTtsFacade *facade,
+                                                    void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_tts_subscribe_say_finished_json_with_subscription(const CTtsFacade *facade,
+                                                                      void (*handler)(const char*, void*),
+                                                                      const CSubscription **subscription);
+
+SNIPS_RESULT hermes_tts_subscribe_version_json(const CTtsFacade *facade,
+                                               void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_tts_subscribe_version_json_with_subscription(const CTtsFacade *facade,
+                                                                 void (*handler)(const char*, void*),
+                                                                 const CSubscription **subscription);
+
+/**
+ * Unregister the handler of `subscription` from the protocol handler and release the token.
+ * The `user_data` of the handler isn't given to it anymore once this returns.
+ */
+SNIPS_RESULT hermes_unsubscribe(const CSubscription *subscription);
+
+SNIPS_RESULT hermes_voice_activity_backend_publish_component_loaded_json(const CVoiceActivityBackendFacade *facade,
+                                                                         const char *id,
+                                                                         const char *message);
+
+SNIPS_RESULT hermes_voice_activity_backend_publish_error_json(const CVoiceActivityBackendFacade *facade,
+                                                              const char *id,
+                                                              const char *message);
+
+SNIPS_RESULT hermes_voice_activity_backend_publish_vad_down_json(const CVoiceActivityBackendFacade *facade,
+                                                                 const char *message);
+
+SNIPS_RESULT hermes_voice_activity_backend_publish_vad_up_json(const CVoiceActivityBackendFacade *facade,
+                                                               const char *message);
+
+SNIPS_RESULT hermes_voice_activity_backend_publish_version_json(const CVoiceActivityBackendFacade *facade,
+                                                                const char *id,
+                                                                const char *message);
+
+SNIPS_RESULT hermes_voice_activity_backend_subscribe_version_request_json(const CVoiceActivityBackendFacade *facade,
+                                                                          const char *id,
+                                                                          void (*handler)(void*));
+
+SNIPS_RESULT hermes_voice_activity_backend_subscribe_version_request_json_with_subscription(const CVoiceActivityBackendFacade *facade,
+                                                                                            const char *id,
+                                                                                            void (*handler)(void*),
+                                                                                            const CSubscription **subscription);
+
+SNIPS_RESULT hermes_voice_activity_publish_version_request_json(const CVoiceActivityFacade *facade,
+                                                                const char *id);
+
+SNIPS_RESULT hermes_voice_activity_subscribe_all_component_loaded_json(const CVoiceActivityFacade *facade,
+                                                                       void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_voice_activity_subscribe_all_component_loaded_json_with_subscription(const CVoiceActivityFacade *facade,
+                                                                                         void (*handler)(const char*, void*),
+                                                                                         const CSubscription **subscription);
+
+SNIPS_RESULT hermes_voice_activity_subscribe_all_error_json(const CVoiceActivityFacade *facade,
+                                                            void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_voice_activity_subscribe_all_error_json_with_subscription(const CVoiceActivityFacade *facade,
+                                                                              void (*handler)(const char*, void*),
+                                                                              const CSubscription **subscription);
+
+SNIPS_RESULT hermes_voice_activity_subscribe_all_vad_down_json(const CVoiceActivityFacade *facade,
+                                                               void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_voice_activity_subscribe_all_vad_down_json_with_subscription(const CVoiceActivityFacade *facade,
+                                                                                 void (*handler)(const char*, void*),
+                                                                                 const CSubscription **subscription);
+
+SNIPS_RESULT hermes_voice_activity_subscribe_all_vad_up_json(const CVoiceActivityFacade *facade,
+                                                             void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_voice_activity_subscribe_all_vad_up_json_with_subscription(const CVoiceActivityFacade *facade,
+                                                                               void (*handler)(const char*, void*),
+                                                                               const CSubscription **subscription);
+
+SNIPS_RESULT hermes_voice_activity_subscribe_component_loaded_json(const CVoiceActivityFacade *facade,
+                                                                   const char *id,
+                                                                   void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_voice_activity_subscribe_component_loaded_json_with_subscription(const CVoiceActivityFacade *facade,
+                                                                                     const char *id,
+                                                                                     void (*handler)(const char*, void*),
+                                                                                     const CSubscription **subscription);
+
+SNIPS_RESULT hermes_voice_activity_subscribe_error_json(const CVoiceActivityFacade *facade,
+                                                        const char *id,
+                                                        void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_voice_activity_subscribe_error_json_with_subscription(const CVoiceActivityFacade *facade,
+                                                                          const char *id,
+                                                                          void (*handler)(const char*, void*),
+                                                                          const CSubscription **subscription);
+
+SNIPS_RESULT hermes_voice_activity_subscribe_vad_down_json(const CVoiceActivityFacade *facade,
+                                                           const char *site_id,
+                                                           void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_voice_activity_subscribe_vad_down_json_with_subscription(const CVoiceActivityFacade *facade,
+                                                                             const char *site_id,
+                                                                             void (*handler)(const char*, void*),
+                                                                             const CSubscription **subscription);
+
+SNIPS_RESULT hermes_voice_activity_subscribe_vad_up_json(const CVoiceActivityFacade *facade,
+                                                         const char *site_id,
+                                                         void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_voice_activity_subscribe_vad_up_json_with_subscription(const CVoiceActivityFacade *facade,
+                                                                           const char *site_id,
+                                                                           void (*handler)(const char*, void*),
+                                                                           const CSubscription **subscription);
+
+SNIPS_RESULT hermes_voice_activity_subscribe_version_json(const CVoiceActivityFacade *facade,
+                                                          const char *id,
+                                                          void (*handler)(const char*, void*));
+
+SNIPS_RESULT hermes_voice_activity_subscribe_version_json_with_subscription(const CVoiceActivityFacade *facade,
+                                                                            const char *id,
+                                                                            void (*handler)(const char*, void*),
+                                                                            const CSubscription **subscription);
+
 #endif /* LIB_HERMES_H_ */
@@ -23,4 +23,10 @@ export interface TextCapturedMessage {
     ]
     likelihood?: number
     seconds?: number
+    speakerHypotheses?: {
+        /** The name of the detected speaker, null for unknown speakers. */
+        name: string | null,
+        /** Confidence of the detection, between 0 and 1, 1 being confident. */
+        confidence: number
+    }[]
 }
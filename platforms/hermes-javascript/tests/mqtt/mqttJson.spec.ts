@@ -27,8 +27,8 @@ import {
 /* Setup */
 
 let
-  mosquitto: any,
-  mosquittoPort: string,
+  broker: any,
+  brokerPort: string,
   client: MqttClient,
   hermes: Hermes,
   dialog: Dialog,
@@ -42,15 +42,19 @@ const robustnessIterations = 500
 const robustnessDelay = 5
 
 beforeAll(async () => {
-  mosquittoPort = '' + await getFreePort()
-  console.log('Launching mosquitto on port [' + mosquittoPort + ']')
-  mosquitto = spawn('mosquitto', ['-p', mosquittoPort, '-v'], { stdio: 'ignore' })
-  console.log('Mosquitto ready!')
+  brokerPort = '' + await getFreePort()
+  console.log('Launching the test broker on port [' + brokerPort + ']')
+  broker = spawn(
+    path.join(__dirname, `../../../../target/${LIB_ENV_FOLDER}/hermes-mqtt-test-broker`),
+    [`127.0.0.1:${brokerPort}`],
+    { stdio: 'ignore' }
+  )
+  console.log('Test broker ready!')
   try {
     hermes = new Hermes({
       libraryPath: path.join(__dirname, `../../../../target/${LIB_ENV_FOLDER}/libhermes_mqtt_ffi`),
       logs: true,
-      address: `localhost:${mosquittoPort}`
+      address: `127.0.0.1:${brokerPort}`
     })
     dialog = hermes.dialog()
     injection = hermes.injection()
@@ -63,7 +67,7 @@ beforeAll(async () => {
 })
 
 beforeEach(done => {
-  client = mqtt.connect(`mqtt://localhost:${mosquittoPort}`)
+  client = mqtt.connect(`mqtt://127.0.0.1:${brokerPort}`)
   client.on('connect', function () {
     done()
   })
@@ -82,8 +86,8 @@ afterAll(done => {
     hermes.destroy()
   console.log('Hermes destroyed.')
   setTimeout(() => {
-    mosquitto.kill()
-    console.log('Mosquitto killed.')
+    broker.kill()
+    console.log('Test broker killed.')
     done()
   }, 500)
 })
@@ -2,14 +2,29 @@
 
 import fs from 'fs'
 import path from 'path'
-import { spawn } from 'child_process'
+import { execSync, spawn } from 'child_process'
 import mqtt from 'mqtt'
 import { Hermes } from '../../dist'
 import { LIB_ENV_FOLDER } from '../constants'
 
 let mosquitto: any, hermes: Hermes
 
+// The test broker does not speak TLS, this test needs a mosquitto installed on the machine
+const hasMosquitto = (() => {
+    try {
+        execSync('command -v mosquitto', { stdio: 'ignore' })
+        return true
+    } catch (error) {
+        return false
+    }
+})()
+const itWithMosquitto = hasMosquitto ? it : it.skip
+
 beforeAll(async () => {
+    if (!hasMosquitto) {
+        console.log('mosquitto is not installed, skipping the TLS test')
+        return
+    }
     console.log('Launching secure mosquitto')
     mosquitto = spawn('mosquitto', ['-c', path.join(__dirname, 'tls/mosquitto-tls.conf')], { stdio: 'ignore' })
     console.log('Mosquitto server using TLS configuration is ready!')
@@ -36,13 +51,14 @@ afterAll(done => {
       hermes.destroy()
     console.log('Hermes destroyed.')
     setTimeout(() => {
-      mosquitto.kill()
+      if (mosquitto)
+        mosquitto.kill()
       console.log('Mosquitto killed.')
       done()
     }, 500)
   })
 
-it('should connect to a secure TLS mosquitto server', done => {
+itWithMosquitto('should connect to a secure TLS mosquitto server', done => {
     const message = {
         siteId: 'default',
         sessionId: 'session id',
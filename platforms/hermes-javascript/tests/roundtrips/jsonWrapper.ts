@@ -17,6 +17,11 @@ export const library = libraryPath => ffi.Library(libraryPath, {
     hermes_ffi_test_round_trip_register_sound_json: [ 'int', [ 'string', 'char **' ]],
     hermes_ffi_test_round_trip_dialogue_configure_json: [ 'int', [ 'string', 'char **' ]],
     hermes_ffi_test_round_trip_text_captured_json: [ 'int', [ 'string', 'char **' ]],
+    hermes_ffi_test_round_trip_vad_up_json: [ 'int', [ 'string', 'char **' ]],
+    hermes_ffi_test_round_trip_vad_down_json: [ 'int', [ 'string', 'char **' ]],
+    hermes_ffi_test_round_trip_stream_bytes_json: [ 'int', [ 'string', 'char **' ]],
+    hermes_ffi_test_round_trip_stream_finished_json: [ 'int', [ 'string', 'char **' ]],
+    hermes_ffi_test_round_trip_replay_request_json: [ 'int', [ 'string', 'char **' ]],
 
     // Error handling
     hermes_ffi_test_get_last_error: [ 'int', [ 'char **' ]],
@@ -457,5 +457,97 @@ describe('It should perform json round-trips on messages', () => {
             data: textCapturedMessage,
             FFIFunctionName: 'hermes_ffi_test_round_trip_text_captured_json'
         })
+        roundTrip({
+            data: {
+                ...textCapturedMessage,
+                speakerHypotheses: [
+                    {
+                        name: 'speaker',
+                        confidence: 0.5
+                    },
+                    {
+                        name: null,
+                        confidence: 0.25
+                    }
+                ]
+            },
+            FFIFunctionName: 'hermes_ffi_test_round_trip_text_captured_json'
+        })
+    })
+
+    it('VadUpMessage', () => {
+        const vadUpMessage = {
+            siteId: 'default',
+            signalMs: 1545696000000
+        }
+
+        roundTrip({
+            data: vadUpMessage,
+            FFIFunctionName: 'hermes_ffi_test_round_trip_vad_up_json'
+        })
+        roundTrip({
+            data: { ...vadUpMessage, signalMs: null },
+            FFIFunctionName: 'hermes_ffi_test_round_trip_vad_up_json'
+        })
+    })
+
+    it('VadDownMessage', () => {
+        const vadDownMessage = {
+            siteId: 'default',
+            signalMs: 1545696000000
+        }
+
+        roundTrip({
+            data: vadDownMessage,
+            FFIFunctionName: 'hermes_ffi_test_round_trip_vad_down_json'
+        })
+        roundTrip({
+            data: { ...vadDownMessage, signalMs: null },
+            FFIFunctionName: 'hermes_ffi_test_round_trip_vad_down_json'
+        })
+    })
+
+    it('StreamBytesMessage', () => {
+        const streamBytesMessage = {
+            streamId: 'stream id',
+            bytes: Buffer.from([0, 1, 2]).toString('base64'),
+            siteId: 'default',
+            chunkNumber: 3,
+            isLastChunk: true
+        }
+
+        roundTrip({
+            data: streamBytesMessage,
+            FFIFunctionName: 'hermes_ffi_test_round_trip_stream_bytes_json'
+        })
+        roundTrip({
+            data: { ...streamBytesMessage, chunkNumber: 0, isLastChunk: false },
+            FFIFunctionName: 'hermes_ffi_test_round_trip_stream_bytes_json'
+        })
+    })
+
+    it('StreamFinishedMessage', () => {
+        const streamFinishedMessage = {
+            id: 'stream id',
+            siteId: 'default'
+        }
+
+        roundTrip({
+            data: streamFinishedMessage,
+            FFIFunctionName: 'hermes_ffi_test_round_trip_stream_finished_json'
+        })
+    })
+
+    it('ReplayRequestMessage', () => {
+        const replayRequestMessage = {
+            requestId: 'request id',
+            startAtMs: 1545696000000,
+            siteId: 'default'
+        }
+
+        roundTrip({
+            data: replayRequestMessage,
+            FFIFunctionName: 'hermes_ffi_test_round_trip_replay_request_json'
+        })
     })
 })
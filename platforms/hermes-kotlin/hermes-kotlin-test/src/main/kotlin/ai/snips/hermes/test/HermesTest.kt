@@ -11,12 +11,18 @@ import ai.snips.hermes.InjectionResetRequestMessage
 import ai.snips.hermes.IntentAlternative
 import ai.snips.hermes.IntentMessage
 import ai.snips.hermes.IntentNotRecognizedMessage
+import ai.snips.hermes.ReplayRequestMessage
 import ai.snips.hermes.SessionEndedMessage
 import ai.snips.hermes.SessionQueuedMessage
 import ai.snips.hermes.SessionStartedMessage
 import ai.snips.hermes.SessionTermination
+import ai.snips.hermes.SpeakerId
 import ai.snips.hermes.StartSessionMessage
+import ai.snips.hermes.StreamBytesMessage
+import ai.snips.hermes.StreamFinishedMessage
 import ai.snips.hermes.TextCapturedMessage
+import ai.snips.hermes.VadDownMessage
+import ai.snips.hermes.VadUpMessage
 import ai.snips.hermes.ffi.CAsrToken
 import ai.snips.hermes.ffi.CAsrTokenArray
 import ai.snips.hermes.ffi.CAsrTokenDoubleArray
@@ -32,17 +38,25 @@ import ai.snips.hermes.ffi.CIntentNotRecognizedMessage
 import ai.snips.hermes.ffi.CMapStringToStringArray
 import ai.snips.hermes.ffi.CNluIntentAlternative
 import ai.snips.hermes.ffi.CNluIntentAlternativeArray
+import ai.snips.hermes.ffi.CReplayRequestMessage
 import ai.snips.hermes.ffi.CSessionEndedMessage
 import ai.snips.hermes.ffi.CSessionTermination
+import ai.snips.hermes.ffi.CSpeakerIdArray
 import ai.snips.hermes.ffi.CStartSessionMessage
+import ai.snips.hermes.ffi.CStreamBytesMessage
+import ai.snips.hermes.ffi.CStreamFinishedMessage
 import ai.snips.hermes.ffi.CTextCapturedMessage
+import ai.snips.hermes.ffi.CVadDownMessage
+import ai.snips.hermes.ffi.CVadUpMessage
 import ai.snips.hermes.test.HermesTest.HermesTestLib.Companion.INSTANCE
 import ai.snips.nlu.ontology.ffi.readString
 import com.fasterxml.jackson.module.kotlin.jacksonObjectMapper
+import com.sun.jna.Callback
 import com.sun.jna.Library
 import com.sun.jna.Native
 import com.sun.jna.Pointer
 import com.sun.jna.ptr.PointerByReference
+import java.util.concurrent.atomic.AtomicInteger
 import kotlin.reflect.jvm.internal.impl.protobuf.ByteString
 
 
@@ -169,6 +183,13 @@ class HermesTest {
                       { CAsrTokenDoubleArray(it).toAsrTokenDoubleList() },
                       INSTANCE::hermes_ffi_test_destroy_asr_token_double_array)
 
+    fun roundTripSpeakerIdArray(input: List<SpeakerId>) =
+            roundTrip(input,
+                      CSpeakerIdArray.Companion::fromSpeakerIdList,
+                      INSTANCE::hermes_ffi_test_round_trip_speaker_id_array,
+                      { CSpeakerIdArray(it).toSpeakerIdList() },
+                      INSTANCE::hermes_ffi_test_destroy_speaker_id_array)
+
     fun roundTripTextCaptured(input: TextCapturedMessage) =
             roundTrip(input,
                       CTextCapturedMessage.Companion::fromTextCapturedMessage,
@@ -190,6 +211,41 @@ class HermesTest {
                   { CSessionEndedMessage(it).toSessionEndedMessage() },
                   INSTANCE::hermes_drop_session_ended_message)
 
+    fun roundTripVadUp(input: VadUpMessage) =
+            roundTrip(input,
+                      CVadUpMessage.Companion::fromVadUpMessage,
+                      INSTANCE::hermes_ffi_test_round_trip_vad_up,
+                      { CVadUpMessage(it).toVadUpMessage() },
+                      INSTANCE::hermes_drop_vad_up_message)
+
+    fun roundTripVadDown(input: VadDownMessage) =
+            roundTrip(input,
+                      CVadDownMessage.Companion::fromVadDownMessage,
+                      INSTANCE::hermes_ffi_test_round_trip_vad_down,
+                      { CVadDownMessage(it).toVadDownMessage() },
+                      INSTANCE::hermes_drop_vad_down_message)
+
+    fun roundTripStreamBytes(input: StreamBytesMessage) =
+            roundTrip(input,
+                      CStreamBytesMessage.Companion::fromStreamBytesMessage,
+                      INSTANCE::hermes_ffi_test_round_trip_stream_bytes,
+                      { CStreamBytesMessage(it).toStreamBytesMessage() },
+                      INSTANCE::hermes_drop_stream_bytes_message)
+
+    fun roundTripStreamFinished(input: StreamFinishedMessage) =
+            roundTrip(input,
+                      CStreamFinishedMessage.Companion::fromStreamFinishedMessage,
+                      INSTANCE::hermes_ffi_test_round_trip_stream_finished,
+                      { CStreamFinishedMessage(it).toStreamFinishedMessage() },
+                      INSTANCE::hermes_drop_stream_finished_message)
+
+    fun roundTripReplayRequest(input: ReplayRequestMessage) =
+            roundTrip(input,
+                      CReplayRequestMessage.Companion::fromReplayRequestMessage,
+                      INSTANCE::hermes_ffi_test_round_trip_replay_request,
+                      { CReplayRequestMessage(it).toReplayRequestMessage() },
+                      INSTANCE::hermes_drop_replay_request_message)
+
     fun <T, U> roundTrip(input: T,
                          toCConverter: (T) -> U,
                          roundTrip: (U, PointerByReference) -> Int,
@@ -247,6 +303,84 @@ class HermesTest {
     fun roundTripDialogueConfigureJson(input: DialogueConfigureMessage) =
             roundTripJson(input, INSTANCE::hermes_ffi_test_round_trip_dialogue_configure_json)
 
+    fun roundTripVadUpJson(input: VadUpMessage) =
+            roundTripJson(input, INSTANCE::hermes_ffi_test_round_trip_vad_up_json)
+
+    fun roundTripVadDownJson(input: VadDownMessage) =
+            roundTripJson(input, INSTANCE::hermes_ffi_test_round_trip_vad_down_json)
+
+    fun roundTripStreamBytesJson(input: StreamBytesMessage) =
+            roundTripJson(input, INSTANCE::hermes_ffi_test_round_trip_stream_bytes_json)
+
+    fun roundTripStreamFinishedJson(input: StreamFinishedMessage) =
+            roundTripJson(input, INSTANCE::hermes_ffi_test_round_trip_stream_finished_json)
+
+    fun roundTripReplayRequestJson(input: ReplayRequestMessage) =
+            roundTripJson(input, INSTANCE::hermes_ffi_test_round_trip_replay_request_json)
+
+    /**
+     * Publish `input` twice on an in process bus to two subscriptions made with a token, the first one
+     * being unsubscribed between the two publications, and give how many messages each one received.
+     */
+    fun receivedAfterUnsubscribeJson(input: SessionStartedMessage): Pair<Int, Int> {
+        val cancelledCount = AtomicInteger(0)
+        val keptCount = AtomicInteger(0)
+        // keep the callbacks referenced so that JNA doesn't collect them while they are subscribed
+        val cancelledHandler = object : JsonHandler {
+            override fun invoke(json: String, userData: Pointer?) {
+                cancelledCount.incrementAndGet()
+            }
+        }
+        val keptHandler = object : JsonHandler {
+            override fun invoke(json: String, userData: Pointer?) {
+                keptCount.incrementAndGet()
+            }
+        }
+        val message = JSON_MAPPER.writeValueAsString(input)
+
+        val handler = PointerByReference().apply {
+            parseError(INSTANCE.hermes_protocol_handler_new_in_process(this, null, null))
+        }.value
+        val dialogue = PointerByReference().apply {
+            parseError(INSTANCE.hermes_protocol_handler_dialogue_facade(handler, this))
+        }.value
+        val backend = PointerByReference().apply {
+            parseError(INSTANCE.hermes_protocol_handler_dialogue_backend_facade(handler, this))
+        }.value
+        val cancelled = PointerByReference().apply {
+            parseError(INSTANCE.hermes_dialogue_subscribe_session_started_json_with_subscription(
+                    dialogue, cancelledHandler, this))
+        }.value
+        val kept = PointerByReference().apply {
+            parseError(INSTANCE.hermes_dialogue_subscribe_session_started_json_with_subscription(
+                    dialogue, keptHandler, this))
+        }.value
+
+        parseError(INSTANCE.hermes_dialogue_backend_publish_session_started_json(backend, message))
+        waitFor { cancelledCount.get() == 1 && keptCount.get() == 1 }
+
+        parseError(INSTANCE.hermes_unsubscribe(cancelled))
+        parseError(INSTANCE.hermes_dialogue_backend_publish_session_started_json(backend, message))
+        waitFor { keptCount.get() == 2 }
+        // give a wrongly kept subscription the time to be called
+        Thread.sleep(200)
+
+        parseError(INSTANCE.hermes_unsubscribe(kept))
+        parseError(INSTANCE.hermes_drop_dialogue_facade(dialogue))
+        parseError(INSTANCE.hermes_drop_dialogue_backend_facade(backend))
+        parseError(INSTANCE.hermes_destroy_in_process_protocol_handler(handler))
+
+        return Pair(cancelledCount.get(), keptCount.get())
+    }
+
+    // the messages are delivered on another thread, wait for them for up to 5s
+    private fun waitFor(condition: () -> Boolean) {
+        for (i in 0 until 500) {
+            if (condition()) return
+            Thread.sleep(10)
+        }
+    }
+
     inline fun <reified T> roundTripJson(input: T,
                                          noinline roundTrip: (String, PointerByReference) -> Int) =
             roundTrip(input,
@@ -256,6 +390,10 @@ class HermesTest {
                       INSTANCE::hermes_ffi_test_destroy_string)
 
 
+    interface JsonHandler : Callback {
+        fun invoke(json: String, userData: Pointer?)
+    }
+
     interface HermesTestLib : Library {
         companion object {
             val INSTANCE: HermesTestLib = Native.loadLibrary("hermes_ffi_test", HermesTestLib::class.java)
@@ -276,9 +414,15 @@ class HermesTest {
         fun hermes_ffi_test_round_trip_asr_token(input: CAsrToken, output: PointerByReference): Int
         fun hermes_ffi_test_round_trip_asr_token_array(input: CAsrTokenArray, output: PointerByReference): Int
         fun hermes_ffi_test_round_trip_asr_token_double_array(input: CAsrTokenDoubleArray, output: PointerByReference): Int
+        fun hermes_ffi_test_round_trip_speaker_id_array(input: CSpeakerIdArray, output: PointerByReference): Int
         fun hermes_ffi_test_round_trip_text_captured(input: CTextCapturedMessage, output: PointerByReference): Int
         fun hermes_ffi_test_round_trip_dialogue_configure(input: CDialogueConfigureMessage, output: PointerByReference): Int
         fun hermes_ffi_test_round_trip_session_ended(input: CSessionEndedMessage, output: PointerByReference) : Int
+        fun hermes_ffi_test_round_trip_vad_up(input: CVadUpMessage, output: PointerByReference): Int
+        fun hermes_ffi_test_round_trip_vad_down(input: CVadDownMessage, output: PointerByReference): Int
+        fun hermes_ffi_test_round_trip_stream_bytes(input: CStreamBytesMessage, output: PointerByReference): Int
+        fun hermes_ffi_test_round_trip_stream_finished(input: CStreamFinishedMessage, output: PointerByReference): Int
+        fun hermes_ffi_test_round_trip_replay_request(input: CReplayRequestMessage, output: PointerByReference): Int
 
 
         fun hermes_ffi_test_round_trip_session_queued_json(input: String, output: PointerByReference): Int
@@ -295,15 +439,34 @@ class HermesTest {
         fun hermes_ffi_test_round_trip_injection_reset_complete_json(input: String, output: PointerByReference): Int
         fun hermes_ffi_test_round_trip_text_captured_json(input: String, output: PointerByReference): Int
         fun hermes_ffi_test_round_trip_dialogue_configure_json(input: String, output: PointerByReference): Int
+        fun hermes_ffi_test_round_trip_vad_up_json(input: String, output: PointerByReference): Int
+        fun hermes_ffi_test_round_trip_vad_down_json(input: String, output: PointerByReference): Int
+        fun hermes_ffi_test_round_trip_stream_bytes_json(input: String, output: PointerByReference): Int
+        fun hermes_ffi_test_round_trip_stream_finished_json(input: String, output: PointerByReference): Int
+        fun hermes_ffi_test_round_trip_replay_request_json(input: String, output: PointerByReference): Int
 
 
         fun hermes_ffi_test_get_last_error(error: PointerByReference): Int
 
+        fun hermes_protocol_handler_new_in_process(handler: PointerByReference, busName: String?, userData: Pointer?): Int
+        fun hermes_destroy_in_process_protocol_handler(handler: Pointer): Int
+        fun hermes_protocol_handler_dialogue_facade(handler: Pointer, facade: PointerByReference): Int
+        fun hermes_protocol_handler_dialogue_backend_facade(handler: Pointer, facade: PointerByReference): Int
+        fun hermes_drop_dialogue_facade(ptr: Pointer): Int
+        fun hermes_drop_dialogue_backend_facade(ptr: Pointer): Int
+        fun hermes_dialogue_backend_publish_session_started_json(facade: Pointer, message: String): Int
+        fun hermes_dialogue_subscribe_session_started_json_with_subscription(facade: Pointer,
+                                                                             handler: JsonHandler,
+                                                                             subscription: PointerByReference): Int
+        fun hermes_unsubscribe(subscription: Pointer): Int
+        fun hermes_drop_subscription(subscription: Pointer): Int
+
         fun hermes_ffi_test_destroy_string(ptr: Pointer): Int
         fun hermes_ffi_test_destroy_map_string_to_string_array(ptr: Pointer): Int
         fun hermes_ffi_test_destroy_asr_token(ptr: Pointer): Int
         fun hermes_ffi_test_destroy_asr_token_array(ptr: Pointer): Int
         fun hermes_ffi_test_destroy_asr_token_double_array(ptr: Pointer): Int
+        fun hermes_ffi_test_destroy_speaker_id_array(ptr: Pointer): Int
         fun hermes_ffi_test_destroy_nlu_intent_alternative(ptr: Pointer): Int
         fun hermes_ffi_test_destroy_nlu_intent_alternative_array(ptr: Pointer): Int
 
@@ -319,5 +482,10 @@ class HermesTest {
         fun hermes_drop_text_captured_message(ptr: Pointer): Int
         fun hermes_drop_dialogue_configure_message(ptr: Pointer): Int
         fun hermes_drop_session_ended_message(ptr: Pointer) : Int
+        fun hermes_drop_vad_up_message(ptr: Pointer): Int
+        fun hermes_drop_vad_down_message(ptr: Pointer): Int
+        fun hermes_drop_stream_bytes_message(ptr: Pointer): Int
+        fun hermes_drop_stream_finished_message(ptr: Pointer): Int
+        fun hermes_drop_replay_request_message(ptr: Pointer): Int
     }
 }
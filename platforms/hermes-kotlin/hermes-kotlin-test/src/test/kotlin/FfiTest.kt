@@ -16,12 +16,19 @@ import ai.snips.hermes.IntentAlternative
 import ai.snips.hermes.IntentClassifierResult
 import ai.snips.hermes.IntentMessage
 import ai.snips.hermes.IntentNotRecognizedMessage
+import ai.snips.hermes.ReplayRequestMessage
 import ai.snips.hermes.SessionEndedMessage
 import ai.snips.hermes.SessionInit
 import ai.snips.hermes.SessionQueuedMessage
+import ai.snips.hermes.SessionStartedMessage
 import ai.snips.hermes.SessionTermination
+import ai.snips.hermes.SpeakerId
 import ai.snips.hermes.StartSessionMessage
+import ai.snips.hermes.StreamBytesMessage
+import ai.snips.hermes.StreamFinishedMessage
 import ai.snips.hermes.TextCapturedMessage
+import ai.snips.hermes.VadDownMessage
+import ai.snips.hermes.VadUpMessage
 import ai.snips.hermes.test.HermesTest
 import ai.snips.nlu.ontology.Range
 import ai.snips.nlu.ontology.Slot
@@ -302,6 +309,14 @@ class FfiTest {
         assertThat(HermesTest().roundTripAsrTokenArray(listOf())).isEqualTo(listOf<List<AsrToken>>())
     }
 
+    @Test
+    fun roundTripSpeakerIdArray() {
+        val input = listOf(SpeakerId(name = "a speaker", confidence = 0.8f),
+                           SpeakerId(name = null, confidence = 0.2f))
+        assertThat(HermesTest().roundTripSpeakerIdArray(input)).isEqualTo(input)
+        assertThat(HermesTest().roundTripSpeakerIdArray(listOf())).isEqualTo(listOf<SpeakerId>())
+    }
+
 
     @Test
     fun roundTripTextCaptured() {
@@ -333,6 +348,10 @@ class FfiTest {
                 tokens = listOf())
         assertThat(HermesTest().roundTripTextCaptured(input2)).isEqualTo(input2)
         assertThat(HermesTest().roundTripTextCapturedJson(input2)).isEqualTo(input2)
+
+        val input3 = input.copy(speakerHypotheses = listOf(SpeakerId(name = "a speaker", confidence = 0.8f),
+                                                           SpeakerId(name = null, confidence = 0.2f)))
+        assertThat(HermesTest().roundTripTextCapturedJson(input3)).isEqualTo(input3)
     }
 
 
@@ -473,4 +492,70 @@ class FfiTest {
         assertThat(HermesTest().roundTripDialogueConfigure(input2)).isEqualTo(input2)
         assertThat(HermesTest().roundTripDialogueConfigureJson(input2)).isEqualTo(input2)
     }
+
+    @Test
+    fun roundTripVadUp() {
+        val input = VadUpMessage(siteId = "a site id", signalMs = 1545696000000L)
+
+        assertThat(HermesTest().roundTripVadUp(input)).isEqualTo(input)
+        assertThat(HermesTest().roundTripVadUpJson(input)).isEqualTo(input)
+
+        val input2 = VadUpMessage(siteId = "a site id", signalMs = null)
+        assertThat(HermesTest().roundTripVadUp(input2)).isEqualTo(input2)
+        assertThat(HermesTest().roundTripVadUpJson(input2)).isEqualTo(input2)
+    }
+
+    @Test
+    fun roundTripVadDown() {
+        val input = VadDownMessage(siteId = "a site id", signalMs = 1545696000000L)
+
+        assertThat(HermesTest().roundTripVadDown(input)).isEqualTo(input)
+        assertThat(HermesTest().roundTripVadDownJson(input)).isEqualTo(input)
+
+        val input2 = VadDownMessage(siteId = "a site id", signalMs = null)
+        assertThat(HermesTest().roundTripVadDown(input2)).isEqualTo(input2)
+        assertThat(HermesTest().roundTripVadDownJson(input2)).isEqualTo(input2)
+    }
+
+    @Test
+    fun roundTripStreamBytes() {
+        val input = StreamBytesMessage(streamId = "a stream id",
+                                       bytes = byteArrayOf(0, 1, 2, 127, -128, -1),
+                                       siteId = "a site id",
+                                       chunkNumber = 3,
+                                       isLastChunk = true)
+
+        assertThat(HermesTest().roundTripStreamBytes(input)).isEqualTo(input)
+        assertThat(HermesTest().roundTripStreamBytesJson(input)).isEqualTo(input)
+
+        val input2 = input.copy(chunkNumber = 0, isLastChunk = false)
+        assertThat(HermesTest().roundTripStreamBytes(input2)).isEqualTo(input2)
+        assertThat(HermesTest().roundTripStreamBytesJson(input2)).isEqualTo(input2)
+    }
+
+    @Test
+    fun roundTripStreamFinished() {
+        val input = StreamFinishedMessage(id = "a stream id", siteId = "a site id")
+
+        assertThat(HermesTest().roundTripStreamFinished(input)).isEqualTo(input)
+        assertThat(HermesTest().roundTripStreamFinishedJson(input)).isEqualTo(input)
+    }
+
+    @Test
+    fun roundTripReplayRequest() {
+        val input = ReplayRequestMessage(requestId = "a request id", startAtMs = 1545696000000L, siteId = "a site id")
+
+        assertThat(HermesTest().roundTripReplayRequest(input)).isEqualTo(input)
+        assertThat(HermesTest().roundTripReplayRequestJson(input)).isEqualTo(input)
+    }
+
+    @Test
+    fun unsubscribedHandlerIsNotCalledAnymore() {
+        val input = SessionStartedMessage(sessionId = "some session id",
+                                          customData = null,
+                                          siteId = "some site id",
+                                          reactivatedFromSessionId = null)
+
+        assertThat(HermesTest().receivedAfterUnsubscribeJson(input)).isEqualTo(Pair(1, 2))
+    }
 }
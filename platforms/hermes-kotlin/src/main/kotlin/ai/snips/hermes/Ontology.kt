@@ -256,6 +256,12 @@ data class AsrToken @ParcelConstructor constructor(
                 this(value, confidence, AsrTokenRange(rangeStart, rangeEnd), time)
 }
 
+@Parcel(BEAN)
+data class SpeakerId @ParcelConstructor constructor(
+        @ParcelProperty("name") val name: String?,
+        @ParcelProperty("confidence") val confidence: Float
+)
+
 @Parcel(BEAN)
 data class TextCapturedMessage @ParcelConstructor constructor(
         @ParcelProperty("text") val text: String,
@@ -263,7 +269,8 @@ data class TextCapturedMessage @ParcelConstructor constructor(
         @ParcelProperty("tokens") val tokens: List<AsrToken>,
         @ParcelProperty("seconds") val seconds: Float,
         @ParcelProperty("siteId") val siteId: String,
-        @ParcelProperty("sessionId") val sessionId: String?
+        @ParcelProperty("sessionId") val sessionId: String?,
+        @ParcelProperty("speakerHypotheses") val speakerHypotheses: List<SpeakerId>? = null
 )
 
 @Parcel(BEAN)
@@ -277,3 +284,47 @@ data class DialogueConfigureIntent @ParcelConstructor constructor(
         @ParcelProperty("intentId") val intentId: String,
         @ParcelProperty("enable") val enable: Boolean?
 )
+
+@Parcel(BEAN)
+data class VadUpMessage @ParcelConstructor constructor(
+        @ParcelProperty("siteId") val siteId: String,
+        @ParcelProperty("signalMs") val signalMs: Long?
+)
+
+@Parcel(BEAN)
+data class VadDownMessage @ParcelConstructor constructor(
+        @ParcelProperty("siteId") val siteId: String,
+        @ParcelProperty("signalMs") val signalMs: Long?
+)
+
+@Parcel(BEAN)
+data class StreamBytesMessage @ParcelConstructor constructor(
+        @ParcelProperty("streamId") val streamId: String,
+        @ParcelProperty("bytes") val bytes: ByteArray,
+        @ParcelProperty("siteId") val siteId: String,
+        @ParcelProperty("chunkNumber") val chunkNumber: Int,
+        @ParcelProperty("isLastChunk") @get:JsonProperty("isLastChunk") val isLastChunk: Boolean
+) {
+        // data classes compare arrays by reference, compare the bytes by content instead
+        override fun equals(other: Any?) = other is StreamBytesMessage &&
+                streamId == other.streamId &&
+                bytes.contentEquals(other.bytes) &&
+                siteId == other.siteId &&
+                chunkNumber == other.chunkNumber &&
+                isLastChunk == other.isLastChunk
+
+        override fun hashCode() = listOf(streamId, bytes.contentHashCode(), siteId, chunkNumber, isLastChunk).hashCode()
+}
+
+@Parcel(BEAN)
+data class StreamFinishedMessage @ParcelConstructor constructor(
+        @ParcelProperty("id") val id: String,
+        @ParcelProperty("siteId") val siteId: String
+)
+
+@Parcel(BEAN)
+data class ReplayRequestMessage @ParcelConstructor constructor(
+        @ParcelProperty("requestId") val requestId: String,
+        @ParcelProperty("startAtMs") val startAtMs: Long,
+        @ParcelProperty("siteId") val siteId: String
+)
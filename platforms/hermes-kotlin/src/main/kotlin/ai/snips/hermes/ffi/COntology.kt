@@ -30,6 +30,7 @@ import ai.snips.hermes.IntentAlternative
 import ai.snips.hermes.IntentClassifierResult
 import ai.snips.hermes.IntentMessage
 import ai.snips.hermes.IntentNotRecognizedMessage
+import ai.snips.hermes.ReplayRequestMessage
 import ai.snips.hermes.SayFinishedMessage
 import ai.snips.hermes.SayMessage
 import ai.snips.hermes.SessionEndedMessage
@@ -46,8 +47,13 @@ import ai.snips.hermes.SessionTermination.IntenNotRecognized
 import ai.snips.hermes.SessionTermination.Nominal
 import ai.snips.hermes.SessionTermination.SiteUnAvailable
 import ai.snips.hermes.SessionTermination.Timeout
+import ai.snips.hermes.SpeakerId
 import ai.snips.hermes.StartSessionMessage
+import ai.snips.hermes.StreamBytesMessage
+import ai.snips.hermes.StreamFinishedMessage
 import ai.snips.hermes.TextCapturedMessage
+import ai.snips.hermes.VadDownMessage
+import ai.snips.hermes.VadUpMessage
 import ai.snips.nlu.ontology.Slot
 import ai.snips.nlu.ontology.ffi.CSlot
 import ai.snips.nlu.ontology.ffi.readRangeTo
@@ -1126,6 +1132,68 @@ class CAsrTokenDoubleArray(p: Pointer?) : Structure(p), Structure.ByReference {
     } else listOf()
 }
 
+class CSpeakerId(p: Pointer?) : Structure(p), Structure.ByReference {
+    companion object {
+        @JvmStatic
+        fun fromSpeakerId(speakerId: SpeakerId) = CSpeakerId(null).apply {
+            name = speakerId.name?.toPointer()
+            confidence = speakerId.confidence
+        }
+    }
+
+    @JvmField
+    var name: Pointer? = null
+
+    @JvmField
+    var confidence: Float? = null
+
+    // be careful this block must be below the field definition if you don't want the native values read by JNA
+    // overridden by the default ones
+    init {
+        read()
+    }
+
+    override fun getFieldOrder() = listOf("name", "confidence")
+
+    fun toSpeakerId() = SpeakerId(
+            name = name?.readString(),
+            confidence = confidence!!
+    )
+}
+
+class CSpeakerIdArray(p: Pointer?) : Structure(p), Structure.ByReference {
+    companion object {
+        @JvmStatic
+        fun fromSpeakerIdList(list: List<SpeakerId>) = CSpeakerIdArray(null).apply {
+            count = list.size
+            entries = if (count > 0)
+                Memory(Pointer.SIZE * list.size.toLong()).apply {
+                    list.forEachIndexed { i, e ->
+                        this.setPointer(i.toLong() * Pointer.SIZE, CSpeakerId.fromSpeakerId(e).apply { write() }.pointer)
+                    }
+                }
+            else null
+        }
+    }
+
+    @JvmField
+    var entries: Pointer? = null
+    @JvmField
+    var count: Int = -1
+
+    // be careful this block must be below the field definition if you don't want the native values read by JNA
+    // overridden by the default ones
+    init {
+        read()
+    }
+
+    override fun getFieldOrder() = listOf("entries", "count")
+
+    fun toSpeakerIdList(): List<SpeakerId> = if (count > 0) {
+        entries!!.getPointerArray(0, count).map { CSpeakerId(it).toSpeakerId() }
+    } else listOf()
+}
+
 
 class CTextCapturedMessage(p: Pointer?) : Structure(p), Structure.ByReference {
     companion object {
@@ -1271,3 +1339,176 @@ class CDialogueConfigureMessage(p: Pointer?) : Structure(p), Structure.ByReferen
             intents = intents?.toDialogueConfigureIntentList() ?: listOf()
     )
 }
+
+class CVadUpMessage(p: Pointer?) : Structure(p), Structure.ByReference {
+    companion object {
+        @JvmStatic
+        fun fromVadUpMessage(message: VadUpMessage) = CVadUpMessage(null).apply {
+            site_id = message.siteId.toPointer()
+            signal_ms = message.signalMs ?: -1
+        }
+    }
+
+    @JvmField
+    var site_id: Pointer? = null
+
+    @JvmField
+    var signal_ms: Long = -1
+
+    // be careful this block must be below the field definition if you don't want the native values read by JNA
+    // overridden by the default ones
+    init {
+        read()
+    }
+
+    override fun getFieldOrder() = listOf("site_id", "signal_ms")
+
+    fun toVadUpMessage() = VadUpMessage(
+            siteId = site_id.readString(),
+            signalMs = if (signal_ms == -1L) null else signal_ms
+    )
+}
+
+class CVadDownMessage(p: Pointer?) : Structure(p), Structure.ByReference {
+    companion object {
+        @JvmStatic
+        fun fromVadDownMessage(message: VadDownMessage) = CVadDownMessage(null).apply {
+            site_id = message.siteId.toPointer()
+            signal_ms = message.signalMs ?: -1
+        }
+    }
+
+    @JvmField
+    var site_id: Pointer? = null
+
+    @JvmField
+    var signal_ms: Long = -1
+
+    // be careful this block must be below the field definition if you don't want the native values read by JNA
+    // overridden by the default ones
+    init {
+        read()
+    }
+
+    override fun getFieldOrder() = listOf("site_id", "signal_ms")
+
+    fun toVadDownMessage() = VadDownMessage(
+            siteId = site_id.readString(),
+            signalMs = if (signal_ms == -1L) null else signal_ms
+    )
+}
+
+class CStreamBytesMessage(p: Pointer?) : Structure(p), Structure.ByReference {
+    companion object {
+        @JvmStatic
+        fun fromStreamBytesMessage(message: StreamBytesMessage) = CStreamBytesMessage(null).apply {
+            stream_id = message.streamId.toPointer()
+            bytes_len = message.bytes.size
+            bytes = if (bytes_len > 0)
+                Memory(bytes_len.toLong()).apply {
+                    write(0, message.bytes, 0, bytes_len)
+                }
+            else null
+            site_id = message.siteId.toPointer()
+            chunk_number = message.chunkNumber
+            is_last_chunk = if (message.isLastChunk) 1 else 0
+        }
+    }
+
+    @JvmField
+    var stream_id: Pointer? = null
+
+    @JvmField
+    var bytes: Pointer? = null
+
+    @JvmField
+    var bytes_len: Int = -1
+
+    @JvmField
+    var site_id: Pointer? = null
+
+    @JvmField
+    var chunk_number: Int = -1
+
+    @JvmField
+    var is_last_chunk: Byte = -1
+
+    // be careful this block must be below the field definition if you don't want the native values read by JNA
+    // overridden by the default ones
+    init {
+        read()
+    }
+
+    override fun getFieldOrder() = listOf("stream_id", "bytes", "bytes_len", "site_id", "chunk_number", "is_last_chunk")
+
+    fun toStreamBytesMessage() = StreamBytesMessage(
+            streamId = stream_id.readString(),
+            bytes = if (bytes_len > 0) bytes!!.getByteArray(0, bytes_len) else byteArrayOf(),
+            siteId = site_id.readString(),
+            chunkNumber = chunk_number,
+            isLastChunk = is_last_chunk == 1.toByte()
+    )
+}
+
+class CStreamFinishedMessage(p: Pointer?) : Structure(p), Structure.ByReference {
+    companion object {
+        @JvmStatic
+        fun fromStreamFinishedMessage(message: StreamFinishedMessage) = CStreamFinishedMessage(null).apply {
+            id = message.id.toPointer()
+            site_id = message.siteId.toPointer()
+        }
+    }
+
+    @JvmField
+    var id: Pointer? = null
+
+    @JvmField
+    var site_id: Pointer? = null
+
+    // be careful this block must be below the field definition if you don't want the native values read by JNA
+    // overridden by the default ones
+    init {
+        read()
+    }
+
+    override fun getFieldOrder() = listOf("id", "site_id")
+
+    fun toStreamFinishedMessage() = StreamFinishedMessage(
+            id = id.readString(),
+            siteId = site_id.readString()
+    )
+}
+
+class CReplayRequestMessage(p: Pointer?) : Structure(p), Structure.ByReference {
+    companion object {
+        @JvmStatic
+        fun fromReplayRequestMessage(message: ReplayRequestMessage) = CReplayRequestMessage(null).apply {
+            request_id = message.requestId.toPointer()
+            start_at_ms = message.startAtMs
+            site_id = message.siteId.toPointer()
+        }
+    }
+
+    @JvmField
+    var request_id: Pointer? = null
+
+    @JvmField
+    var start_at_ms: Long = -1
+
+    @JvmField
+    var site_id: Pointer? = null
+
+    // be careful this block must be below the field definition if you don't want the native values read by JNA
+    // overridden by the default ones
+    init {
+        read()
+    }
+
+    override fun getFieldOrder() = listOf("request_id", "start_at_ms", "site_id")
+
+    fun toReplayRequestMessage() = ReplayRequestMessage(
+            requestId = request_id.readString(),
+            startAtMs = start_at_ms,
+            siteId = site_id.readString()
+    )
+}